@@ -1,15 +1,30 @@
 use crate::cli::AIProvider as CliAIProvider;
 use crate::cli::AIAgentAction;
-use crate::known_issues::{IssueCategory, KnownIssuesDatabase};
+use crate::config::ProviderConfig;
+use crate::known_issues::{IssueCategory, KnownIssue, KnownIssuesDatabase};
 use async_trait::async_trait;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::pin::Pin;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+#[cfg(feature = "local-inference")]
+mod llama_cpp;
+
+/// A response in the chunks of text the provider sends as it generates them
+pub type AnalysisStream<'a> = Pin<Box<dyn Stream<Item = Result<String, AIError>> + Send + 'a>>;
+
 #[async_trait]
 pub trait AIProvider: Send + Sync {
-    async fn analyze(&self, input: &str) -> Result<String, AIError>;
+    /// Analyze `input`, yielding the response as it is generated so it can be shown before
+    /// the model has finished
+    fn analyze_stream<'a>(&'a self, input: &'a str) -> AnalysisStream<'a>;
+    /// The whole response of `analyze_stream`
+    async fn analyze(&self, input: &str) -> Result<String, AIError> {
+        self.analyze_stream(input).try_collect().await
+    }
     async fn analyze_with_known_issues(
         &self,
         input: &str,
@@ -21,6 +36,8 @@ pub trait AIProvider: Send + Sync {
         question: &str,
         system_context: &str,
     ) -> Result<String, AIError>;
+    /// Cheap reachability check run before collecting system info, so failures surface early
+    async fn health_check(&self) -> Result<(), AIError>;
     fn name(&self) -> &str;
 }
 
@@ -34,6 +51,24 @@ pub enum AIError {
     ConfigError(String),
     #[error("Local model error: {0}")]
     LocalError(String),
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+}
+
+impl AIError {
+    /// Map an unsuccessful HTTP status to an error; malformed requests get their own variant
+    /// because retrying them against another provider would fail the same way
+    fn from_status(status: reqwest::StatusCode, message: String) -> Self {
+        match status.as_u16() {
+            400 | 422 => AIError::BadRequest(message),
+            _ => AIError::APIError(message),
+        }
+    }
+
+    /// Whether another provider might succeed where this one failed (auth, network, 5xx)
+    pub fn allows_failover(&self) -> bool {
+        !matches!(self, AIError::BadRequest(_))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -44,20 +79,78 @@ pub struct AIConfig {
     pub base_url: Option<String>,
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
+    /// Maximum number of known issues injected into a prompt
+    pub max_known_issues: usize,
+    /// Enrich prompts with matching known issues; when off the database is never opened
+    pub use_known_issues: bool,
+    /// Upper bound on a whole `analyze*`/`answer_question` call, including retries
+    pub analysis_timeout_secs: u64,
+    /// Sampling seed for reproducible responses, where the provider supports one
+    pub seed: Option<u64>,
 }
 
+/// Default for `ai.analysis_timeout_secs`; generous enough for slow local models
+pub const DEFAULT_ANALYSIS_TIMEOUT_SECS: u64 = 120;
+
+/// System prompt for a health analysis (Anthropic and in-process models)
+const ANALYSIS_SYSTEM_PROMPT: &str = "You are an experienced Linux system administrator tasked with analyzing system health and identifying real, actionable issues. Your role is to:
+
+1. **Focus on REAL issues only** - Ignore minor warnings or expected behavior
+2. **Provide VERIFICATION steps** - Give specific commands to verify each issue
+3. **Provide CORRECTION steps** - Give specific commands to fix each issue
+4. **Prioritize by severity** - Security issues first, then performance, then configuration
+5. **Be specific and actionable** - No generic advice, only concrete steps
+6. **Consider the distribution** - Tailor advice to the specific Linux distribution
+7. **Acknowledge common non-issues** - If you see ACPI/BIOS errors but no real problems, mention they're often normal
+
+Format your response as:
+## Critical Issues (if any)
+- **Issue**: [Specific problem]
+- **Verify**: `command to check`
+- **Fix**: `command to fix`
+
+## Performance Issues (if any)
+- **Issue**: [Specific problem]
+- **Verify**: `command to check`
+- **Fix**: `command to fix`
+
+## Configuration Issues (if any)
+- **Issue**: [Specific problem]
+- **Verify**: `command to check`
+- **Fix**: `command to fix`
+
+If no actionable issues are found, state: 'System appears healthy. Any ACPI/BIOS errors shown above are often normal on Linux systems and can be ignored unless you're experiencing specific hardware problems.'";
+
+/// System prompt for answering a question about the system (Anthropic and in-process models)
+const QUESTION_SYSTEM_PROMPT: &str = "You are an experienced Linux system administrator and troubleshooting expert. Your role is to help users resolve their system issues by:
+
+1. **Listen carefully** - Understand exactly what the user is asking
+2. **Provide helpful answers** - Give clear, actionable guidance based on the system context
+3. **Be practical** - Focus on steps the user can actually take
+4. **Be conversational** - Answer in a friendly, approachable tone
+5. **Be concise** - Keep your response focused and to the point
+6. **Acknowledge limitations** - If you can't answer based on available information, say so
+
+Your goal is to help the user resolve their issue, not to perform a general system health analysis.";
+
 #[derive(Debug, Clone)]
 pub enum AIProviderType {
     OpenAI,
     Anthropic,
     Local,
+    /// A GGUF file run in-process by llama.cpp; `model` is its path
+    LlamaCpp,
 }
 
 pub struct AIClient {
     config: AIConfig,
     client: reqwest::Client,
     conversation_history: Arc<Mutex<Vec<ConversationMessage>>>,
-    known_issues: Arc<KnownIssuesDatabase>,
+    /// `None` when known-issue enrichment is turned off
+    known_issues: Option<Arc<KnownIssuesDatabase>>,
+    /// The GGUF model, loaded on first use so a run that never reaches the AI skips the load
+    #[cfg(feature = "local-inference")]
+    local_model: tokio::sync::OnceCell<Arc<llama_cpp::LocalModel>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,11 +162,18 @@ struct ConversationMessage {
 impl AIClient {
     pub async fn new(config: AIConfig) -> Result<Self, AIError> {
         let client = reqwest::Client::new();
+        let known_issues = if config.use_known_issues {
+            Some(KnownIssuesDatabase::shared().await)
+        } else {
+            None
+        };
         Ok(Self {
             config,
             client,
             conversation_history: Arc::new(Mutex::new(Vec::new())),
-            known_issues: Arc::new(KnownIssuesDatabase::new().await),
+            known_issues,
+            #[cfg(feature = "local-inference")]
+            local_model: tokio::sync::OnceCell::new(),
         })
     }
 
@@ -86,6 +186,7 @@ impl AIClient {
             "openai" => AIProviderType::OpenAI,
             "anthropic" => AIProviderType::Anthropic,
             "local" => AIProviderType::Local,
+            "llama-cpp" => AIProviderType::LlamaCpp,
             _ => {
                 return Err(AIError::ConfigError(format!(
                     "Unknown provider: {}",
@@ -99,6 +200,7 @@ impl AIClient {
             AIProviderType::OpenAI => "gpt-4o-mini".to_string(),
             AIProviderType::Anthropic => "claude-3-5-sonnet-20241022".to_string(),
             AIProviderType::Local => "llama2".to_string(),
+            AIProviderType::LlamaCpp => String::new(),
         });
 
         let base_url = env::var("AI_BASE_URL").ok();
@@ -108,6 +210,7 @@ impl AIClient {
         let temperature = env::var("AI_TEMPERATURE")
             .ok()
             .and_then(|s| s.parse::<f32>().ok());
+        let seed = env::var("AI_SEED").ok().and_then(|s| s.parse::<u64>().ok());
 
         let config = AIConfig {
             provider: provider_type,
@@ -116,11 +219,16 @@ impl AIClient {
             base_url,
             max_tokens,
             temperature,
+            max_known_issues: crate::known_issues::DEFAULT_MAX_RELEVANT_ISSUES,
+            use_known_issues: true,
+            analysis_timeout_secs: DEFAULT_ANALYSIS_TIMEOUT_SECS,
+            seed,
         };
 
         Self::new(config).await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn from_cli(
         cli_provider: &CliAIProvider,
         api_key: Option<String>,
@@ -128,17 +236,27 @@ impl AIClient {
         base_url: Option<String>,
         max_tokens: Option<u32>,
         temperature: Option<f32>,
+        seed: Option<u64>,
+        max_known_issues: usize,
+        use_known_issues: bool,
+        analysis_timeout_secs: u64,
     ) -> Result<Self, AIError> {
         let provider_type = match cli_provider {
             CliAIProvider::OpenAI => AIProviderType::OpenAI,
             CliAIProvider::Anthropic => AIProviderType::Anthropic,
             CliAIProvider::Local => AIProviderType::Local,
+            CliAIProvider::LlamaCpp => AIProviderType::LlamaCpp,
         };
 
+        if seed.is_some() && matches!(provider_type, AIProviderType::Anthropic) {
+            eprintln!("⚠️  Anthropic doesn't support a sampling seed; --seed is ignored for it");
+        }
+
         let default_model = match provider_type {
             AIProviderType::OpenAI => "gpt-4o-mini".to_string(),
             AIProviderType::Anthropic => "claude-3-5-sonnet-20241022".to_string(),
             AIProviderType::Local => "llama2".to_string(),
+            AIProviderType::LlamaCpp => String::new(),
         };
 
         let config = AIConfig {
@@ -148,20 +266,183 @@ impl AIClient {
             base_url,
             max_tokens,
             temperature,
+            max_known_issues,
+            use_known_issues,
+            analysis_timeout_secs,
+            seed,
         };
 
         Self::new(config).await
     }
+
+    /// The GGUF model at `ai.model`, loaded once per client
+    #[cfg(feature = "local-inference")]
+    async fn load_local_model(&self) -> Result<Arc<llama_cpp::LocalModel>, AIError> {
+        let model = self
+            .local_model
+            .get_or_try_init(|| async {
+                let path = std::path::PathBuf::from(&self.config.model);
+                tokio::task::spawn_blocking(move || llama_cpp::LocalModel::load(&path))
+                    .await
+                    .map_err(|e| AIError::LocalError(e.to_string()))?
+                    .map(Arc::new)
+            })
+            .await?;
+        Ok(Arc::clone(model))
+    }
+
+    #[cfg(not(feature = "local-inference"))]
+    async fn load_local_model(&self) -> Result<(), AIError> {
+        Err(AIError::ConfigError(
+            "this build of raid can't run GGUF models; rebuild it with `cargo build --features local-inference`".to_string(),
+        ))
+    }
+
+    /// Run the in-process model on a system prompt and user input. Tokens are echoed to stderr
+    /// as they arrive when it is a terminal, since CPU inference can take minutes.
+    #[cfg(feature = "local-inference")]
+    async fn generate_llama_cpp(&self, system: &'static str, input: String) -> Result<String, AIError> {
+        use std::io::IsTerminal;
+
+        let model = self.load_local_model().await?;
+        let max_tokens = self.config.max_tokens.unwrap_or(1000);
+        let temperature = self.config.temperature.unwrap_or(0.7);
+        let seed = self.config.seed;
+        let echo = std::io::stderr().is_terminal();
+        let output = tokio::task::spawn_blocking(move || {
+            let never = std::sync::atomic::AtomicBool::new(false);
+            model.generate(system, &input, max_tokens, temperature, seed, &never, |piece| {
+                if echo {
+                    eprint!("{}", piece);
+                }
+            })
+        })
+        .await
+        .map_err(|e| AIError::LocalError(e.to_string()))?;
+        if echo {
+            eprintln!();
+        }
+        output
+    }
+
+    #[cfg(not(feature = "local-inference"))]
+    async fn generate_llama_cpp(&self, _system: &'static str, _input: String) -> Result<String, AIError> {
+        self.load_local_model().await.map(|_| String::new())
+    }
+
+    /// `ai.seed`: OpenAI takes it at the top level of the request, Ollama among the options.
+    /// Anthropic has no equivalent; llama.cpp gets it in `generate_llama_cpp`.
+    fn add_seed(&self, request_body: &mut serde_json::Value) {
+        let Some(seed) = self.config.seed else {
+            return;
+        };
+        match self.config.provider {
+            AIProviderType::OpenAI => request_body["seed"] = seed.into(),
+            AIProviderType::Local => request_body["options"]["seed"] = seed.into(),
+            AIProviderType::Anthropic | AIProviderType::LlamaCpp => {}
+        }
+    }
+
+    /// Send the analysis request with streaming on and return its text as it arrives
+    async fn open_analysis_stream(&self, input: &str) -> Result<AnalysisStream<'static>, AIError> {
+        match self.config.provider {
+            AIProviderType::OpenAI => {
+                let response = self.openai_analysis_request(input, true)?.send().await?;
+                let lines = response_lines(self.successful(response, "OpenAI").await?);
+                let api_key = self.config.api_key.clone();
+                Ok(Box::pin(lines.try_filter_map(move |line| {
+                    std::future::ready(openai_stream_text(&line).map_err(|e| scrub_stream_error(e, api_key.as_deref())))
+                })))
+            }
+            AIProviderType::Anthropic => {
+                let response = self.anthropic_analysis_request(input, true)?.send().await?;
+                let lines = response_lines(self.successful(response, "Anthropic").await?);
+                let api_key = self.config.api_key.clone();
+                Ok(Box::pin(lines.try_filter_map(move |line| {
+                    std::future::ready(anthropic_stream_text(&line).map_err(|e| scrub_stream_error(e, api_key.as_deref())))
+                })))
+            }
+            AIProviderType::Local => {
+                let base_url = self
+                    .config
+                    .base_url
+                    .as_deref()
+                    .unwrap_or("http://localhost:11434");
+                // Like analyze_local, fall back to the placeholder when Ollama can't be reached
+                match self.ollama_analysis_request(base_url, input, true).send().await {
+                    Ok(response) if response.status().is_success() => Ok(Box::pin(
+                        response_lines(response).try_filter_map(|line| std::future::ready(ollama_stream_text(&line))),
+                    )),
+                    _ => Ok(Box::pin(stream::once(std::future::ready(Ok(local_placeholder_analysis(input)))))),
+                }
+            }
+            AIProviderType::LlamaCpp => self.stream_llama_cpp(ANALYSIS_SYSTEM_PROMPT, input.to_string()).await,
+        }
+    }
+
+    /// Run the in-process model, yielding each piece of text as it is decoded
+    #[cfg(feature = "local-inference")]
+    async fn stream_llama_cpp(&self, system: &'static str, input: String) -> Result<AnalysisStream<'static>, AIError> {
+        let model = self.load_local_model().await?;
+        let max_tokens = self.config.max_tokens.unwrap_or(1000);
+        let temperature = self.config.temperature.unwrap_or(0.7);
+        let seed = self.config.seed;
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        tokio::task::spawn_blocking(move || {
+            // Once the stream is dropped nobody reads the rest, so stop decoding it
+            let cancelled = std::sync::atomic::AtomicBool::new(false);
+            let output = model.generate(system, &input, max_tokens, temperature, seed, &cancelled, |piece| {
+                if sender.send(Ok(piece.to_string())).is_err() {
+                    cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+                }
+            });
+            if let Err(e) = output {
+                let _ = sender.send(Err(e));
+            }
+        });
+        Ok(Box::pin(stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|chunk| (chunk, receiver))
+        })))
+    }
+
+    #[cfg(not(feature = "local-inference"))]
+    async fn stream_llama_cpp(&self, _system: &'static str, _input: String) -> Result<AnalysisStream<'static>, AIError> {
+        self.load_local_model().await?;
+        Ok(Box::pin(stream::empty()))
+    }
+
+    /// Bound a whole analysis call so a slowly streaming provider cannot hang the run
+    async fn with_analysis_timeout(
+        &self,
+        analysis: impl std::future::Future<Output = Result<String, AIError>>,
+    ) -> Result<String, AIError> {
+        let limit = std::time::Duration::from_secs(self.config.analysis_timeout_secs);
+        tokio::time::timeout(limit, analysis).await.map_err(|_| {
+            AIError::APIError(format!(
+                "analysis timed out after {}s",
+                self.config.analysis_timeout_secs
+            ))
+        })?
+    }
 }
 
 #[async_trait]
 impl AIProvider for AIClient {
     async fn analyze(&self, input: &str) -> Result<String, AIError> {
-        match self.config.provider {
-            AIProviderType::OpenAI => self.analyze_openai(input).await,
-            AIProviderType::Anthropic => self.analyze_anthropic(input).await,
-            AIProviderType::Local => self.analyze_local(input).await,
-        }
+        self.with_analysis_timeout(async {
+            match self.config.provider {
+                AIProviderType::OpenAI => self.analyze_openai(input).await,
+                AIProviderType::Anthropic => self.analyze_anthropic(input).await,
+                AIProviderType::Local => self.analyze_local(input).await,
+                AIProviderType::LlamaCpp => self.generate_llama_cpp(ANALYSIS_SYSTEM_PROMPT, input.to_string()).await,
+            }
+        })
+        .await
+    }
+
+    fn analyze_stream<'a>(&'a self, input: &'a str) -> AnalysisStream<'a> {
+        let chunks = stream::once(self.open_analysis_stream(input)).try_flatten();
+        with_stream_deadline(Box::pin(chunks), self.config.analysis_timeout_secs)
     }
 
     async fn analyze_with_known_issues(
@@ -169,28 +450,27 @@ impl AIProvider for AIClient {
         input: &str,
         category: Option<IssueCategory>,
     ) -> Result<String, AIError> {
+        let Some(known_issues) = &self.known_issues else {
+            return self.analyze(input).await;
+        };
+
         // Get relevant known issues for this context
-        let relevant_issues = self
-            .known_issues
-            .get_relevant_issues_for_context(input, category)
+        let relevant_issues = known_issues
+            .get_scored_issues_for_context(input, category, self.config.max_known_issues)
             .await;
 
         // Build enhanced prompt with known issues
-        let mut enhanced_input = input.to_string();
-        if !relevant_issues.is_empty() {
-            enhanced_input.push_str("\n\nKNOWN ISSUES THAT MAY BE RELEVANT:\n");
-            for issue in relevant_issues {
-                enhanced_input.push_str(&format!("- {}: {}\n", issue.title, issue.description));
+        let enhanced_input = format!("{}{}", input, known_issues_prompt_section(&relevant_issues));
+
+        self.with_analysis_timeout(async {
+            match self.config.provider {
+                AIProviderType::OpenAI => self.analyze_openai(&enhanced_input).await,
+                AIProviderType::Anthropic => self.analyze_anthropic(&enhanced_input).await,
+                AIProviderType::Local => self.analyze_local(&enhanced_input).await,
+                AIProviderType::LlamaCpp => self.generate_llama_cpp(ANALYSIS_SYSTEM_PROMPT, enhanced_input.clone()).await,
             }
-            enhanced_input
-                .push_str("\nConsider these known issues when analyzing the system state.\n");
-        }
-
-        match self.config.provider {
-            AIProviderType::OpenAI => self.analyze_openai(&enhanced_input).await,
-            AIProviderType::Anthropic => self.analyze_anthropic(&enhanced_input).await,
-            AIProviderType::Local => self.analyze_local(&enhanced_input).await,
-        }
+        })
+        .await
     }
 
     async fn answer_question(
@@ -199,34 +479,102 @@ impl AIProvider for AIClient {
         system_context: &str,
     ) -> Result<String, AIError> {
         // Get relevant known issues for this context
-        let relevant_issues = self
-            .known_issues
-            .get_relevant_issues_for_context(question, None)
-            .await;
+        let relevant_issues = match &self.known_issues {
+            Some(known_issues) => {
+                known_issues
+                    .get_scored_issues_for_context(question, None, self.config.max_known_issues)
+                    .await
+            }
+            None => Vec::new(),
+        };
 
         // Build context with known issues
         let mut enhanced_context = system_context.to_string();
         if !relevant_issues.is_empty() {
             enhanced_context.push_str("\n\nRELEVANT KNOWN ISSUES:\n");
-            for issue in relevant_issues {
+            for (issue, _score) in relevant_issues {
                 enhanced_context.push_str(&format!("- {}: {}\n", issue.title, issue.description));
             }
         }
 
-        match self.config.provider {
+        self.with_analysis_timeout(async {
+            match self.config.provider {
+                AIProviderType::OpenAI => {
+                    self.answer_question_openai(question, &enhanced_context)
+                        .await
+                }
+                AIProviderType::Anthropic => {
+                    self.answer_question_anthropic(question, &enhanced_context)
+                        .await
+                }
+                AIProviderType::Local => {
+                    self.answer_question_local(question, &enhanced_context)
+                        .await
+                }
+                AIProviderType::LlamaCpp => {
+                    let prompt = format!("System Context:\n{}\n\nUser Question: {}", enhanced_context, question);
+                    self.generate_llama_cpp(QUESTION_SYSTEM_PROMPT, prompt).await
+                }
+            }
+        })
+        .await
+    }
+
+    async fn health_check(&self) -> Result<(), AIError> {
+        let timeout = std::time::Duration::from_secs(10);
+        let request = match self.config.provider {
             AIProviderType::OpenAI => {
-                self.answer_question_openai(question, &enhanced_context)
-                    .await
+                let api_key = self
+                    .config
+                    .api_key
+                    .as_ref()
+                    .ok_or_else(|| AIError::ConfigError("OpenAI API key not found".to_string()))?;
+                let base_url = self
+                    .config
+                    .base_url
+                    .as_deref()
+                    .unwrap_or("https://api.openai.com/v1");
+                self.client
+                    .get(format!("{}/models", base_url))
+                    .header("Authorization", format!("Bearer {}", api_key))
             }
             AIProviderType::Anthropic => {
-                self.answer_question_anthropic(question, &enhanced_context)
-                    .await
+                let api_key = self
+                    .config
+                    .api_key
+                    .as_ref()
+                    .ok_or_else(|| AIError::ConfigError("Anthropic API key not found".to_string()))?;
+                let base_url = self
+                    .config
+                    .base_url
+                    .as_deref()
+                    .unwrap_or("https://api.anthropic.com/v1");
+                self.client
+                    .get(format!("{}/models", base_url))
+                    .header("x-api-key", api_key)
+                    .header("anthropic-version", "2023-06-01")
             }
             AIProviderType::Local => {
-                self.answer_question_local(question, &enhanced_context)
-                    .await
+                let base_url = self
+                    .config
+                    .base_url
+                    .as_deref()
+                    .unwrap_or("http://localhost:11434");
+                self.client.get(format!("{}/api/tags", base_url))
             }
+            // Nothing to reach; loading the model proves it is usable
+            AIProviderType::LlamaCpp => return self.load_local_model().await.map(|_| ()),
+        };
+
+        let response = request.timeout(timeout).send().await?;
+        if !response.status().is_success() {
+            return Err(AIError::APIError(format!(
+                "{} returned HTTP {}",
+                self.name(),
+                response.status()
+            )));
         }
+        Ok(())
     }
 
     fn name(&self) -> &str {
@@ -234,12 +582,33 @@ impl AIProvider for AIClient {
             AIProviderType::OpenAI => "OpenAI",
             AIProviderType::Anthropic => "Anthropic",
             AIProviderType::Local => "Local",
+            AIProviderType::LlamaCpp => "llama.cpp",
         }
     }
 }
 
 impl AIClient {
+    /// Replace the API key in provider error text so it never reaches logs or output
+    fn scrub_secrets(&self, text: &str) -> String {
+        mask_api_key(text, self.config.api_key.as_deref())
+    }
+
     async fn analyze_openai(&self, input: &str) -> Result<String, AIError> {
+        let response = self.openai_analysis_request(input, false)?.send().await?;
+        let response = self.successful(response, "OpenAI").await?;
+
+        let response_json: serde_json::Value = response.json().await?;
+
+        let content = response_json["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| AIError::APIError("Invalid response format".to_string()))?;
+
+        Ok(content.to_string())
+    }
+
+    /// The chat completion request for an analysis; with `stream` the reply comes as
+    /// server-sent events
+    fn openai_analysis_request(&self, input: &str, stream: bool) -> Result<reqwest::RequestBuilder, AIError> {
         let api_key = self
             .config
             .api_key
@@ -290,40 +659,53 @@ If no actionable issues are found, state: 'System appears healthy. Any ACPI/BIOS
             },
         ];
 
-        let request_body = serde_json::json!({
+        let mut request_body = serde_json::json!({
             "model": self.config.model,
             "messages": messages,
             "max_tokens": self.config.max_tokens.unwrap_or(1000),
             "temperature": self.config.temperature.unwrap_or(0.7),
         });
+        if stream {
+            request_body["stream"] = true.into();
+        }
+        self.add_seed(&mut request_body);
 
-        let response = self
+        Ok(self
             .client
             .post(&format!("{}/chat/completions", base_url))
             .header("Authorization", format!("Bearer {}", api_key))
             .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await?;
+            .json(&request_body))
+    }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(AIError::APIError(format!(
-                "OpenAI API error: {}",
-                error_text
-            )));
+    /// The response, or the provider's error text (with the API key scrubbed) for a failed status
+    async fn successful(&self, response: reqwest::Response, provider: &str) -> Result<reqwest::Response, AIError> {
+        if response.status().is_success() {
+            return Ok(response);
         }
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        Err(AIError::from_status(
+            status,
+            format!("{} API error: {}", provider, self.scrub_secrets(&error_text)),
+        ))
+    }
+
+    async fn analyze_anthropic(&self, input: &str) -> Result<String, AIError> {
+        let response = self.anthropic_analysis_request(input, false)?.send().await?;
+        let response = self.successful(response, "Anthropic").await?;
 
         let response_json: serde_json::Value = response.json().await?;
 
-        let content = response_json["choices"][0]["message"]["content"]
+        let content = response_json["content"][0]["text"]
             .as_str()
             .ok_or_else(|| AIError::APIError("Invalid response format".to_string()))?;
 
         Ok(content.to_string())
     }
 
-    async fn analyze_anthropic(&self, input: &str) -> Result<String, AIError> {
+    /// The messages request for an analysis; with `stream` the reply comes as server-sent events
+    fn anthropic_analysis_request(&self, input: &str, stream: bool) -> Result<reqwest::RequestBuilder, AIError> {
         let api_key = self
             .config
             .api_key
@@ -340,33 +722,8 @@ If no actionable issues are found, state: 'System appears healthy. Any ACPI/BIOS
             "model": self.config.model,
             "max_tokens": self.config.max_tokens.unwrap_or(1000),
             "temperature": self.config.temperature.unwrap_or(0.7),
-            "system": "You are an experienced Linux system administrator tasked with analyzing system health and identifying real, actionable issues. Your role is to:
-
-1. **Focus on REAL issues only** - Ignore minor warnings or expected behavior
-2. **Provide VERIFICATION steps** - Give specific commands to verify each issue
-3. **Provide CORRECTION steps** - Give specific commands to fix each issue
-4. **Prioritize by severity** - Security issues first, then performance, then configuration
-5. **Be specific and actionable** - No generic advice, only concrete steps
-6. **Consider the distribution** - Tailor advice to the specific Linux distribution
-7. **Acknowledge common non-issues** - If you see ACPI/BIOS errors but no real problems, mention they're often normal
-
-Format your response as:
-## Critical Issues (if any)
-- **Issue**: [Specific problem]
-- **Verify**: `command to check`
-- **Fix**: `command to fix`
-
-## Performance Issues (if any)
-- **Issue**: [Specific problem]
-- **Verify**: `command to check`
-- **Fix**: `command to fix`
-
-## Configuration Issues (if any)
-- **Issue**: [Specific problem]
-- **Verify**: `command to check`
-- **Fix**: `command to fix`
-
-If no actionable issues are found, state: 'System appears healthy. Any ACPI/BIOS errors shown above are often normal on Linux systems and can be ignored unless you're experiencing specific hardware problems.'",
+            "system": ANALYSIS_SYSTEM_PROMPT,
+            "stream": stream,
             "messages": [
                 {
                     "role": "user",
@@ -375,31 +732,13 @@ If no actionable issues are found, state: 'System appears healthy. Any ACPI/BIOS
             ]
         });
 
-        let response = self
+        Ok(self
             .client
             .post(&format!("{}/messages", base_url))
             .header("x-api-key", api_key)
             .header("anthropic-version", "2023-06-01")
             .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(AIError::APIError(format!(
-                "Anthropic API error: {}",
-                error_text
-            )));
-        }
-
-        let response_json: serde_json::Value = response.json().await?;
-
-        let content = response_json["content"][0]["text"]
-            .as_str()
-            .ok_or_else(|| AIError::APIError("Invalid response format".to_string()))?;
-
-        Ok(content.to_string())
+            .json(&request_body))
     }
 
     async fn analyze_local(&self, input: &str) -> Result<String, AIError> {
@@ -418,14 +757,29 @@ If no actionable issues are found, state: 'System appears healthy. Any ACPI/BIOS
         }
 
         // Fallback to a simple local analysis
-        Ok(format!(
-            "[Local AI] Analysis of system information: {}. This is a placeholder response. To use a real local model, configure Ollama or another local model server.",
-            input
-        ))
+        Ok(local_placeholder_analysis(input))
     }
 
     async fn try_ollama(&self, base_url: &str, input: &str) -> Result<String, AIError> {
-        let request_body = serde_json::json!({
+        let response = self.ollama_analysis_request(base_url, input, false).send().await?;
+
+        if !response.status().is_success() {
+            return Err(AIError::LocalError("Ollama request failed".to_string()));
+        }
+
+        let response_json: serde_json::Value = response.json().await?;
+
+        let content = response_json["response"]
+            .as_str()
+            .ok_or_else(|| AIError::LocalError("Invalid Ollama response format".to_string()))?;
+
+        Ok(content.to_string())
+    }
+
+    /// The generate request for an analysis; with `stream` the reply comes as one JSON
+    /// object per line
+    fn ollama_analysis_request(&self, base_url: &str, input: &str, stream: bool) -> reqwest::RequestBuilder {
+        let mut request_body = serde_json::json!({
             "model": self.config.model,
             "prompt": format!("You are an experienced Linux system administrator tasked with analyzing system health and identifying real, actionable issues. Your role is to:
 
@@ -466,32 +820,18 @@ Format your response as:
 If no actionable issues are found, state: 'System appears healthy. Any ACPI/BIOS errors shown above are often normal on Linux systems and can be ignored unless you're experiencing specific hardware problems.'
 
 Analyze the following system information: {}", input),
-            "stream": false,
+            "stream": stream,
             "options": {
                 "temperature": self.config.temperature.unwrap_or(0.7),
                 "num_predict": self.config.max_tokens.unwrap_or(10000),
             }
         });
+        self.add_seed(&mut request_body);
 
-        let response = self
-            .client
+        self.client
             .post(&format!("{}/api/generate", base_url))
             .header("Content-Type", "application/json")
             .json(&request_body)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(AIError::LocalError("Ollama request failed".to_string()));
-        }
-
-        let response_json: serde_json::Value = response.json().await?;
-
-        let content = response_json["response"]
-            .as_str()
-            .ok_or_else(|| AIError::LocalError("Invalid Ollama response format".to_string()))?;
-
-        Ok(content.to_string())
     }
 
     async fn answer_question_openai(
@@ -531,12 +871,13 @@ Your goal is to help the user resolve their issue, not to perform a general syst
             },
         ];
 
-        let request_body = serde_json::json!({
+        let mut request_body = serde_json::json!({
             "model": self.config.model,
             "messages": messages,
             "max_tokens": self.config.max_tokens.unwrap_or(1000),
             "temperature": self.config.temperature.unwrap_or(0.7),
         });
+        self.add_seed(&mut request_body);
 
         let response = self
             .client
@@ -548,11 +889,12 @@ Your goal is to help the user resolve their issue, not to perform a general syst
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            return Err(AIError::APIError(format!(
-                "OpenAI API error: {}",
-                error_text
-            )));
+            return Err(AIError::from_status(
+                status,
+                format!("OpenAI API error: {}", self.scrub_secrets(&error_text)),
+            ));
         }
 
         let response_json: serde_json::Value = response.json().await?;
@@ -585,16 +927,7 @@ Your goal is to help the user resolve their issue, not to perform a general syst
             "model": self.config.model,
             "max_tokens": self.config.max_tokens.unwrap_or(1000),
             "temperature": self.config.temperature.unwrap_or(0.7),
-            "system": "You are an experienced Linux system administrator and troubleshooting expert. Your role is to help users resolve their system issues by:
-
-1. **Listen carefully** - Understand exactly what the user is asking
-2. **Provide helpful answers** - Give clear, actionable guidance based on the system context
-3. **Be practical** - Focus on steps the user can actually take
-4. **Be conversational** - Answer in a friendly, approachable tone
-5. **Be concise** - Keep your response focused and to the point
-6. **Acknowledge limitations** - If you can't answer based on available information, say so
-
-Your goal is to help the user resolve their issue, not to perform a general system health analysis.",
+            "system": QUESTION_SYSTEM_PROMPT,
             "messages": [
                 {
                     "role": "user",
@@ -614,11 +947,12 @@ Your goal is to help the user resolve their issue, not to perform a general syst
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            return Err(AIError::APIError(format!(
-                "Anthropic API error: {}",
-                error_text
-            )));
+            return Err(AIError::from_status(
+                status,
+                format!("Anthropic API error: {}", self.scrub_secrets(&error_text)),
+            ));
         }
 
         let response_json: serde_json::Value = response.json().await?;
@@ -662,7 +996,7 @@ Your goal is to help the user resolve their issue, not to perform a general syst
         question: &str,
         system_context: &str,
     ) -> Result<String, AIError> {
-        let request_body = serde_json::json!({
+        let mut request_body = serde_json::json!({
             "model": self.config.model,
             "prompt": format!("You are an experienced Linux system administrator and troubleshooting expert. Your role is to help users resolve their system issues by:
 
@@ -685,6 +1019,7 @@ User Question: {}", system_context, question),
                 "num_predict": self.config.max_tokens.unwrap_or(1000),
             }
         });
+        self.add_seed(&mut request_body);
 
         let response = self
             .client
@@ -708,13 +1043,142 @@ User Question: {}", system_context, question),
     }
 }
 
-// Legacy DummyAI for testing
-pub struct DummyAI;
+/// The answer when `ai.provider: local` finds no Ollama server
+fn local_placeholder_analysis(input: &str) -> String {
+    format!(
+        "[Local AI] Analysis of system information: {}. This is a placeholder response. To use a real local model, configure Ollama or another local model server.",
+        input
+    )
+}
 
-#[async_trait]
+/// End `chunks` with an error once `timeout_secs` have passed, so a slowly streaming
+/// provider cannot hang the run
+fn with_stream_deadline(chunks: AnalysisStream<'_>, timeout_secs: u64) -> AnalysisStream<'_> {
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+    Box::pin(stream::unfold(Some(chunks), move |chunks| async move {
+        let mut chunks = chunks?;
+        match tokio::time::timeout_at(deadline, chunks.next()).await {
+            Ok(Some(chunk)) => Some((chunk, Some(chunks))),
+            Ok(None) => None,
+            Err(_) => Some((Err(AIError::APIError(format!("analysis timed out after {}s", timeout_secs))), None)),
+        }
+    }))
+}
+
+/// Remove and return the first complete line of `buffer`, without its line ending
+fn take_line(buffer: &mut Vec<u8>) -> Option<String> {
+    let end = buffer.iter().position(|byte| *byte == b'\n')?;
+    let line: Vec<u8> = buffer.drain(..=end).collect();
+    Some(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// A streamed response body one line at a time, however the lines are split across chunks
+fn response_lines(response: reqwest::Response) -> impl Stream<Item = Result<String, AIError>> + Send + 'static {
+    stream::try_unfold((Some(response), Vec::new()), |(mut response, mut buffer)| async move {
+        loop {
+            if let Some(line) = take_line(&mut buffer) {
+                return Ok(Some((line, (response, buffer))));
+            }
+            let chunk = match response.as_mut() {
+                Some(body) => body.chunk().await?,
+                None => None,
+            };
+            match chunk {
+                Some(bytes) => buffer.extend_from_slice(&bytes),
+                None if buffer.is_empty() => return Ok(None),
+                // The body is done; its last line may have no line ending
+                None => {
+                    response = None;
+                    buffer.push(b'\n');
+                }
+            }
+        }
+    })
+}
+
+/// The payload of a server-sent event `data:` line; event names, ids and comments carry no text
+fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim_start)
+}
+
+fn stream_event(data: &str) -> Result<serde_json::Value, AIError> {
+    serde_json::from_str(data).map_err(|e| AIError::APIError(format!("Invalid stream event: {}", e)))
+}
+
+/// `text` with the API key masked the way `config show` masks it
+fn mask_api_key(text: &str, api_key: Option<&str>) -> String {
+    match api_key {
+        Some(key) if !key.is_empty() => text.replace(key, &crate::config::mask_secret(key)),
+        _ => text.to_string(),
+    }
+}
+
+/// An error event from a provider stream, with the API key scrubbed like `successful` does
+fn scrub_stream_error(error: AIError, api_key: Option<&str>) -> AIError {
+    match error {
+        AIError::APIError(message) => AIError::APIError(mask_api_key(&message, api_key)),
+        other => other,
+    }
+}
+
+/// The text in one line of an OpenAI chat completion stream, which ends with `data: [DONE]`
+fn openai_stream_text(line: &str) -> Result<Option<String>, AIError> {
+    let Some(data) = sse_data(line).filter(|data| *data != "[DONE]") else {
+        return Ok(None);
+    };
+    let event = stream_event(data)?;
+    if let Some(message) = event["error"]["message"].as_str() {
+        return Err(AIError::APIError(format!("OpenAI API error: {}", message)));
+    }
+    Ok(event["choices"][0]["delta"]["content"]
+        .as_str()
+        .filter(|text| !text.is_empty())
+        .map(str::to_string))
+}
+
+/// The text in one line of an Anthropic message stream: only `content_block_delta` events
+/// carry any; the others mark the start and end of the message and its blocks
+fn anthropic_stream_text(line: &str) -> Result<Option<String>, AIError> {
+    let Some(data) = sse_data(line) else {
+        return Ok(None);
+    };
+    let event = stream_event(data)?;
+    match event["type"].as_str() {
+        Some("content_block_delta") => Ok(event["delta"]["text"]
+            .as_str()
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)),
+        Some("error") => Err(AIError::APIError(format!(
+            "Anthropic API error: {}",
+            event["error"]["message"].as_str().unwrap_or("stream failed")
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// The text in one line of an Ollama generate stream, which is a JSON object per line
+fn ollama_stream_text(line: &str) -> Result<Option<String>, AIError> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let event: serde_json::Value = serde_json::from_str(line)
+        .map_err(|e| AIError::LocalError(format!("Invalid Ollama response format: {}", e)))?;
+    if let Some(message) = event["error"].as_str() {
+        return Err(AIError::LocalError(format!("Ollama error: {}", message)));
+    }
+    Ok(event["response"].as_str().filter(|text| !text.is_empty()).map(str::to_string))
+}
+
+const DUMMY_ANALYSIS: &str = "System appears healthy. Any ACPI/BIOS errors shown above are often normal on Linux systems and can be ignored unless you're experiencing specific hardware problems.";
+
+// Legacy DummyAI for testing
+pub struct DummyAI;
+
+#[async_trait]
 impl AIProvider for DummyAI {
-    async fn analyze(&self, _input: &str) -> Result<String, AIError> {
-        Ok("System appears healthy. Any ACPI/BIOS errors shown above are often normal on Linux systems and can be ignored unless you're experiencing specific hardware problems.".to_string())
+    /// The whole canned analysis as a single chunk
+    fn analyze_stream<'a>(&'a self, _input: &'a str) -> AnalysisStream<'a> {
+        Box::pin(stream::once(std::future::ready(Ok(DUMMY_ANALYSIS.to_string()))))
     }
 
     async fn analyze_with_known_issues(
@@ -722,7 +1186,7 @@ impl AIProvider for DummyAI {
         _input: &str,
         _category: Option<IssueCategory>,
     ) -> Result<String, AIError> {
-        Ok("System appears healthy. Any ACPI/BIOS errors shown above are often normal on Linux systems and can be ignored unless you're experiencing specific hardware problems.".to_string())
+        Ok(DUMMY_ANALYSIS.to_string())
     }
 
     async fn answer_question(
@@ -733,6 +1197,10 @@ impl AIProvider for DummyAI {
         Ok("I cannot answer that question.".to_string())
     }
 
+    async fn health_check(&self) -> Result<(), AIError> {
+        Ok(())
+    }
+
     fn name(&self) -> &str {
         "DummyAI"
     }
@@ -750,6 +1218,7 @@ pub async fn create_ai_provider() -> Result<Box<dyn AIProvider>, AIError> {
 }
 
 // Factory function to create AI provider from CLI
+#[allow(clippy::too_many_arguments)]
 pub async fn create_ai_provider_from_cli(
     cli_provider: &CliAIProvider,
     api_key: Option<String>,
@@ -757,6 +1226,11 @@ pub async fn create_ai_provider_from_cli(
     base_url: Option<String>,
     max_tokens: Option<u32>,
     temperature: Option<f32>,
+    seed: Option<u64>,
+    max_known_issues: usize,
+    use_known_issues: bool,
+    analysis_timeout_secs: u64,
+    fallback_providers: &[ProviderConfig],
 ) -> Result<Box<dyn AIProvider>, AIError> {
     if let Ok(client) = AIClient::from_cli(
         cli_provider,
@@ -765,24 +1239,426 @@ pub async fn create_ai_provider_from_cli(
         base_url,
         max_tokens,
         temperature,
+        seed,
+        max_known_issues,
+        use_known_issues,
+        analysis_timeout_secs,
     )
     .await
     {
-        return Ok(Box::new(client));
+        if fallback_providers.is_empty() {
+            return Ok(Box::new(client));
+        }
+
+        let mut chain: Vec<Box<dyn AIProvider>> = vec![Box::new(client)];
+        for fallback in fallback_providers {
+            let client = AIClient::from_cli(
+                &fallback.get_ai_provider(),
+                fallback.api_key.clone(),
+                fallback.model.clone(),
+                fallback.base_url.clone(),
+                max_tokens,
+                temperature,
+                seed,
+                max_known_issues,
+                use_known_issues,
+                analysis_timeout_secs,
+            )
+            .await?;
+            chain.push(Box::new(client));
+        }
+        return Ok(Box::new(FallbackAIProvider::new(chain)));
     }
 
     // Fallback to dummy AI
     Ok(Box::new(DummyAI))
 }
 
+/// Tries each provider in order until one answers. Errors that would fail the same way
+/// everywhere (a malformed request) are returned immediately instead of failing over.
+pub struct FallbackAIProvider {
+    providers: Vec<Box<dyn AIProvider>>,
+}
+
+type ProviderCall<'a> = std::pin::Pin<Box<dyn std::future::Future<Output = Result<String, AIError>> + Send + 'a>>;
+
+impl FallbackAIProvider {
+    pub fn new(providers: Vec<Box<dyn AIProvider>>) -> Self {
+        Self { providers }
+    }
+
+    async fn first_success<'a>(
+        &'a self,
+        call: impl Fn(&'a dyn AIProvider) -> ProviderCall<'a>,
+    ) -> Result<String, AIError> {
+        let mut last_error = AIError::ConfigError("no AI providers configured".to_string());
+        for (index, provider) in self.providers.iter().enumerate() {
+            match call(provider.as_ref()).await {
+                Ok(response) => {
+                    if index > 0 {
+                        eprintln!("✅ Answered by fallback AI provider {}", provider.name());
+                    }
+                    return Ok(response);
+                }
+                Err(e) if !e.allows_failover() => return Err(e),
+                Err(e) => {
+                    if let Some(next) = self.providers.get(index + 1) {
+                        eprintln!("⚠️  {} failed ({}); trying {}", provider.name(), e, next.name());
+                    }
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    /// The stream of the first provider that produces a chunk. Failing over is only possible
+    /// before any text has been shown; an error after that ends the stream.
+    async fn first_stream<'a>(&'a self, input: &'a str) -> AnalysisStream<'a> {
+        let mut last_error = AIError::ConfigError("no AI providers configured".to_string());
+        for (index, provider) in self.providers.iter().enumerate() {
+            let mut chunks = provider.analyze_stream(input);
+            match chunks.next().await {
+                Some(Ok(first)) => {
+                    if index > 0 {
+                        eprintln!("✅ Answered by fallback AI provider {}", provider.name());
+                    }
+                    return Box::pin(stream::once(std::future::ready(Ok(first))).chain(chunks));
+                }
+                None => return chunks,
+                Some(Err(e)) if !e.allows_failover() => {
+                    last_error = e;
+                    break;
+                }
+                Some(Err(e)) => {
+                    if let Some(next) = self.providers.get(index + 1) {
+                        eprintln!("⚠️  {} failed ({}); trying {}", provider.name(), e, next.name());
+                    }
+                    last_error = e;
+                }
+            }
+        }
+        Box::pin(stream::once(std::future::ready(Err(last_error))))
+    }
+}
+
+#[async_trait]
+impl AIProvider for FallbackAIProvider {
+    async fn analyze(&self, input: &str) -> Result<String, AIError> {
+        self.first_success(|provider| provider.analyze(input)).await
+    }
+
+    fn analyze_stream<'a>(&'a self, input: &'a str) -> AnalysisStream<'a> {
+        Box::pin(stream::once(self.first_stream(input)).flatten())
+    }
+
+    async fn analyze_with_known_issues(
+        &self,
+        input: &str,
+        category: Option<IssueCategory>,
+    ) -> Result<String, AIError> {
+        self.first_success(|provider| provider.analyze_with_known_issues(input, category.clone()))
+            .await
+    }
+
+    async fn answer_question(
+        &self,
+        question: &str,
+        system_context: &str,
+    ) -> Result<String, AIError> {
+        self.first_success(|provider| provider.answer_question(question, system_context))
+            .await
+    }
+
+    /// Healthy when any provider in the chain is reachable
+    async fn health_check(&self) -> Result<(), AIError> {
+        self.first_success(|provider| {
+            Box::pin(async move { provider.health_check().await.map(|_| String::new()) })
+        })
+        .await
+        .map(|_| ())
+    }
+
+    fn name(&self) -> &str {
+        self.providers.first().map_or("Fallback", |provider| provider.name())
+    }
+}
+
 /// Multi-round AI agent that can iteratively call tools
+/// How many times in a row the agent asks the model to restate an unparseable response
+pub const MAX_FORMAT_REPROMPTS: usize = 2;
+
+/// Longest `--duration` the agent may ask a sampling tool (bpftrace, tcpdump, strace) for,
+/// so a model asking for a day of tracing can't hang the run
+pub const AGENT_MAX_DURATION_SECS: u64 = 30;
+
+const FORMAT_REPROMPT: &str = "Your response didn't use the required format. Please respond with exactly one of:\n\nREASONING: <why> followed by CALL_TOOL: <tool_name> [arguments]\nANALYZE: <analysis>\nCOMPLETE: <final analysis>";
+
+const EXPLAIN_REASONING_PROMPT: &str = "\n\nThe user is learning Linux administration and wants to follow your investigation. Every CALL_TOOL must be preceded by a REASONING: line that says what you suspect, why this tool can confirm or rule it out, and what you expect to see.";
+
+/// `--apply-fixes`: appended to the agent's task, or to each per-category input, so the final
+/// answer has Verify/Fix pairs that `output::parse_fix_commands` can read and a verify exit
+/// status that means something
+pub const FIX_COMMANDS_PROMPT: &str = "\n\nIn your final answer (the COMPLETE: answer when you are using tools), list each problem as:\n- **Issue**: <the problem>\n- **Verify**: `<one command that exits non-zero while the problem exists and zero once it is fixed>`\n- **Fix**: `<one command that fixes it>`\nUse commands the user can run as written, without placeholders. Never suggest deleting data or reformatting disks.";
+
+const REASONING_REPROMPT: &str = "Your tool call was missing its reasoning. Repeat it with a REASONING: line first explaining why this tool is needed and what you expect it to show:\n\nREASONING: <why>\nCALL_TOOL: <tool_name> [arguments]";
+
+/// The agent's system message: the tool list, the collected system context and the response format
+pub fn agent_system_prompt(system_context: &str, explain_reasoning: bool) -> String {
+    format!(
+        "You are an expert Linux systems administrator and Kubernetes operator. You can iteratively call diagnostic tools to help solve problems.
+
+Available tools:
+{}
+
+System Context:
+{}
+
+Your task is to help diagnose and solve the user's problem by:
+1. Analyzing the problem description
+2. Calling appropriate diagnostic tools to gather information
+3. Making decisions based on tool results
+4. Calling additional tools if needed to get a complete picture
+5. Continue investigating until you have thoroughly examined all relevant aspects
+6. Only provide a final analysis when you are confident you have gathered sufficient information
+
+IMPORTANT: Be thorough in your investigation. Use multiple tools to cross-reference findings and build a complete understanding of the system state. Do not stop early - continue checking different aspects until you have a comprehensive view.
+
+IMPORTANT: For each response, you MUST use one of these formats:
+
+For tool calls, use this EXACT format:
+REASONING: <explanation of why this tool is needed and what you're checking>
+CALL_TOOL: <tool_name> [arguments]
+
+For analysis without tools:
+ANALYZE: <analysis>
+
+For final solutions:
+COMPLETE: <final_analysis>
+
+CRITICAL: When calling any tool, you MUST first provide a REASONING: line explaining:
+- What you're trying to check or diagnose
+- Why this specific tool is the right choice
+- What information you expect to gather
+
+Example:
+REASONING: Checking memory usage to identify potential memory leaks or high consumption that could cause system slowdown
+CALL_TOOL: free
+
+If you can answer the question with current information, use COMPLETE: followed by your answer.{}",
+        available_tools_description(),
+        system_context,
+        if explain_reasoning { EXPLAIN_REASONING_PROMPT } else { "" }
+    )
+}
+
+/// The tool list given to the agent, grouped by category
+fn available_tools_description() -> String {
+    r#"
+KUBERNETES TOOLS:
+- kubectl_get_pods [--namespace <ns>]: List all pods in namespace
+- kubectl_describe_pod <pod_name> [--namespace <ns>]: Get detailed pod information (REQUIRES pod name)
+- kubectl_get_services [--namespace <ns>]: List all services in namespace
+- kubectl_get_nodes: List all cluster nodes
+- kubectl_describe_node <node_name>: Show a node's Conditions (MemoryPressure, DiskPressure, PIDPressure, Ready) and Allocatable resources (REQUIRES node name)
+- kubectl_get_events [--namespace <ns>]: Get recent cluster events
+
+IMPORTANT: For kubectl_describe_pod, you MUST provide a pod name. First use kubectl_get_pods to see available pods, then describe specific ones.
+Example: 
+  1. CALL_TOOL: kubectl_get_pods --namespace kube-system
+  2. CALL_TOOL: kubectl_describe_pod coredns-12345 --namespace kube-system
+
+When pods are Evicted or stuck Pending, check node health: run kubectl_get_nodes, then kubectl_describe_node <node_name> to look for pressure conditions or exhausted allocatable resources.
+
+NETWORK DIAGNOSTIC TOOLS:
+- ip_addr: Show network interfaces and IP addresses
+- ip_route: Show routing table
+- ss: Show socket statistics and listening ports
+- ping: Test connectivity to 8.8.8.8 (Google DNS)
+- dig: Perform DNS lookup for google.com
+- traceroute: Trace network route to 8.8.8.8
+- dns_config: Show DNS configuration (/etc/resolv.conf)
+- dns_test: Test DNS resolution with multiple servers
+- getent_hosts <name>: Resolve a name the way applications do (nsswitch, /etc/hosts, systemd-resolved); use when dig works but an application cannot resolve a name
+- resolvectl_status: Show systemd-resolved DNS servers and search domains per link
+- connectivity_test: Test connectivity to multiple hosts
+- network_setup_check: Quick network setup check for standard users
+- network_health_check: Comprehensive network health check (runs multiple tools)
+- arp_table: Show ARP table
+- iptables: Show firewall rules
+- ufw_status: Check UFW firewall status
+- networkmanager_status: Check NetworkManager status
+- wireless_info: Show wireless interface information
+- interface_stats: Show network interface statistics
+- ethtool_stats [interface]: NIC driver counters (ethtool -S) with non-zero CRC errors, missed packets and drops called out (default: the interface with the default route); use when interface_stats shows errors or drops
+- tcpdump_sample [--duration <secs>]: Capture a packet sample (10 packets, or everything seen for <secs> seconds, max 30; requires root)
+
+SYSTEM LOGS:
+- journalctl_recent [--lines <n>]: Get recent system logs (default 50 lines)
+- journalctl_service <service_name> [--lines <n>]: Get logs for specific service (REQUIRES service name)
+- journalctl_boot: Get boot logs
+- journalctl_errors [--lines <n>]: Get error logs only
+- journal_disk_usage: Journal size against its SystemMaxUse cap and journald's rotation errors (use when logs seem to be missing or end too early)
+
+SYSTEM SERVICES:
+- systemctl_status <service_name>: Get status of specific service (REQUIRES service name)
+- systemctl_cat <service_name>: Show the effective unit file including drop-in overrides (REQUIRES service name). Use after systemctl_status to explain misconfigured ExecStart=, Restart= or dependency settings
+- systemctl_failed: Show failed systemd units (use this first to find service names)
+
+IMPORTANT: For service-specific tools, use systemctl_failed first to see available service names.
+Example workflow:
+  1. CALL_TOOL: systemctl_failed
+  2. CALL_TOOL: systemctl_status docker
+  3. CALL_TOOL: journalctl_service docker --lines 50
+  4. CALL_TOOL: systemctl_cat docker (if the service restarts or starts with the wrong command)
+
+PROCESS & PERFORMANCE:
+- ps_aux: List all running processes
+- ps_tree: Process hierarchy with parents of many children and deeply nested processes called out (use for "what spawned all these processes" or to trace a rogue process to its origin)
+- lsof_port <port>: Find the process holding a port (use for "Address already in use")
+- lsof_file <path>: Find the processes holding a file open (use for "file is locked")
+- top_consumers [--by cpu|mem] [--count <n>]: Show the top processes by CPU or memory (prefer over ps_aux)
+- process_limits [<pid>]: Open file descriptors and threads against their limits for a PID, or for the top CPU/memory consumers (use for "Too many open files")
+- strace_pid <pid> [--duration <secs>]: Count a process's syscalls for a few seconds (default 5, max 30) to see what it is blocked on (use when a process is stuck, hung or unresponsive; needs ptrace permission)
+- free: Show memory usage
+- df: Show disk usage
+- netstat: Show network connections (legacy)
+
+When a process is stuck or not responding, find its PID with top_consumers or ps_aux, then run strace_pid <pid>. A summary dominated by one syscall (futex, epoll_wait, read on a socket or NFS file) names what it is waiting on; no syscalls at all means it is blocked inside a single call.
+
+SECURITY:
+- security_denials: Summarize recent SELinux (AVC) and AppArmor denials (use when a service fails to start or gets "Permission denied" even as root)
+- auth_summary: Failed SSH logins in the last 24 hours grouped by source address, flagging likely brute force (use for "is someone trying to break in" or unexpected sshd load)
+- w: Users currently logged in, where from, and what they are running (use for "who is logged in")
+- last [--lines <n>]: Recent logins and reboots from wtmp (default 20 entries; use for "who logged in recently" or "when did it reboot")
+
+SYSTEM STATE:
+- refresh_system_info: Re-collect memory, disk, swap, pressure, zombie and failed unit information (the System Context is from the start of the session; use only when you suspect it has changed, e.g. after a service restart)
+
+TIME:
+- timedatectl: Clock synchronization status and NTP offset (use for TLS "certificate not yet valid" errors, etcd clock drift warnings or expired Kubernetes tokens)
+
+MEMORY PRESSURE:
+- swapon: Active swap devices and files with size, usage and priority (use when swap is heavily used or the system is thrashing)
+- bpftrace_oom [--duration <secs>]: Trace OOM kills live (default 10 seconds, max 30) and report which processes were killed (requires root and bpftrace; use when memory is exhausted or processes disappear)
+
+SAMPLING:
+- bpftrace_syscalls [--duration <secs>]: Count syscalls per process (default 5 seconds, max 30; use a longer duration on quiet systems, shorter on busy ones; requires root)
+"#.to_string()
+}
+
+/// Rough token count for a prompt: about four characters per token for English text and logs
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Warning shown before sending a prompt that is larger than the model's context window
+pub fn context_window_warning(tokens: usize, context_window: usize) -> String {
+    format!(
+        "⚠️  The prompt is ~{} tokens, more than the configured context window of {} (ai.context_window); \
+         the model may silently truncate it. Inspect it with `raid --prompt-only > prompt.txt`, or shrink it \
+         with a focused check (`raid check <component>`) or a lower ai.max_known_issues.",
+        tokens, context_window
+    )
+}
+
+/// Known issues appended to a prompt; empty when nothing matched
+pub fn known_issues_prompt_section(relevant_issues: &[(KnownIssue, f32)]) -> String {
+    if relevant_issues.is_empty() {
+        return String::new();
+    }
+    let mut section = String::from("\n\nKNOWN ISSUES THAT MAY BE RELEVANT:\n");
+    for (issue, _score) in relevant_issues {
+        section.push_str(&format!("- {}: {}\n", issue.title, issue.description));
+    }
+    section.push_str("\nConsider these known issues when analyzing the system state.\n");
+    section
+}
+
+/// `--explain-issue`: the task for checking one catalog issue against this system. The
+/// issue's patterns, symptoms and commands go in verbatim so the agent verifies them
+/// against live data instead of reciting the catalog entry.
+pub fn explain_issue_prompt(issue: &KnownIssue) -> String {
+    let mut prompt = format!(
+        "Determine whether this known issue applies to this system.\n\n\
+         KNOWN ISSUE {}: {}\nCategory: {:?}, Severity: {:?}\n{}\n",
+        issue.id, issue.title, issue.category, issue.severity, issue.description
+    );
+    let lists = [
+        ("Output patterns", &issue.patterns),
+        ("Symptoms", &issue.symptoms),
+        ("Verification commands", &issue.verification_commands),
+        ("Fix commands", &issue.fix_commands),
+        ("Prerequisites", &issue.prerequisites),
+    ];
+    for (title, items) in lists {
+        if !items.is_empty() {
+            prompt.push_str(&format!("\n{}:\n", title));
+            for item in items {
+                prompt.push_str(&format!("- {}\n", item));
+            }
+        }
+    }
+    if let Some(distribution) = &issue.distribution_specific {
+        prompt.push_str(&format!("\nOnly applies to: {}\n", distribution));
+    }
+    prompt.push_str(
+        "\nUse the diagnostic tools to look for the patterns and symptoms on this system. \
+         Start with a clear verdict: APPLIES, DOES NOT APPLY, or INCONCLUSIVE, citing the \
+         evidence you found. If it applies, give fix steps tailored to this system (its actual \
+         service names, paths, devices and distribution) rather than repeating the generic fix \
+         commands.",
+    );
+    prompt
+}
+
+/// `--since-boot`: the collected journal covers only the current boot, so steer the analysis
+/// to what broke since the reboot
+pub fn since_boot_context(boot_time: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    let booted = boot_time
+        .map(|time| format!(" (booted {})", time.format("%Y-%m-%d %H:%M:%S UTC")))
+        .unwrap_or_default();
+    format!(
+        "Scope: current boot only{}. Journal entries are from this boot. Focus on what has failed or \
+         started erroring since the last reboot, and treat anything that can only predate it as out of scope.\n",
+        booted
+    )
+}
+
+/// The first request the agent sends for a problem, exactly as the provider receives it
+pub fn initial_agent_prompt(problem_description: &str, system_context: &str, explain_reasoning: bool) -> String {
+    format!(
+        "SYSTEM: {}\n\nUSER: {}\n\n",
+        agent_system_prompt(system_context, explain_reasoning),
+        problem_description
+    )
+}
+
 pub struct AIAgent {
     provider: Box<dyn AIProvider>,
     debug_tools: crate::tools::DebugTools,
     max_tool_calls: usize,
     current_tool_calls: usize,
+    /// Corrective reprompts sent for unparseable responses; these don't count as tool calls
+    format_reprompts: usize,
+    /// Require a REASONING: line before every tool call
+    explain_reasoning: bool,
+    verbose_logging: bool,
+    /// The model's context window in tokens; larger prompts get a warning before they are sent
+    context_window: Option<usize>,
+    context_window_warned: bool,
+    /// Extra attempts for a failing tool (`tools.retries`)
+    tool_retries: u32,
+    /// What refresh_system_info collects
+    collect_options: crate::sysinfo::CollectOptions,
+    /// System information collected once per session and reused; refresh_system_info replaces it
+    system_info: Option<crate::sysinfo::SystemInfo>,
     conversation_history: Vec<AIAgentMessage>,
     tool_call_database: std::collections::HashMap<String, crate::tools::DebugToolResult>,
+    /// `--redact-preview`: tool output is redacted with this before it goes to the provider
+    anonymizer: Option<crate::output::anonymize::Anonymizer>,
+    /// Echo each response to stderr as the provider streams it
+    stream_responses: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -806,6 +1682,7 @@ pub struct AIToolCall {
     pub tool_name: String,
     pub arguments: std::collections::HashMap<String, String>,
     pub result: Option<crate::tools::DebugToolResult>,
+    pub reasoning: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -814,6 +1691,10 @@ pub struct AIAgentConfig {
     pub pause_on_limit: bool,
     pub allow_user_continuation: bool,
     pub verbose_logging: bool,
+    /// Reprompt tool calls that come without a REASONING: line
+    pub explain_reasoning: bool,
+    /// Warn before sending a prompt estimated to be larger than this many tokens
+    pub context_window: Option<usize>,
 }
 
 impl Default for AIAgentConfig {
@@ -823,6 +1704,8 @@ impl Default for AIAgentConfig {
             pause_on_limit: true,
             allow_user_continuation: true,
             verbose_logging: false,
+            explain_reasoning: false,
+            context_window: None,
         }
     }
 }
@@ -842,11 +1725,64 @@ impl AIAgent {
             debug_tools: crate::tools::DebugTools::new(),
             max_tool_calls: config.max_tool_calls,
             current_tool_calls: 0,
+            format_reprompts: 0,
+            explain_reasoning: config.explain_reasoning,
+            verbose_logging: config.verbose_logging,
+            context_window: config.context_window,
+            context_window_warned: false,
+            tool_retries: 0,
+            collect_options: crate::sysinfo::CollectOptions::default(),
+            system_info: None,
             conversation_history: Vec::new(),
             tool_call_database: std::collections::HashMap::new(),
+            anonymizer: None,
+            stream_responses: false,
         }
     }
 
+    /// Point the agent's tools at a different procfs/sysfs root (e.g. the host's from a container)
+    pub fn with_host_paths(mut self, host_paths: crate::sysinfo::HostPaths) -> Self {
+        self.collect_options.paths = host_paths.clone();
+        self.debug_tools = self.debug_tools.with_host_paths(host_paths);
+        self
+    }
+
+    /// Retry a failing tool this many times before reporting the failure
+    pub fn with_tool_retries(mut self, retries: u32) -> Self {
+        self.tool_retries = retries;
+        self
+    }
+
+    /// What refresh_system_info collects (journal unit, unit filter and host paths)
+    pub fn with_collect_options(mut self, options: crate::sysinfo::CollectOptions) -> Self {
+        self.collect_options = options;
+        self
+    }
+
+    /// Start from system information that was already collected, instead of collecting again
+    pub fn with_system_info(mut self, system_info: crate::sysinfo::SystemInfo) -> Self {
+        self.system_info = Some(system_info);
+        self
+    }
+
+    /// Redact every tool output with this before it is added to the conversation. Pass the
+    /// anonymizer that redacted the system context, so a value keeps the same pseudonym.
+    pub fn with_anonymizer(mut self, anonymizer: crate::output::anonymize::Anonymizer) -> Self {
+        self.anonymizer = Some(anonymizer);
+        self
+    }
+
+    /// Show each response while the model is still writing it, instead of only the result
+    pub fn with_streaming(mut self, enabled: bool) -> Self {
+        self.stream_responses = enabled;
+        self
+    }
+
+    /// The session's system information, as of the last refresh
+    pub fn system_info(&self) -> Option<&crate::sysinfo::SystemInfo> {
+        self.system_info.as_ref()
+    }
+
     /// Run the AI agent with the given problem description
     pub async fn run(&mut self, problem_description: &str, system_context: &str) -> Result<AIAgentResult, AIError> {
         // Check if this is a simple question that doesn't need iterative tool calling
@@ -882,6 +1818,7 @@ impl AIAgent {
                 system_context, problem_description
             );
             
+            self.check_prompt_size(&direct_prompt);
             match self.provider.analyze(&direct_prompt).await {
                 Ok(response) => {
                     // If the response looks complete, return it
@@ -900,58 +1837,16 @@ impl AIAgent {
 
         // For diagnostic questions or when direct answer isn't sufficient, use the full AI agent
         // Initialize conversation with system context and user problem
-        self.add_message(MessageRole::System, format!(
-            "You are an expert Linux systems administrator and Kubernetes operator. You can iteratively call diagnostic tools to help solve problems.
+        self.add_message(MessageRole::System, agent_system_prompt(system_context, self.explain_reasoning));
 
-Available tools:
-{}
+        self.add_message(MessageRole::User, problem_description.to_string());
 
-System Context:
-{}
-
-Your task is to help diagnose and solve the user's problem by:
-1. Analyzing the problem description
-2. Calling appropriate diagnostic tools to gather information
-3. Making decisions based on tool results
-4. Calling additional tools if needed to get a complete picture
-5. Continue investigating until you have thoroughly examined all relevant aspects
-6. Only provide a final analysis when you are confident you have gathered sufficient information
-
-IMPORTANT: Be thorough in your investigation. Use multiple tools to cross-reference findings and build a complete understanding of the system state. Do not stop early - continue checking different aspects until you have a comprehensive view.
-
-IMPORTANT: For each response, you MUST use one of these formats:
-
-For tool calls, use this EXACT format:
-REASONING: <explanation of why this tool is needed and what you're checking>
-CALL_TOOL: <tool_name> [arguments]
-
-For analysis without tools:
-ANALYZE: <analysis>
-
-For final solutions:
-COMPLETE: <final_analysis>
-
-CRITICAL: When calling any tool, you MUST first provide a REASONING: line explaining:
-- What you're trying to check or diagnose
-- Why this specific tool is the right choice
-- What information you expect to gather
-
-Example:
-REASONING: Checking memory usage to identify potential memory leaks or high consumption that could cause system slowdown
-CALL_TOOL: free
-
-If you can answer the question with current information, use COMPLETE: followed by your answer.", 
-            self.get_available_tools_description(),
-            system_context
-        ));
-
-        self.add_message(MessageRole::User, problem_description.to_string());
-
-        // Safety counters to prevent infinite loops
-        let mut consecutive_analysis_count = 0;
-        let max_consecutive_analysis = 5; // Reduced back to prevent infinite loops
-        let mut total_iterations = 0;
-        let max_total_iterations = 30; // Reduced to prevent excessive iterations
+        // Safety counters to prevent infinite loops
+        let mut consecutive_analysis_count = 0;
+        let max_consecutive_analysis = 5; // Reduced back to prevent infinite loops
+        let mut consecutive_reprompts = 0;
+        let mut total_iterations = 0;
+        let max_total_iterations = 30; // Reduced to prevent excessive iterations
 
         // Main agent loop
         loop {
@@ -975,45 +1870,52 @@ If you can answer the question with current information, use COMPLETE: followed
 
             // Get AI response based on conversation history
             let conversation_context = self.build_conversation_context();
-            println!("🔄 AI agent iteration {} (tool calls: {}/{})", total_iterations, self.current_tool_calls, self.max_tool_calls);
+            eprintln!("🔄 AI agent iteration {} (tool calls: {}/{})", total_iterations, self.current_tool_calls, self.max_tool_calls);
+            self.check_prompt_size(&conversation_context);
             
             // Use direct API call to avoid conflicting system prompts from analyze() method
             let ai_response = self.get_ai_response(&conversation_context).await?;
 
             // Parse AI response and determine action
-            println!("🔍 AI response preview: {}", ai_response.chars().take(150).collect::<String>().replace('\n', " "));
+            eprintln!("🔍 AI response preview: {}", ai_response.chars().take(150).collect::<String>().replace('\n', " "));
             match self.parse_ai_action(&ai_response).await {
-                AIAgentAction::RunTool { tool, namespace, pod, service, lines, reasoning } => {
+                AIAgentAction::RunTool { tool, args, reasoning } => {
+                    if self.reprompt_for_reasoning(&ai_response, reasoning.as_deref(), &mut consecutive_reprompts) {
+                        continue;
+                    }
+
                     // Reset consecutive analysis counter since we're doing something useful
                     consecutive_analysis_count = 0;
+                    consecutive_reprompts = 0;
                     
                     // Print the reasoning if provided
                     if let Some(reason) = &reasoning {
-                        println!("🧠 AI reasoning: {}", reason);
+                        eprintln!("🧠 AI reasoning: {}", reason);
                     }
                     
                     // Check if this tool call has been made before
-                    if let Some(duplicate_result) = self.check_and_handle_duplicate_tool_call(&tool, &namespace, &pod, &service, &lines).await {
+                    if let Some(duplicate_result) = self.check_and_handle_duplicate_tool_call(&tool, &args).await {
                         // Tool was already executed - AI has been reminded, continue to next iteration
                         continue;
                     }
                     
                     // Execute the tool (not a duplicate)
-                    let result = self.execute_tool(tool.clone(), namespace.clone(), pod.clone(), service.clone(), lines).await;
+                    let result = self.execute_tool(tool.clone(), (*args).clone()).await;
                     self.current_tool_calls += 1;
 
                     // Store result in database for future deduplication
-                    let key = Self::generate_tool_call_key(&tool, &namespace, &pod, &service, &lines);
+                    let key = Self::generate_tool_call_key(&tool, &args);
                     self.tool_call_database.insert(key, result.clone());
 
                     // Add tool result to conversation
-                    self.add_tool_result(tool, result).await;
+                    self.add_tool_result(tool, result, reasoning).await;
 
                     // Continue loop for next iteration
                 }
                 AIAgentAction::ProvideAnalysis { analysis } => {
                     consecutive_analysis_count += 1;
-                    println!("🤔 AI provided analysis (consecutive: {}/{})", consecutive_analysis_count, max_consecutive_analysis);
+                    consecutive_reprompts = 0;
+                    eprintln!("🤔 AI provided analysis (consecutive: {}/{})", consecutive_analysis_count, max_consecutive_analysis);
                     
                     // Check if this is asking for user input
                     if analysis.to_lowercase().contains("need more information") || 
@@ -1038,17 +1940,21 @@ If you can answer the question with current information, use COMPLETE: followed
                         (analysis_lower.contains("nothing more") && analysis_lower.contains("tool"));
                     
                     if indicates_completion {
-                        println!("🏁 AI indicated completion with phrases suggesting no more tools needed");
+                        eprintln!("🏁 AI indicated completion with phrases suggesting no more tools needed");
                     }
                     
-                    // WRONG FORMAT DETECTION: If AI is using old format but should be calling tools
-                    let using_old_format = analysis_lower.contains("## critical") || 
-                                          analysis_lower.contains("**issue**:") ||
-                                          analysis_lower.contains("**verify**:") ||
-                                          analysis_lower.contains("**fix**:");
+                    // WRONG FORMAT DETECTION: If AI is using old format but should be calling tools.
+                    // A COMPLETE: answer is the final one, where FIX_COMMANDS_PROMPT asks for
+                    // exactly these Issue/Verify/Fix lines.
+                    let is_final_answer = ai_response.contains("COMPLETE:");
+                    let using_old_format = !is_final_answer
+                        && (analysis_lower.contains("## critical")
+                            || analysis_lower.contains("**issue**:")
+                            || analysis_lower.contains("**verify**:")
+                            || analysis_lower.contains("**fix**:"));
                     
                     if using_old_format && consecutive_analysis_count >= 2 {
-                        println!("⚠️  AI is using old format instead of REASONING/CALL_TOOL. Providing guidance.");
+                        eprintln!("⚠️  AI is using old format instead of REASONING/CALL_TOOL. Providing guidance.");
                         self.add_message(MessageRole::Assistant, analysis);
                         self.add_message(MessageRole::System, 
                             "You are providing analysis in the old format instead of using tools. Remember to use this format:\n\nREASONING: [explain what you want to check]\nCALL_TOOL: [tool_name] [arguments]\n\nFor example:\nREASONING: Need to check PersistentVolume status to understand why PVC mounting is failing\nCALL_TOOL: kubectl_get_pv".to_string());
@@ -1057,7 +1963,7 @@ If you can answer the question with current information, use COMPLETE: followed
                     
                     // Safety check: if we've had too many consecutive analysis responses without tool calls
                     if consecutive_analysis_count >= max_consecutive_analysis {
-                        println!("⚠️  Stopping due to consecutive analysis limit reached");
+                        eprintln!("⚠️  Stopping due to consecutive analysis limit reached");
                         return Ok(AIAgentResult::Success {
                             final_analysis: analysis,
                             tool_calls_used: self.current_tool_calls,
@@ -1073,6 +1979,12 @@ If you can answer the question with current information, use COMPLETE: followed
                         tool_calls_used: self.current_tool_calls,
                     });
                 }
+                AIAgentAction::Unparseable { response } => {
+                    if let Some(result) = self.reprompt_for_format(response, &mut consecutive_reprompts) {
+                        return Ok(result);
+                    }
+                    continue;
+                }
             }
 
             // Check if AI indicated completion
@@ -1094,6 +2006,30 @@ If you can answer the question with current information, use COMPLETE: followed
         self.run_continuation().await
     }
 
+    /// Ask a follow-up question in the same conversation, reusing the tool results gathered so far.
+    /// Each question gets a fresh tool budget.
+    pub async fn ask_follow_up(&mut self, question: &str, system_context: &str) -> Result<AIAgentResult, AIError> {
+        // A direct answer leaves no conversation to continue, so start the full agent instead
+        if self.conversation_history.is_empty() {
+            return self.run(question, system_context).await;
+        }
+
+        self.current_tool_calls = 0;
+        self.add_message(MessageRole::User, format!(
+            "Follow-up question: {}\n\nReuse the tool results gathered so far. Only call a tool again if its output may have changed or you need information you don't have yet.",
+            question
+        ));
+        self.run_continuation().await
+    }
+
+    /// Start over for an unrelated question: the conversation and its tool results are dropped,
+    /// the collected system information is kept
+    pub fn reset(&mut self) {
+        self.conversation_history.clear();
+        self.tool_call_database.clear();
+        self.current_tool_calls = 0;
+    }
+
     /// Allow user to manually continue after hitting limit
     pub async fn continue_after_limit(&mut self) -> Result<AIAgentResult, AIError> {
         // Reset the counter to allow more tool calls
@@ -1107,6 +2043,7 @@ If you can answer the question with current information, use COMPLETE: followed
         // Same logic as main run loop, but continues from current state
         let mut consecutive_analysis_count = 0;
         let max_consecutive_analysis = 5;
+        let mut consecutive_reprompts = 0;
         let mut total_iterations = 0;
         let max_total_iterations = 30;
 
@@ -1129,38 +2066,45 @@ If you can answer the question with current information, use COMPLETE: followed
             }
 
             let conversation_context = self.build_conversation_context();
-            println!("🔄 AI continuation iteration {} (tool calls: {}/{})", total_iterations, self.current_tool_calls, self.max_tool_calls);
+            eprintln!("🔄 AI continuation iteration {} (tool calls: {}/{})", total_iterations, self.current_tool_calls, self.max_tool_calls);
+            self.check_prompt_size(&conversation_context);
             let ai_response = self.get_ai_response(&conversation_context).await?;
 
-            println!("🔍 AI continuation response preview: {}", ai_response.chars().take(150).collect::<String>().replace('\n', " "));
+            eprintln!("🔍 AI continuation response preview: {}", ai_response.chars().take(150).collect::<String>().replace('\n', " "));
             match self.parse_ai_action(&ai_response).await {
-                AIAgentAction::RunTool { tool, namespace, pod, service, lines, reasoning } => {
+                AIAgentAction::RunTool { tool, args, reasoning } => {
+                    if self.reprompt_for_reasoning(&ai_response, reasoning.as_deref(), &mut consecutive_reprompts) {
+                        continue;
+                    }
+
                     // Reset consecutive analysis counter since we're doing something useful
                     consecutive_analysis_count = 0;
+                    consecutive_reprompts = 0;
                     
                     // Print the reasoning if provided
                     if let Some(reason) = &reasoning {
-                        println!("🧠 AI reasoning: {}", reason);
+                        eprintln!("🧠 AI reasoning: {}", reason);
                     }
                     
                     // Check if this tool call has been made before
-                    if let Some(duplicate_result) = self.check_and_handle_duplicate_tool_call(&tool, &namespace, &pod, &service, &lines).await {
+                    if let Some(duplicate_result) = self.check_and_handle_duplicate_tool_call(&tool, &args).await {
                         // Tool was already executed - AI has been reminded, continue to next iteration
                         continue;
                     }
                     
-                    let result = self.execute_tool(tool.clone(), namespace.clone(), pod.clone(), service.clone(), lines).await;
+                    let result = self.execute_tool(tool.clone(), (*args).clone()).await;
                     self.current_tool_calls += 1;
                     
                     // Store result in database for future deduplication
-                    let key = Self::generate_tool_call_key(&tool, &namespace, &pod, &service, &lines);
+                    let key = Self::generate_tool_call_key(&tool, &args);
                     self.tool_call_database.insert(key, result.clone());
                     
-                    self.add_tool_result(tool.clone(), result).await;
+                    self.add_tool_result(tool.clone(), result, reasoning).await;
                 }
                 AIAgentAction::ProvideAnalysis { analysis } => {
                     consecutive_analysis_count += 1;
-                    println!("🤔 AI continuation analysis (consecutive: {}/{})", consecutive_analysis_count, max_consecutive_analysis);
+                    consecutive_reprompts = 0;
+                    eprintln!("🤔 AI continuation analysis (consecutive: {}/{})", consecutive_analysis_count, max_consecutive_analysis);
                     
                     if analysis.to_lowercase().contains("need more information") || 
                        analysis.to_lowercase().contains("could you") ||
@@ -1199,6 +2143,12 @@ If you can answer the question with current information, use COMPLETE: followed
                         tool_calls_used: self.current_tool_calls,
                     });
                 }
+                AIAgentAction::Unparseable { response } => {
+                    if let Some(result) = self.reprompt_for_format(response, &mut consecutive_reprompts) {
+                        return Ok(result);
+                    }
+                    continue;
+                }
             }
 
             if ai_response.to_lowercase().contains("COMPLETE:") {
@@ -1211,6 +2161,45 @@ If you can answer the question with current information, use COMPLETE: followed
         }
     }
 
+    /// Ask the model to restate a response that used none of the required formats. Gives up
+    /// (pausing for the user, as before) once `MAX_FORMAT_REPROMPTS` in a row have failed.
+    fn reprompt_for_format(&mut self, response: String, consecutive_reprompts: &mut usize) -> Option<AIAgentResult> {
+        if *consecutive_reprompts >= MAX_FORMAT_REPROMPTS {
+            return Some(AIAgentResult::PausedForUserInput {
+                reason: format!("The response was unclear: {}. Could you provide more detail?", response),
+                tool_calls_used: self.current_tool_calls,
+            });
+        }
+
+        *consecutive_reprompts += 1;
+        self.format_reprompts += 1;
+        eprintln!("↩️  AI response was not in the required format, reprompting ({}/{})", consecutive_reprompts, MAX_FORMAT_REPROMPTS);
+        self.add_message(MessageRole::Assistant, response);
+        self.add_message(MessageRole::System, FORMAT_REPROMPT.to_string());
+        None
+    }
+
+    /// With `explain_reasoning`, send a tool call that lacks a REASONING: line back to the model.
+    /// Returns true when the call was reprompted; once the limit is hit the tool runs anyway.
+    fn reprompt_for_reasoning(&mut self, response: &str, reasoning: Option<&str>, consecutive_reprompts: &mut usize) -> bool {
+        let has_reasoning = reasoning.is_some_and(|reason| !reason.trim().is_empty());
+        if !self.explain_reasoning || has_reasoning || *consecutive_reprompts >= MAX_FORMAT_REPROMPTS {
+            return false;
+        }
+
+        *consecutive_reprompts += 1;
+        self.format_reprompts += 1;
+        eprintln!("↩️  Tool call came without reasoning, reprompting ({}/{})", consecutive_reprompts, MAX_FORMAT_REPROMPTS);
+        self.add_message(MessageRole::Assistant, response.to_string());
+        self.add_message(MessageRole::System, REASONING_REPROMPT.to_string());
+        true
+    }
+
+    /// Number of corrective reprompts sent so far (not counted against the tool call limit)
+    pub fn format_reprompts(&self) -> usize {
+        self.format_reprompts
+    }
+
     fn add_message(&mut self, role: MessageRole, content: String) {
         self.conversation_history.push(AIAgentMessage {
             role,
@@ -1221,27 +2210,42 @@ If you can answer the question with current information, use COMPLETE: followed
     }
 
     /// Generate a unique key for a tool call based on tool name and arguments
-    fn generate_tool_call_key(
-        tool: &crate::cli::DebugTool,
-        namespace: &Option<String>,
-        pod: &Option<String>,
-        service: &Option<String>,
-        lines: &Option<usize>,
-    ) -> String {
+    fn generate_tool_call_key(tool: &crate::cli::DebugTool, args: &crate::cli::DebugToolArgs) -> String {
         let mut key = format!("{:?}", tool);
         
-        if let Some(ns) = namespace {
+        if let Some(ns) = &args.namespace {
             key.push_str(&format!("|namespace:{}", ns));
         }
-        if let Some(p) = pod {
+        if let Some(p) = &args.pod {
             key.push_str(&format!("|pod:{}", p));
         }
-        if let Some(s) = service {
+        if let Some(node) = &args.node {
+            key.push_str(&format!("|node:{}", node));
+        }
+        if let Some(s) = &args.service {
             key.push_str(&format!("|service:{}", s));
         }
-        if let Some(l) = lines {
+        if let Some(l) = args.lines {
             key.push_str(&format!("|lines:{}", l));
         }
+        if let Some(port) = args.port {
+            key.push_str(&format!("|port:{}", port));
+        }
+        if let Some(path) = &args.path {
+            key.push_str(&format!("|path:{}", path));
+        }
+        if let Some(by) = args.by {
+            key.push_str(&format!("|by:{:?}", by));
+        }
+        if let Some(count) = args.count {
+            key.push_str(&format!("|count:{}", count));
+        }
+        if let Some(duration) = args.duration {
+            key.push_str(&format!("|duration:{}", duration));
+        }
+        if let Some(pid) = args.pid {
+            key.push_str(&format!("|pid:{}", pid));
+        }
         
         key
     }
@@ -1250,19 +2254,21 @@ If you can answer the question with current information, use COMPLETE: followed
     async fn check_and_handle_duplicate_tool_call(
         &mut self,
         tool: &crate::cli::DebugTool,
-        namespace: &Option<String>,
-        pod: &Option<String>,
-        service: &Option<String>,
-        lines: &Option<usize>,
+        args: &crate::cli::DebugToolArgs,
     ) -> Option<crate::tools::DebugToolResult> {
-        let key = Self::generate_tool_call_key(tool, namespace, pod, service, lines);
+        // refresh_system_info takes no arguments, so its key never changes; a second call
+        // exists to see what changed since the first
+        if matches!(tool, crate::cli::DebugTool::RefreshSystemInfo) {
+            return None;
+        }
+        let key = Self::generate_tool_call_key(tool, args);
         
         // Check for previous result first, then handle messaging separately to avoid borrow conflicts
         let previous_result = self.tool_call_database.get(&key).cloned();
         
         if let Some(result) = previous_result {
-            println!("🔁 Tool call already executed: {}", result.command);
-            println!("📋 Reminding AI of previous result instead of re-executing");
+            eprintln!("🔁 Tool call already executed: {}", result.command);
+            eprintln!("📋 Reminding AI of previous result instead of re-executing");
             
             // Add a system message to remind the AI of the previous result
             let reminder_message = format!(
@@ -1285,6 +2291,22 @@ If you can answer the question with current information, use COMPLETE: followed
         None // No duplicate found
     }
 
+    /// Log the prompt's estimated size and warn (once) when it exceeds the configured context window
+    fn check_prompt_size(&mut self, prompt: &str) -> usize {
+        let tokens = estimate_tokens(prompt);
+        if self.verbose_logging {
+            eprintln!("📏 Prompt size: ~{} tokens", tokens);
+        }
+        if let Some(window) = self.context_window
+            && tokens > window
+            && !self.context_window_warned
+        {
+            self.context_window_warned = true;
+            eprintln!("{}", context_window_warning(tokens, window));
+        }
+        tokens
+    }
+
     async fn get_ai_response(&self, conversation_context: &str) -> Result<String, AIError> {
         // Make direct API call with conversation context to avoid conflicting system prompts
         // The conversation context already contains our AI Agent system prompt
@@ -1308,20 +2330,50 @@ DO NOT use any other format like '## Critical Issues' or markdown headers.
 Here is the conversation:\n\n{}", 
                     conversation_context
                 );
-                self.provider.analyze(&explicit_prompt).await
+                self.request_analysis(&explicit_prompt).await
             }
             _ => {
                 // For other providers, use the conversation context as-is
-                self.provider.analyze(conversation_context).await
+                self.request_analysis(conversation_context).await
             }
         }
     }
 
-    async fn add_tool_result(&mut self, tool: crate::cli::DebugTool, result: crate::tools::DebugToolResult) {
+    /// The provider's response, echoed chunk by chunk to stderr as it arrives when streaming is on
+    async fn request_analysis(&self, prompt: &str) -> Result<String, AIError> {
+        if !self.stream_responses {
+            return self.provider.analyze(prompt).await;
+        }
+        let mut chunks = self.provider.analyze_stream(prompt);
+        let mut response = String::new();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    if !response.is_empty() {
+                        eprintln!();
+                    }
+                    return Err(e);
+                }
+            };
+            eprint!("{}", chunk);
+            response.push_str(&chunk);
+        }
+        eprintln!();
+        Ok(response)
+    }
+
+    async fn add_tool_result(
+        &mut self,
+        tool: crate::cli::DebugTool,
+        result: crate::tools::DebugToolResult,
+        reasoning: Option<String>,
+    ) {
         let tool_call = AIToolCall {
             tool_name: format!("{:?}", tool),
             arguments: std::collections::HashMap::new(), // We could extract args from result.command
             result: Some(result.clone()),
+            reasoning,
         };
 
         let message_content = format!(
@@ -1336,6 +2388,10 @@ Here is the conversation:\n\n{}",
                 String::new()
             }
         );
+        let message_content = match &mut self.anonymizer {
+            Some(anonymizer) => anonymizer.anonymize_text(&message_content),
+            None => message_content,
+        };
 
         self.conversation_history.push(AIAgentMessage {
             role: MessageRole::Tool,
@@ -1433,8 +2489,24 @@ Here is the conversation:\n\n{}",
                         // Extract arguments - improved to handle positional arguments
                         let mut namespace = self.extract_arg(&parts, "--namespace");
                         let mut pod = self.extract_arg(&parts, "--pod");
+                        let mut node = self.extract_arg(&parts, "--node");
                         let mut service = self.extract_arg(&parts, "--service");
                         let lines = self.extract_arg(&parts, "--lines").and_then(|s| s.parse().ok());
+                        let mut port = self.extract_arg(&parts, "--port").and_then(|s| s.parse().ok());
+                        let mut path = self.extract_arg(&parts, "--path");
+                        let by = self.extract_arg(&parts, "--by").and_then(|s| match s.to_lowercase().as_str() {
+                            "cpu" => Some(crate::cli::SortKey::Cpu),
+                            "mem" | "memory" => Some(crate::cli::SortKey::Mem),
+                            _ => None,
+                        });
+                        let count = self.extract_arg(&parts, "--count").and_then(|s| s.parse().ok());
+                        let duration = self
+                            .extract_arg(&parts, "--duration")
+                            .and_then(|s| s.parse::<u64>().ok())
+                            .map(|secs| secs.clamp(1, AGENT_MAX_DURATION_SECS));
+                        let mut host = self.extract_arg(&parts, "--host");
+                        let mut pid = self.extract_arg(&parts, "--pid").and_then(|s| s.parse().ok());
+                        let mut interface = self.extract_arg(&parts, "--interface");
                         
                         // Handle positional arguments for specific tools
                         match tool {
@@ -1454,7 +2526,64 @@ Here is the conversation:\n\n{}",
                                     }
                                 }
                             }
-                            crate::cli::DebugTool::JournalctlService | crate::cli::DebugTool::SystemctlStatus => {
+                            crate::cli::DebugTool::KubectlDescribeNode => {
+                                // For kubectl_describe_node, the first non-flag argument is the node name
+                                if node.is_none() {
+                                    node = parts
+                                        .iter()
+                                        .skip(1)
+                                        .find(|part| !part.starts_with('-'))
+                                        .map(|part| part.to_string());
+                                }
+                            }
+                            crate::cli::DebugTool::LsofPort => {
+                                // For lsof_port, the first numeric argument is the port (":8080" is accepted too)
+                                if port.is_none() {
+                                    port = parts
+                                        .iter()
+                                        .skip(1)
+                                        .find_map(|part| part.trim_start_matches(':').parse().ok());
+                                }
+                            }
+                            crate::cli::DebugTool::ProcessLimits | crate::cli::DebugTool::StracePid => {
+                                // For process_limits and strace_pid, the first numeric argument is the PID
+                                if pid.is_none() {
+                                    pid = parts.iter().skip(1).find_map(|part| part.parse().ok());
+                                }
+                            }
+                            crate::cli::DebugTool::GetentHosts => {
+                                // For getent_hosts, the first non-flag argument is the name to resolve
+                                if host.is_none() {
+                                    host = parts
+                                        .iter()
+                                        .skip(1)
+                                        .find(|part| !part.starts_with('-'))
+                                        .map(|part| part.to_string());
+                                }
+                            }
+                            crate::cli::DebugTool::EthtoolStats => {
+                                // For ethtool_stats, the first non-flag argument is the interface
+                                if interface.is_none() {
+                                    interface = parts
+                                        .iter()
+                                        .skip(1)
+                                        .find(|part| !part.starts_with('-'))
+                                        .map(|part| part.to_string());
+                                }
+                            }
+                            crate::cli::DebugTool::LsofFile => {
+                                // For lsof_file, the first non-flag argument is the file path
+                                if path.is_none() {
+                                    path = parts
+                                        .iter()
+                                        .skip(1)
+                                        .find(|part| !part.starts_with('-'))
+                                        .map(|part| part.to_string());
+                                }
+                            }
+                            crate::cli::DebugTool::JournalctlService
+                            | crate::cli::DebugTool::SystemctlStatus
+                            | crate::cli::DebugTool::SystemctlCat => {
                                 // For service tools, first non-flag argument is the service name
                                 if service.is_none() && parts.len() > 1 {
                                     for i in 1..parts.len() {
@@ -1500,10 +2629,21 @@ Here is the conversation:\n\n{}",
                         
                         return crate::cli::AIAgentAction::RunTool {
                             tool,
-                            namespace,
-                            pod,
-                            service,
-                            lines,
+                            args: Box::new(crate::cli::DebugToolArgs {
+                                namespace,
+                                pod,
+                                node,
+                                service,
+                                lines,
+                                port,
+                                path,
+                                by,
+                                count,
+                                duration,
+                                pid,
+                                host,
+                                interface,
+                            }),
                             reasoning,
                         };
                     }
@@ -1523,12 +2663,11 @@ Here is the conversation:\n\n{}",
             return crate::cli::AIAgentAction::ProvideAnalysis { analysis };
         }
 
-        // If response seems to be asking for more information or is incomplete
+        // If response seems to be asking for more information
         if response_lower.contains("need more") || 
            response_lower.contains("would need") ||
            response_lower.contains("could you provide") ||
-           response_lower.contains("more information") ||
-           response.len() < 30 {
+           response_lower.contains("more information") {
             return crate::cli::AIAgentAction::AskUser { 
                 question: response.to_string() 
             };
@@ -1540,9 +2679,9 @@ Here is the conversation:\n\n{}",
                 analysis: response.to_string(),
             }
         } else {
-            // Short responses are likely incomplete - ask for clarification
-            crate::cli::AIAgentAction::AskUser {
-                question: format!("The response was unclear: {}. Could you provide more detail?", response),
+            // Short responses are likely incomplete - ask the model to restate in the required format
+            crate::cli::AIAgentAction::Unparseable {
+                response: response.to_string(),
             }
         }
     }
@@ -1564,13 +2703,17 @@ Here is the conversation:\n\n{}",
             "kubectl_describe_pod" => Some(DebugTool::KubectlDescribePod),
             "kubectl_get_services" => Some(DebugTool::KubectlGetServices),
             "kubectl_get_nodes" => Some(DebugTool::KubectlGetNodes),
+            "kubectl_describe_node" => Some(DebugTool::KubectlDescribeNode),
             "kubectl_get_events" => Some(DebugTool::KubectlGetEvents),
             "journalctl_recent" => Some(DebugTool::JournalctlRecent),
             "journalctl_service" => Some(DebugTool::JournalctlService),
             "journalctl_boot" => Some(DebugTool::JournalctlBoot),
             "journalctl_errors" => Some(DebugTool::JournalctlErrors),
+            "journal_disk_usage" => Some(DebugTool::JournalDiskUsage),
             "systemctl_status" => Some(DebugTool::SystemctlStatus),
+            "systemctl_cat" => Some(DebugTool::SystemctlCat),
             "ps_aux" => Some(DebugTool::PsAux),
+            "ps_tree" => Some(DebugTool::PsTree),
             "netstat" => Some(DebugTool::Netstat),
             "df" => Some(DebugTool::Df),
             "free" => Some(DebugTool::Free),
@@ -1584,6 +2727,8 @@ Here is the conversation:\n\n{}",
             "traceroute" => Some(DebugTool::Traceroute),
             "dns_config" => Some(DebugTool::DnsConfig),
             "dns_test" => Some(DebugTool::DnsTest),
+            "getent_hosts" => Some(DebugTool::GetentHosts),
+            "resolvectl_status" => Some(DebugTool::ResolvectlStatus),
             "connectivity_test" => Some(DebugTool::ConnectivityTest),
             "network_setup_check" => Some(DebugTool::NetworkSetupCheck),
             "arp_table" => Some(DebugTool::ArpTable),
@@ -1592,23 +2737,50 @@ Here is the conversation:\n\n{}",
             "networkmanager_status" => Some(DebugTool::NetworkManagerStatus),
             "wireless_info" => Some(DebugTool::WirelessInfo),
             "interface_stats" => Some(DebugTool::InterfaceStats),
+            "ethtool_stats" => Some(DebugTool::EthtoolStats),
             "network_health_check" => Some(DebugTool::NetworkHealthCheck),
+            "tcpdump_sample" => Some(DebugTool::TcpdumpSample),
+            // Process debugging tools
+            "lsof_port" => Some(DebugTool::LsofPort),
+            "lsof_file" => Some(DebugTool::LsofFile),
+            "top_consumers" => Some(DebugTool::TopConsumers),
+            "process_limits" => Some(DebugTool::ProcessLimits),
+            "strace_pid" => Some(DebugTool::StracePid),
+            "security_denials" => Some(DebugTool::SecurityDenials),
+            "auth_summary" => Some(DebugTool::AuthSummary),
+            "w" => Some(DebugTool::W),
+            "last" => Some(DebugTool::Last),
+            "timedatectl" => Some(DebugTool::Timedatectl),
+            "swapon" => Some(DebugTool::Swapon),
+            "refresh_system_info" => Some(DebugTool::RefreshSystemInfo),
+            // eBPF tools
+            "bpftrace_syscalls" => Some(DebugTool::BpftraceSyscalls),
+            "bpftrace_oom" => Some(DebugTool::BpftraceOom),
             _ => None,
         }
     }
 
+    /// Run a tool, retrying transient failures up to `tool_retries` times with a short backoff
     async fn execute_tool(
-        &self,
+        &mut self,
+        tool: crate::cli::DebugTool,
+        args: crate::cli::DebugToolArgs,
+    ) -> crate::tools::DebugToolResult {
+        let retries = self.tool_retries;
+        crate::tools::run_with_retries(retries, async |_| self.execute_tool_once(tool.clone(), args.clone()).await).await
+    }
+
+    async fn execute_tool_once(
+        &mut self,
         tool: crate::cli::DebugTool,
-        namespace: Option<String>,
-        pod: Option<String>,
-        service: Option<String>,
-        lines: Option<usize>,
+        args: crate::cli::DebugToolArgs,
     ) -> crate::tools::DebugToolResult {
         use crate::cli::DebugTool;
+
+        let crate::cli::DebugToolArgs { namespace, pod, node, service, lines, port, path, by, count, duration, pid, host, interface } = args;
         
         // Print what tool is being executed
-        println!("🔧 AI is running tool: {:?}", tool);
+        eprintln!("🔧 AI is running tool: {:?}", tool);
         
         let result = match tool {
             DebugTool::KubectlGetPods => {
@@ -1636,6 +2808,20 @@ Here is the conversation:\n\n{}",
                     .await
             }
             DebugTool::KubectlGetNodes => self.debug_tools.run_kubectl_get_nodes().await,
+            DebugTool::KubectlDescribeNode => {
+                if let Some(node_name) = node {
+                    self.debug_tools.run_kubectl_describe_node(&node_name).await
+                } else {
+                    crate::tools::DebugToolResult {
+                        tool_name: "kubectl_describe_node".to_string(),
+                        command: "kubectl describe node <missing-node-name>".to_string(),
+                        success: false,
+                        output: "To describe a node, you must first get the list of cluster nodes.\n\nSUGGESTED NEXT STEPS:\n1. Run: kubectl_get_nodes\n2. Run: kubectl_describe_node <node-name>".to_string(),
+                        error: Some("Node name required. Use kubectl_get_nodes first to see available nodes.".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
             DebugTool::KubectlGetEvents => {
                 self.debug_tools
                     .run_kubectl_get_events(namespace.as_deref())
@@ -1660,6 +2846,7 @@ Here is the conversation:\n\n{}",
             }
             DebugTool::JournalctlBoot => self.debug_tools.run_journalctl_boot().await,
             DebugTool::JournalctlErrors => self.debug_tools.run_journalctl_errors(lines).await,
+            DebugTool::JournalDiskUsage => self.debug_tools.run_journalctl_disk_usage().await,
             DebugTool::SystemctlStatus => {
                 if let Some(service_name) = service {
                     self.debug_tools.run_systemctl_status(&service_name).await
@@ -1674,7 +2861,22 @@ Here is the conversation:\n\n{}",
                     }
                 }
             }
+            DebugTool::SystemctlCat => {
+                if let Some(service_name) = service {
+                    self.debug_tools.run_systemctl_cat(&service_name).await
+                } else {
+                    crate::tools::DebugToolResult {
+                        tool_name: "systemctl_cat".to_string(),
+                        command: "systemctl cat <missing-service-name>".to_string(),
+                        success: false,
+                        output: "To show a unit definition, you must specify a service name.\n\nSUGGESTED NEXT STEPS:\n1. Use: systemctl_failed to see failed services\n2. Run: systemctl_cat <service-name>\n\nExample:\n- systemctl_cat nginx".to_string(),
+                        error: Some("Service name required. Try: systemctl_failed to see available services.".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
             DebugTool::PsAux => self.debug_tools.run_ps_aux().await,
+            DebugTool::PsTree => self.debug_tools.run_ps_tree().await,
             DebugTool::Netstat => self.debug_tools.run_netstat().await,
             DebugTool::Df => self.debug_tools.run_df().await,
             DebugTool::Free => self.debug_tools.run_free().await,
@@ -1696,6 +2898,12 @@ Here is the conversation:\n\n{}",
             }
             DebugTool::DnsConfig => self.debug_tools.run_dns_config().await,
             DebugTool::DnsTest => self.debug_tools.run_dns_test("google.com").await,
+            DebugTool::GetentHosts => {
+                self.debug_tools
+                    .run_getent_hosts(host.as_deref().unwrap_or("google.com"))
+                    .await
+            }
+            DebugTool::ResolvectlStatus => self.debug_tools.run_resolvectl_status().await,
             DebugTool::ConnectivityTest => self.debug_tools.run_connectivity_test().await,
             DebugTool::NetworkSetupCheck => self.debug_tools.run_network_setup_check().await,
             DebugTool::ArpTable => self.debug_tools.run_arp_table().await,
@@ -1704,31 +2912,78 @@ Here is the conversation:\n\n{}",
             DebugTool::NetworkManagerStatus => self.debug_tools.run_networkmanager_status().await,
             DebugTool::WirelessInfo => self.debug_tools.run_wireless_info().await,
             DebugTool::InterfaceStats => self.debug_tools.run_interface_stats().await,
+            DebugTool::EthtoolStats => {
+                let interface = self.debug_tools.interface_or_primary(interface.as_deref());
+                self.debug_tools.run_ethtool_stats(&interface).await
+            }
             DebugTool::NetworkHealthCheck => {
-                // For the comprehensive health check, run it and return combined results
-                let results = self.debug_tools.run_network_health_check().await;
-                
-                // Show each individual command that was executed
-                let combined_output = results.iter()
-                    .map(|r| format!("=== {} ===\nCommand: {}\n{}", r.tool_name, r.command, r.output))
-                    .collect::<Vec<_>>()
-                    .join("\n\n");
-                
-                // List all the actual commands that were run
-                let commands_run = results.iter()
-                    .map(|r| r.command.clone())
-                    .collect::<Vec<_>>()
-                    .join("; ");
-                    
-                crate::tools::DebugToolResult {
-                    tool_name: "network_health_check".to_string(),
-                    command: commands_run,
-                    success: results.iter().any(|r| r.success),
-                    output: combined_output,
-                    error: None,
-                    execution_time_ms: results.iter().map(|r| r.execution_time_ms).sum(),
+                // Composite tool: flatten all sub-results into one for the conversation
+                self.debug_tools.run_network_health_check().await.to_debug_tool_result()
+            }
+            // Process debugging tools
+            DebugTool::LsofPort => {
+                if let Some(port) = port {
+                    self.debug_tools.run_lsof_port(port).await
+                } else {
+                    crate::tools::DebugToolResult {
+                        tool_name: "lsof_port".to_string(),
+                        command: "lsof -i :<missing-port>".to_string(),
+                        success: false,
+                        output: "To find what holds a port, you must specify the port number.\n\nSUGGESTED NEXT STEPS:\n1. Use: ss to see listening ports\n2. Run: lsof_port <port>\n\nExample:\n- lsof_port 8080".to_string(),
+                        error: Some("Port number required. Try: ss to see listening ports.".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::LsofFile => {
+                if let Some(path) = path {
+                    self.debug_tools.run_lsof_file(&path).await
+                } else {
+                    crate::tools::DebugToolResult {
+                        tool_name: "lsof_file".to_string(),
+                        command: "lsof -- <missing-path>".to_string(),
+                        success: false,
+                        output: "To find what holds a file open, you must specify the file path.\n\nExample:\n- lsof_file /var/lib/dpkg/lock".to_string(),
+                        error: Some("File path required.".to_string()),
+                        execution_time_ms: 0,
+                    }
                 }
             }
+            DebugTool::TopConsumers => {
+                self.debug_tools
+                    .run_top_consumers(by.unwrap_or(crate::cli::SortKey::Cpu), count.unwrap_or(10))
+                    .await
+            }
+            DebugTool::ProcessLimits => self.debug_tools.run_process_limits(pid).await,
+            DebugTool::StracePid => match pid {
+                Some(pid) => {
+                    self.debug_tools
+                        .run_strace_pid(pid, duration.unwrap_or(crate::tools::process_debug::STRACE_DEFAULT_SECS))
+                        .await
+                }
+                None => crate::tools::DebugToolResult {
+                    tool_name: "strace_pid".to_string(),
+                    command: "strace -p".to_string(),
+                    success: false,
+                    output: String::new(),
+                    error: Some("PID required (e.g. strace_pid 1234)".to_string()),
+                    execution_time_ms: 0,
+                },
+            },
+            DebugTool::SecurityDenials => self.debug_tools.run_security_denials().await,
+            DebugTool::AuthSummary => self.debug_tools.run_auth_summary().await,
+            DebugTool::W => self.debug_tools.run_w().await,
+            DebugTool::Last => self.debug_tools.run_last(lines).await,
+            DebugTool::Timedatectl => self.debug_tools.run_timedatectl().await,
+            DebugTool::Swapon => self.debug_tools.run_swapon().await,
+            DebugTool::RefreshSystemInfo => {
+                let (system_info, result) = self.debug_tools.run_refresh_system_info(&self.collect_options).await;
+                self.system_info = Some(system_info);
+                result
+            }
+            DebugTool::TcpdumpSample => self.debug_tools.run_tcpdump_sample(None, duration).await,
+            DebugTool::BpftraceSyscalls => self.debug_tools.run_bpftrace_syscalls(duration).await,
+            DebugTool::BpftraceOom => self.debug_tools.run_bpftrace_oom(duration).await,
             // Add more tool implementations as needed
             _ => {
                 crate::tools::DebugToolResult {
@@ -1743,83 +2998,28 @@ Here is the conversation:\n\n{}",
         };
         
         // Print the actual command that was executed
-        println!("💻 Command executed: {}", result.command);
+        eprintln!("💻 Command executed: {}", result.command);
         if result.success {
-            println!("✅ Command completed successfully");
+            eprintln!("✅ Command completed successfully");
         } else {
-            println!("❌ Command failed");
+            eprintln!("❌ Command failed");
             if let Some(error) = &result.error {
-                println!("   Error: {}", error);
+                eprintln!("   Error: {}", error);
             }
         }
         
         result
     }
 
-    fn get_available_tools_description(&self) -> String {
-        r#"
-KUBERNETES TOOLS:
-- kubectl_get_pods [--namespace <ns>]: List all pods in namespace
-- kubectl_describe_pod <pod_name> [--namespace <ns>]: Get detailed pod information (REQUIRES pod name)
-- kubectl_get_services [--namespace <ns>]: List all services in namespace
-- kubectl_get_nodes: List all cluster nodes
-- kubectl_get_events [--namespace <ns>]: Get recent cluster events
-
-IMPORTANT: For kubectl_describe_pod, you MUST provide a pod name. First use kubectl_get_pods to see available pods, then describe specific ones.
-Example: 
-  1. CALL_TOOL: kubectl_get_pods --namespace kube-system
-  2. CALL_TOOL: kubectl_describe_pod coredns-12345 --namespace kube-system
-
-NETWORK DIAGNOSTIC TOOLS:
-- ip_addr: Show network interfaces and IP addresses
-- ip_route: Show routing table
-- ss: Show socket statistics and listening ports
-- ping: Test connectivity to 8.8.8.8 (Google DNS)
-- dig: Perform DNS lookup for google.com
-- traceroute: Trace network route to 8.8.8.8
-- dns_config: Show DNS configuration (/etc/resolv.conf)
-- dns_test: Test DNS resolution with multiple servers
-- connectivity_test: Test connectivity to multiple hosts
-- network_setup_check: Quick network setup check for standard users
-- network_health_check: Comprehensive network health check (runs multiple tools)
-- arp_table: Show ARP table
-- iptables: Show firewall rules
-- ufw_status: Check UFW firewall status
-- networkmanager_status: Check NetworkManager status
-- wireless_info: Show wireless interface information
-- interface_stats: Show network interface statistics
-
-SYSTEM LOGS:
-- journalctl_recent [--lines <n>]: Get recent system logs (default 50 lines)
-- journalctl_service <service_name> [--lines <n>]: Get logs for specific service (REQUIRES service name)
-- journalctl_boot: Get boot logs
-- journalctl_errors [--lines <n>]: Get error logs only
-
-SYSTEM SERVICES:
-- systemctl_status <service_name>: Get status of specific service (REQUIRES service name)
-- systemctl_failed: Show failed systemd units (use this first to find service names)
-
-IMPORTANT: For service-specific tools, use systemctl_failed first to see available service names.
-Example workflow:
-  1. CALL_TOOL: systemctl_failed
-  2. CALL_TOOL: systemctl_status docker
-  3. CALL_TOOL: journalctl_service docker --lines 50
-
-PROCESS & PERFORMANCE:
-- ps_aux: List all running processes
-- free: Show memory usage
-- df: Show disk usage
-- netstat: Show network connections (legacy)
-        "#.to_string()
-    }
 
     /// Get a summary of the conversation for debugging
     pub fn get_conversation_summary(&self) -> String {
         format!(
-            "Conversation with {} messages, {} tool calls used of {} limit",
+            "Conversation with {} messages, {} tool calls used of {} limit, {} format reprompts",
             self.conversation_history.len(),
             self.current_tool_calls,
-            self.max_tool_calls
+            self.max_tool_calls,
+            self.format_reprompts
         )
     }
 
@@ -1827,6 +3027,23 @@ PROCESS & PERFORMANCE:
     pub fn get_conversation_history(&self) -> &[AIAgentMessage] {
         &self.conversation_history
     }
+
+    /// Get the ordered list of tool calls made by the agent, for inclusion in reports
+    pub fn get_tool_call_transcript(&self) -> Vec<crate::output::ToolCallRecord> {
+        self.conversation_history
+            .iter()
+            .flat_map(|message| message.tool_calls.iter())
+            .filter_map(|tool_call| {
+                tool_call.result.as_ref().map(|result| crate::output::ToolCallRecord {
+                    tool_name: result.tool_name.clone(),
+                    command: result.command.clone(),
+                    success: result.success,
+                    execution_time_ms: result.execution_time_ms,
+                    reasoning: tool_call.reasoning.clone(),
+                })
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -1846,6 +3063,181 @@ mod tests {
         assert_eq!(agent.conversation_history.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_dummy_ai_health_check() {
+        assert!(DummyAI.health_check().await.is_ok());
+    }
+
+    #[cfg(not(feature = "local-inference"))]
+    #[tokio::test]
+    async fn test_llama_cpp_needs_the_local_inference_feature() {
+        let client = AIClient::new(AIConfig {
+            provider: AIProviderType::LlamaCpp,
+            api_key: None,
+            model: "/models/qwen2.5-3b-instruct-q4_k_m.gguf".to_string(),
+            base_url: None,
+            max_tokens: None,
+            temperature: None,
+            max_known_issues: 5,
+            use_known_issues: false,
+            analysis_timeout_secs: DEFAULT_ANALYSIS_TIMEOUT_SECS,
+            seed: None,
+        })
+        .await
+        .unwrap();
+        assert_eq!(client.name(), "llama.cpp");
+        assert!(matches!(client.health_check().await, Err(AIError::ConfigError(message)) if message.contains("local-inference")));
+        assert!(client.analyze("hello").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_health_check_fails_fast() {
+        let missing_key = AIClient::new(AIConfig {
+            provider: AIProviderType::OpenAI,
+            api_key: None,
+            model: "gpt-4o-mini".to_string(),
+            base_url: None,
+            max_tokens: None,
+            temperature: None,
+            max_known_issues: 5,
+            use_known_issues: true,
+            analysis_timeout_secs: DEFAULT_ANALYSIS_TIMEOUT_SECS,
+            seed: None,
+        })
+        .await
+        .unwrap();
+        assert!(matches!(missing_key.health_check().await, Err(AIError::ConfigError(_))));
+
+        // Nothing listens on the discard port, so the request errors without a long wait
+        let unreachable = AIClient::new(AIConfig {
+            provider: AIProviderType::Local,
+            api_key: None,
+            model: "llama2".to_string(),
+            base_url: Some("http://127.0.0.1:9".to_string()),
+            max_tokens: None,
+            temperature: None,
+            max_known_issues: 5,
+            use_known_issues: true,
+            analysis_timeout_secs: DEFAULT_ANALYSIS_TIMEOUT_SECS,
+            seed: None,
+        })
+        .await
+        .unwrap();
+        assert!(unreachable.health_check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_analysis_timeout() {
+        // Accept connections but never answer, like a provider that stalls mid-stream
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let client = AIClient::new(AIConfig {
+            provider: AIProviderType::Local,
+            api_key: None,
+            model: "llama2".to_string(),
+            base_url: Some(format!("http://{}", addr)),
+            max_tokens: None,
+            temperature: None,
+            max_known_issues: 5,
+            use_known_issues: true,
+            analysis_timeout_secs: 1,
+            seed: None,
+        })
+        .await
+        .unwrap();
+
+        match client.analyze("system is slow").await {
+            Err(AIError::APIError(message)) => assert!(message.contains("analysis timed out")),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_known_issues_disabled_skips_database() {
+        let config = AIConfig {
+            provider: AIProviderType::Local,
+            api_key: None,
+            model: "llama2".to_string(),
+            base_url: None,
+            max_tokens: None,
+            temperature: None,
+            max_known_issues: 5,
+            use_known_issues: false,
+            analysis_timeout_secs: DEFAULT_ANALYSIS_TIMEOUT_SECS,
+            seed: None,
+        };
+        let client = AIClient::new(config.clone()).await.unwrap();
+        assert!(client.known_issues.is_none());
+
+        // Every client reuses the one shared database
+        let first = AIClient::new(AIConfig { use_known_issues: true, ..config.clone() }).await.unwrap();
+        let second = AIClient::new(AIConfig { use_known_issues: true, ..config }).await.unwrap();
+        assert!(Arc::ptr_eq(first.known_issues.as_ref().unwrap(), second.known_issues.as_ref().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_scrub_secrets_masks_api_key() {
+        let client = AIClient::new(AIConfig {
+            provider: AIProviderType::OpenAI,
+            api_key: Some("sk-proj-abcdefghijkl1234".to_string()),
+            model: "gpt-4o-mini".to_string(),
+            base_url: None,
+            max_tokens: None,
+            temperature: None,
+            max_known_issues: 5,
+            use_known_issues: true,
+            analysis_timeout_secs: DEFAULT_ANALYSIS_TIMEOUT_SECS,
+            seed: None,
+        })
+        .await
+        .unwrap();
+
+        let scrubbed = client.scrub_secrets("Incorrect API key provided: sk-proj-abcdefghijkl1234.");
+        assert_eq!(scrubbed, "Incorrect API key provided: sk-...1234.");
+    }
+
+    #[tokio::test]
+    async fn test_seed_is_added_where_the_provider_supports_it() {
+        let config = AIConfig {
+            provider: AIProviderType::OpenAI,
+            api_key: Some("test-key".to_string()),
+            model: "gpt-4o-mini".to_string(),
+            base_url: None,
+            max_tokens: None,
+            temperature: None,
+            max_known_issues: 5,
+            use_known_issues: false,
+            analysis_timeout_secs: DEFAULT_ANALYSIS_TIMEOUT_SECS,
+            seed: Some(42),
+        };
+        let with_seed = |config: AIConfig| async move {
+            let mut body = serde_json::json!({ "model": config.model, "options": { "temperature": 0.7 } });
+            AIClient::new(config).await.unwrap().add_seed(&mut body);
+            body
+        };
+
+        let openai = with_seed(config.clone()).await;
+        assert_eq!(openai["seed"], 42);
+
+        let ollama = with_seed(AIConfig { provider: AIProviderType::Local, ..config.clone() }).await;
+        assert_eq!(ollama["options"]["seed"], 42);
+        assert_eq!(ollama["options"]["temperature"], 0.7);
+        assert!(ollama.get("seed").is_none());
+
+        let anthropic = with_seed(AIConfig { provider: AIProviderType::Anthropic, ..config.clone() }).await;
+        assert!(anthropic.get("seed").is_none() && anthropic["options"].get("seed").is_none());
+
+        let unseeded = with_seed(AIConfig { seed: None, ..config }).await;
+        assert!(unseeded.get("seed").is_none());
+    }
+
     #[tokio::test]
     async fn test_ai_agent_config_customization() {
         let dummy_ai = Box::new(DummyAI);
@@ -1854,6 +3246,8 @@ mod tests {
             pause_on_limit: false,
             allow_user_continuation: false,
             verbose_logging: true,
+            explain_reasoning: false,
+            context_window: None,
         };
         
         let agent = AIAgent::new(dummy_ai, config).await;
@@ -1877,6 +3271,10 @@ mod tests {
             agent.string_to_debug_tool("systemctl_status"),
             Some(DebugTool::SystemctlStatus)
         ));
+        assert!(matches!(
+            agent.string_to_debug_tool("systemctl_cat"),
+            Some(DebugTool::SystemctlCat)
+        ));
         
         assert!(agent.string_to_debug_tool("nonexistent_tool").is_none());
     }
@@ -1900,6 +3298,155 @@ mod tests {
         assert_eq!(agent.extract_arg(&parts, "--missing"), None);
     }
 
+    #[tokio::test]
+    async fn test_parse_sampling_duration() {
+        let dummy_ai = Box::new(DummyAI);
+        let config = AIAgentConfig::default();
+        let agent = AIAgent::new(dummy_ai, config).await;
+
+        let action = agent.parse_ai_action("CALL_TOOL: bpftrace_syscalls --duration 15").await;
+        match action {
+            crate::cli::AIAgentAction::RunTool { tool, args, .. } => {
+                assert!(matches!(tool, DebugTool::BpftraceSyscalls));
+                assert_eq!(args.duration, Some(15));
+            }
+            other => panic!("expected RunTool, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_describe_node_positional() {
+        let dummy_ai = Box::new(DummyAI);
+        let config = AIAgentConfig::default();
+        let agent = AIAgent::new(dummy_ai, config).await;
+
+        let action = agent.parse_ai_action("CALL_TOOL: kubectl_describe_node worker-1").await;
+        match action {
+            crate::cli::AIAgentAction::RunTool { tool, args, .. } => {
+                assert!(matches!(tool, DebugTool::KubectlDescribeNode));
+                assert_eq!(args.node.as_deref(), Some("worker-1"));
+                assert!(args.pod.is_none());
+            }
+            other => panic!("expected RunTool, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_strace_pid_positional() {
+        let agent = AIAgent::new(Box::new(DummyAI), AIAgentConfig::default()).await;
+
+        let action = agent.parse_ai_action("CALL_TOOL: strace_pid 4242 --duration 3").await;
+        match action {
+            crate::cli::AIAgentAction::RunTool { tool, args, .. } => {
+                assert!(matches!(tool, DebugTool::StracePid));
+                assert_eq!(args.pid, Some(4242));
+                assert_eq!(args.duration, Some(3));
+            }
+            other => panic!("expected RunTool, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_ethtool_stats_interface() {
+        let agent = AIAgent::new(Box::new(DummyAI), AIAgentConfig::default()).await;
+
+        let action = agent.parse_ai_action("CALL_TOOL: ethtool_stats enp3s0").await;
+        match action {
+            crate::cli::AIAgentAction::RunTool { tool, args, .. } => {
+                assert!(matches!(tool, DebugTool::EthtoolStats));
+                assert_eq!(args.interface.as_deref(), Some("enp3s0"));
+            }
+            other => panic!("expected RunTool, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_getent_hosts_positional() {
+        let agent = AIAgent::new(Box::new(DummyAI), AIAgentConfig::default()).await;
+
+        let action = agent.parse_ai_action("CALL_TOOL: getent_hosts db.internal.example").await;
+        match action {
+            crate::cli::AIAgentAction::RunTool { tool, args, .. } => {
+                assert!(matches!(tool, DebugTool::GetentHosts));
+                assert_eq!(args.host.as_deref(), Some("db.internal.example"));
+                assert!(args.service.is_none());
+            }
+            other => panic!("expected RunTool, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_system_info_cache_survives_reset() {
+        let info = crate::sysinfo::SystemInfo {
+            os: "Ubuntu 24.04".to_string(),
+            ..Default::default()
+        };
+        let mut agent = AIAgent::new(Box::new(DummyAI), AIAgentConfig::default())
+            .await
+            .with_system_info(info);
+        agent.reset();
+        assert_eq!(agent.system_info().map(|info| info.os.as_str()), Some("Ubuntu 24.04"));
+
+        let action = agent.parse_ai_action("CALL_TOOL: refresh_system_info").await;
+        assert!(matches!(
+            action,
+            crate::cli::AIAgentAction::RunTool { tool: DebugTool::RefreshSystemInfo, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_system_info_runs_every_time() {
+        let provider = scripted_agent_provider(vec![
+            "CALL_TOOL: refresh_system_info",
+            "CALL_TOOL: refresh_system_info",
+            "COMPLETE: Nothing changed between the two collections.",
+        ]);
+        let stale = crate::sysinfo::SystemInfo {
+            os: "stale".to_string(),
+            ..Default::default()
+        };
+        let mut agent = AIAgent::new(provider, AIAgentConfig::default()).await.with_system_info(stale);
+
+        let result = agent.run("did the restart help?", "OS: Linux").await.unwrap();
+        assert!(matches!(result, AIAgentResult::Success { tool_calls_used: 2, .. }));
+        assert_ne!(agent.system_info().map(|info| info.os.as_str()), Some("stale"));
+        assert!(!agent
+            .conversation_history
+            .iter()
+            .any(|m| m.content.starts_with("REMINDER: You already executed this tool call")));
+    }
+
+    #[tokio::test]
+    async fn test_agent_duration_is_clamped() {
+        let agent = AIAgent::new(Box::new(DummyAI), AIAgentConfig::default()).await;
+
+        let action = agent.parse_ai_action("CALL_TOOL: bpftrace_syscalls --duration 86400").await;
+        match action {
+            crate::cli::AIAgentAction::RunTool { args, .. } => assert_eq!(args.duration, Some(AGENT_MAX_DURATION_SECS)),
+            other => panic!("expected RunTool, got {:?}", other),
+        }
+
+        let action = agent.parse_ai_action("CALL_TOOL: tcpdump_sample --duration 5").await;
+        assert!(matches!(action, crate::cli::AIAgentAction::RunTool { args, .. } if args.duration == Some(5)));
+    }
+
+    #[tokio::test]
+    async fn test_parse_login_session_tools() {
+        let agent = AIAgent::new(Box::new(DummyAI), AIAgentConfig::default()).await;
+
+        let action = agent.parse_ai_action("REASONING: Check who logged in before the reboot\nCALL_TOOL: last --lines 5").await;
+        match action {
+            crate::cli::AIAgentAction::RunTool { tool, args, .. } => {
+                assert!(matches!(tool, DebugTool::Last));
+                assert_eq!(args.lines, Some(5));
+            }
+            other => panic!("expected RunTool, got {:?}", other),
+        }
+
+        let action = agent.parse_ai_action("CALL_TOOL: w").await;
+        assert!(matches!(action, crate::cli::AIAgentAction::RunTool { tool: DebugTool::W, .. }));
+    }
+
     #[tokio::test]
     async fn test_conversation_tracking() {
         let dummy_ai = Box::new(DummyAI);
@@ -1918,6 +3465,346 @@ mod tests {
         assert!(summary.contains("0 tool calls"));
     }
 
+    #[tokio::test]
+    async fn test_ask_follow_up_continues_conversation() {
+        let mut agent = AIAgent::new(Box::new(DummyAI), AIAgentConfig::default()).await;
+        agent.add_message(MessageRole::System, "You are a diagnostic agent".to_string());
+        agent.add_message(MessageRole::User, "Why is the disk full?".to_string());
+        agent.current_tool_calls = 7;
+
+        let result = agent.ask_follow_up("Which directory is largest?", "OS: Linux").await;
+        assert!(result.is_ok());
+        assert!(agent.current_tool_calls < 7);
+
+        let follow_up = agent
+            .conversation_history
+            .iter()
+            .find(|m| m.content.starts_with("Follow-up question:"))
+            .expect("follow-up recorded in the conversation");
+        assert!(follow_up.content.contains("Which directory is largest?"));
+        assert!(matches!(follow_up.role, MessageRole::User));
+    }
+
+    /// Replays canned responses, repeating the last one once the script runs out
+    struct ScriptedAI {
+        responses: std::sync::Mutex<Vec<&'static str>>,
+    }
+
+    #[async_trait]
+    impl AIProvider for ScriptedAI {
+        async fn analyze(&self, _input: &str) -> Result<String, AIError> {
+            let mut responses = self.responses.lock().unwrap();
+            let response = if responses.len() > 1 { responses.remove(0) } else { responses[0] };
+            Ok(response.to_string())
+        }
+
+        fn analyze_stream<'a>(&'a self, input: &'a str) -> AnalysisStream<'a> {
+            Box::pin(stream::once(self.analyze(input)))
+        }
+
+        async fn analyze_with_known_issues(&self, input: &str, _category: Option<IssueCategory>) -> Result<String, AIError> {
+            self.analyze(input).await
+        }
+
+        async fn answer_question(&self, question: &str, _system_context: &str) -> Result<String, AIError> {
+            self.analyze(question).await
+        }
+
+        async fn health_check(&self) -> Result<(), AIError> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+    }
+
+    fn scripted_agent_provider(responses: Vec<&'static str>) -> Box<dyn AIProvider> {
+        Box::new(ScriptedAI { responses: std::sync::Mutex::new(responses) })
+    }
+
+    #[tokio::test]
+    async fn test_unparseable_response_is_reprompted() {
+        let provider = scripted_agent_provider(vec!["ok", "COMPLETE: The root filesystem has plenty of free space left."]);
+        let mut agent = AIAgent::new(provider, AIAgentConfig::default()).await;
+
+        let result = agent.run("check disk usage", "OS: Linux").await.unwrap();
+        assert!(matches!(result, AIAgentResult::Success { tool_calls_used: 0, .. }));
+        assert_eq!(agent.format_reprompts(), 1);
+        assert!(agent
+            .conversation_history
+            .iter()
+            .any(|m| matches!(m.role, MessageRole::System) && m.content == FORMAT_REPROMPT));
+    }
+
+    #[tokio::test]
+    async fn test_complete_answer_with_fix_commands_is_kept() {
+        let answer = "COMPLETE: The disk is nearly full.\n\
+                      - **Issue**: /var/log is using 90% of the root filesystem\n\
+                      - **Verify**: `test $(df --output=pcent / | tail -1 | tr -dc 0-9) -lt 90`\n\
+                      - **Fix**: `journalctl --vacuum-size=200M`";
+        let mut agent = AIAgent::new(scripted_agent_provider(vec![answer]), AIAgentConfig::default()).await;
+
+        let result = agent
+            .run(&format!("why is my disk full{}", FIX_COMMANDS_PROMPT), "OS: Linux")
+            .await
+            .unwrap();
+        let AIAgentResult::Success { final_analysis, .. } = result else {
+            panic!("expected a final analysis");
+        };
+        let suggestions = crate::output::parse_fix_commands(&final_analysis);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].fix_command.as_deref(), Some("journalctl --vacuum-size=200M"));
+        assert!(suggestions[0].verify_command.as_deref().unwrap().starts_with("test $(df"));
+    }
+
+    #[tokio::test]
+    async fn test_reprompts_give_up_after_limit() {
+        let mut agent = AIAgent::new(scripted_agent_provider(vec!["hmm"]), AIAgentConfig::default()).await;
+
+        let result = agent.run("check disk usage", "OS: Linux").await.unwrap();
+        assert!(matches!(result, AIAgentResult::PausedForUserInput { .. }));
+        assert_eq!(agent.format_reprompts(), MAX_FORMAT_REPROMPTS);
+        assert_eq!(agent.current_tool_calls, 0);
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_without_reasoning_is_reprompted() {
+        let provider = scripted_agent_provider(vec![
+            "CALL_TOOL: df",
+            "REASONING: A full root filesystem would explain the failed writes\nCALL_TOOL: df",
+            "COMPLETE: The root filesystem has plenty of free space left.",
+        ]);
+        let config = AIAgentConfig { explain_reasoning: true, ..AIAgentConfig::default() };
+        let mut agent = AIAgent::new(provider, config).await;
+
+        let result = agent.run("check disk usage", "OS: Linux").await.unwrap();
+        assert!(matches!(result, AIAgentResult::Success { tool_calls_used: 1, .. }));
+        assert_eq!(agent.format_reprompts(), 1);
+        assert!(agent.conversation_history[0].content.contains("learning Linux administration"));
+
+        let transcript = agent.get_tool_call_transcript();
+        assert_eq!(transcript.len(), 1);
+        assert_eq!(
+            transcript[0].reasoning.as_deref(),
+            Some("A full root filesystem would explain the failed writes")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_reasoning_is_accepted_by_default() {
+        let provider = scripted_agent_provider(vec![
+            "CALL_TOOL: df",
+            "COMPLETE: The root filesystem has plenty of free space left.",
+        ]);
+        let mut agent = AIAgent::new(provider, AIAgentConfig::default()).await;
+
+        let result = agent.run("check disk usage", "OS: Linux").await.unwrap();
+        assert!(matches!(result, AIAgentResult::Success { tool_calls_used: 1, .. }));
+        assert_eq!(agent.format_reprompts(), 0);
+    }
+
+    /// Always fails with the error built by `error`
+    struct FailingAI {
+        error: fn() -> AIError,
+    }
+
+    impl FailingAI {
+        fn new(error: fn() -> AIError) -> Self {
+            Self { error }
+        }
+    }
+
+    #[async_trait]
+    impl AIProvider for FailingAI {
+        async fn analyze(&self, _input: &str) -> Result<String, AIError> {
+            Err((self.error)())
+        }
+
+        fn analyze_stream<'a>(&'a self, input: &'a str) -> AnalysisStream<'a> {
+            Box::pin(stream::once(self.analyze(input)))
+        }
+
+        async fn analyze_with_known_issues(&self, input: &str, _category: Option<IssueCategory>) -> Result<String, AIError> {
+            self.analyze(input).await
+        }
+
+        async fn answer_question(&self, question: &str, _system_context: &str) -> Result<String, AIError> {
+            self.analyze(question).await
+        }
+
+        async fn health_check(&self) -> Result<(), AIError> {
+            Err((self.error)())
+        }
+
+        fn name(&self) -> &str {
+            "Failing"
+        }
+    }
+
+    #[test]
+    fn test_stream_text_parsers() {
+        assert_eq!(
+            openai_stream_text(r#"data: {"choices":[{"delta":{"content":"Disk "}}]}"#).unwrap(),
+            Some("Disk ".to_string())
+        );
+        assert_eq!(openai_stream_text(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#).unwrap(), None);
+        assert_eq!(openai_stream_text("data: [DONE]").unwrap(), None);
+        assert_eq!(openai_stream_text("").unwrap(), None);
+        assert!(openai_stream_text(r#"data: {"error":{"message":"overloaded"}}"#).is_err());
+
+        assert_eq!(
+            anthropic_stream_text(r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"full"}}"#).unwrap(),
+            Some("full".to_string())
+        );
+        assert_eq!(anthropic_stream_text("event: content_block_delta").unwrap(), None);
+        assert_eq!(anthropic_stream_text(r#"data: {"type":"message_stop"}"#).unwrap(), None);
+        assert!(anthropic_stream_text(r#"data: {"type":"error","error":{"message":"Overloaded"}}"#).is_err());
+
+        let key = "sk-proj-abcdefghijkl1234";
+        let error = openai_stream_text(r#"data: {"error":{"message":"Incorrect API key provided: sk-proj-abcdefghijkl1234"}}"#)
+            .unwrap_err();
+        let scrubbed = scrub_stream_error(error, Some(key)).to_string();
+        assert!(!scrubbed.contains(key) && scrubbed.contains("sk-...1234"));
+
+        assert_eq!(ollama_stream_text(r#"{"response":"ok","done":false}"#).unwrap(), Some("ok".to_string()));
+        assert_eq!(ollama_stream_text(r#"{"response":"","done":true}"#).unwrap(), None);
+        assert!(ollama_stream_text(r#"{"error":"model not found"}"#).is_err());
+    }
+
+    #[test]
+    fn test_take_line_waits_for_the_line_ending() {
+        let mut buffer = b"data: {\"a\"".to_vec();
+        assert_eq!(take_line(&mut buffer), None);
+        buffer.extend_from_slice(b":1}\r\n\ndata: [DONE]");
+        assert_eq!(take_line(&mut buffer).as_deref(), Some("data: {\"a\":1}"));
+        assert_eq!(take_line(&mut buffer).as_deref(), Some(""));
+        assert_eq!(take_line(&mut buffer), None);
+        assert_eq!(buffer, b"data: [DONE]");
+    }
+
+    #[tokio::test]
+    async fn test_dummy_ai_streams_one_chunk() {
+        let chunks: Vec<_> = DummyAI.analyze_stream("check disk usage").collect().await;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(DummyAI.analyze("check disk usage").await.unwrap(), DUMMY_ANALYSIS);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_stream_fails_over_before_the_first_chunk() {
+        let chain = FallbackAIProvider::new(vec![
+            Box::new(FailingAI::new(|| AIError::APIError("OpenAI API error: 503".to_string()))),
+            scripted_agent_provider(vec!["from the local model"]),
+        ]);
+        let streamed: String = chain.analyze_stream("why is nginx down?").try_collect().await.unwrap();
+        assert_eq!(streamed, "from the local model");
+
+        let bad_request = FallbackAIProvider::new(vec![
+            Box::new(FailingAI::new(|| AIError::BadRequest("prompt too long".to_string()))),
+            scripted_agent_provider(vec!["never asked"]),
+        ]);
+        let chunks: Vec<_> = bad_request.analyze_stream("why is nginx down?").collect().await;
+        assert!(matches!(chunks.as_slice(), [Err(AIError::BadRequest(_))]));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_agent_still_parses_responses() {
+        let provider = scripted_agent_provider(vec!["COMPLETE: The root filesystem has plenty of free space left."]);
+        let mut agent = AIAgent::new(provider, AIAgentConfig::default()).await.with_streaming(true);
+
+        let result = agent.run("check disk usage", "OS: Linux").await.unwrap();
+        assert!(matches!(result, AIAgentResult::Success { tool_calls_used: 0, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_fallback_provider_fails_over_on_server_error() {
+        let chain = FallbackAIProvider::new(vec![
+            Box::new(FailingAI::new(|| AIError::APIError("OpenAI API error: 503".to_string()))),
+            Box::new(FailingAI::new(|| AIError::ConfigError("Anthropic API key not found".to_string()))),
+            scripted_agent_provider(vec!["from the local model"]),
+        ]);
+
+        assert_eq!(chain.analyze("why is nginx down?").await.unwrap(), "from the local model");
+        assert!(chain.health_check().await.is_ok());
+        assert_eq!(chain.name(), "Failing");
+    }
+
+    #[tokio::test]
+    async fn test_fallback_provider_stops_on_bad_request() {
+        let chain = FallbackAIProvider::new(vec![
+            Box::new(FailingAI::new(|| AIError::BadRequest("prompt too long".to_string()))),
+            scripted_agent_provider(vec!["should not be used"]),
+        ]);
+
+        // The scripted provider would have answered had the chain failed over
+        assert!(matches!(chain.analyze("why is nginx down?").await, Err(AIError::BadRequest(_))));
+
+        let all_failing = FallbackAIProvider::new(vec![Box::new(FailingAI::new(|| {
+            AIError::APIError("OpenAI API error: 502".to_string())
+        }))]);
+        assert!(matches!(all_failing.answer_question("q", "ctx").await, Err(AIError::APIError(_))));
+    }
+
+    #[test]
+    fn test_error_from_status() {
+        let bad_request = AIError::from_status(reqwest::StatusCode::BAD_REQUEST, "bad".to_string());
+        assert!(!bad_request.allows_failover());
+        let unauthorized = AIError::from_status(reqwest::StatusCode::UNAUTHORIZED, "auth".to_string());
+        assert!(unauthorized.allows_failover());
+        let unavailable = AIError::from_status(reqwest::StatusCode::SERVICE_UNAVAILABLE, "5xx".to_string());
+        assert!(unavailable.allows_failover());
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_transcript() {
+        let dummy_ai = Box::new(DummyAI);
+        let config = AIAgentConfig::default();
+        let mut agent = AIAgent::new(dummy_ai, config).await;
+
+        agent.add_message(MessageRole::User, "Why is the disk full?".to_string());
+        assert!(agent.get_tool_call_transcript().is_empty());
+
+        let result = crate::tools::DebugToolResult {
+            tool_name: "df".to_string(),
+            command: "df -h".to_string(),
+            success: true,
+            output: "Filesystem Size Used Avail Use% Mounted on".to_string(),
+            error: None,
+            execution_time_ms: 12,
+        };
+        agent
+            .add_tool_result(DebugTool::Df, result, Some("Check disk usage".to_string()))
+            .await;
+
+        let transcript = agent.get_tool_call_transcript();
+        assert_eq!(transcript.len(), 1);
+        assert_eq!(transcript[0].tool_name, "df");
+        assert_eq!(transcript[0].command, "df -h");
+        assert!(transcript[0].success);
+        assert_eq!(transcript[0].execution_time_ms, 12);
+        assert_eq!(transcript[0].reasoning.as_deref(), Some("Check disk usage"));
+    }
+
+    #[tokio::test]
+    async fn test_anonymizer_redacts_tool_output() {
+        let anonymizer = crate::output::anonymize::Anonymizer::new(vec!["db-7".to_string()], Vec::new());
+        let mut agent = AIAgent::new(Box::new(DummyAI), AIAgentConfig::default())
+            .await
+            .with_anonymizer(anonymizer);
+        let result = crate::tools::DebugToolResult {
+            tool_name: "ip_addr".to_string(),
+            command: "ip addr".to_string(),
+            success: true,
+            output: "db-7: inet 10.20.30.40/24".to_string(),
+            error: None,
+            execution_time_ms: 3,
+        };
+        agent.add_tool_result(DebugTool::IpAddr, result, None).await;
+
+        let sent = &agent.conversation_history.last().unwrap().content;
+        assert!(sent.ends_with("Output:\nhost-1: inet 10.0.0.1/24"));
+    }
+
     #[test]
     fn test_ai_agent_result_display() {
         let success_result = AIAgentResult::Success {
@@ -1946,4 +3833,58 @@ mod tests {
             _ => panic!("Expected LimitReached result"),
         }
     }
+
+    #[test]
+    fn test_initial_agent_prompt_matches_first_request() {
+        let prompt = initial_agent_prompt("Why is nginx failing?", "Operating System: Debian 12\n", false);
+        assert!(prompt.starts_with("SYSTEM: You are an expert Linux systems administrator"));
+        assert!(prompt.contains("- swapon:"));
+        assert!(prompt.contains("System Context:\nOperating System: Debian 12\n"));
+        assert!(prompt.ends_with("USER: Why is nginx failing?\n\n"));
+        assert!(!prompt.contains(EXPLAIN_REASONING_PROMPT));
+        assert!(initial_agent_prompt("q", "", true).contains(EXPLAIN_REASONING_PROMPT));
+    }
+
+    #[tokio::test]
+    async fn test_known_issues_prompt_section() {
+        assert_eq!(known_issues_prompt_section(&[]), "");
+
+        let dir = tempfile::tempdir().unwrap();
+        let database = KnownIssuesDatabase::with_issues_file(&dir.path().join("known_issues.yaml")).await;
+        let relevant = database
+            .get_scored_issues_for_context("Service 'nginx.service' has failed", None, 3)
+            .await;
+        let section = known_issues_prompt_section(&relevant);
+        assert!(section.starts_with("\n\nKNOWN ISSUES THAT MAY BE RELEVANT:\n- "));
+        assert!(section.ends_with("Consider these known issues when analyzing the system state.\n"));
+    }
+
+    #[tokio::test]
+    async fn test_explain_issue_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = KnownIssuesDatabase::with_issues_file(&dir.path().join("known_issues.yaml")).await;
+        let issue = database.get_issue("systemd-failed-units").await.unwrap();
+        let prompt = explain_issue_prompt(&issue);
+
+        assert!(prompt.starts_with("Determine whether this known issue applies to this system.\n\nKNOWN ISSUE systemd-failed-units: "));
+        assert!(prompt.contains(&format!("\nFix commands:\n- {}\n", issue.fix_commands[0])));
+        assert!(prompt.contains(&format!("\nSymptoms:\n- {}\n", issue.symptoms[0])));
+        assert!(prompt.contains("APPLIES, DOES NOT APPLY, or INCONCLUSIVE"));
+    }
+
+    #[tokio::test]
+    async fn test_prompt_size_warns_once_over_context_window() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcde"), 2);
+
+        let config = AIAgentConfig { context_window: Some(10), ..AIAgentConfig::default() };
+        let mut agent = AIAgent::new(Box::new(DummyAI), config).await;
+        assert_eq!(agent.check_prompt_size("short"), 2);
+        assert!(!agent.context_window_warned);
+        assert_eq!(agent.check_prompt_size(&"x".repeat(100)), 25);
+        assert!(agent.context_window_warned);
+
+        let warning = context_window_warning(25, 10);
+        assert!(warning.contains("~25 tokens") && warning.contains("context window of 10"));
+    }
 }
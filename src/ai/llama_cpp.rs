@@ -0,0 +1,176 @@
+//! In-process inference on a GGUF model through llama.cpp, for `--ai-provider llama-cpp`.
+//! Only built with the `local-inference` feature.
+
+use super::AIError;
+use llama_cpp_2::context::params::LlamaContextParams;
+use llama_cpp_2::llama_backend::LlamaBackend;
+use llama_cpp_2::llama_batch::LlamaBatch;
+use llama_cpp_2::model::params::LlamaModelParams;
+use llama_cpp_2::model::{LlamaChatMessage, LlamaModel};
+use llama_cpp_2::sampling::LlamaSampler;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+/// llama.cpp's seed value for "pick a random seed"
+const RANDOM_SEED: u32 = u32::MAX;
+
+/// llama.cpp's backend can only be initialized once per process
+static BACKEND: OnceLock<Result<LlamaBackend, String>> = OnceLock::new();
+
+fn backend() -> Result<&'static LlamaBackend, AIError> {
+    BACKEND
+        .get_or_init(|| LlamaBackend::init().map_err(|e| e.to_string()))
+        .as_ref()
+        .map_err(|e| AIError::LocalError(format!("llama.cpp failed to start: {}", e)))
+}
+
+fn local_error(e: impl std::fmt::Display) -> AIError {
+    AIError::LocalError(e.to_string())
+}
+
+/// A loaded GGUF model. Each generation gets its own context, so one model serves
+/// concurrent calls.
+pub struct LocalModel {
+    model: LlamaModel,
+}
+
+impl LocalModel {
+    pub fn load(path: &Path) -> Result<Self, AIError> {
+        if !path.is_file() {
+            return Err(AIError::ConfigError(format!(
+                "GGUF model '{}' not found; set ai.model (or --model) to the path of a .gguf file",
+                path.display()
+            )));
+        }
+        let model = LlamaModel::load_from_file(backend()?, path, &LlamaModelParams::default())
+            .map_err(|e| AIError::LocalError(format!("failed to load {}: {}", path.display(), e)))?;
+        Ok(Self { model })
+    }
+
+    /// The system and user messages in the model's own chat template, ending with an open
+    /// assistant turn. Models without a template get the messages separated by blank lines.
+    fn chat_prompt(&self, system: &str, user: &str) -> Result<String, AIError> {
+        let Ok(template) = self.model.chat_template(None) else {
+            return Ok(format!("{}\n\n{}\n\n", system, user));
+        };
+        let messages = [("system", system), ("user", user)]
+            .into_iter()
+            .map(|(role, content)| LlamaChatMessage::new(role.to_string(), content.to_string()).map_err(local_error))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.model.apply_chat_template(&template, &messages, true).map_err(local_error)
+    }
+
+    /// Generate a reply of at most `max_tokens` tokens, or fewer when the prompt leaves less
+    /// room in the context, passing each piece of text to `on_piece` as soon as it is decoded.
+    /// Generation stops early, keeping the text so far, once `cancelled` is set. A temperature
+    /// of 0 samples greedily; otherwise `seed` fixes the sampler so the same prompt gives the
+    /// same reply.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate(
+        &self,
+        system: &str,
+        user: &str,
+        max_tokens: u32,
+        temperature: f32,
+        seed: Option<u64>,
+        cancelled: &AtomicBool,
+        mut on_piece: impl FnMut(&str),
+    ) -> Result<String, AIError> {
+        let prompt = self.chat_prompt(system, user)?;
+        let vocab = self.model.vocab();
+        let tokens = vocab.tokenize(prompt.as_bytes(), true, true);
+        let prompt_tokens = u32::try_from(tokens.len()).map_err(local_error)?;
+        let n_ctx = prompt_tokens.saturating_add(max_tokens).min(self.model.n_ctx_train());
+        if tokens.is_empty() || prompt_tokens >= n_ctx {
+            return Err(AIError::BadRequest(format!(
+                "the prompt is {} tokens but the model's context holds {}",
+                prompt_tokens,
+                self.model.n_ctx_train()
+            )));
+        }
+
+        let params = LlamaContextParams::default()
+            .with_n_ctx(NonZeroU32::new(n_ctx))
+            .with_n_batch(n_ctx);
+        let mut context = self.model.new_context(backend()?, params).map_err(local_error)?;
+
+        // The whole prompt in one batch; only the last token's logits are needed
+        let mut batch = LlamaBatch::new(n_ctx as usize, 1);
+        let last = tokens.len() - 1;
+        for (position, token) in tokens.iter().enumerate() {
+            batch.add(*token, position as i32, &[0], position == last).map_err(local_error)?;
+        }
+        context.decode(&mut batch).map_err(local_error)?;
+
+        let mut sampler = if temperature <= 0.0 {
+            LlamaSampler::greedy()
+        } else {
+            LlamaSampler::chain_simple([LlamaSampler::temp(temperature), LlamaSampler::dist(seed.map_or(RANDOM_SEED, |seed| seed as u32))])
+        };
+
+        // Decoding past the end of the context fails, which would throw away the answer so far
+        let room = n_ctx - prompt_tokens;
+        let mut output = String::new();
+        let mut pending = Vec::new();
+        let mut position = tokens.len() as i32;
+        for _ in 0..max_tokens.min(room) {
+            if cancelled.load(Ordering::Relaxed) {
+                break;
+            }
+            let token = sampler.sample(&context, batch.n_tokens() - 1);
+            if vocab.is_eog(token) {
+                break;
+            }
+            pending.extend(vocab.token_to_piece(token, false, None));
+            let piece = take_complete_utf8(&mut pending);
+            if !piece.is_empty() {
+                on_piece(&piece);
+                output.push_str(&piece);
+            }
+
+            batch.clear();
+            batch.add(token, position, &[0], true).map_err(local_error)?;
+            position += 1;
+            context.decode(&mut batch).map_err(local_error)?;
+        }
+        output.push_str(&String::from_utf8_lossy(&pending));
+        Ok(output)
+    }
+}
+
+/// Remove and return the complete UTF-8 text at the start of `bytes`, leaving the start of a
+/// character split across tokens for the next piece. Invalid bytes are replaced, not kept.
+fn take_complete_utf8(bytes: &mut Vec<u8>) -> String {
+    let complete = match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => bytes.len(),
+    };
+    let text = String::from_utf8_lossy(&bytes[..complete]).into_owned();
+    bytes.drain(..complete);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_complete_utf8_keeps_split_characters() {
+        // "é" is 0xC3 0xA9; the first byte alone is not yet text
+        let mut bytes = b"caf\xC3".to_vec();
+        assert_eq!(take_complete_utf8(&mut bytes), "caf");
+        assert_eq!(bytes, vec![0xC3]);
+        bytes.push(0xA9);
+        assert_eq!(take_complete_utf8(&mut bytes), "é");
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_missing_model_is_a_config_error() {
+        let result = LocalModel::load(Path::new("/nonexistent/model.gguf"));
+        assert!(matches!(result, Err(AIError::ConfigError(message)) if message.contains("ai.model")));
+    }
+}
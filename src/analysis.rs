@@ -0,0 +1,150 @@
+//! Offline analysis: a readable report body built from the structured findings, used when
+//! no AI is asked for (`--no-ai`) or the AI provider cannot be reached
+
+use crate::output::{Issue, SystemHealthReport};
+use std::collections::BTreeMap;
+
+/// How many problems the summary lists before pointing at the full issue list
+const TOP_PROBLEMS: usize = 5;
+
+/// Severities from most to least urgent; anything else sorts after them
+const SEVERITY_ORDER: [&str; 5] = ["critical", "high", "medium", "low", "info"];
+
+fn severity_rank(severity: &str) -> usize {
+    SEVERITY_ORDER
+        .iter()
+        .position(|known| known.eq_ignore_ascii_case(severity))
+        .unwrap_or(SEVERITY_ORDER.len())
+}
+
+/// Summarize a report without AI: issue counts by severity, the most urgent problems and
+/// the fix commands of matched known issues. The same report always gives the same text.
+pub fn summarize_offline(report: &SystemHealthReport) -> String {
+    if report.issues.is_empty() && report.known_issue_matches.is_empty() {
+        return format!(
+            "Offline analysis (no AI): no issues found, overall status is {}.",
+            report.status.overall
+        );
+    }
+
+    let mut counts: BTreeMap<(usize, String), usize> = BTreeMap::new();
+    for issue in &report.issues {
+        let severity = issue.severity.to_lowercase();
+        *counts.entry((severity_rank(&severity), severity)).or_default() += 1;
+    }
+    let breakdown: Vec<String> = counts
+        .iter()
+        .map(|((_, severity), count)| format!("{} {}", count, severity))
+        .collect();
+
+    let mut summary = format!(
+        "Offline analysis (no AI): overall status is {}, {} issue(s)",
+        report.status.overall,
+        report.issues.len()
+    );
+    if !breakdown.is_empty() {
+        summary.push_str(&format!(" ({})", breakdown.join(", ")));
+    }
+    summary.push_str(".\n");
+
+    // Stable sort keeps the collection order within a severity
+    let mut problems: Vec<&Issue> = report.issues.iter().collect();
+    problems.sort_by_key(|issue| severity_rank(&issue.severity));
+    if !problems.is_empty() {
+        summary.push_str("\nTop problems:\n");
+        for (index, issue) in problems.iter().take(TOP_PROBLEMS).enumerate() {
+            summary.push_str(&format!(
+                "{}. [{}] {}: {}\n",
+                index + 1,
+                issue.severity,
+                issue.category,
+                issue.message
+            ));
+        }
+        if problems.len() > TOP_PROBLEMS {
+            summary.push_str(&format!("...and {} more in the issue list\n", problems.len() - TOP_PROBLEMS));
+        }
+    }
+
+    if !report.known_issue_matches.is_empty() {
+        summary.push_str("\nMatching known issues:\n");
+        for known in &report.known_issue_matches {
+            summary.push_str(&format!(
+                "- {} ({}, {} severity, {:.0}% match)\n",
+                known.title,
+                known.id,
+                known.severity,
+                (known.confidence * 100.0).min(100.0)
+            ));
+            for command in &known.fix_commands {
+                summary.push_str(&format!("    {}\n", command));
+            }
+        }
+    }
+
+    summary.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{create_system_health_report, KnownIssueMatch};
+    use crate::sysinfo::SystemInfo;
+
+    fn issue(category: &str, severity: &str, message: &str) -> Issue {
+        Issue::new(category, severity, message, None)
+    }
+
+    #[test]
+    fn test_summarize_offline_healthy() {
+        let report = create_system_health_report(&SystemInfo::default(), "", false);
+        assert_eq!(
+            summarize_offline(&report),
+            "Offline analysis (no AI): no issues found, overall status is healthy."
+        );
+    }
+
+    #[test]
+    fn test_summarize_offline_orders_by_severity() {
+        let mut report = create_system_health_report(&SystemInfo::default(), "", false);
+        report.status.overall = "critical".to_string();
+        report.issues = vec![
+            issue("log", "medium", "Error in sshd: connection reset"),
+            issue("service", "high", "Service 'nginx.service' has failed"),
+            issue("time", "medium", "System clock is not synchronized"),
+        ];
+        report.known_issue_matches = vec![KnownIssueMatch {
+            id: "systemd-failed-units".to_string(),
+            title: "Systemd Failed Units".to_string(),
+            severity: "high".to_string(),
+            confidence: 0.8,
+            fix_commands: vec!["systemctl restart <unit-name>".to_string()],
+        }];
+
+        let summary = summarize_offline(&report);
+        assert_eq!(
+            summary,
+            "Offline analysis (no AI): overall status is critical, 3 issue(s) (1 high, 2 medium).\n\
+             \n\
+             Top problems:\n\
+             1. [high] service: Service 'nginx.service' has failed\n\
+             2. [medium] log: Error in sshd: connection reset\n\
+             3. [medium] time: System clock is not synchronized\n\
+             \n\
+             Matching known issues:\n\
+             - Systemd Failed Units (systemd-failed-units, high severity, 80% match)\n    \
+             systemctl restart <unit-name>"
+        );
+        assert_eq!(summary, summarize_offline(&report));
+    }
+
+    #[test]
+    fn test_summarize_offline_caps_problem_list() {
+        let mut report = create_system_health_report(&SystemInfo::default(), "", false);
+        report.issues = (0..8).map(|i| issue("log", "low", &format!("error {}", i))).collect();
+        let summary = summarize_offline(&report);
+        assert!(summary.contains("5. [low] log: error 4"));
+        assert!(!summary.contains("error 5"));
+        assert!(summary.ends_with("...and 3 more in the issue list"));
+    }
+}
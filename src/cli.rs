@@ -27,8 +27,8 @@ pub struct Cli {
     #[arg(long, short = 'k', env = "AI_API_KEY")]
     pub ai_api_key: Option<String>,
 
-    /// AI model to use
-    #[arg(long, short = 'm', env = "AI_MODEL")]
+    /// AI model to use for this run, overriding `ai.model` from the config file
+    #[arg(long, short = 'm', visible_alias = "model", env = "AI_MODEL")]
     pub ai_model: Option<String>,
 
     /// Base URL for AI provider (for custom endpoints)
@@ -43,6 +43,10 @@ pub struct Cli {
     #[arg(long, env = "AI_TEMPERATURE")]
     pub ai_temperature: Option<f32>,
 
+    /// Sampling seed so repeated runs give the same AI response (OpenAI, Ollama, llama.cpp)
+    #[arg(long, env = "AI_SEED")]
+    pub seed: Option<u64>,
+
     /// Maximum tool calls for AI agent mode (default: 50)
     #[arg(long, env = "AI_MAX_TOOL_CALLS", default_value = "50")]
     pub ai_max_tool_calls: usize,
@@ -55,18 +59,120 @@ pub struct Cli {
     #[arg(long)]
     pub dry_run: bool,
 
-    /// Enable verbose output
-    #[arg(long, short = 'v', default_value = "false")]
-    pub verbose: bool,
+    /// Run every check and match known issues locally, without calling an AI provider
+    #[arg(long, visible_alias = "offline", conflicts_with = "dry_run")]
+    pub no_ai: bool,
+
+    /// Print the prompt the AI would receive (system and user parts) and exit without sending it
+    #[arg(long, conflicts_with_all = ["dry_run", "no_ai"])]
+    pub prompt_only: bool,
+
+    /// Redact the prompt like --anonymize does, show it and ask before sending it; tool output sent later is redacted too
+    #[arg(long, conflicts_with_all = ["dry_run", "no_ai", "prompt_only"])]
+    pub redact_preview: bool,
+
+    /// Answer yes to confirmation prompts (e.g. --redact-preview)
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
+    /// Answer every question in this file (one per line) against a single collection
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["dry_run", "no_ai", "prompt_only", "redact_preview"])]
+    pub questions_file: Option<String>,
+
+    /// After the analysis, list the AI's Verify/Fix command pairs; `--apply-fixes=run --yes` runs them
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "print",
+        conflicts_with_all = ["dry_run", "no_ai", "prompt_only", "questions_file", "redact_preview"]
+    )]
+    pub apply_fixes: Option<ApplyFixesMode>,
+
+    /// Let --apply-fixes run commands outside its safe list (rm, sudo, redirects into files, ...)
+    #[arg(long, requires = "apply_fixes")]
+    pub allow_destructive: bool,
+
+    /// File `--apply-fixes=run` appends every command, its output and exit code to
+    #[arg(long, value_name = "PATH", default_value = "raid-fixes.log")]
+    pub fix_log: String,
+
+    /// Have the AI check whether a known issue (see `issues list`) applies to this system and tailor its fix
+    #[arg(long, value_name = "ISSUE_ID", conflicts_with_all = ["dry_run", "no_ai", "questions_file"])]
+    pub explain_issue: Option<String>,
+
+    /// Increase output detail (-v for detailed, -vv for full)
+    #[arg(long, short = 'v', action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Stay silent on healthy systems; print a summary only when issues are found
+    #[arg(long, short = 'q', conflicts_with = "verbose")]
+    pub quiet: bool,
 
-    /// Output format (text, yaml, json)
-    #[arg(long, short = 'o', value_enum, default_value = "text")]
-    pub output_format: OutputFormat,
+    /// In quiet mode with JSON output, print {"status":"healthy"} instead of nothing
+    #[arg(long, requires = "quiet")]
+    pub quiet_json: bool,
+
+    /// Output format (text, yaml, json, jsonl, csv); defaults to output.format from the config, then text
+    #[arg(long, short = 'o', visible_alias = "format", value_enum)]
+    pub output_format: Option<OutputFormat>,
+
+    /// JSON Lines output: one compact JSON report per line (same as --format jsonl)
+    #[arg(long)]
+    pub jsonl: bool,
 
     /// Configuration file path
     #[arg(long, short = 'c')]
     pub config: Option<String>,
 
+    /// Only collect and show journal entries from this systemd unit
+    #[arg(long)]
+    pub unit: Option<String>,
+
+    /// Only analyze the current boot: journal entries since the last reboot, and an AI told
+    /// to focus on what broke since then
+    #[arg(long)]
+    pub since_boot: bool,
+
+    /// List every check component with what it covers, then exit (honors --output)
+    #[arg(long)]
+    pub list_checks: bool,
+
+    /// Add this text file to the AI context as its own section (e.g. region, cluster name,
+    /// on-call runbook URL)
+    #[arg(long, value_name = "PATH")]
+    pub context_file: Option<std::path::PathBuf>,
+
+    /// Only report systemd units matching this glob (repeatable, e.g. 'nginx*')
+    #[arg(long, value_name = "GLOB")]
+    pub unit_include: Vec<String>,
+
+    /// Ignore systemd units matching this glob (repeatable, e.g. '*.scope')
+    #[arg(long, value_name = "GLOB")]
+    pub unit_exclude: Vec<String>,
+
+    /// Replace hostnames, IPs, MACs and usernames in JSON/YAML reports with stable pseudonyms
+    #[arg(long, visible_alias = "redact-host")]
+    pub anonymize: bool,
+
+    /// Include a previous JSON/YAML report in the AI context and ask what changed since
+    #[arg(long, value_name = "REPORT")]
+    pub continue_from: Option<String>,
+
+    /// Omit the "Commands executed" footer listing the commands run for the analysis
+    #[arg(long)]
+    pub no_commands: bool,
+
+    /// Make the AI explain why it runs each tool, and list that reasoning after the answer
+    #[arg(long)]
+    pub explain_reasoning: bool,
+
+    /// Don't add matching known issues to the AI prompt (smaller prompts, no database lookup)
+    #[arg(long)]
+    pub no_known_issues: bool,
+
     /// Disable colored output
     #[arg(long)]
     pub no_color: bool,
@@ -75,6 +181,22 @@ pub struct Cli {
     #[arg(long)]
     pub no_progress: bool,
 
+    /// Show progress indicators even when stdout is not a terminal
+    #[arg(long, conflicts_with = "no_progress")]
+    pub force_progress: bool,
+
+    /// Do not store checks in the database for this run
+    #[arg(long, visible_alias = "ephemeral")]
+    pub no_store: bool,
+
+    /// Hard wall-clock limit for the whole run; on expiry print what was collected and exit 124
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: Option<u64>,
+
+    /// Check GitHub for a newer RAID release and print a notice if there is one (off by default)
+    #[arg(long)]
+    pub check_update: bool,
+
     /// Subcommand to execute
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -99,12 +221,42 @@ pub enum Commands {
         /// Pod name for describe commands
         #[arg(long, short = 'p')]
         pod: Option<String>,
+        /// Node name (for kubectl-describe-node)
+        #[arg(long)]
+        node: Option<String>,
         /// Service name for service-specific commands
         #[arg(long, short = 's')]
         service: Option<String>,
         /// Number of lines to show (for journalctl)
         #[arg(long, short = 'l')]
         lines: Option<usize>,
+        /// Port number (for lsof-port)
+        #[arg(long)]
+        port: Option<u16>,
+        /// File path (for lsof-file)
+        #[arg(long)]
+        path: Option<String>,
+        /// Sort key (for top-consumers)
+        #[arg(long, value_enum)]
+        by: Option<SortKey>,
+        /// Number of entries to show (for top-consumers)
+        #[arg(long)]
+        count: Option<usize>,
+        /// Sample duration in seconds (for bpftrace, tcpdump and strace sampling tools)
+        #[arg(long)]
+        duration: Option<u64>,
+        /// Process ID (for process-limits and strace-pid)
+        #[arg(long)]
+        pid: Option<u32>,
+        /// Host name to resolve (for getent-hosts)
+        #[arg(long)]
+        host: Option<String>,
+        /// Network interface (for ethtool and ethtool-stats; default: the one with the default route)
+        #[arg(long)]
+        interface: Option<String>,
+        /// Print the tool's output line by line as it arrives instead of when it finishes
+        #[arg(long)]
+        stream: bool,
     },
     /// Manage known issues database
     Issues {
@@ -117,6 +269,44 @@ pub enum Commands {
         /// Search query (for search action)
         #[arg(long, short = 'q')]
         query: Option<String>,
+        /// YAML or JSON file (for import and export actions)
+        #[arg(long, short = 'f')]
+        file: Option<String>,
+        /// Replace existing issues with the same id (for import action)
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Interactive session: ask follow-up questions about this system until /quit
+    Ask {
+        /// First question (prompted for when omitted)
+        question: Option<String>,
+    },
+    /// Check RAID's own prerequisites (tools, AI provider, config, database)
+    Doctor,
+    /// List every probed tool per category, whether it was found and why not
+    ToolsList,
+    /// Time each quick tool of the available categories and suggest a tool timeout
+    Benchmark {
+        /// Tool categories to run (default: every available one)
+        #[arg(value_enum)]
+        categories: Vec<crate::tools::ToolCategory>,
+    },
+    /// Package a full offline scan, the report, system info and redacted config into a .tar.gz
+    #[command(visible_alias = "offline-bundle")]
+    Bundle {
+        /// Tarball path (default: raid-bundle-<timestamp>.tar.gz)
+        #[arg(long, short = 'o')]
+        output: Option<String>,
+        /// Also write a SHA-256 checksum file, and an HMAC when signing.key is set
+        #[arg(long)]
+        sign: bool,
+    },
+    /// Check a report file against the checksum or HMAC written by --sign
+    Verify {
+        /// The report file (e.g. a bundle tarball)
+        report: std::path::PathBuf,
+        /// Its `.sha256` or `.hmac` file
+        signature: std::path::PathBuf,
     },
     /// Configuration management
     Config {
@@ -126,14 +316,62 @@ pub enum Commands {
         /// Output path for generated config (for init action)
         #[arg(long, short = 'o')]
         output: Option<String>,
+        /// Show secrets such as the API key unmasked (for show action)
+        #[arg(long)]
+        reveal_secrets: bool,
     },
 }
 
-#[derive(ValueEnum, Debug, Clone)]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputFormat {
     Text,
     Yaml,
     Json,
+    /// One compact JSON report per line
+    Jsonl,
+    /// One row per issue (category, severity, message, details, timestamp)
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Json => "json",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Csv => "csv",
+        }
+    }
+
+    /// Parse a format name from a config file or the environment (case-insensitive, `yml` for `yaml`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "yaml" | "yml" => Some(OutputFormat::Yaml),
+            "json" => Some(OutputFormat::Json),
+            "jsonl" => Some(OutputFormat::Jsonl),
+            "csv" => Some(OutputFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// What `--apply-fixes` does with the suggested fixes
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyFixesMode {
+    /// List the Verify/Fix pairs without running anything
+    Print,
+    /// Run each verify command, and its fix only when the verify command fails
+    Run,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Sort by CPU usage
+    Cpu,
+    /// Sort by memory usage
+    Mem,
 }
 
 #[derive(ValueEnum, Debug, Clone)]
@@ -141,6 +379,8 @@ pub enum AIProvider {
     OpenAI,
     Anthropic,
     Local,
+    /// A GGUF model run in-process (needs a build with the `local-inference` feature)
+    LlamaCpp,
 }
 
 impl AIProvider {
@@ -149,8 +389,14 @@ impl AIProvider {
             AIProvider::OpenAI => "openai",
             AIProvider::Anthropic => "anthropic",
             AIProvider::Local => "local",
+            AIProvider::LlamaCpp => "llama-cpp",
         }
     }
+
+    /// Hosted APIs need a key; Ollama and in-process models don't
+    pub fn needs_api_key(&self) -> bool {
+        matches!(self, AIProvider::OpenAI | AIProvider::Anthropic)
+    }
 }
 
 #[derive(ValueEnum, Debug, Clone)]
@@ -179,11 +425,15 @@ pub enum IssueAction {
     Update,
     /// Delete an issue
     Delete,
+    /// Write all issues to a YAML/JSON file (requires --file)
+    Export,
+    /// Merge issues from a YAML/JSON file into the database (requires --file)
+    Import,
 }
 
 #[derive(ValueEnum, Debug, Clone)]
 pub enum ConfigAction {
-    /// Initialize a new configuration file
+    /// Initialize a new configuration file (TOML when the output path ends in .toml, YAML otherwise)
     Init,
     /// Show current configuration (merged from all sources)
     Show,
@@ -191,6 +441,8 @@ pub enum ConfigAction {
     Validate,
     /// Show configuration file locations
     Locations,
+    /// Upgrade an older config file to the current schema, keeping the original as <file>.bak
+    Migrate,
 }
 
 #[derive(ValueEnum, Debug, Clone)]
@@ -203,6 +455,8 @@ pub enum DebugTool {
     KubectlGetServices,
     /// Get Kubernetes nodes
     KubectlGetNodes,
+    /// Describe a Kubernetes node's conditions and allocatable resources (requires --node)
+    KubectlDescribeNode,
     /// Get Kubernetes events
     KubectlGetEvents,
     /// Get recent journal logs
@@ -213,10 +467,16 @@ pub enum DebugTool {
     JournalctlBoot,
     /// Get error logs
     JournalctlErrors,
+    /// Journal size against its SystemMaxUse cap, plus journald rotation errors (journalctl --disk-usage)
+    JournalDiskUsage,
     /// Get systemctl status for a service
     SystemctlStatus,
+    /// Show a service's unit file plus drop-in overrides (requires --service)
+    SystemctlCat,
     /// Get process list
     PsAux,
+    /// Process hierarchy from /proc, calling out parents of many children and deep nesting
+    PsTree,
     /// Get network connections
     Netstat,
     /// Get disk usage
@@ -311,6 +571,8 @@ pub enum DebugTool {
     Iptables,
     /// [Network] Show ethernet interface statistics
     Ethtool,
+    /// [Network] Show NIC driver statistics (ethtool -S) and flag non-zero error counters
+    EthtoolStats,
     /// [Network] Show network connections and routing tables (deprecated netstat alternative)
     NetstatLegacy,
     /// [Network] Show ARP table
@@ -321,7 +583,7 @@ pub enum DebugTool {
     Iperf3,
     /// [Network] Show network namespaces
     NetworkNamespaces,
-    /// [Network] Monitor network traffic
+    /// [Network] Monitor network traffic (--duration to capture for a fixed time)
     TcpdumpSample,
     /// [Network] Show bridge information
     BridgeInfo,
@@ -337,6 +599,10 @@ pub enum DebugTool {
     NetworkManagerStatus,
     /// [Network] Check DNS configuration (/etc/resolv.conf)
     DnsConfig,
+    /// [Network] Resolve a name through the system resolver stack, as applications do (--host, default: google.com)
+    GetentHosts,
+    /// [Network] Show systemd-resolved DNS servers and search domains per link
+    ResolvectlStatus,
     /// [Network] Test connectivity to multiple hosts
     ConnectivityTest,
     /// [Network] Comprehensive network health check
@@ -373,12 +639,56 @@ pub enum DebugTool {
     BpfLsPinned,
     /// [eBPF] Show kernel BPF configuration
     BpfKernelConfig,
-    /// [eBPF] Simple BPF tracing (syscall counts)
+    /// [eBPF] Simple BPF tracing (syscall counts, --duration)
     BpftraceSyscalls,
+    /// [eBPF] Trace OOM kills for a few seconds (requires root, --duration)
+    BpftraceOom,
     /// [eBPF] List available BPF tracepoints
     BpftraceListTracepoints,
     /// [eBPF] Check BPF JIT compiler status
     BpfJitStatus,
+    /// [Process] Find the process holding a port (requires --port)
+    LsofPort,
+    /// [Process] Find the processes holding a file open (requires --path)
+    LsofFile,
+    /// [Process] Show the top processes by CPU or memory (--by cpu|mem, --count)
+    TopConsumers,
+    /// [Process] Open files and threads against their limits (--pid, default: top consumers)
+    ProcessLimits,
+    /// [Process] Summarize the syscalls of a stuck process for a few seconds (requires --pid, --duration)
+    StracePid,
+    /// [Security] Summarize recent SELinux and AppArmor denials
+    SecurityDenials,
+    /// [Security] Failed SSH logins in the last 24h by source address, flagging brute force
+    AuthSummary,
+    /// [Security] Users currently logged in and what they are running
+    W,
+    /// [Security] Recent logins and reboots (--lines, default: 20)
+    Last,
+    /// [System] Clock synchronization status and NTP offset (timedatectl, chronyc/ntpq)
+    Timedatectl,
+    /// [System] Active swap devices and files with their usage (swapon --show)
+    Swapon,
+    /// [System] Re-collect system information (memory, disk, swap, pressure, failed units)
+    RefreshSystemInfo,
+}
+
+/// Optional arguments passed to a debug tool
+#[derive(Debug, Clone, Default)]
+pub struct DebugToolArgs {
+    pub namespace: Option<String>,
+    pub pod: Option<String>,
+    pub node: Option<String>,
+    pub service: Option<String>,
+    pub lines: Option<usize>,
+    pub port: Option<u16>,
+    pub path: Option<String>,
+    pub by: Option<SortKey>,
+    pub count: Option<usize>,
+    pub duration: Option<u64>,
+    pub pid: Option<u32>,
+    pub host: Option<String>,
+    pub interface: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -386,16 +696,15 @@ pub enum AIAgentAction {
     /// Run a debug tool
     RunTool {
         tool: DebugTool,
-        namespace: Option<String>,
-        pod: Option<String>,
-        service: Option<String>,
-        lines: Option<usize>,
+        args: Box<DebugToolArgs>,
         reasoning: Option<String>,
     },
     /// Provide final analysis/answer
     ProvideAnalysis { analysis: String },
     /// Ask user for more information
     AskUser { question: String },
+    /// The response used none of the required formats and should be reprompted
+    Unparseable { response: String },
 }
 
 impl CheckComponent {
@@ -411,15 +720,39 @@ impl CheckComponent {
             CheckComponent::Debug => "debug",
         }
     }
+
+    /// What a check of this component looks at; used in the agent prompt and `--list-checks`
+    pub fn focus(&self) -> &'static str {
+        match self {
+            CheckComponent::All => "comprehensive system health check",
+            CheckComponent::System => "system information and performance analysis",
+            CheckComponent::Containers => "container and Docker analysis",
+            CheckComponent::Kubernetes => "Kubernetes cluster analysis",
+            CheckComponent::Cgroups => "cgroups and resource management analysis",
+            CheckComponent::Systemd => "systemd services and system management analysis",
+            CheckComponent::Journal => "system logs and journal analysis",
+            CheckComponent::Debug => "debug tools analysis",
+        }
+    }
 }
 
 impl Cli {
+    /// The component selected with `check --component`, or `All` for every other run
+    pub fn check_component(&self) -> CheckComponent {
+        match &self.command {
+            Some(Commands::Check { component }) => component.clone(),
+            _ => CheckComponent::All,
+        }
+    }
+
     /// Get the default model for the selected AI provider
     pub fn get_default_model(&self) -> String {
         match self.ai_provider {
             AIProvider::OpenAI => "gpt-4o-mini".to_string(),
             AIProvider::Anthropic => "claude-3-5-sonnet-20241022".to_string(),
             AIProvider::Local => "llama2".to_string(),
+            // The GGUF path has no sensible default
+            AIProvider::LlamaCpp => String::new(),
         }
     }
 
@@ -437,6 +770,12 @@ impl Cli {
             Some(Commands::Debug { .. }) => false, // Debug commands don't store in database
             Some(Commands::Issues { .. }) => false, // Issues commands don't store in database
             Some(Commands::Config { .. }) => false, // Config commands don't store in database
+            Some(Commands::Doctor) => false,        // Doctor doesn't store in database
+            Some(Commands::ToolsList) => false,     // Tool lists don't store in database
+            Some(Commands::Benchmark { .. }) => false, // Benchmarks only time tools
+            Some(Commands::Ask { .. }) => false,    // Ask sessions don't store in database
+            Some(Commands::Bundle { .. }) => false, // Bundles go to a tarball, not the database
+            Some(Commands::Verify { .. }) => false, // Verification only reads files
             None => true,                          // Default to full check when no subcommand
         }
     }
@@ -448,6 +787,12 @@ impl Cli {
             Some(Commands::Debug { .. }) => CheckComponent::Debug,
             Some(Commands::Issues { .. }) => CheckComponent::All, // Issues commands default to all
             Some(Commands::Config { .. }) => CheckComponent::All, // Config commands default to all
+            Some(Commands::Doctor) => CheckComponent::All,        // Doctor defaults to all
+            Some(Commands::ToolsList) => CheckComponent::All,     // Tool lists cover every category
+            Some(Commands::Benchmark { .. }) => CheckComponent::Debug, // Benchmarks run debug tools
+            Some(Commands::Ask { .. }) => CheckComponent::All,    // Ask sessions default to all
+            Some(Commands::Bundle { .. }) => CheckComponent::All, // Bundles always cover everything
+            Some(Commands::Verify { .. }) => CheckComponent::All, // Verification runs no checks
             None => CheckComponent::All,                          // Default to all if no subcommand
         }
     }
@@ -1,8 +1,15 @@
-use crate::ai::{create_ai_provider_from_cli, AIAgent, AIAgentConfig, AIAgentResult};
+use crate::ai::{create_ai_provider_from_cli, since_boot_context, AIAgent, AIAgentConfig, AIAgentResult, AIError, AIProvider};
+use crate::cli::{CheckComponent, OutputFormat};
 use crate::config::RaidConfig;
-use crate::sysinfo::collect_basic_system_info;
-use crate::ui::UIFormatter;
+use crate::context::ContextBuilder;
+use crate::known_issues::IssueCategory;
+use crate::output::{self, Issue};
+use crate::sysinfo::{self, collect_basic_system_info_with, BasicSystemInfo, SystemInfo};
+use crate::ui::{print_commands_footer, print_reasoning_transcript, UIFormatter};
+use serde::Serialize;
 use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
 
 /// Types of AI analysis to determine prompting strategy
 #[derive(Debug, Clone)]
@@ -19,10 +26,10 @@ pub async fn run_question_answering_with_config(
     ui_formatter: &UIFormatter,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Check if AI API key is available
-    if config.ai.api_key.is_none() {
+    if config.missing_api_key() {
         println!("❌ No AI API key found. Question answering requires an AI provider.");
         println!("Please set your AI_API_KEY environment variable or use --ai-api-key flag.");
-        println!("Supported providers: OpenAI, Anthropic, Local (Ollama)");
+        println!("Supported providers: OpenAI, Anthropic, Local (Ollama), llama.cpp (GGUF file)");
         println!("\nFor a basic system check without AI, use: cargo run -- --dry-run");
         return Ok(());
     }
@@ -35,6 +42,11 @@ pub async fn run_question_answering_with_config(
         config.ai.base_url.clone(),
         config.ai.max_tokens,
         config.ai.temperature,
+        config.ai.seed,
+        config.ai.max_known_issues,
+        config.ai.use_known_issues,
+        config.ai.analysis_timeout_secs,
+        &config.ai.fallback_providers,
     ).await {
         Ok(provider) => provider,
         Err(e) => {
@@ -49,13 +61,13 @@ pub async fn run_question_answering_with_config(
         }
     };
 
-    // Quick test to verify the AI provider is working
-    match ai_provider.analyze("test").await {
+    // Fail fast if the AI provider is unreachable, before collecting system info
+    match ai_provider.health_check().await {
         Ok(_) => {
             // Provider is working, proceed with question answering
         },
         Err(e) => {
-            println!("❌ AI provider test failed: {}", e);
+            println!("❌ AI provider unreachable: {}", e);
             println!("This usually indicates:");
             println!("  • Invalid or expired API key");
             println!("  • Insufficient API credits/quota");
@@ -76,10 +88,10 @@ pub async fn run_ai_agent_mode(
     max_tool_calls: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Check if AI API key is available
-    if config.ai.api_key.is_none() {
+    if config.missing_api_key() {
         println!("❌ No AI API key found. AI Agent mode requires an AI provider.");
         println!("Please set your AI_API_KEY environment variable or use --ai-api-key flag.");
-        println!("Supported providers: OpenAI, Anthropic, Local (Ollama)");
+        println!("Supported providers: OpenAI, Anthropic, Local (Ollama), llama.cpp (GGUF file)");
         println!("\nFor a basic system check without AI, use: cargo run -- --dry-run");
         return Ok(());
     }
@@ -91,6 +103,11 @@ pub async fn run_ai_agent_mode(
         config.ai.base_url.clone(),
         config.ai.max_tokens,
         config.ai.temperature,
+        config.ai.seed,
+        config.ai.max_known_issues,
+        config.ai.use_known_issues,
+        config.ai.analysis_timeout_secs,
+        &config.ai.fallback_providers,
     )
     .await?;
 
@@ -101,41 +118,29 @@ pub async fn run_ai_agent_mode(
 
     // Collect basic system info with progress
     let sys_info = ui_formatter.show_progress("Collecting system information", || {
-        collect_basic_system_info()
+        collect_basic_system_info_with(&config.collect_options())
     });
 
     // Create comprehensive context about the system
-    let mut system_context = String::new();
-    system_context.push_str(&format!("Operating System: {}\n", sys_info.os));
-    system_context.push_str(&format!("CPU: {}\n", sys_info.cpu));
-    system_context.push_str(&format!(
-        "Memory: {}/{}\n",
-        sys_info.free_memory, sys_info.total_memory
-    ));
-    system_context.push_str(&format!(
-        "Disk: {}/{}\n",
-        sys_info.free_disk, sys_info.total_disk
-    ));
-
-    if sys_info.is_kubernetes {
-        system_context.push_str("Environment: Kubernetes cluster\n");
-    }
-
-    if sys_info.container_runtime_available {
-        system_context.push_str("Container Runtime: Available\n");
-    }
+    let system_context = basic_system_context(&sys_info, config);
 
     // Create AI agent configuration
     let agent_config = AIAgentConfig {
         max_tool_calls,
         pause_on_limit: true,
         allow_user_continuation: true,
-        verbose_logging: config.output.verbose,
+        verbose_logging: config.get_verbosity().is_detailed(),
+        explain_reasoning: config.ai.explain_reasoning,
+        context_window: config.ai.context_window,
     };
 
     // Create and run the AI agent
     let mut agent = ui_formatter.show_progress("Initializing AI agent", || async {
-        AIAgent::new(ai_provider, agent_config).await
+        AIAgent::new(ai_provider, agent_config)
+            .await
+            .with_host_paths(config.host_paths())
+            .with_collect_options(config.collect_options())
+            .with_tool_retries(config.tools.retries)
     }).await;
 
     // Run the agent
@@ -151,7 +156,7 @@ pub async fn run_ai_agent_mode(
                 println!("{}", final_analysis);
                 
                 // Show conversation summary if verbose
-                if config.output.verbose {
+                if config.get_verbosity().is_detailed() {
                     println!("\n📊 Conversation Summary:");
                     println!("{}", agent.get_conversation_summary());
                 }
@@ -205,6 +210,29 @@ pub async fn run_ai_agent_mode(
     Ok(())
 }
 
+/// The context for runs that only do the basic collection: headline facts, the
+/// `--since-boot` scope and the operator's configured sections
+fn basic_system_context(sys_info: &BasicSystemInfo, config: &RaidConfig) -> String {
+    let mut context = ContextBuilder::new();
+    context
+        .push_line(format!("Environment: {}", sys_info.environment.summary()))
+        .push_line(format!("Operating System: {}", sys_info.os))
+        .push_line(format!("CPU: {}", sys_info.cpu))
+        .push_line(format!("Memory: {}/{}", sys_info.free_memory, sys_info.total_memory))
+        .push_line(format!("Disk: {}/{}", sys_info.free_disk, sys_info.total_disk));
+    if sys_info.is_kubernetes {
+        context.push_line("Environment: Kubernetes cluster");
+    }
+    if sys_info.container_runtime_available {
+        context.push_line("Container Runtime: Available");
+    }
+    if config.journal.since_boot {
+        context.push_str(&since_boot_context(sysinfo::boot_time(&config.host_paths())));
+    }
+    context.add_configured_sections(&config.context);
+    context.build()
+}
+
 /// Unified AI interaction system that always uses tools but with different prompting strategies
 pub async fn run_unified_ai_analysis(
     config: &RaidConfig,
@@ -213,10 +241,10 @@ pub async fn run_unified_ai_analysis(
     analysis_type: AnalysisType,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Check if AI API key is available
-    if config.ai.api_key.is_none() {
+    if config.missing_api_key() {
         println!("❌ No AI API key found. AI analysis requires an AI provider.");
         println!("Please set your AI_API_KEY environment variable or use --ai-api-key flag.");
-        println!("Supported providers: OpenAI, Anthropic, Local (Ollama)");
+        println!("Supported providers: OpenAI, Anthropic, Local (Ollama), llama.cpp (GGUF file)");
         println!("\nFor a basic system check without AI, use: cargo run -- --dry-run");
         return Ok(());
     }
@@ -229,6 +257,11 @@ pub async fn run_unified_ai_analysis(
         config.ai.base_url.clone(),
         config.ai.max_tokens,
         config.ai.temperature,
+        config.ai.seed,
+        config.ai.max_known_issues,
+        config.ai.use_known_issues,
+        config.ai.analysis_timeout_secs,
+        &config.ai.fallback_providers,
     ).await {
         Ok(provider) => provider,
         Err(e) => {
@@ -243,13 +276,13 @@ pub async fn run_unified_ai_analysis(
         }
     };
 
-    // Quick test to verify the AI provider is working
-    match ai_provider.analyze("test").await {
+    // Fail fast if the AI provider is unreachable, before collecting system info
+    match ai_provider.health_check().await {
         Ok(_) => {
             // Provider is working, proceed with analysis
         },
         Err(e) => {
-            println!("❌ AI provider test failed: {}", e);
+            println!("❌ AI provider unreachable: {}", e);
             println!("This usually indicates:");
             println!("  • Invalid or expired API key");
             println!("  • Insufficient API credits/quota");
@@ -282,38 +315,26 @@ pub async fn run_unified_ai_analysis(
         },
         pause_on_limit: false,
         allow_user_continuation: false,
-        verbose_logging: config.output.verbose,
+        verbose_logging: config.get_verbosity().is_detailed(),
+        explain_reasoning: config.ai.explain_reasoning,
+        context_window: config.ai.context_window,
     };
 
     // Collect basic system info
     let sys_info = ui_formatter.show_progress("Collecting system information", || {
-        collect_basic_system_info()
+        collect_basic_system_info_with(&config.collect_options())
     });
 
     // Create system context
-    let mut system_context = String::new();
-    system_context.push_str(&format!("Operating System: {}\n", sys_info.os));
-    system_context.push_str(&format!("CPU: {}\n", sys_info.cpu));
-    system_context.push_str(&format!(
-        "Memory: {}/{}\n",
-        sys_info.free_memory, sys_info.total_memory
-    ));
-    system_context.push_str(&format!(
-        "Disk: {}/{}\n",
-        sys_info.free_disk, sys_info.total_disk
-    ));
-
-    if sys_info.is_kubernetes {
-        system_context.push_str("Environment: Kubernetes cluster\n");
-    }
-
-    if sys_info.container_runtime_available {
-        system_context.push_str("Container Runtime: Available\n");
-    }
+    let system_context = basic_system_context(&sys_info, config);
 
     // Create and run agent
     let mut agent = ui_formatter.show_progress("Initializing AI agent", || async {
-        AIAgent::new(ai_provider, agent_config).await
+        AIAgent::new(ai_provider, agent_config)
+            .await
+            .with_host_paths(config.host_paths())
+            .with_collect_options(config.collect_options())
+            .with_tool_retries(config.tools.retries)
     }).await;
 
     let result = ui_formatter.show_progress("Running AI analysis", || async {
@@ -326,7 +347,7 @@ pub async fn run_unified_ai_analysis(
             println!("\n🎯 Analysis Result (used {} tools):", tool_calls_used);
             println!("{}", final_analysis);
             
-            if config.output.verbose {
+            if config.get_verbosity().is_detailed() {
                 println!("\n📊 Tool Usage Summary:");
                 println!("{}", agent.get_conversation_summary());
             }
@@ -346,4 +367,419 @@ pub async fn run_unified_ai_analysis(
     }
 
     Ok(())
-} 
\ No newline at end of file
+} 
+/// A line typed at the `ask` prompt
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplInput {
+    Question(String),
+    Empty,
+    Quit,
+}
+
+pub fn parse_repl_input(line: &str) -> ReplInput {
+    match line.trim() {
+        "" => ReplInput::Empty,
+        "/quit" | "/exit" | "/q" => ReplInput::Quit,
+        question => ReplInput::Question(question.to_string()),
+    }
+}
+
+/// Prompt until the user types a question; `None` on /quit or end of input
+fn read_question() -> io::Result<Option<String>> {
+    loop {
+        print!("\nask> ");
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        match parse_repl_input(&line) {
+            ReplInput::Question(question) => return Ok(Some(question)),
+            ReplInput::Empty => continue,
+            ReplInput::Quit => return Ok(None),
+        }
+    }
+}
+
+/// Interactive question loop that keeps one agent alive, so follow-ups reuse the
+/// collected system context and earlier tool results
+#[allow(clippy::too_many_arguments)]
+pub async fn run_ask_session(
+    ai_provider: Box<dyn AIProvider>,
+    first_question: Option<&str>,
+    system_context: &str,
+    system_info: &SystemInfo,
+    config: &RaidConfig,
+    ui_formatter: &UIFormatter,
+    max_tool_calls: usize,
+    initial_commands: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("💬 RAID Ask ({}): type /quit to exit", ai_provider.name());
+
+    let agent_config = AIAgentConfig {
+        max_tool_calls,
+        pause_on_limit: false,
+        allow_user_continuation: true,
+        verbose_logging: config.get_verbosity().is_detailed(),
+        explain_reasoning: config.ai.explain_reasoning,
+        context_window: config.ai.context_window,
+    };
+    let mut agent = AIAgent::new(ai_provider, agent_config)
+        .await
+        .with_host_paths(config.host_paths())
+        .with_collect_options(config.collect_options())
+        .with_tool_retries(config.tools.retries)
+        .with_system_info(system_info.clone());
+
+    let mut question = match first_question {
+        Some(question) => Some(question.to_string()),
+        None => read_question()?,
+    };
+
+    // Each answer lists only its own commands; initial diagnostics go with the first one
+    let mut commands_shown = 0;
+    let mut initial_commands = initial_commands;
+
+    while let Some(current) = question {
+        let result = ui_formatter.show_progress("Thinking", || async {
+            agent.ask_follow_up(&current, system_context).await
+        }).await?;
+
+        match result {
+            AIAgentResult::Success { final_analysis, tool_calls_used } => {
+                println!("\n🎯 Answer (used {} tools):", tool_calls_used);
+                println!("{}", final_analysis);
+            }
+            AIAgentResult::LimitReached { partial_analysis, tool_calls_used } => {
+                println!("\n⚠️  Stopped at tool limit ({} tools used):", tool_calls_used);
+                println!("{}", partial_analysis);
+            }
+            AIAgentResult::PausedForUserInput { reason, .. } => {
+                // The agent's question is answered at the next prompt
+                println!("\n🤖 {}", reason);
+            }
+            AIAgentResult::Error { error, tool_calls_used } => {
+                println!("\n❌ Failed after {} tool calls: {}", tool_calls_used, error);
+            }
+        }
+
+        let transcript = agent.get_tool_call_transcript();
+        if config.ai.explain_reasoning {
+            print_reasoning_transcript(ui_formatter, &transcript[commands_shown..]);
+        }
+        if config.output.show_commands {
+            print_commands_footer(
+                ui_formatter,
+                initial_commands
+                    .iter()
+                    .map(String::as_str)
+                    .chain(transcript[commands_shown..].iter().map(|record| record.command.as_str())),
+            );
+        }
+        commands_shown = transcript.len();
+        initial_commands = &[];
+
+        question = read_question()?;
+    }
+
+    if config.get_verbosity().is_detailed() {
+        println!("\n📊 Tool Usage Summary:");
+        println!("{}", agent.get_conversation_summary());
+    }
+    println!("👋 Ending session.");
+    Ok(())
+}
+
+/// One answered question from a `--questions-file` batch
+#[derive(Debug, Clone, Serialize)]
+pub struct QuestionAnswer {
+    pub question: String,
+    pub analysis: String,
+    /// Commands the agent ran for this question
+    pub commands: Vec<String>,
+}
+
+/// Questions from a batch file, one per line; blank lines and `#` comments are skipped
+pub fn read_questions_file(path: &Path) -> io::Result<Vec<String>> {
+    Ok(parse_questions(&std::fs::read_to_string(path)?))
+}
+
+fn parse_questions(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Answer each question in turn against the same collected system context. Every question
+/// starts a fresh conversation, so answers don't lean on each other. Text output is printed
+/// as each answer arrives; other formats are written once, as a list, when all are done.
+pub async fn run_question_batch(
+    ai_provider: Box<dyn AIProvider>,
+    questions: &[String],
+    system_context: &str,
+    system_info: &SystemInfo,
+    config: &RaidConfig,
+    ui_formatter: &UIFormatter,
+    max_tool_calls: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let format = config.get_output_format();
+    let agent_config = AIAgentConfig {
+        max_tool_calls,
+        pause_on_limit: false,
+        allow_user_continuation: false,
+        verbose_logging: config.get_verbosity().is_detailed(),
+        explain_reasoning: config.ai.explain_reasoning,
+        context_window: config.ai.context_window,
+    };
+    let mut agent = AIAgent::new(ai_provider, agent_config)
+        .await
+        .with_host_paths(config.host_paths())
+        .with_collect_options(config.collect_options())
+        .with_tool_retries(config.tools.retries)
+        .with_system_info(system_info.clone());
+
+    let mut answers = Vec::new();
+    for (index, question) in questions.iter().enumerate() {
+        agent.reset();
+        let result = ui_formatter.show_progress(&format!("Question {}/{}", index + 1, questions.len()), || async {
+            agent.run(question, system_context).await
+        }).await;
+
+        let analysis = match result {
+            Ok(AIAgentResult::Success { final_analysis, .. }) => final_analysis,
+            Ok(AIAgentResult::LimitReached { partial_analysis, .. }) => partial_analysis,
+            Ok(AIAgentResult::PausedForUserInput { reason, .. }) => reason,
+            Ok(AIAgentResult::Error { error, .. }) | Err(error) => format!("Analysis failed: {}", error),
+        };
+        let answer = QuestionAnswer {
+            question: question.clone(),
+            analysis,
+            commands: agent.get_tool_call_transcript().into_iter().map(|record| record.command).collect(),
+        };
+
+        if format == OutputFormat::Text {
+            write_question_answer(&mut io::stdout(), index + 1, questions.len(), &answer)?;
+        }
+        answers.push(answer);
+    }
+
+    if format != OutputFormat::Text {
+        write_question_answers(&mut io::stdout(), &answers, format)?;
+    }
+    Ok(())
+}
+
+fn write_question_answer(w: &mut dyn Write, number: usize, total: usize, answer: &QuestionAnswer) -> io::Result<()> {
+    writeln!(w, "\n❓ Question {}/{}: {}", number, total, answer.question)?;
+    writeln!(w, "{}", answer.analysis)?;
+    if !answer.commands.is_empty() {
+        writeln!(w, "Commands run:")?;
+        for command in &answer.commands {
+            writeln!(w, "  $ {}", command)?;
+        }
+    }
+    Ok(())
+}
+
+/// The combined batch result: a JSON or YAML list, one JSON object per line for JSON Lines, or one CSV row per question
+pub fn write_question_answers(w: &mut dyn Write, answers: &[QuestionAnswer], format: OutputFormat) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for (index, answer) in answers.iter().enumerate() {
+                write_question_answer(w, index + 1, answers.len(), answer)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => writeln!(w, "{}", serde_json::to_string_pretty(answers).map_err(io::Error::other)?),
+        OutputFormat::Jsonl => {
+            for answer in answers {
+                writeln!(w, "{}", serde_json::to_string(answer).map_err(io::Error::other)?)?;
+            }
+            w.flush()
+        }
+        OutputFormat::Yaml => write!(w, "{}", serde_yaml::to_string(answers).map_err(io::Error::other)?),
+        OutputFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(w);
+            csv.write_record(["question", "analysis", "commands"])?;
+            for answer in answers {
+                csv.write_record([&answer.question, &answer.analysis, &answer.commands.join("; ")])?;
+            }
+            csv.flush()
+        }
+    }
+}
+
+/// One category of a full check, analyzed on its own when `ai.per_category_analysis` is set
+#[derive(Debug)]
+pub struct CategoryInput {
+    pub title: &'static str,
+    pub category: IssueCategory,
+    pub input: String,
+}
+
+/// Split a full check into one focused input per category: the category's collected data,
+/// the issues RAID already detected in it and the operator's configured context. Containers
+/// and Kubernetes are left out when there is nothing of theirs to look at.
+pub fn category_inputs(system_info: &SystemInfo, issues: &[&Issue], config: &RaidConfig) -> Vec<CategoryInput> {
+    let mut system = system_info.context_summary();
+    if system_info.pressure.is_available() {
+        system.push_str(&format!("Pressure stall information (PSI, % of time stalled):\n{}", system_info.pressure.to_context()));
+    }
+    system.push_str(&format!("Filesystems:\n{}", yaml(&system_info.filesystems)));
+
+    let mut slices = vec![
+        ("System", IssueCategory::System, CheckComponent::System, system),
+        ("Systemd", IssueCategory::Systemd, CheckComponent::Systemd, yaml(&system_info.systemd)),
+        ("Journal", IssueCategory::Journal, CheckComponent::Journal, yaml(&system_info.journal)),
+        ("Cgroups", IssueCategory::Cgroups, CheckComponent::Cgroups, yaml(&system_info.cgroups)),
+    ];
+    if !system_info.containers.is_empty() {
+        slices.push(("Containers", IssueCategory::Container, CheckComponent::Containers, yaml(&system_info.containers)));
+    }
+    if system_info.kubernetes.is_kubernetes {
+        slices.push(("Kubernetes", IssueCategory::Kubernetes, CheckComponent::Kubernetes, yaml(&system_info.kubernetes)));
+    }
+
+    slices
+        .into_iter()
+        .map(|(title, category, component, data)| {
+            let categories = output::component_issue_categories(&component).unwrap_or_default();
+            let mut context = ContextBuilder::new();
+            context
+                .push_line(format!(
+                    "Analyze only the {} part of this Linux system's health; other areas are analyzed separately.",
+                    title.to_lowercase()
+                ))
+                .push_line(format!("Environment: {}", system_info.environment.summary()))
+                .push_line(format!("Operating System: {}", system_info.os))
+                .add_section(&format!("{} data", title), &data)
+                .add_list_section(
+                    "Issues RAID already detected",
+                    issues
+                        .iter()
+                        .filter(|issue| categories.contains(&issue.category.as_str()))
+                        .map(|issue| format!("[{}] {}", issue.severity, issue.message)),
+                )
+                .add_configured_sections(&config.context);
+            CategoryInput { title, category, input: context.build() }
+        })
+        .collect()
+}
+
+/// Analyze every category concurrently and merge the answers into one sectioned analysis,
+/// in the order of `inputs`. A category whose call fails says so in its section.
+pub async fn run_per_category_analysis(provider: Box<dyn AIProvider>, inputs: Vec<CategoryInput>) -> String {
+    let provider: Arc<dyn AIProvider> = Arc::from(provider);
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, input) in inputs.into_iter().enumerate() {
+        let provider = Arc::clone(&provider);
+        tasks.spawn(async move {
+            let result = provider.analyze_with_known_issues(&input.input, Some(input.category)).await;
+            (index, input.title, result)
+        });
+    }
+
+    let mut sections = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(section) => sections.push(section),
+            Err(e) => eprintln!("⚠️  A category analysis task failed: {}", e),
+        }
+    }
+    sections.sort_by_key(|(index, _, _)| *index);
+    compose_sectioned_analysis(sections.into_iter().map(|(_, title, result)| (title, result)))
+}
+
+/// Collected data as YAML for a category prompt; it only holds plain fields, so it serializes
+fn yaml<T: Serialize>(value: &T) -> String {
+    serde_yaml::to_string(value).unwrap_or_default()
+}
+
+/// `# Title` followed by each category's analysis, or its error
+pub fn compose_sectioned_analysis<'a>(sections: impl IntoIterator<Item = (&'a str, Result<String, AIError>)>) -> String {
+    sections
+        .into_iter()
+        .map(|(title, result)| match result {
+            Ok(analysis) => format!("# {}\n\n{}\n", title, analysis.trim()),
+            Err(e) => format!("# {}\n\nAnalysis failed: {}\n", title, e),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sysinfo::SystemdInfo;
+
+    #[test]
+    fn test_parse_questions_skips_blanks_and_comments() {
+        let content = "# disk runbook\nWhy is /var filling up?\n\n   Is swap in use?  \n";
+        assert_eq!(parse_questions(content), vec!["Why is /var filling up?", "Is swap in use?"]);
+    }
+
+    #[test]
+    fn test_write_question_answers_json_array() {
+        let answers = vec![QuestionAnswer {
+            question: "Is swap in use?".to_string(),
+            analysis: "No swap is configured.".to_string(),
+            commands: vec!["swapon --show".to_string()],
+        }];
+        let mut out = Vec::new();
+        write_question_answers(&mut out, &answers, OutputFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(parsed[0]["question"], "Is swap in use?");
+        assert_eq!(parsed[0]["commands"][0], "swapon --show");
+
+        let mut out = Vec::new();
+        write_question_answers(&mut out, &[answers[0].clone(), answers[0].clone()], OutputFormat::Jsonl).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_category_inputs() {
+        let info = SystemInfo {
+            os: "Debian GNU/Linux 12".to_string(),
+            systemd: SystemdInfo { failed_units: vec!["nginx.service".to_string()], ..Default::default() },
+            ..Default::default()
+        };
+        let failed = Issue::new("service", "high", "Failed systemd unit: nginx.service", None);
+
+        let inputs = category_inputs(&info, &[&failed], &RaidConfig::default());
+        let titles: Vec<_> = inputs.iter().map(|input| input.title).collect();
+        // No containers and not in a pod, so those categories are skipped
+        assert_eq!(titles, vec!["System", "Systemd", "Journal", "Cgroups"]);
+
+        let systemd = &inputs[1];
+        assert!(matches!(systemd.category, IssueCategory::Systemd));
+        assert!(systemd.input.contains("Debian GNU/Linux 12"));
+        assert!(systemd.input.contains("nginx.service"));
+        assert!(systemd.input.contains("[high] Failed systemd unit: nginx.service"));
+        // Each issue only goes to its own category
+        assert!(!inputs[2].input.contains("Failed systemd unit"));
+    }
+
+    #[test]
+    fn test_compose_sectioned_analysis() {
+        let analysis = compose_sectioned_analysis([
+            ("System", Ok("System appears healthy.\n".to_string())),
+            ("Journal", Err(AIError::APIError("rate limited".to_string()))),
+        ]);
+        assert_eq!(
+            analysis,
+            "# System\n\nSystem appears healthy.\n\n# Journal\n\nAnalysis failed: API response error: rate limited\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_repl_input() {
+        assert_eq!(parse_repl_input("  why is it slow?\n"), ReplInput::Question("why is it slow?".to_string()));
+        assert_eq!(parse_repl_input("\n"), ReplInput::Empty);
+        assert_eq!(parse_repl_input("/quit\n"), ReplInput::Quit);
+        assert_eq!(parse_repl_input("/exit"), ReplInput::Quit);
+    }
+}
@@ -0,0 +1,409 @@
+use crate::cli::ApplyFixesMode;
+use crate::output::FixSuggestion;
+use crate::tools::pool;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+/// Programs a verify or fix command may run without `--allow-destructive`. A program listed
+/// with subcommands is only allowed when its first non-option argument is one of them; an
+/// empty list allows any arguments. Anything else, including `sudo` and shells, needs the flag.
+const SAFE_PROGRAMS: [(&str, &[&str]); 24] = [
+    ("test", &[]),
+    ("[", &[]),
+    ("true", &[]),
+    ("false", &[]),
+    ("cat", &[]),
+    ("grep", &[]),
+    ("head", &[]),
+    ("tail", &[]),
+    ("ls", &[]),
+    ("stat", &[]),
+    ("df", &[]),
+    ("findmnt", &[]),
+    ("mountpoint", &[]),
+    ("pgrep", &[]),
+    ("pidof", &[]),
+    ("getent", &[]),
+    ("id", &[]),
+    ("touch", &[]),
+    ("mkdir", &[]),
+    ("journalctl", &[]),
+    ("chronyc", &["tracking", "sources", "makestep"]),
+    (
+        "systemctl",
+        &["is-active", "is-enabled", "is-failed", "status", "show", "start", "restart", "reload", "reset-failed", "daemon-reload", "enable"],
+    ),
+    ("timedatectl", &["status", "show", "set-ntp"]),
+    ("resolvectl", &["status", "query", "flush-caches"]),
+];
+
+/// Options that make an otherwise read-only program on `SAFE_PROGRAMS` delete or rewrite
+/// data; `--vacuum` also covers `--vacuum-size=`, `--vacuum-time=` and `--vacuum-files=`
+const WRITING_OPTIONS: [(&str, &[&str]); 1] = [(
+    "journalctl",
+    &["--vacuum", "--rotate", "--flush", "--relinquish-var", "--smart-relinquish-var", "--setup-keys", "--update-catalog"],
+)];
+
+/// Whether `command` has a `(`, `)`, `{` or `}` outside quotes: a subshell, group or
+/// `<(...)` process substitution, which bash runs even when invoked as `sh`
+fn has_unquoted_grouping(command: &str) -> bool {
+    let mut quote = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, '\\') => {
+                chars.next();
+            }
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, '(' | ')' | '{' | '}') => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// How long one verify or fix command may run before it is killed
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The simple commands of a shell line: split at `;`, `|`, `&`, `&&`, `||` and newlines, but
+/// not at the `&` of a `2>&1` redirect
+fn simple_commands(command: &str) -> Vec<&str> {
+    let mut commands = Vec::new();
+    let mut start = 0;
+    let mut previous = ' ';
+    for (at, c) in command.char_indices() {
+        if matches!(c, ';' | '|' | '\n') || (c == '&' && !matches!(previous, '>' | '<')) {
+            commands.push(&command[start..at]);
+            start = at + c.len_utf8();
+        }
+        previous = c;
+    }
+    commands.push(&command[start..]);
+    commands
+}
+
+/// Why `command` can't run without `--allow-destructive`: a program (or subcommand) that isn't
+/// on the safe list, an option that deletes data, a redirect that writes a file, or a command
+/// substitution, subshell or process substitution that could hide any of them. Quoting is
+/// barely parsed, so an unusual but harmless command may be refused too.
+pub fn unsafe_reason(command: &str) -> Option<String> {
+    if command.contains('`') || command.contains("$(") {
+        return Some("command substitution".to_string());
+    }
+    if has_unquoted_grouping(command) {
+        return Some("subshell or process substitution".to_string());
+    }
+    // `> /etc/fstab` truncates a file as surely as rm removes it
+    for (at, _) in command.match_indices('>') {
+        let target = command[at + 1..].trim_start_matches('>').trim_start();
+        if !target.starts_with('&') && !target.starts_with("/dev/null") {
+            let target = target.split_whitespace().next().unwrap_or("a file");
+            return Some(format!("redirect to {}", target));
+        }
+    }
+    for simple_command in simple_commands(command) {
+        let mut words = simple_command.split_whitespace().map(|word| word.trim_matches(['\'', '"']));
+        let Some(word) = words.next() else {
+            continue;
+        };
+        let program = word.rsplit('/').next().unwrap_or(word);
+        let Some((_, subcommands)) = SAFE_PROGRAMS.iter().find(|(name, _)| *name == program) else {
+            return Some(program.to_string());
+        };
+        if let Some((_, options)) = WRITING_OPTIONS.iter().find(|(name, _)| *name == program)
+            && let Some(option) = words.clone().find(|word| options.iter().any(|option| word.starts_with(option)))
+        {
+            return Some(format!("{} {}", program, option));
+        }
+        if subcommands.is_empty() {
+            continue;
+        }
+        match words.find(|word| !word.starts_with('-')) {
+            Some(subcommand) if subcommands.contains(&subcommand) => {}
+            Some(subcommand) => return Some(format!("{} {}", program, subcommand)),
+            None => return Some(program.to_string()),
+        }
+    }
+    None
+}
+
+/// The verify and fix commands of a suggestion, or why it must not run
+pub fn runnable_pair(suggestion: &FixSuggestion, allow_destructive: bool) -> Result<(&str, &str), String> {
+    let Some(fix) = suggestion.fix_command.as_deref() else {
+        return Err("no fix command".to_string());
+    };
+    // Without a check the fix would run whether or not the problem exists
+    let Some(verify) = suggestion.verify_command.as_deref() else {
+        return Err("no verify command".to_string());
+    };
+    if !allow_destructive {
+        for command in [verify, fix] {
+            if let Some(reason) = unsafe_reason(command) {
+                return Err(format!("not on the safe list ({}); pass --allow-destructive to run it", reason));
+            }
+        }
+    }
+    Ok((verify, fix))
+}
+
+/// What happened to one suggestion in `--apply-fixes=run`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixOutcome {
+    /// Nothing ran
+    Refused(String),
+    /// The verify command succeeded, so there was no problem to fix
+    NotConfirmed,
+    /// The verify command timed out or couldn't be run, so it proved nothing either way
+    Inconclusive(String),
+    /// The fix succeeded; `resolved` is whether the verify command succeeds afterwards
+    Applied { resolved: bool },
+    /// The fix ran and failed or timed out
+    Failed(String),
+}
+
+/// How a command run ended: exit code (None when killed or timed out) and combined output
+struct CommandRun {
+    code: Option<i32>,
+    output: String,
+}
+
+async fn run_shell(command: &str) -> CommandRun {
+    // Waiting for a slot blocks, so do it off the runtime; the permit is held until the command exits
+    let _permit = match tokio::task::spawn_blocking(pool::acquire).await {
+        Ok(permit) => permit,
+        Err(e) => return CommandRun { code: None, output: format!("failed to start: {}", e) },
+    };
+    let child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(COMMAND_TIMEOUT, child).await {
+        Ok(Ok(output)) => CommandRun {
+            code: output.status.code(),
+            output: format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        },
+        Ok(Err(e)) => CommandRun { code: None, output: format!("failed to start: {}", e) },
+        Err(_) => CommandRun { code: None, output: format!("timed out after {}s", COMMAND_TIMEOUT.as_secs()) },
+    }
+}
+
+/// Writes every step to the terminal and, with a timestamp, to the fix log
+struct FixLog<'a> {
+    out: &'a mut dyn Write,
+    file: std::fs::File,
+}
+
+impl FixLog<'_> {
+    fn line(&mut self, text: &str) -> std::io::Result<()> {
+        writeln!(self.out, "{}", text)?;
+        writeln!(self.file, "{} {}", chrono::Utc::now().to_rfc3339(), text)
+    }
+
+    fn command(&mut self, label: &str, command: &str, run: &CommandRun) -> std::io::Result<()> {
+        let status = run.code.map_or("no exit code".to_string(), |code| format!("exit {}", code));
+        self.line(&format!("  {}: {} ({})", label, command, status))?;
+        for output_line in run.output.lines() {
+            self.line(&format!("    | {}", output_line))?;
+        }
+        Ok(())
+    }
+}
+
+/// Verify, then fix only when the verify command fails (exits non-zero), then verify again
+async fn apply_one(suggestion: &FixSuggestion, allow_destructive: bool, log: &mut FixLog<'_>) -> std::io::Result<FixOutcome> {
+    log.line(&format!("Issue: {}", suggestion.issue))?;
+    let (verify, fix) = match runnable_pair(suggestion, allow_destructive) {
+        Ok(pair) => pair,
+        Err(reason) => {
+            log.line(&format!("  skipped: {}", reason))?;
+            return Ok(FixOutcome::Refused(reason));
+        }
+    };
+
+    let check = run_shell(verify).await;
+    log.command("verify", verify, &check)?;
+    match check.code {
+        Some(0) => {
+            log.line("  verify passed; the problem was not confirmed, fix not run")?;
+            return Ok(FixOutcome::NotConfirmed);
+        }
+        // No exit code means a timeout; 126 and 127 are the shell failing to run the command
+        None | Some(126) | Some(127) => {
+            let reason = check.output.trim().to_string();
+            log.line("  verify was inconclusive; fix not run")?;
+            return Ok(FixOutcome::Inconclusive(reason));
+        }
+        Some(_) => {}
+    }
+
+    let applied = run_shell(fix).await;
+    log.command("fix", fix, &applied)?;
+    if applied.code != Some(0) {
+        log.line("  fix failed")?;
+        return Ok(FixOutcome::Failed(applied.output.trim().to_string()));
+    }
+
+    let recheck = run_shell(verify).await;
+    log.command("re-verify", verify, &recheck)?;
+    let resolved = recheck.code == Some(0);
+    log.line(if resolved { "  fixed" } else { "  fix ran but verify still fails" })?;
+    Ok(FixOutcome::Applied { resolved })
+}
+
+/// `--apply-fixes=run`: apply each suggestion in order, appending the whole run to `log_path`
+pub async fn run_fixes(
+    suggestions: &[FixSuggestion],
+    allow_destructive: bool,
+    log_path: &Path,
+    out: &mut dyn Write,
+) -> std::io::Result<Vec<FixOutcome>> {
+    let file = std::fs::OpenOptions::new().create(true).append(true).open(log_path)?;
+    let mut log = FixLog { out, file };
+    log.line(&format!("Applying {} suggested fix(es); log: {}", suggestions.len(), log_path.display()))?;
+    let mut outcomes = Vec::with_capacity(suggestions.len());
+    for suggestion in suggestions {
+        outcomes.push(apply_one(suggestion, allow_destructive, &mut log).await?);
+    }
+    Ok(outcomes)
+}
+
+/// `--apply-fixes` (print mode): list each pair and whether run mode would run it. Nothing is executed.
+pub fn print_fix_plan(suggestions: &[FixSuggestion], allow_destructive: bool, out: &mut dyn Write) -> std::io::Result<()> {
+    writeln!(out, "\n🔧 Suggested fixes (not run):")?;
+    if suggestions.is_empty() {
+        writeln!(out, "  The analysis gave no Verify/Fix commands.")?;
+        return Ok(());
+    }
+    for (index, suggestion) in suggestions.iter().enumerate() {
+        writeln!(out, "  {}. {}", index + 1, suggestion.issue)?;
+        if let Some(verify) = &suggestion.verify_command {
+            writeln!(out, "     verify: {}", verify)?;
+        }
+        if let Some(fix) = &suggestion.fix_command {
+            writeln!(out, "     fix:    {}", fix)?;
+        }
+        if let Err(reason) = runnable_pair(suggestion, allow_destructive) {
+            writeln!(out, "     would be skipped: {}", reason)?;
+        }
+    }
+    writeln!(out, "\nRun `raid --apply-fixes=run --yes` to run each fix whose verify command fails (exits non-zero).")?;
+    Ok(())
+}
+
+/// Print or run the suggestions, per `--apply-fixes`
+pub async fn apply_fixes(
+    mode: ApplyFixesMode,
+    suggestions: &[FixSuggestion],
+    allow_destructive: bool,
+    log_path: &Path,
+    out: &mut dyn Write,
+) -> std::io::Result<()> {
+    match mode {
+        ApplyFixesMode::Print => print_fix_plan(suggestions, allow_destructive, out),
+        ApplyFixesMode::Run => run_fixes(suggestions, allow_destructive, log_path, out).await.map(|_| ()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(verify: Option<&str>, fix: Option<&str>) -> FixSuggestion {
+        FixSuggestion {
+            issue: "test issue".to_string(),
+            verify_command: verify.map(str::to_string),
+            fix_command: fix.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_unsafe_reason() {
+        assert_eq!(unsafe_reason("rm -rf /var/log/old"), Some("rm".to_string()));
+        assert_eq!(unsafe_reason("sudo systemctl restart nginx"), Some("sudo".to_string()));
+        assert_eq!(unsafe_reason("sh -c 'systemctl status nginx'"), Some("sh".to_string()));
+        assert_eq!(unsafe_reason("systemctl is-active nginx && mv /etc/fstab /tmp"), Some("mv".to_string()));
+        assert_eq!(unsafe_reason("systemctl stop nginx"), Some("systemctl stop".to_string()));
+        assert_eq!(unsafe_reason("kubectl delete pod web-0"), Some("kubectl".to_string()));
+        assert_eq!(unsafe_reason("echo x > /etc/fstab"), Some("redirect to /etc/fstab".to_string()));
+        assert_eq!(unsafe_reason("cat $(which rm)"), Some("command substitution".to_string()));
+        assert_eq!(
+            unsafe_reason("journalctl --vacuum-size=200M"),
+            Some("journalctl --vacuum-size=200M".to_string())
+        );
+        assert_eq!(unsafe_reason("journalctl --rotate"), Some("journalctl --rotate".to_string()));
+        assert_eq!(unsafe_reason("cat <(rm -rf /x)"), Some("subshell or process substitution".to_string()));
+        assert_eq!(unsafe_reason("(rm -rf /x)"), Some("subshell or process substitution".to_string()));
+        assert_eq!(unsafe_reason("grep '(ok)' /var/log/app.log"), None);
+
+        assert_eq!(unsafe_reason("systemctl restart nginx.service"), None);
+        assert_eq!(unsafe_reason("timedatectl set-ntp true > /dev/null"), None);
+        assert_eq!(unsafe_reason("journalctl -u nginx 2>&1 | grep -q failed"), None);
+        assert_eq!(unsafe_reason("/usr/bin/test -e /run/nginx.pid"), None);
+    }
+
+    #[test]
+    fn test_runnable_pair() {
+        let pair = suggestion(Some("systemctl is-active nginx"), Some("systemctl restart nginx"));
+        assert_eq!(runnable_pair(&pair, false), Ok(("systemctl is-active nginx", "systemctl restart nginx")));
+
+        assert!(runnable_pair(&suggestion(None, Some("systemctl restart nginx")), false).unwrap_err().contains("verify"));
+        assert!(runnable_pair(&suggestion(Some("true"), None), false).unwrap_err().contains("fix"));
+
+        let destructive = suggestion(Some("test ! -d /tmp/cache"), Some("rm -rf /tmp/cache"));
+        assert!(runnable_pair(&destructive, false).unwrap_err().contains("--allow-destructive"));
+        assert!(runnable_pair(&destructive, true).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_fixes_only_fixes_confirmed_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("fixed");
+        let log_path = dir.path().join("fixes.log");
+        let suggestions = vec![
+            // Verify passes: no problem, so the fix must not run
+            suggestion(Some("true"), Some(&format!("touch {}.unexpected", marker.display()))),
+            // Verify fails until the fix creates the marker
+            suggestion(
+                Some(&format!("test -e {}", marker.display())),
+                Some(&format!("touch {}", marker.display())),
+            ),
+            suggestion(Some("false"), Some("rm -f /nonexistent")),
+        ];
+
+        let mut out = Vec::new();
+        let outcomes = run_fixes(&suggestions, false, &log_path, &mut out).await.unwrap();
+
+        assert_eq!(outcomes[0], FixOutcome::NotConfirmed);
+        assert!(!dir.path().join("fixed.unexpected").exists());
+        assert_eq!(outcomes[1], FixOutcome::Applied { resolved: true });
+        assert!(marker.exists());
+        assert!(matches!(&outcomes[2], FixOutcome::Refused(reason) if reason.contains("rm")));
+
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("verify: true (exit 0)"));
+        assert!(log.contains("fix: touch"));
+        assert!(log.contains("skipped: not on the safe list (rm)"));
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), log.lines().count());
+    }
+
+    #[tokio::test]
+    async fn test_inconclusive_verify_does_not_run_the_fix() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("fixed");
+        let log_path = dir.path().join("fixes.log");
+        // 127: the verify command doesn't exist, which says nothing about the problem
+        let suggestions = vec![suggestion(Some("raid-no-such-command"), Some(&format!("touch {}", marker.display())))];
+
+        let outcomes = run_fixes(&suggestions, true, &log_path, &mut Vec::new()).await.unwrap();
+        assert!(matches!(&outcomes[0], FixOutcome::Inconclusive(_)));
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::cli::OutputFormat;
+use crate::tools::{DebugToolResult, DebugTools, ToolCategory};
+use serde::Serialize;
+
+/// Journal lines read by the journal tools, the agent's default
+const BENCHMARK_JOURNAL_LINES: usize = 50;
+
+/// How one tool did in a benchmark run
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ToolTiming {
+    pub tool: String,
+    pub category: String,
+    pub command: String,
+    pub success: bool,
+    pub execution_time_ms: u64,
+}
+
+/// Every timing, slowest first, and the percentiles a tool timeout can be picked from
+#[derive(Debug, Serialize, PartialEq)]
+pub struct BenchmarkSummary {
+    pub tools: Vec<ToolTiming>,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+    /// Twice the p95, rounded up to a whole second, so a slow run of a typical tool still fits
+    pub suggested_timeout_secs: u64,
+}
+
+/// `raid benchmark [categories...]`: run each quick read-only tool of the available categories
+/// once and report how long each took. No categories means every available one.
+pub async fn run_benchmark(
+    debug_tools: &DebugTools,
+    categories: &[ToolCategory],
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let selected: Vec<&ToolCategory> = if categories.is_empty() {
+        ToolCategory::ALL.iter().collect()
+    } else {
+        categories.iter().collect()
+    };
+
+    let mut timings = Vec::new();
+    for category in selected {
+        let available = debug_tools
+            .available_tools
+            .get(category)
+            .is_some_and(|info| info.is_available);
+        if !available {
+            // stderr, so JSON and CSV on stdout stay parseable
+            if !categories.is_empty() {
+                eprintln!("⏭️  Skipping {:?}: not available on this system", category);
+            }
+            continue;
+        }
+        if format == OutputFormat::Text {
+            eprintln!("⏱️  Benchmarking {:?}...", category);
+        }
+        timings.extend(
+            category_tools(debug_tools, category)
+                .await
+                .into_iter()
+                .map(|result| tool_timing(result, category)),
+        );
+    }
+    let summary = summarize(timings);
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+        OutputFormat::Jsonl => {
+            for timing in &summary.tools {
+                println!("{}", serde_json::to_string(timing)?);
+            }
+        }
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&summary)?),
+        OutputFormat::Text => print!("{}", format_benchmark(&summary)),
+        OutputFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(std::io::stdout());
+            csv.write_record(["tool", "category", "command", "success", "execution_time_ms"])?;
+            for timing in &summary.tools {
+                csv.write_record([
+                    timing.tool.as_str(),
+                    timing.category.as_str(),
+                    timing.command.as_str(),
+                    if timing.success { "true" } else { "false" },
+                    timing.execution_time_ms.to_string().as_str(),
+                ])?;
+            }
+            csv.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The quick, read-only tools of a category that need no arguments. Tools that are
+/// benchmarks or scans themselves (sysbench, clamscan, perf) are left out.
+async fn category_tools(debug_tools: &DebugTools, category: &ToolCategory) -> Vec<DebugToolResult> {
+    match category {
+        ToolCategory::SystemInfo => vec![
+            debug_tools.run_ps_aux().await,
+            debug_tools.run_df().await,
+            debug_tools.run_free().await,
+            debug_tools.run_swapon().await,
+            debug_tools.run_timedatectl().await,
+        ],
+        ToolCategory::NetworkDebug => vec![
+            debug_tools.run_ip_addr().await,
+            debug_tools.run_ip_route().await,
+            debug_tools.run_ss().await,
+            debug_tools.run_interface_stats().await,
+            debug_tools.run_iptables().await,
+            debug_tools.run_nftables().await,
+            debug_tools.run_dns_config().await,
+            debug_tools.run_resolvectl_status().await,
+        ],
+        ToolCategory::ProcessDebug => vec![debug_tools.run_lsof().await, debug_tools.run_pidstat().await],
+        ToolCategory::StorageDebug => vec![
+            debug_tools.run_iostat().await,
+            debug_tools.run_lsblk().await,
+            debug_tools.run_mount().await,
+        ],
+        ToolCategory::PerformanceDebug => vec![
+            debug_tools.run_top().await,
+            debug_tools.run_vmstat().await,
+            debug_tools.run_mpstat().await,
+        ],
+        ToolCategory::SecurityDebug => vec![
+            debug_tools.run_security_denials().await,
+            debug_tools.run_auth_summary().await,
+            debug_tools.run_w().await,
+        ],
+        ToolCategory::ContainerInfo => vec![debug_tools.run_docker_ps().await, debug_tools.run_lsns().await],
+        ToolCategory::Kubernetes => vec![
+            debug_tools.run_kubectl_get_nodes().await,
+            debug_tools.run_kubectl_get_pods(None).await,
+            debug_tools.run_kubectl_get_events(None).await,
+            debug_tools.run_kubectl_top_nodes().await,
+        ],
+        ToolCategory::ArchLinux => vec![
+            debug_tools.run_pacman_orphans().await,
+            debug_tools.run_systemd_analyze_blame().await,
+            debug_tools.run_lsmod().await,
+        ],
+        ToolCategory::EbpfDebug => vec![
+            debug_tools.run_bpftool_prog_list().await,
+            debug_tools.run_bpf_jit_status().await,
+        ],
+        ToolCategory::Journalctl => vec![
+            debug_tools.run_journalctl_recent(Some(BENCHMARK_JOURNAL_LINES)).await,
+            debug_tools.run_journalctl_errors(Some(BENCHMARK_JOURNAL_LINES)).await,
+            debug_tools.run_journalctl_boot().await,
+        ],
+        ToolCategory::Systemctl => vec![debug_tools.run_systemctl_failed().await],
+    }
+}
+
+fn tool_timing(result: DebugToolResult, category: &ToolCategory) -> ToolTiming {
+    ToolTiming {
+        tool: result.tool_name,
+        category: format!("{:?}", category),
+        command: result.command,
+        success: result.success,
+        execution_time_ms: result.execution_time_ms,
+    }
+}
+
+/// Sort slowest first and work out the percentiles
+pub fn summarize(mut tools: Vec<ToolTiming>) -> BenchmarkSummary {
+    tools.sort_by_key(|timing| std::cmp::Reverse(timing.execution_time_ms));
+    let mut times: Vec<u64> = tools.iter().map(|timing| timing.execution_time_ms).collect();
+    times.sort_unstable();
+    let p95_ms = percentile(&times, 95);
+    BenchmarkSummary {
+        p50_ms: percentile(&times, 50),
+        p95_ms,
+        max_ms: times.last().copied().unwrap_or(0),
+        suggested_timeout_secs: (p95_ms * 2).div_ceil(1000).max(1),
+        tools,
+    }
+}
+
+/// Nearest-rank percentile of sorted values; 0 for none
+fn percentile(sorted: &[u64], percent: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn format_benchmark(summary: &BenchmarkSummary) -> String {
+    if summary.tools.is_empty() {
+        return "No tools were run: none of the selected categories is available\n".to_string();
+    }
+    let mut text = format!("{:<32} {:<18} {:>10}\n", "Tool", "Category", "Time");
+    for timing in &summary.tools {
+        text.push_str(&format!(
+            "{:<32} {:<18} {:>7} ms {}\n",
+            timing.tool,
+            timing.category,
+            timing.execution_time_ms,
+            if timing.success { "✅" } else { "❌" }
+        ));
+    }
+    text.push_str(&format!(
+        "\n{} tools: p50 {} ms, p95 {} ms, slowest {} ms\n",
+        summary.tools.len(),
+        summary.p50_ms,
+        summary.p95_ms,
+        summary.max_ms
+    ));
+    text.push_str(&format!(
+        "Suggested tool timeout: {}s (twice the p95)\n",
+        summary.suggested_timeout_secs
+    ));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(tool: &str, execution_time_ms: u64) -> ToolTiming {
+        ToolTiming {
+            tool: tool.to_string(),
+            category: "SystemInfo".to_string(),
+            command: tool.to_string(),
+            success: true,
+            execution_time_ms,
+        }
+    }
+
+    #[test]
+    fn test_summarize_percentiles() {
+        let mut tools: Vec<ToolTiming> = (1..=19).map(|index| timing(&format!("tool{}", index), index * 10)).collect();
+        tools.push(timing("kubectl_get_events", 2400));
+        let summary = summarize(tools);
+
+        assert_eq!(summary.tools[0].tool, "kubectl_get_events");
+        assert_eq!(summary.tools.last().unwrap().execution_time_ms, 10);
+        assert_eq!((summary.p50_ms, summary.p95_ms, summary.max_ms), (100, 190, 2400));
+        assert_eq!(summary.suggested_timeout_secs, 1);
+        assert!(format_benchmark(&summary).contains("20 tools: p50 100 ms, p95 190 ms, slowest 2400 ms"));
+
+        let slow = summarize(vec![timing("sar", 1200), timing("df", 5)]);
+        assert_eq!(slow.p95_ms, 1200);
+        assert_eq!(slow.suggested_timeout_secs, 3);
+
+        let empty = summarize(Vec::new());
+        assert_eq!((empty.p95_ms, empty.suggested_timeout_secs), (0, 1));
+    }
+}
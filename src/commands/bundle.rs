@@ -0,0 +1,287 @@
+use crate::cli::{CheckComponent, SortKey};
+use crate::commands::offline::offline_report;
+use crate::commands::verify::sign_file;
+use crate::config::{mask_secret, RaidConfig};
+use crate::output::anonymize::Anonymizer;
+use crate::tools::{DebugToolResult, DebugTools};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+/// Journal lines per journal tool in a bundle, well past what fits in an AI context
+const BUNDLE_JOURNAL_LINES: usize = 2000;
+
+/// `manifest.json`: what the bundle holds and how each tool did
+#[derive(Debug, Serialize)]
+pub struct BundleManifest {
+    pub raid_version: String,
+    pub created_at: String,
+    pub files: Vec<String>,
+    pub tools: Vec<BundleToolEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BundleToolEntry {
+    pub name: String,
+    pub command: String,
+    pub success: bool,
+    pub execution_time_ms: u64,
+    pub file: String,
+}
+
+/// `raid bundle`: write a full offline scan, the structured report, the collected system
+/// information and RAID's redacted configuration into one `.tar.gz` for a support team
+pub async fn run_bundle(
+    output: Option<&str>,
+    sign: bool,
+    config: &RaidConfig,
+    debug_tools: &DebugTools,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let created_at = chrono::Utc::now();
+    let root = format!("raid-bundle-{}", created_at.format("%Y%m%d-%H%M%S"));
+    let path = output.map(str::to_string).unwrap_or_else(|| format!("{}.tar.gz", root));
+
+    println!("📦 Collecting diagnostic bundle...");
+    let mut report = offline_report(config, debug_tools, &CheckComponent::All).await;
+    let mut results = run_bundle_tools(debug_tools).await;
+
+    let secrets = config_secrets(config);
+    for result in &mut results {
+        result.output = scrub_secrets(&result.output, &secrets);
+        result.error = result.error.as_deref().map(|error| scrub_secrets(error, &secrets));
+    }
+
+    // One anonymizer for everything, so a host gets the same pseudonym in every file
+    if config.output.anonymize {
+        let mut anonymizer = Anonymizer::for_local_system();
+        anonymizer.anonymize_report(&mut report);
+        for result in &mut results {
+            result.output = anonymizer.anonymize_text(&result.output);
+            result.error = result.error.as_deref().map(|error| anonymizer.anonymize_text(error));
+        }
+    }
+
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        ("report.json".to_string(), serde_json::to_vec_pretty(&report)?),
+        ("system_info.json".to_string(), serde_json::to_vec_pretty(&report.system_info)?),
+        ("config.yaml".to_string(), serde_yaml::to_string(&config.redacted())?.into_bytes()),
+    ];
+    let mut tools = Vec::new();
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for result in &results {
+        // A tool run twice (e.g. top_consumers by CPU and by memory) gets numbered files
+        let runs = seen.entry(result.tool_name.as_str()).or_default();
+        *runs += 1;
+        let file = match *runs {
+            1 => format!("tools/{}.txt", result.tool_name),
+            n => format!("tools/{}-{}.txt", result.tool_name, n),
+        };
+        tools.push(BundleToolEntry {
+            name: result.tool_name.clone(),
+            command: result.command.clone(),
+            success: result.success,
+            execution_time_ms: result.execution_time_ms,
+            file: file.clone(),
+        });
+        files.push((file, tool_output_file(result).into_bytes()));
+    }
+    let manifest = BundleManifest {
+        raid_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: created_at.to_rfc3339(),
+        files: files.iter().map(|(name, _)| name.clone()).collect(),
+        tools,
+    };
+    files.insert(0, ("manifest.json".to_string(), serde_json::to_vec_pretty(&manifest)?));
+
+    let file = std::fs::File::create(&path).map_err(|e| format!("Failed to create '{}': {}", path, e))?;
+    write_bundle(file, &root, &files)?;
+
+    let failed = results.iter().filter(|result| !result.success).count();
+    println!(
+        "✅ Wrote {} ({} files, {} of {} tools succeeded)",
+        path,
+        files.len(),
+        results.len() - failed,
+        results.len()
+    );
+    if sign {
+        for signature in sign_file(Path::new(&path), config.signing.key.as_deref())? {
+            println!("🔏 Wrote {}", signature.display());
+        }
+    }
+    println!("   Compare later with: raid --continue-from <extracted>/{}/report.json", root);
+    Ok(())
+}
+
+/// Every read-only tool that needs no arguments and finishes in seconds. Tools that are
+/// missing on this host still get a file, so the bundle shows what could not be collected.
+async fn run_bundle_tools(debug_tools: &DebugTools) -> Vec<DebugToolResult> {
+    let mut results = vec![
+        debug_tools.run_ps_aux().await,
+        debug_tools.run_top_consumers(SortKey::Cpu, 20).await,
+        debug_tools.run_top_consumers(SortKey::Mem, 20).await,
+        debug_tools.run_process_limits(None).await,
+        debug_tools.run_df().await,
+        debug_tools.run_free().await,
+        debug_tools.run_swapon().await,
+        debug_tools.run_timedatectl().await,
+        debug_tools.run_systemctl_failed().await,
+        debug_tools.run_systemd_analyze_blame().await,
+        debug_tools.run_journalctl_recent(Some(BUNDLE_JOURNAL_LINES)).await,
+        debug_tools.run_journalctl_errors(Some(BUNDLE_JOURNAL_LINES)).await,
+        debug_tools.run_journalctl_boot().await,
+        debug_tools.run_lsmod().await,
+        debug_tools.run_cat_proc_cgroups().await,
+        debug_tools.run_cat_proc_self_cgroup().await,
+        debug_tools.run_cat_proc_self_mountinfo().await,
+        debug_tools.run_lsns().await,
+        debug_tools.run_ip_addr().await,
+        debug_tools.run_ip_route().await,
+        debug_tools.run_ss().await,
+        debug_tools.run_interface_stats().await,
+        debug_tools.run_iptables().await,
+        debug_tools.run_nftables().await,
+        debug_tools.run_dns_config().await,
+        debug_tools.run_resolvectl_status().await,
+        debug_tools.run_security_denials().await,
+        debug_tools.run_auth_summary().await,
+        debug_tools.run_w().await,
+        debug_tools.run_last(None).await,
+    ];
+
+    if debug_tools.kubernetes_enabled {
+        results.extend([
+            debug_tools.run_kubectl_get_nodes().await,
+            debug_tools.run_kubectl_top_nodes().await,
+            debug_tools.run_kubectl_get_pods(None).await,
+            debug_tools.run_kubectl_get_events(None).await,
+            debug_tools.run_kubectl_get_pv().await,
+            debug_tools.run_kubectl_get_pvc(None).await,
+        ]);
+    }
+    results
+}
+
+/// The file a tool's result is stored in: the command, how it went and the complete output
+pub fn tool_output_file(result: &DebugToolResult) -> String {
+    let mut text = format!(
+        "$ {}\n# {} in {} ms\n\n{}",
+        result.command,
+        if result.success { "succeeded" } else { "failed" },
+        result.execution_time_ms,
+        result.output
+    );
+    if let Some(error) = result.error.as_deref().filter(|error| !error.is_empty()) {
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&format!("\n# stderr\n{}", error));
+    }
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+/// Every secret `config.redacted()` masks: the API keys and the signing key
+fn config_secrets(config: &RaidConfig) -> Vec<String> {
+    config
+        .ai
+        .api_key
+        .iter()
+        .chain(config.ai.fallback_providers.iter().filter_map(|fallback| fallback.api_key.as_ref()))
+        .chain(config.signing.key.iter())
+        .filter(|key| !key.is_empty())
+        .cloned()
+        .collect()
+}
+
+/// Mask API keys the way `config show` does, in case one turns up in a process list or log
+fn scrub_secrets(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), &mask_secret(secret)))
+}
+
+/// Write the files as a gzip-compressed tarball, all under one top-level directory
+pub fn write_bundle<W: Write>(writer: W, root: &str, files: &[(String, Vec<u8>)]) -> std::io::Result<()> {
+    let mut archive = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+    let mtime = chrono::Utc::now().timestamp().max(0) as u64;
+    for (name, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        archive.append_data(&mut header, Path::new(root).join(name), contents.as_slice())?;
+    }
+    archive.into_inner()?.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AIConfig, SigningConfig};
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_write_bundle_round_trip() {
+        let large = "x".repeat(1 << 20);
+        let files = vec![
+            ("manifest.json".to_string(), b"{}".to_vec()),
+            ("tools/ps_aux.txt".to_string(), large.clone().into_bytes()),
+        ];
+        let mut buffer = Vec::new();
+        write_bundle(&mut buffer, "raid-bundle-test", &files).unwrap();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(buffer.as_slice()));
+        let mut entries = Vec::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).unwrap();
+            entries.push((entry.path().unwrap().display().to_string(), contents));
+        }
+        assert_eq!(entries[0], ("raid-bundle-test/manifest.json".to_string(), "{}".to_string()));
+        assert_eq!(entries[1].0, "raid-bundle-test/tools/ps_aux.txt");
+        // Nothing is cut down to fit an AI context
+        assert_eq!(entries[1].1.len(), large.len());
+    }
+
+    #[test]
+    fn test_tool_output_file_and_scrubbing() {
+        let result = DebugToolResult {
+            tool_name: "ps_aux".to_string(),
+            command: "ps aux".to_string(),
+            success: false,
+            output: "app --key sk-proj-abcdefghijkl1234".to_string(),
+            error: Some("permission denied".to_string()),
+            execution_time_ms: 12,
+        };
+        assert_eq!(
+            tool_output_file(&result),
+            "$ ps aux\n# failed in 12 ms\n\napp --key sk-proj-abcdefghijkl1234\n\n# stderr\npermission denied\n"
+        );
+        assert_eq!(
+            scrub_secrets(&result.output, &["sk-proj-abcdefghijkl1234".to_string()]),
+            "app --key sk-...1234"
+        );
+    }
+
+    #[test]
+    fn test_signing_key_is_scrubbed() {
+        let config = RaidConfig {
+            ai: AIConfig { api_key: Some("sk-proj-abcdefghijkl1234".to_string()), ..RaidConfig::default().ai },
+            signing: SigningConfig { key: Some("bundle-signing-secret-5678".to_string()) },
+            ..Default::default()
+        };
+
+        let scrubbed = scrub_secrets("env RAID_SIGNING__KEY=bundle-signing-secret-5678", &config_secrets(&config));
+        assert!(!scrubbed.contains("bundle-signing-secret-5678"));
+        assert_eq!(config_secrets(&config).len(), 2);
+    }
+}
@@ -1,9 +1,12 @@
 use crate::cli::ConfigAction;
-use crate::config::RaidConfig;
+use crate::config::{RaidConfig, UnknownKeyPolicy, CONFIG_LAYERS_ENV, CONFIG_VERSION};
+use std::path::{Path, PathBuf};
 
 pub async fn run_config_command(
     action: &ConfigAction,
+    config_path: Option<&str>,
     output_path: Option<&str>,
+    reveal_secrets: bool,
     config: &RaidConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
     match action {
@@ -15,11 +18,28 @@ pub async fn run_config_command(
             println!("   cargo run -- --config {}", output_file);
         }
         ConfigAction::Show => {
-            let yaml_content = serde_yaml::to_string(config)?;
+            let yaml_content = if reveal_secrets {
+                serde_yaml::to_string(config)?
+            } else {
+                serde_yaml::to_string(&config.redacted())?
+            };
             println!("Current Configuration (merged from all sources):");
+            let files = RaidConfig::config_files(config_path.map(Path::new));
+            if files.is_empty() {
+                println!("# No config files; built-in defaults, RAID_* variables and flags only");
+            } else {
+                println!("# Files merged, later overriding earlier:");
+                for file in &files {
+                    println!("#   {}", file.display());
+                }
+            }
             println!("{}", yaml_content);
         }
         ConfigAction::Validate => {
+            if let Err(e) = check_config_files(config_path, config) {
+                eprintln!("❌ Configuration validation failed: {}", e);
+                std::process::exit(1);
+            }
             match config.validate() {
                 Ok(_) => println!("✅ Configuration is valid"),
                 Err(e) => {
@@ -29,13 +49,16 @@ pub async fn run_config_command(
             }
         }
         ConfigAction::Locations => {
-            println!("Configuration File Locations (in order of precedence):");
-            println!("1. Command line: --config <file>");
-            println!("2. Current directory: ./raid.yaml, ./raid.yml, ./raid.toml");
-            println!("3. User config: ~/.config/raid/raid.yaml");
-            println!("4. System config: /etc/raid.yaml");
-            println!("5. Environment variables: RAID_*");
-            println!("6. Built-in defaults");
+            println!("Configuration sources, merged in this order (later ones override only the keys they set):");
+            println!("1. Built-in defaults");
+            println!("2. System config: /etc/raid/config.yaml, /etc/raid.yaml or /usr/local/etc/raid.yaml");
+            println!("3. User config: ~/.config/raid/raid.yaml (.yml, .toml)");
+            println!("4. Current directory: ./raid.yaml, ./raid.yml, ./raid.toml (or .raid.*)");
+            println!("   --config <file> replaces 2-4");
+            println!("5. Local override: raid.local.yaml (.yml, .toml) next to the base config");
+            println!("6. {}: colon-separated files, in order", CONFIG_LAYERS_ENV);
+            println!("7. Environment variables: RAID_*");
+            println!("8. Command line flags");
             
             if let Some(user_config_dir) = dirs::config_dir() {
                 let user_config_path = user_config_dir.join("raid").join("raid.yaml");
@@ -43,6 +66,57 @@ pub async fn run_config_command(
                 println!("   {}", user_config_path.display());
             }
         }
+        ConfigAction::Migrate => {
+            // The --config file, or else the highest-precedence base config
+            let Some(path) = config_path.map(PathBuf::from).or_else(|| RaidConfig::base_config_files().pop()) else {
+                eprintln!("❌ No config file found; pass --config <file> to migrate one");
+                std::process::exit(1);
+            };
+            let dropped = RaidConfig::unknown_keys(std::slice::from_ref(&path));
+            let (from_version, backup) = RaidConfig::migrate_file(&path)?;
+            println!(
+                "✅ Migrated {} from config version {} to {}",
+                path.display(),
+                from_version,
+                CONFIG_VERSION
+            );
+            println!("   Missing keys were filled in with their defaults.");
+            for (_, message) in &dropped {
+                println!("   Dropped {}", message);
+            }
+            println!("   The original (with its comments) is saved as {}", backup.display());
+        }
     }
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Warn about config files written for an older schema and, following `unknown_keys`, about
+/// keys no setting reads. With `unknown_keys: error` an unknown key is an error.
+pub fn check_config_files(config_path: Option<&str>, config: &RaidConfig) -> Result<(), String> {
+    let files = RaidConfig::config_files(config_path.map(Path::new));
+    for (file, version) in RaidConfig::outdated_config_files(&files) {
+        eprintln!(
+            "⚠️  {} is written for config version {} (current: {}); upgrade it with `raid config migrate --config {}`",
+            file.display(),
+            version,
+            CONFIG_VERSION,
+            file.display()
+        );
+    }
+
+    if config.unknown_keys == UnknownKeyPolicy::Ignore {
+        return Ok(());
+    }
+    let unknown = RaidConfig::unknown_keys(&files);
+    for (file, message) in &unknown {
+        eprintln!("⚠️  {}: {}", file.display(), message);
+    }
+    match unknown.len() {
+        count if count > 0 && config.unknown_keys == UnknownKeyPolicy::Error => Err(format!(
+            "{} unknown config key{} (set unknown_keys: warn to only warn)",
+            count,
+            if count == 1 { "" } else { "s" }
+        )),
+        _ => Ok(()),
+    }
+}
@@ -1,24 +1,339 @@
-use crate::cli::Cli;
-use crate::tools::DebugToolResult;
+use crate::cli::{Cli, Commands, DebugTool, SortKey};
+use crate::config::RaidConfig;
+use crate::tools::network_debug::NETWORK_HEALTH_CHECK_STEPS;
+use crate::tools::process_debug::STRACE_DEFAULT_SECS;
+use crate::tools::{run_with_retries, DebugToolResult, DebugTools};
+use crate::ui::UIFormatter;
 
-// Simplified debug module - the original debug functionality is complex 
-// and tightly coupled with specific CLI structures. For now, provide a
-// basic placeholder that can be extended later.
-pub async fn run_debug_tools(_cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔧 Debug Tools");
-    println!("This functionality has been moved to a modular structure.");
-    println!("Debug tools are available through the existing CLI interface.");
-    println!("The original complex debug functionality is preserved in main_old.rs");
+/// Run the debug tool selected on the command line and print its result
+pub async fn run_debug_tools(cli: &Cli, config: &RaidConfig, ui_formatter: &UIFormatter) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(Commands::Debug {
+        tool,
+        namespace,
+        pod,
+        node,
+        service,
+        lines,
+        port,
+        path,
+        by,
+        count,
+        duration,
+        pid,
+        host,
+        interface,
+        stream,
+    }) = &cli.command
+    else {
+        return Ok(());
+    };
+
+    let mut debug_tools = DebugTools::new().with_host_paths(config.host_paths());
+    let lines = *lines;
+
+    println!("🔧 Running debug tool: {:?}", tool);
+    if *stream {
+        println!();
+    }
+
+    // Built once, so a retry starts the same bar over instead of drawing a second one
+    let health_check_progress = matches!(tool, DebugTool::NetworkHealthCheck)
+        .then(|| ui_formatter.progress_bar(NETWORK_HEALTH_CHECK_STEPS));
+
+    // Transient failures (API timeouts, flaky DNS) are retried when tools.retries allows it.
+    // Only the first attempt streams, so a retry doesn't print the output a second time.
+    let mut attempts = 0;
+    let result = run_with_retries(config.tools.retries, async |attempt| {
+        attempts = attempt;
+        debug_tools.stream_output = *stream && attempt == 1;
+        let debug_tools = &debug_tools;
+        match tool {
+            DebugTool::KubectlGetPods => {
+                debug_tools.run_kubectl_get_pods(namespace.as_deref()).await
+            }
+            DebugTool::KubectlDescribePod => {
+                if let Some(pod_name) = pod {
+                    debug_tools
+                        .run_kubectl_describe_pod(pod_name, namespace.as_deref())
+                        .await
+                } else {
+                    DebugToolResult {
+                        tool_name: "kubectl_describe_pod".to_string(),
+                        command: "kubectl describe pod".to_string(),
+                        success: false,
+                        output: String::new(),
+                        error: Some("Pod name required".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::KubectlGetServices => {
+                debug_tools
+                    .run_kubectl_get_services(namespace.as_deref())
+                    .await
+            }
+            DebugTool::KubectlGetNodes => debug_tools.run_kubectl_get_nodes().await,
+            DebugTool::KubectlDescribeNode => {
+                if let Some(node_name) = node {
+                    debug_tools.run_kubectl_describe_node(node_name).await
+                } else {
+                    DebugToolResult {
+                        tool_name: "kubectl_describe_node".to_string(),
+                        command: "kubectl describe node".to_string(),
+                        success: false,
+                        output: String::new(),
+                        error: Some("Node name required (use --node)".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::KubectlGetEvents => {
+                debug_tools
+                    .run_kubectl_get_events(namespace.as_deref())
+                    .await
+            }
+            DebugTool::JournalctlRecent => debug_tools.run_journalctl_recent(lines).await,
+            DebugTool::JournalctlService => {
+                if let Some(service_name) = service {
+                    debug_tools
+                        .run_journalctl_service(service_name, lines)
+                        .await
+                } else {
+                    DebugToolResult {
+                        tool_name: "journalctl_service".to_string(),
+                        command: "journalctl -u".to_string(),
+                        success: false,
+                        output: String::new(),
+                        error: Some("Service name required".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::JournalctlBoot => debug_tools.run_journalctl_boot().await,
+            DebugTool::JournalctlErrors => debug_tools.run_journalctl_errors(lines).await,
+            DebugTool::JournalDiskUsage => debug_tools.run_journalctl_disk_usage().await,
+            DebugTool::SystemctlStatus => {
+                if let Some(service_name) = service {
+                    debug_tools.run_systemctl_status(service_name).await
+                } else {
+                    DebugToolResult {
+                        tool_name: "systemctl_status".to_string(),
+                        command: "systemctl status".to_string(),
+                        success: false,
+                        output: String::new(),
+                        error: Some("Service name required".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::SystemctlCat => {
+                if let Some(service_name) = service {
+                    debug_tools.run_systemctl_cat(service_name).await
+                } else {
+                    DebugToolResult {
+                        tool_name: "systemctl_cat".to_string(),
+                        command: "systemctl cat".to_string(),
+                        success: false,
+                        output: String::new(),
+                        error: Some("Service name required".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::PsAux => debug_tools.run_ps_aux().await,
+            DebugTool::PsTree => debug_tools.run_ps_tree().await,
+            DebugTool::Netstat => debug_tools.run_netstat().await,
+            DebugTool::Df => debug_tools.run_df().await,
+            DebugTool::Free => debug_tools.run_free().await,
+            DebugTool::CatProcCgroups => debug_tools.run_cat_proc_cgroups().await,
+            DebugTool::LsCgroup => debug_tools.run_ls_cgroup().await,
+            DebugTool::CatProcSelfCgroup => debug_tools.run_cat_proc_self_cgroup().await,
+            DebugTool::CatProcSelfMountinfo => {
+                debug_tools.run_cat_proc_self_mountinfo().await
+            }
+            DebugTool::Lsns => debug_tools.run_lsns().await,
+            DebugTool::CatProcSelfStatus => debug_tools.run_cat_proc_self_status().await,
+            DebugTool::CatProcSelfNs => debug_tools.run_cat_proc_self_ns().await,
+            // Arch Linux specific debugging tools
+            DebugTool::PacmanListPackages => debug_tools.run_pacman_list_packages().await,
+            DebugTool::PacmanOrphans => debug_tools.run_pacman_orphans().await,
+            DebugTool::PacmanCheckFiles => debug_tools.run_pacman_check_files().await,
+            DebugTool::Checkupdates => debug_tools.run_checkupdates().await,
+            DebugTool::PaccacheInfo => debug_tools.run_paccache_info().await,
+            DebugTool::SystemdAnalyzeTime => debug_tools.run_systemd_analyze_time().await,
+            DebugTool::SystemdAnalyzeCriticalChain => debug_tools.run_systemd_analyze_critical_chain().await,
+            DebugTool::SystemdAnalyzeBlame => debug_tools.run_systemd_analyze_blame().await,
+            DebugTool::JournalctlListBoots => debug_tools.run_journalctl_list_boots().await,
+            DebugTool::Lsmod => debug_tools.run_lsmod().await,
+            DebugTool::SystemctlFailed => debug_tools.run_systemctl_failed().await,
+            DebugTool::NeedsReboot => debug_tools.run_needs_reboot().await,
+            DebugTool::PacmanMirrorlist => debug_tools.run_pacman_mirrorlist().await,
+            DebugTool::AurHelperInfo => debug_tools.run_aur_helper_info().await,
+            // Kubernetes specific debugging tools
+            DebugTool::KubectlGetDeployments => debug_tools.run_kubectl_get_deployments(namespace.as_deref()).await,
+            DebugTool::KubectlGetConfigmaps => debug_tools.run_kubectl_get_configmaps(namespace.as_deref()).await,
+            DebugTool::KubectlLogs => {
+                if let Some(pod_name) = pod {
+                    debug_tools.run_kubectl_logs(pod_name, namespace.as_deref(), lines).await
+                } else {
+                    DebugToolResult {
+                        tool_name: "kubectl_logs".to_string(),
+                        command: "kubectl logs".to_string(),
+                        success: false,
+                        output: String::new(),
+                        error: Some("Pod name required".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::KubectlTopPods => debug_tools.run_kubectl_top_pods(namespace.as_deref()).await,
+            DebugTool::KubectlTopNodes => debug_tools.run_kubectl_top_nodes().await,
+            DebugTool::KubectlClusterInfo => debug_tools.run_kubectl_cluster_info().await,
+            DebugTool::KubectlGetPv => debug_tools.run_kubectl_get_pv().await,
+            DebugTool::KubectlGetPvc => debug_tools.run_kubectl_get_pvc(namespace.as_deref()).await,
+            DebugTool::KubeletStatus => debug_tools.run_kubelet_status().await,
+            DebugTool::KubeletLogs => debug_tools.run_kubelet_logs(lines).await,
+            DebugTool::KubeletConfig => debug_tools.run_kubelet_config().await,
+            DebugTool::EtcdClusterHealth => debug_tools.run_etcd_cluster_health().await,
+            DebugTool::EtcdMemberList => debug_tools.run_etcd_member_list().await,
+            DebugTool::EtcdEndpointHealth => debug_tools.run_etcd_endpoint_health().await,
+            DebugTool::EtcdEndpointStatus => debug_tools.run_etcd_endpoint_status().await,
+            // Network debugging tools
+            DebugTool::IpAddr => debug_tools.run_ip_addr().await,
+            DebugTool::IpRoute => debug_tools.run_ip_route().await,
+            DebugTool::Ss => debug_tools.run_ss().await,
+            DebugTool::Ping => debug_tools.run_ping("8.8.8.8").await,
+            DebugTool::Traceroute => debug_tools.run_traceroute("8.8.8.8").await,
+            DebugTool::Dig => debug_tools.run_dig("google.com").await,
+            DebugTool::Iptables => debug_tools.run_iptables().await,
+            DebugTool::Ethtool => debug_tools.run_ethtool(&debug_tools.interface_or_primary(interface.as_deref())).await,
+            DebugTool::EthtoolStats => debug_tools.run_ethtool_stats(&debug_tools.interface_or_primary(interface.as_deref())).await,
+            DebugTool::NetstatLegacy => debug_tools.run_netstat_legacy().await,
+            DebugTool::ArpTable => debug_tools.run_arp_table().await,
+            DebugTool::InterfaceStats => debug_tools.run_interface_stats().await,
+            DebugTool::Iperf3 => debug_tools.run_iperf3_server_check().await,
+            DebugTool::NetworkNamespaces => debug_tools.run_network_namespaces().await,
+            DebugTool::TcpdumpSample => debug_tools.run_tcpdump_sample(None, *duration).await,
+            DebugTool::BridgeInfo => debug_tools.run_bridge_info().await,
+            DebugTool::WirelessInfo => debug_tools.run_wireless_info().await,
+            DebugTool::Nftables => debug_tools.run_nftables().await,
+            DebugTool::DnsTest => debug_tools.run_dns_test("google.com").await,
+            DebugTool::GetentHosts => {
+                debug_tools
+                    .run_getent_hosts(host.as_deref().unwrap_or("google.com"))
+                    .await
+            }
+            DebugTool::ResolvectlStatus => debug_tools.run_resolvectl_status().await,
+            DebugTool::UfwStatus => debug_tools.run_ufw_status().await,
+            DebugTool::NetworkManagerStatus => debug_tools.run_networkmanager_status().await,
+            DebugTool::DnsConfig => debug_tools.run_dns_config().await,
+            DebugTool::ConnectivityTest => debug_tools.run_connectivity_test().await,
+            DebugTool::NetworkHealthCheck => {
+                let progress = health_check_progress.as_ref().expect("created for the network health check");
+                progress.restart();
+                let composite = debug_tools
+                    .run_network_health_check_with_progress(|result| progress.inc(&result.tool_name))
+                    .await;
+                composite.to_debug_tool_result()
+            }
+            DebugTool::NetworkSetupCheck => debug_tools.run_network_setup_check().await,
+            // eBPF debugging tools
+            DebugTool::BpftoolProgList => debug_tools.run_bpftool_prog_list().await,
+            DebugTool::BpftoolProgShow => debug_tools.run_bpftool_prog_show("1").await,
+            DebugTool::BpftoolProgDumpXlated => debug_tools.run_bpftool_prog_dump_xlated("1").await,
+            DebugTool::BpftoolProgDumpJited => debug_tools.run_bpftool_prog_dump_jited("1").await,
+            DebugTool::BpftoolMapList => debug_tools.run_bpftool_map_list().await,
+            DebugTool::BpftoolMapShow => debug_tools.run_bpftool_map_show("1").await,
+            DebugTool::BpftoolMapDump => debug_tools.run_bpftool_map_dump("1").await,
+            DebugTool::BpftoolLinkList => debug_tools.run_bpftool_link_list().await,
+            DebugTool::BpftoolFeatureProbe => debug_tools.run_bpftool_feature_probe().await,
+            DebugTool::BpftoolNetList => debug_tools.run_bpftool_net_list().await,
+            DebugTool::BpftoolCgroupList => debug_tools.run_bpftool_cgroup_list().await,
+            DebugTool::BpftoolBtfList => debug_tools.run_bpftool_btf_list().await,
+            DebugTool::BpfMountCheck => debug_tools.run_bpf_mount_check().await,
+            DebugTool::BpfLsPinned => debug_tools.run_bpf_ls_pinned().await,
+            DebugTool::BpfKernelConfig => debug_tools.run_bpf_kernel_config().await,
+            DebugTool::BpftraceSyscalls => debug_tools.run_bpftrace_syscalls(*duration).await,
+            DebugTool::BpftraceOom => debug_tools.run_bpftrace_oom(*duration).await,
+            DebugTool::BpftraceListTracepoints => debug_tools.run_bpftrace_list_tracepoints().await,
+            DebugTool::BpfJitStatus => debug_tools.run_bpf_jit_status().await,
+            // Process debugging tools
+            DebugTool::LsofPort => {
+                if let Some(port) = port {
+                    debug_tools.run_lsof_port(*port).await
+                } else {
+                    DebugToolResult {
+                        tool_name: "lsof_port".to_string(),
+                        command: "lsof -i".to_string(),
+                        success: false,
+                        output: String::new(),
+                        error: Some("Port required (use --port)".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::LsofFile => {
+                if let Some(path) = path {
+                    debug_tools.run_lsof_file(path).await
+                } else {
+                    DebugToolResult {
+                        tool_name: "lsof_file".to_string(),
+                        command: "lsof --".to_string(),
+                        success: false,
+                        output: String::new(),
+                        error: Some("File path required (use --path)".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::TopConsumers => {
+                debug_tools
+                    .run_top_consumers(by.unwrap_or(SortKey::Cpu), count.unwrap_or(10))
+                    .await
+            }
+            DebugTool::ProcessLimits => debug_tools.run_process_limits(*pid).await,
+            DebugTool::StracePid => {
+                if let Some(pid) = pid {
+                    debug_tools
+                        .run_strace_pid(*pid, duration.unwrap_or(STRACE_DEFAULT_SECS))
+                        .await
+                } else {
+                    DebugToolResult {
+                        tool_name: "strace_pid".to_string(),
+                        command: "strace -p".to_string(),
+                        success: false,
+                        output: String::new(),
+                        error: Some("Process ID required (use --pid)".to_string()),
+                        execution_time_ms: 0,
+                    }
+                }
+            }
+            DebugTool::SecurityDenials => debug_tools.run_security_denials().await,
+            DebugTool::AuthSummary => debug_tools.run_auth_summary().await,
+            DebugTool::W => debug_tools.run_w().await,
+            DebugTool::Last => debug_tools.run_last(lines).await,
+            DebugTool::Timedatectl => debug_tools.run_timedatectl().await,
+            DebugTool::Swapon => debug_tools.run_swapon().await,
+            DebugTool::RefreshSystemInfo => debug_tools.run_refresh_system_info(&config.collect_options()).await.1,
+        }
+    })
+    .await;
+    if let Some(progress) = &health_check_progress {
+        progress.finish();
+    }
+
+    print_debug_result(&result, *stream && attempts == 1);
     Ok(())
 }
 
-pub fn print_debug_result(result: &DebugToolResult) {
+/// Print a tool's result; `output_streamed` leaves out the output already printed by `--stream`
+pub fn print_debug_result(result: &DebugToolResult, output_streamed: bool) {
     println!("\n🔧 Debug Tool: {}", result.tool_name);
     println!("{}", "=".repeat(50));
     
     if result.success {
         println!("✅ Status: Success");
-        if !result.output.is_empty() {
+        if !result.output.is_empty() && !output_streamed {
             println!("\n📋 Output:");
             println!("{}", result.output);
         }
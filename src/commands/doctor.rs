@@ -0,0 +1,261 @@
+use crate::ai::{AIClient, AIProvider as _};
+use crate::cli::Cli;
+use crate::config::{DatabaseConfig, RaidConfig};
+use crate::database::IN_MEMORY_PATH;
+use crate::tools::{DebugTools, ToolCategory};
+use std::fs::OpenOptions;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        }
+    }
+}
+
+/// Outcome of a single doctor check
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl DoctorCheck {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+pub async fn run_doctor(
+    cli: &Cli,
+    config: &RaidConfig,
+    debug_tools: &DebugTools,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🩺 RAID Doctor: checking prerequisites...\n");
+
+    let mut checks = vec![check_config(cli.config.as_deref(), config)];
+    checks.extend(check_tool_categories(debug_tools));
+    checks.push(check_ai_provider(config).await);
+    checks.push(check_database(&config.database));
+
+    for check in &checks {
+        let icon = match check.status {
+            CheckStatus::Pass => "✅",
+            CheckStatus::Warn => "⚠️ ",
+            CheckStatus::Fail => "❌",
+        };
+        println!("{} [{}] {}: {}", icon, check.status.label(), check.name, check.detail);
+    }
+
+    let failed = checks.iter().filter(|c| c.status == CheckStatus::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == CheckStatus::Warn).count();
+    println!();
+    println!(
+        "{} passed, {} warnings, {} failed",
+        checks.len() - failed - warned,
+        warned,
+        failed
+    );
+
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Re-load the configuration so parse errors that were silently defaulted are reported
+fn check_config(config_file: Option<&str>, config: &RaidConfig) -> DoctorCheck {
+    let loaded = match config_file {
+        Some(path) => RaidConfig::load_from_file(path).map(|_| format!("{} parses", path)),
+        None => RaidConfig::load().map(|_| "configuration parses".to_string()),
+    };
+
+    match (loaded, config.validate()) {
+        (Ok(detail), Ok(())) => DoctorCheck::new("Config", CheckStatus::Pass, detail),
+        (Ok(_), Err(e)) => DoctorCheck::new("Config", CheckStatus::Fail, format!("invalid: {}", e)),
+        (Err(e), _) => DoctorCheck::new(
+            "Config",
+            CheckStatus::Fail,
+            format!("failed to parse, falling back to defaults: {}", e),
+        ),
+    }
+}
+
+fn check_tool_categories(debug_tools: &DebugTools) -> Vec<DoctorCheck> {
+    let mut categories: Vec<(&ToolCategory, _)> = debug_tools.available_tools.iter().collect();
+    categories.sort_by_key(|(category, _)| format!("{:?}", category));
+
+    categories
+        .into_iter()
+        .map(|(category, info)| {
+            let name = format!("Tools: {:?}", category);
+            if info.is_available {
+                DoctorCheck::new(&name, CheckStatus::Pass, format!("{} tools available", info.tool_names.len()))
+            } else {
+                DoctorCheck::new(
+                    &name,
+                    CheckStatus::Warn,
+                    format!("unavailable (missing: {})", info.missing_dependencies.join(", ")),
+                )
+            }
+        })
+        .collect()
+}
+
+async fn check_ai_provider(config: &RaidConfig) -> DoctorCheck {
+    let provider = config.get_ai_provider();
+    if config.missing_api_key() {
+        return DoctorCheck::new(
+            "AI provider",
+            CheckStatus::Warn,
+            "no API key configured; RAID will run in dry-run mode (set AI_API_KEY)",
+        );
+    }
+
+    let client = match AIClient::from_cli(
+        &provider,
+        config.ai.api_key.clone(),
+        Some(config.get_model()),
+        config.ai.base_url.clone(),
+        config.ai.max_tokens,
+        config.ai.temperature,
+        config.ai.seed,
+        config.ai.max_known_issues,
+        config.ai.use_known_issues,
+        config.ai.analysis_timeout_secs,
+    )
+    .await
+    {
+        Ok(client) => client,
+        Err(e) => return DoctorCheck::new("AI provider", CheckStatus::Fail, e.to_string()),
+    };
+
+    match client.health_check().await {
+        Ok(()) => DoctorCheck::new(
+            "AI provider",
+            CheckStatus::Pass,
+            format!("{:?} reachable (model {})", provider, config.get_model()),
+        ),
+        Err(e) => DoctorCheck::new("AI provider", CheckStatus::Fail, format!("{:?} unreachable: {}", provider, e)),
+    }
+}
+
+fn check_database(config: &DatabaseConfig) -> DoctorCheck {
+    if !config.enabled {
+        return DoctorCheck::new("Database", CheckStatus::Pass, "storage disabled");
+    }
+    if config.path == IN_MEMORY_PATH {
+        return DoctorCheck::new("Database", CheckStatus::Pass, "in-memory (checks are not persisted)");
+    }
+    check_database_path(Path::new(&config.path))
+}
+
+/// Check that the database file (or the nearest existing directory it will be created under) is writable
+pub fn check_database_path(path: &Path) -> DoctorCheck {
+    if path.exists() {
+        return match OpenOptions::new().append(true).open(path) {
+            Ok(_) => DoctorCheck::new("Database", CheckStatus::Pass, format!("{} is writable", path.display())),
+            Err(e) => DoctorCheck::new(
+                "Database",
+                CheckStatus::Fail,
+                format!("{} is not writable: {}", path.display(), e),
+            ),
+        };
+    }
+
+    // Missing directories are created on first store, so probe the closest one that exists
+    let parent = path
+        .ancestors()
+        .skip(1)
+        .map(|p| if p.as_os_str().is_empty() { Path::new(".") } else { p })
+        .find(|p| p.exists())
+        .unwrap_or(Path::new("."));
+    if !parent.is_dir() {
+        return DoctorCheck::new(
+            "Database",
+            CheckStatus::Fail,
+            format!("{} is not a directory", parent.display()),
+        );
+    }
+
+    let probe = parent.join(format!(".raid-doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            DoctorCheck::new(
+                "Database",
+                CheckStatus::Pass,
+                format!("{} will be created (directory is writable)", path.display()),
+            )
+        }
+        Err(e) => DoctorCheck::new(
+            "Database",
+            CheckStatus::Fail,
+            format!("directory {} is not writable: {}", parent.display(), e),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_database_path_existing_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("checks.db");
+        std::fs::write(&path, b"").unwrap();
+
+        let check = check_database_path(&path);
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert!(check.detail.contains("is writable"));
+    }
+
+    #[test]
+    fn test_database_path_not_yet_created() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("checks.db");
+
+        let check = check_database_path(&path);
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_database_path_missing_directory() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("missing").join("checks.db");
+
+        let check = check_database_path(&path);
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert!(check.detail.contains("will be created"));
+        assert!(!dir.path().join("missing").exists());
+    }
+
+    #[test]
+    fn test_database_in_memory_and_disabled() {
+        let mut config = RaidConfig::default().database;
+        config.path = IN_MEMORY_PATH.to_string();
+        assert!(check_database(&config).detail.contains("in-memory"));
+
+        config.enabled = false;
+        assert_eq!(check_database(&config).detail, "storage disabled");
+    }
+}
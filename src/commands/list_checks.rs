@@ -0,0 +1,118 @@
+use crate::cli::{CheckComponent, OutputFormat};
+use crate::output::component_issue_categories;
+use crate::tools::ToolCategory;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// One `check --component` value and what it covers
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CheckInfo {
+    pub name: String,
+    pub description: String,
+    /// Tool categories the agent leans on for this check
+    pub tool_categories: Vec<String>,
+    /// Report issue categories kept by `check --component`; empty means every issue
+    pub issue_categories: Vec<String>,
+}
+
+/// `raid --list-checks`: every check component, for scripts that build `check` invocations
+pub fn run_list_checks(format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let checks = list_checks();
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&checks)?),
+        OutputFormat::Jsonl => {
+            for check in &checks {
+                println!("{}", serde_json::to_string(check)?);
+            }
+        }
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&checks)?),
+        OutputFormat::Text => print!("{}", format_list_checks(&checks)),
+        OutputFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(std::io::stdout());
+            csv.write_record(["name", "description", "tool_categories", "issue_categories"])?;
+            for check in &checks {
+                csv.write_record([
+                    check.name.as_str(),
+                    check.description.as_str(),
+                    check.tool_categories.join(";").as_str(),
+                    check.issue_categories.join(";").as_str(),
+                ])?;
+            }
+            csv.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Every component in the order `check --component` lists them
+pub fn list_checks() -> Vec<CheckInfo> {
+    CheckComponent::value_variants()
+        .iter()
+        .map(|component| CheckInfo {
+            name: component.as_str().to_string(),
+            description: component.focus().to_string(),
+            tool_categories: tool_categories(component).iter().map(|category| format!("{:?}", category)).collect(),
+            issue_categories: component_issue_categories(component)
+                .unwrap_or_default()
+                .iter()
+                .map(|category| category.to_string())
+                .collect(),
+        })
+        .collect()
+}
+
+fn tool_categories(component: &CheckComponent) -> &'static [ToolCategory] {
+    match component {
+        CheckComponent::All | CheckComponent::Debug => &ToolCategory::ALL,
+        CheckComponent::System => &[
+            ToolCategory::SystemInfo,
+            ToolCategory::NetworkDebug,
+            ToolCategory::ProcessDebug,
+            ToolCategory::StorageDebug,
+            ToolCategory::PerformanceDebug,
+        ],
+        CheckComponent::Containers => &[ToolCategory::ContainerInfo],
+        CheckComponent::Kubernetes => &[ToolCategory::Kubernetes, ToolCategory::ContainerInfo],
+        CheckComponent::Cgroups => &[ToolCategory::SystemInfo, ToolCategory::PerformanceDebug],
+        CheckComponent::Systemd => &[ToolCategory::Systemctl, ToolCategory::SecurityDebug],
+        CheckComponent::Journal => &[ToolCategory::Journalctl],
+    }
+}
+
+fn format_list_checks(checks: &[CheckInfo]) -> String {
+    let mut text = String::new();
+    for check in checks {
+        text.push_str(&format!("{:<11} {}\n", check.name, check.description));
+        text.push_str(&format!("            tools:  {}\n", check.tool_categories.join(", ")));
+        let issues = if check.issue_categories.is_empty() {
+            "all".to_string()
+        } else {
+            check.issue_categories.join(", ")
+        };
+        text.push_str(&format!("            issues: {}\n", issues));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_checks_covers_every_component() {
+        let checks = list_checks();
+        assert_eq!(checks.len(), CheckComponent::value_variants().len());
+
+        let journal = checks.iter().find(|check| check.name == "journal").unwrap();
+        assert_eq!(journal.tool_categories, vec!["Journalctl"]);
+        assert_eq!(journal.issue_categories, vec!["log"]);
+
+        let all = checks.iter().find(|check| check.name == "all").unwrap();
+        assert_eq!(all.tool_categories.len(), ToolCategory::ALL.len());
+        assert!(all.issue_categories.is_empty());
+
+        let json = serde_json::to_value(&checks).unwrap();
+        assert_eq!(json[0]["name"], "all");
+        assert!(format_list_checks(&checks).contains("issues: all"));
+    }
+}
@@ -1,3 +1,12 @@
 pub mod ai;
+pub mod apply_fixes;
+pub mod benchmark;
+pub mod bundle;
 pub mod config;
-pub mod debug; 
\ No newline at end of file
+pub mod debug;
+pub mod doctor;
+pub mod offline;
+pub mod list_checks;
+pub mod tools_list;
+pub mod update;
+pub mod verify;
@@ -0,0 +1,134 @@
+use crate::analysis::summarize_offline;
+use crate::cli::{CheckComponent, Cli};
+use crate::config::RaidConfig;
+use crate::known_issues::KnownIssuesDatabase;
+use crate::output::{self, KnownIssueMatch, SystemHealthReport, Verbosity};
+use crate::sysinfo::{self, collect_system_info_with};
+use crate::tools::DebugTools;
+
+/// `--no-ai`: run every check an AI run would, match the findings against the known-issues
+/// database and print a complete report without contacting any AI provider
+pub async fn run_offline_check(
+    cli: &Cli,
+    config: &RaidConfig,
+    debug_tools: &DebugTools,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut report = offline_report(config, debug_tools, &cli.check_component()).await;
+
+    // Quiet mode stays silent on a healthy system, as it does with AI analysis
+    if config.get_verbosity() == Verbosity::Quiet && report.is_healthy() {
+        return Ok(());
+    }
+
+    report.apply_output_config(&config.output);
+    output::print_report(&report, config.get_output_format(), config.get_verbosity());
+    Ok(())
+}
+
+/// Collect, run the threshold checks, match known issues and summarize, all without AI.
+/// The report keeps the full collected `system_info`.
+pub async fn offline_report(
+    config: &RaidConfig,
+    debug_tools: &DebugTools,
+    component: &CheckComponent,
+) -> SystemHealthReport {
+    let info = collect_system_info_with(&config.collect_options());
+    let mut report = output::create_system_health_report(&info, "", config.get_verbosity().is_detailed());
+
+    // The same threshold checks that feed the AI context
+    if info.kubernetes.is_kubernetes
+        && let Some((pods, events)) = debug_tools.get_pods_and_events(None).await
+    {
+        report.issues.extend(output::pending_pod_issues(&pods, &events));
+        if let Some(volumes) = debug_tools.get_volume_binding_state().await {
+            report.issues.extend(output::unbound_claim_issues(&volumes, &events));
+        }
+    }
+    let failed_units = sysinfo::collect_failed_units();
+    if !failed_units.is_empty() {
+        let statuses = debug_tools.get_service_statuses(&failed_units).await;
+        output::add_service_status_issues(&mut report.issues, &statuses);
+        report.issues.extend(output::security_denial_issues(
+            &debug_tools.get_security_denials().await,
+            &failed_units,
+        ));
+    }
+    report.issues.extend(output::brute_force_issues(&debug_tools.get_failed_logins().await));
+    if let Some(status) = debug_tools.get_time_sync_status().await {
+        report.issues.extend(output::time_sync_issues(&status));
+    }
+    if let Some(name) = &config.network.dns_probe_name {
+        report.issues.extend(output::dns_resolution_issues(&debug_tools.get_dns_resolution_status(name).await));
+    }
+    if !report.issues.is_empty() && report.status.overall == "healthy" {
+        report.status.overall = "warning".to_string();
+    }
+    report.scope_to(component);
+
+    let known_issues = KnownIssuesDatabase::shared().await;
+    report.known_issue_matches = match_known_issues(&known_issues, &report, config.ai.max_known_issues).await;
+    report.analysis = summarize_offline(&report);
+    report
+}
+
+/// Match the report's findings against the known-issues database
+pub async fn match_known_issues(
+    database: &KnownIssuesDatabase,
+    report: &SystemHealthReport,
+    limit: usize,
+) -> Vec<KnownIssueMatch> {
+    let context = findings_context(report);
+    if context.is_empty() {
+        return Vec::new();
+    }
+
+    database
+        .get_scored_issues_for_context(&context, None, limit)
+        .await
+        .into_iter()
+        .map(|(issue, confidence)| KnownIssueMatch {
+            id: issue.id,
+            title: issue.title,
+            severity: format!("{:?}", issue.severity).to_lowercase(),
+            confidence,
+            fix_commands: issue.fix_commands,
+        })
+        .collect()
+}
+
+/// The text the known-issue patterns are matched against: every issue plus the raw journal errors
+fn findings_context(report: &SystemHealthReport) -> String {
+    let mut context = String::new();
+    for issue in &report.issues {
+        context.push_str(&issue.message);
+        context.push('\n');
+        if let Some(details) = &issue.details {
+            context.push_str(details);
+            context.push('\n');
+        }
+    }
+    for entry in report.status.logs.recent_errors.iter().chain(&report.status.logs.boot_errors) {
+        context.push_str(&format!("{}: {}\n", entry.unit, entry.message));
+    }
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Issue;
+    use crate::sysinfo::SystemInfo;
+
+    #[tokio::test]
+    async fn test_match_known_issues_from_findings() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = KnownIssuesDatabase::with_issues_file(&dir.path().join("known_issues.yaml")).await;
+        let mut report = output::create_system_health_report(&SystemInfo::default(), "", false);
+        assert!(match_known_issues(&database, &report, 5).await.is_empty());
+
+        report.issues.push(Issue::new("service", "high", "Service 'nginx.service' has failed", None));
+        let matches = match_known_issues(&database, &report, 5).await;
+        assert!(matches.iter().any(|m| m.id == "systemd-failed-units" && !m.fix_commands.is_empty()));
+        assert!(matches.iter().all(|m| m.confidence > 0.3));
+    }
+}
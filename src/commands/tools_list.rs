@@ -0,0 +1,159 @@
+use crate::cli::OutputFormat;
+use crate::tools::{AvailableToolInfo, DebugTools, ToolCategory};
+use serde::Serialize;
+
+/// One category from the startup availability check
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CategoryAvailability {
+    pub category: String,
+    /// Whether the agent is offered this category's tools
+    pub usable_by_agent: bool,
+    pub tools: Vec<ToolStatus>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ToolStatus {
+    pub name: String,
+    pub available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// `raid tools-list`: what the availability check found, per category
+pub fn run_tools_list(debug_tools: &DebugTools, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let categories = tools_list(debug_tools);
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&categories)?),
+        OutputFormat::Jsonl => {
+            for category in &categories {
+                println!("{}", serde_json::to_string(category)?);
+            }
+        }
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&categories)?),
+        OutputFormat::Text => print!("{}", format_tools_list(&categories)),
+        OutputFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(std::io::stdout());
+            csv.write_record(["category", "tool", "available", "reason"])?;
+            for category in &categories {
+                for tool in &category.tools {
+                    csv.write_record([
+                        category.category.as_str(),
+                        tool.name.as_str(),
+                        if tool.available { "true" } else { "false" },
+                        tool.reason.as_deref().unwrap_or(""),
+                    ])?;
+                }
+            }
+            csv.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Every checked category in `ToolCategory::ALL` order
+pub fn tools_list(debug_tools: &DebugTools) -> Vec<CategoryAvailability> {
+    ToolCategory::ALL
+        .iter()
+        .filter_map(|category| debug_tools.available_tools.get(category))
+        .map(category_availability)
+        .collect()
+}
+
+fn category_availability(info: &AvailableToolInfo) -> CategoryAvailability {
+    let found = info.tool_names.iter().map(|name| ToolStatus {
+        name: name.clone(),
+        available: true,
+        reason: None,
+    });
+    // Missing entries are either a binary name or a sentence such as "BPF filesystem not mounted"
+    let missing = info.missing_dependencies.iter().map(|missing| match missing.split_once(": ") {
+        Some((_, file)) if missing.starts_with("missing file") => ToolStatus {
+            name: file.to_string(),
+            available: false,
+            reason: Some("file not found".to_string()),
+        },
+        _ if missing.contains(' ') => ToolStatus {
+            name: missing.clone(),
+            available: false,
+            reason: Some(missing.clone()),
+        },
+        _ => ToolStatus {
+            name: missing.clone(),
+            available: false,
+            reason: Some("not found in PATH".to_string()),
+        },
+    });
+
+    CategoryAvailability {
+        category: format!("{:?}", info.category),
+        usable_by_agent: info.is_available,
+        tools: found.chain(missing).collect(),
+    }
+}
+
+fn format_tools_list(categories: &[CategoryAvailability]) -> String {
+    let mut text = String::new();
+    for category in categories {
+        text.push_str(&format!(
+            "{} ({})\n",
+            category.category,
+            if category.usable_by_agent { "usable by the agent" } else { "not usable by the agent" }
+        ));
+        for tool in &category.tools {
+            match (&tool.reason, tool.available) {
+                (_, true) => text.push_str(&format!("  ✅ {}\n", tool.name)),
+                (Some(reason), false) if *reason != tool.name => {
+                    text.push_str(&format!("  ❌ {}: {}\n", tool.name, reason))
+                }
+                _ => text.push_str(&format!("  ❌ {}\n", tool.name)),
+            }
+        }
+    }
+    let usable = categories.iter().filter(|category| category.usable_by_agent).count();
+    text.push_str(&format!("\n{} of {} categories usable by the agent\n", usable, categories.len()));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_availability_reasons() {
+        let info = AvailableToolInfo {
+            category: ToolCategory::EbpfDebug,
+            tool_names: vec!["bpftrace".to_string()],
+            is_available: false,
+            missing_dependencies: vec![
+                "bpftool".to_string(),
+                "BPF filesystem not mounted".to_string(),
+                "missing file: /var/run/docker.sock".to_string(),
+            ],
+        };
+        let category = category_availability(&info);
+        assert_eq!(category.category, "EbpfDebug");
+        assert!(!category.usable_by_agent);
+        let reasons: Vec<(&str, bool, Option<&str>)> = category
+            .tools
+            .iter()
+            .map(|tool| (tool.name.as_str(), tool.available, tool.reason.as_deref()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("bpftrace", true, None),
+                ("bpftool", false, Some("not found in PATH")),
+                ("BPF filesystem not mounted", false, Some("BPF filesystem not mounted")),
+                ("/var/run/docker.sock", false, Some("file not found")),
+            ]
+        );
+
+        let text = format_tools_list(&[category]);
+        assert_eq!(
+            text,
+            "EbpfDebug (not usable by the agent)\n  ✅ bpftrace\n  ❌ bpftool: not found in PATH\n  \
+             ❌ BPF filesystem not mounted\n  ❌ /var/run/docker.sock: file not found\n\n\
+             0 of 1 categories usable by the agent\n"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Latest published release of RAID
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/null-channel/RAID/releases/latest";
+
+/// The check must never hold up a run
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Start the opt-in update check in the background. It prints a one-line notice to stderr
+/// when a newer release exists and stays silent on any error, including no network.
+pub fn spawn_update_check() {
+    tokio::spawn(async {
+        if let Some(latest) = fetch_latest_tag().await
+            && let Some(notice) = update_notice(env!("CARGO_PKG_VERSION"), &latest)
+        {
+            eprintln!("{}", notice);
+        }
+    });
+}
+
+async fn fetch_latest_tag() -> Option<String> {
+    let client = reqwest::Client::builder()
+        .timeout(UPDATE_CHECK_TIMEOUT)
+        .user_agent(concat!("raid/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .ok()?;
+    let release: Release = client
+        .get(LATEST_RELEASE_URL)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    Some(release.tag_name)
+}
+
+/// The notice to print when `latest` is newer than `current`
+pub fn update_notice(current: &str, latest: &str) -> Option<String> {
+    (parse_version(latest)? > parse_version(current)?).then(|| {
+        format!(
+            "ℹ️  RAID {} is available (running {}): https://github.com/null-channel/RAID/releases",
+            latest.trim_start_matches('v'),
+            current
+        )
+    })
+}
+
+/// `major.minor.patch` from a tag such as `v0.2.1`; pre-release and build suffixes are ignored
+fn parse_version(tag: &str) -> Option<(u64, u64, u64)> {
+    let version = tag.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_notice_only_for_newer_releases() {
+        assert!(update_notice("0.1.0", "v0.2.0").unwrap().contains("RAID 0.2.0 is available (running 0.1.0)"));
+        assert!(update_notice("0.1.9", "0.1.10").is_some());
+        assert!(update_notice("0.1.0", "v0.1.0").is_none());
+        assert!(update_notice("0.2.0", "v0.1.5").is_none());
+        assert!(update_notice("0.1.0", "nightly").is_none());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.4"), Some((1, 4, 0)));
+        assert_eq!(parse_version("v2.0.0-rc.1"), Some((2, 0, 0)));
+        assert_eq!(parse_version("release-3"), None);
+    }
+}
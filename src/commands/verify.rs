@@ -0,0 +1,150 @@
+use crate::config::RaidConfig;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Prefix of an HMAC signature line, as `openssl dgst -sha256 -hmac` prints it
+const HMAC_PREFIX: &str = "HMAC-SHA256(";
+
+/// What a signature file proved about a report
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignatureKind {
+    /// The SHA-256 checksum matched: the file is intact
+    Checksum,
+    /// The HMAC matched: the file is intact and was signed by a holder of the key
+    Hmac,
+}
+
+/// `--sign`: write `<file>.sha256` in `sha256sum` format next to a report file, and
+/// `<file>.hmac` too when `signing.key` is set. Returns the files written.
+pub fn sign_file(path: &Path, key: Option<&str>) -> std::io::Result<Vec<PathBuf>> {
+    let content = std::fs::read(path)?;
+    let name = file_name(path);
+    let mut written = Vec::new();
+
+    let checksum = append_extension(path, "sha256");
+    std::fs::write(&checksum, format!("{}  {}\n", hex(&Sha256::digest(&content)), name))?;
+    written.push(checksum);
+
+    if let Some(key) = key.filter(|key| !key.is_empty()) {
+        let signature = append_extension(path, "hmac");
+        std::fs::write(&signature, format!("{}{})= {}\n", HMAC_PREFIX, name, hex(&hmac(key, &content))))?;
+        written.push(signature);
+    }
+    Ok(written)
+}
+
+/// Check a report against a `.sha256` or `.hmac` file written by `sign_file`. An HMAC
+/// signature needs the same `signing.key` it was made with.
+pub fn verify_file(report: &Path, signature: &Path, key: Option<&str>) -> Result<SignatureKind, String> {
+    let content = std::fs::read(report).map_err(|e| format!("Failed to read '{}': {}", report.display(), e))?;
+    let signature_text = std::fs::read_to_string(signature)
+        .map_err(|e| format!("Failed to read '{}': {}", signature.display(), e))?;
+    let line = signature_text.lines().next().unwrap_or_default().trim();
+
+    if line.starts_with(HMAC_PREFIX) {
+        let key = key
+            .filter(|key| !key.is_empty())
+            .ok_or("The signature is an HMAC; set signing.key (or RAID_SIGNING__KEY) to verify it")?;
+        let expected = line
+            .rsplit_once("= ")
+            .and_then(|(_, digest)| unhex(digest))
+            .ok_or_else(|| format!("'{}' is not a valid HMAC signature", signature.display()))?;
+        let mut mac = HmacSha256::new_from_slice(key.as_bytes()).map_err(|e| e.to_string())?;
+        mac.update(&content);
+        mac.verify_slice(&expected)
+            .map(|_| SignatureKind::Hmac)
+            .map_err(|_| "HMAC mismatch: the report was modified or signed with a different key".to_string())
+    } else {
+        let expected = line
+            .split_whitespace()
+            .next()
+            .filter(|digest| digest.len() == 64 && unhex(digest).is_some())
+            .ok_or_else(|| format!("'{}' is not a SHA-256 checksum file", signature.display()))?;
+        if expected.eq_ignore_ascii_case(&hex(&Sha256::digest(&content))) {
+            Ok(SignatureKind::Checksum)
+        } else {
+            Err("Checksum mismatch: the report was modified".to_string())
+        }
+    }
+}
+
+/// `raid verify <report> <signature>`; exits with status 1 when the report doesn't match
+pub fn run_verify(report: &Path, signature: &Path, config: &RaidConfig) -> Result<(), Box<dyn std::error::Error>> {
+    match verify_file(report, signature, config.signing.key.as_deref()) {
+        Ok(SignatureKind::Checksum) => println!("✅ {}: SHA-256 checksum matches", report.display()),
+        Ok(SignatureKind::Hmac) => println!("✅ {}: HMAC-SHA256 signature is valid", report.display()),
+        Err(e) => {
+            eprintln!("❌ {}: {}", report.display(), e);
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+fn hmac(key: &str, content: &[u8]) -> Vec<u8> {
+    // HMAC takes keys of any length, so this can't fail
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(content);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// `report.tar.gz` -> `report.tar.gz.sha256`
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&text[index..index + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = dir.path().join("report.json");
+        std::fs::write(&report, "{\"status\":\"healthy\"}").unwrap();
+
+        let written = sign_file(&report, Some("audit-key")).unwrap();
+        let (checksum, signature) = (&written[0], &written[1]);
+        assert_eq!(checksum, &dir.path().join("report.json.sha256"));
+        let checksum_line = std::fs::read_to_string(checksum).unwrap();
+        assert!(checksum_line.ends_with("  report.json\n"));
+        assert!(std::fs::read_to_string(signature).unwrap().starts_with("HMAC-SHA256(report.json)= "));
+
+        assert_eq!(verify_file(&report, checksum, None), Ok(SignatureKind::Checksum));
+        assert_eq!(verify_file(&report, signature, Some("audit-key")), Ok(SignatureKind::Hmac));
+        assert!(verify_file(&report, signature, None).unwrap_err().contains("set signing.key"));
+        assert!(verify_file(&report, signature, Some("other-key")).unwrap_err().starts_with("HMAC mismatch"));
+
+        std::fs::write(&report, "{\"status\":\"critical\"}").unwrap();
+        assert!(verify_file(&report, checksum, None).unwrap_err().starts_with("Checksum mismatch"));
+        assert!(verify_file(&report, signature, Some("audit-key")).is_err());
+
+        // No key, no HMAC file
+        assert_eq!(sign_file(&report, None).unwrap(), vec![checksum.clone()]);
+        assert!(verify_file(&report, &report, None).unwrap_err().contains("is not a SHA-256 checksum file"));
+    }
+}
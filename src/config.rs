@@ -1,16 +1,110 @@
 use crate::cli::{AIProvider, OutputFormat};
-use config::{Config, ConfigError, Environment, File};
+use crate::output::Verbosity;
+use crate::sysinfo::{CollectOptions, HostPaths, UnitFilter};
+use config::{Config, ConfigError, Environment, File, FileFormat};
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RaidConfig {
+    /// Schema version the file was written for; files from before versioning count as 0
+    #[serde(default)]
+    pub config_version: u32,
+    /// What to do about keys in config files that no setting reads, usually typos
+    #[serde(default)]
+    pub unknown_keys: UnknownKeyPolicy,
     pub ai: AIConfig,
     pub output: OutputConfig,
     pub ui: UIConfig,
     pub database: DatabaseConfig,
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub journal: JournalConfig,
+    #[serde(default)]
+    pub system: SystemConfig,
+    #[serde(default)]
+    pub systemd: SystemdConfig,
+    #[serde(default)]
+    pub updates: UpdatesConfig,
+    #[serde(default)]
+    pub tools: ToolsConfig,
+    #[serde(default)]
+    pub context: ContextConfig,
+    #[serde(default)]
+    pub signing: SigningConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+}
+
+/// `unknown_keys`: warn about unrecognized config keys, refuse to run, or say nothing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownKeyPolicy {
+    #[default]
+    Warn,
+    Error,
+    Ignore,
+}
+
+/// How debug tools are run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolsConfig {
+    /// Extra attempts for a tool that fails (e.g. a kubectl API timeout); 0 reports the first failure
+    #[serde(default)]
+    pub retries: u32,
+}
+
+/// Upper bound on `tools.retries`, so a broken tool can't stall a run for minutes
+pub const MAX_TOOL_RETRIES: u32 = 5;
+
+/// Facts about this host the AI can't collect itself, such as region, cluster name or the
+/// on-call runbook
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextConfig {
+    /// Section title to content, added to every AI context. Config keys are case-insensitive,
+    /// so titles arrive in lower case.
+    #[serde(default)]
+    pub extra_sections: std::collections::BTreeMap<String, String>,
+    /// A text file added to every AI context as its own section (`--context-file`)
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+}
+
+/// `--sign`: report checksums, plus an HMAC when a key is set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SigningConfig {
+    /// HMAC-SHA256 key; best set through `RAID_SIGNING__KEY` rather than a config file
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+/// Network checks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Name resolved to check DNS. Unset by default, since no single name resolves
+    /// everywhere (an air-gapped host would always fail on a public one); set a name the
+    /// host must be able to resolve to turn the check on
+    #[serde(default)]
+    pub dns_probe_name: Option<String>,
+}
+
+/// RAID's own resource footprint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PerformanceConfig {
+    /// Most external commands (collection and AI tools together) run at once; unset means
+    /// one per CPU
+    #[serde(default)]
+    pub max_parallelism: Option<usize>,
+}
+
+/// Checking GitHub for a newer release is opt-in, since it contacts an outside service
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdatesConfig {
+    #[serde(default)]
+    pub check: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,14 +115,102 @@ pub struct AIConfig {
     pub base_url: Option<String>,
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
+    /// Sampling seed for reproducible responses; OpenAI, Ollama and llama.cpp honour it, Anthropic doesn't
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Maximum number of known issues injected into the AI prompt
+    #[serde(default = "default_max_known_issues")]
+    pub max_known_issues: usize,
+    /// Add matching known issues to the AI prompt (`--no-known-issues` turns this off)
+    #[serde(default = "default_use_known_issues")]
+    pub use_known_issues: bool,
+    /// Upper bound in seconds on a whole analysis call, including retries
+    #[serde(default = "default_analysis_timeout_secs")]
+    pub analysis_timeout_secs: u64,
+    /// Require the agent to explain each tool call and show the reasoning after the answer
+    #[serde(default)]
+    pub explain_reasoning: bool,
+    /// Context window of the model in tokens; prompts estimated to be larger are warned about
+    #[serde(default)]
+    pub context_window: Option<usize>,
+    /// Providers tried in order when the primary one fails with an auth, network or server error
+    #[serde(default)]
+    pub fallback_providers: Vec<ProviderConfig>,
+    /// Analyze a full check one category at a time (system, systemd, journal, ...) in
+    /// concurrent calls and merge the results, instead of one agent session over everything
+    #[serde(default)]
+    pub per_category_analysis: bool,
+}
+
+/// A fallback AI provider; token and temperature settings are shared with the primary provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    pub provider: String,
+    pub api_key: Option<String>,
+    pub model: Option<String>,
+    pub base_url: Option<String>,
+}
+
+impl ProviderConfig {
+    pub fn get_ai_provider(&self) -> AIProvider {
+        parse_ai_provider(&self.provider)
+    }
+}
+
+/// Provider names `validate` accepts, the spellings `parse_ai_provider` recognizes
+const AI_PROVIDER_NAMES: [&str; 7] = ["open-ai", "openai", "anthropic", "local", "llama-cpp", "llamacpp", "llama.cpp"];
+
+fn parse_ai_provider(name: &str) -> AIProvider {
+    match name.to_lowercase().as_str() {
+        "openai" | "open-ai" => AIProvider::OpenAI,
+        "anthropic" => AIProvider::Anthropic,
+        "local" => AIProvider::Local,
+        "llama-cpp" | "llamacpp" | "llama.cpp" => AIProvider::LlamaCpp,
+        _ => AIProvider::OpenAI, // Default fallback
+    }
+}
+
+fn default_max_known_issues() -> usize {
+    crate::known_issues::DEFAULT_MAX_RELEVANT_ISSUES
+}
+
+fn default_use_known_issues() -> bool {
+    true
+}
+
+fn default_analysis_timeout_secs() -> u64 {
+    crate::ai::DEFAULT_ANALYSIS_TIMEOUT_SECS
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputConfig {
     pub format: String,
+    /// Legacy flag; `true` is treated as full verbosity when `verbosity` is unset
     pub verbose: bool,
+    #[serde(default)]
+    pub verbosity: Verbosity,
     pub color: bool,
     pub progress: bool,
+    /// Embed the raw collected `system_info` in JSON/YAML reports
+    #[serde(default = "default_include_raw_sysinfo")]
+    pub include_raw_sysinfo: bool,
+    /// Cap on journal entries per list in JSON/YAML reports (unlimited when unset)
+    #[serde(default)]
+    pub max_journal_entries: Option<usize>,
+    /// Replace hostnames, IPs, MACs and usernames in JSON/YAML reports with stable pseudonyms
+    #[serde(default)]
+    pub anonymize: bool,
+    /// End text output with the commands that were run, so results can be reproduced by hand
+    #[serde(default = "default_show_commands")]
+    pub show_commands: bool,
+}
+
+fn default_include_raw_sysinfo() -> bool {
+    true
+}
+
+fn default_show_commands() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,13 +221,67 @@ pub struct UIConfig {
     pub compact_mode: bool,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JournalConfig {
+    /// Only collect journal entries from this systemd unit (`journalctl -u <unit>`)
+    pub unit: Option<String>,
+    /// Only collect journal entries from the current boot and focus the AI on them
+    #[serde(default)]
+    pub since_boot: bool,
+}
+
+/// Which systemd units are collected and reported. Globs match the full unit name or the
+/// name without `.service`; an empty include list means every unit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SystemdConfig {
+    #[serde(default)]
+    pub include_patterns: Vec<String>,
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+}
+
+/// Where to read kernel state from; point at the host's mounts (e.g. `/host/proc`) to
+/// inspect the host from inside a container
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemConfig {
+    #[serde(default = "default_procfs_root")]
+    pub procfs_root: String,
+    #[serde(default = "default_sysfs_root")]
+    pub sysfs_root: String,
+}
+
+fn default_procfs_root() -> String {
+    "/proc".to_string()
+}
+
+fn default_sysfs_root() -> String {
+    "/sys".to_string()
+}
+
+impl Default for SystemConfig {
+    fn default() -> Self {
+        Self {
+            procfs_root: default_procfs_root(),
+            sysfs_root: default_sysfs_root(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
+    /// Store checks at all; when false, storing is a silent no-op
+    #[serde(default = "default_database_enabled")]
+    pub enabled: bool,
+    /// SQLite file, or `:memory:` to keep checks for the current run only
     pub path: String,
     pub auto_cleanup: bool,
     pub retention_days: u32,
 }
 
+fn default_database_enabled() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
     pub level: String,
@@ -55,6 +291,8 @@ pub struct LoggingConfig {
 impl Default for RaidConfig {
     fn default() -> Self {
         Self {
+            config_version: CONFIG_VERSION,
+            unknown_keys: UnknownKeyPolicy::default(),
             ai: AIConfig {
                 provider: "open-ai".to_string(),
                 api_key: None,
@@ -62,12 +300,25 @@ impl Default for RaidConfig {
                 base_url: None,
                 max_tokens: Some(1000),
                 temperature: Some(0.7),
+                seed: None,
+                max_known_issues: default_max_known_issues(),
+                use_known_issues: default_use_known_issues(),
+                analysis_timeout_secs: default_analysis_timeout_secs(),
+                explain_reasoning: false,
+                context_window: None,
+                fallback_providers: Vec::new(),
+                per_category_analysis: false,
             },
             output: OutputConfig {
                 format: "text".to_string(),
                 verbose: false,
+                verbosity: Verbosity::Normal,
                 color: true,
                 progress: true,
+                include_raw_sysinfo: default_include_raw_sysinfo(),
+                max_journal_entries: None,
+                anonymize: false,
+                show_commands: default_show_commands(),
             },
             ui: UIConfig {
                 color: true,
@@ -76,6 +327,7 @@ impl Default for RaidConfig {
                 compact_mode: false,
             },
             database: DatabaseConfig {
+                enabled: default_database_enabled(),
                 path: "system_checks.db".to_string(),
                 auto_cleanup: false,
                 retention_days: 30,
@@ -84,70 +336,127 @@ impl Default for RaidConfig {
                 level: "info".to_string(),
                 file: None,
             },
+            journal: JournalConfig::default(),
+            system: SystemConfig::default(),
+            systemd: SystemdConfig::default(),
+            updates: UpdatesConfig::default(),
+            tools: ToolsConfig::default(),
+            context: ContextConfig::default(),
+            signing: SigningConfig::default(),
+            network: NetworkConfig::default(),
+            performance: PerformanceConfig::default(),
         }
     }
 }
 
-impl RaidConfig {
-    /// Load configuration from files, environment variables, and defaults
-    pub fn load() -> Result<Self, ConfigError> {
-        let mut builder = Config::builder();
+/// How a config file is parsed, from its extension: `.toml` and `.json` as such, anything
+/// else (including no extension) as YAML
+fn config_file_format(path: &Path) -> FileFormat {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("toml") => FileFormat::Toml,
+        Some(extension) if extension.eq_ignore_ascii_case("json") => FileFormat::Json,
+        _ => FileFormat::Yaml,
+    }
+}
 
-        // Start with defaults
-        builder = builder.add_source(config::Config::try_from(&RaidConfig::default())?);
+/// Schema version written by this release. When a key moves or changes meaning, bump this
+/// and add the step that upgrades older files to `MIGRATIONS`.
+pub const CONFIG_VERSION: u32 = 1;
 
-        // Add configuration files in order of precedence (last wins)
-        
-        // 1. System-wide config
-        if let Some(system_config) = Self::get_system_config_path() {
-            if system_config.exists() {
-                builder = builder.add_source(File::from(system_config).required(false));
-            }
-        }
+/// Step `n` upgrades a config file from version `n` to `n + 1`
+const MIGRATIONS: [fn(&mut serde_json::Value); CONFIG_VERSION as usize] = [migrate_verbose_to_verbosity];
 
-        // 2. User config directory
-        if let Some(user_config_dir) = Self::get_user_config_dir() {
-            for filename in &["raid.yaml", "raid.yml", "raid.toml"] {
-                let config_file = user_config_dir.join(filename);
-                if config_file.exists() {
-                    builder = builder.add_source(File::from(config_file).required(false));
-                    break; // Use the first one found
-                }
-            }
+/// v1: the legacy `output.verbose: true` becomes `output.verbosity: full`
+fn migrate_verbose_to_verbosity(value: &mut serde_json::Value) {
+    let Some(output) = value.get_mut("output").and_then(serde_json::Value::as_object_mut) else {
+        return;
+    };
+    if output.get("verbose").and_then(serde_json::Value::as_bool) == Some(true) {
+        // An explicit verbosity other than normal already won over `verbose`
+        if matches!(output.get("verbosity").and_then(serde_json::Value::as_str), None | Some("normal")) {
+            output.insert("verbosity".to_string(), "full".into());
         }
+        output.insert("verbose".to_string(), false.into());
+    }
+}
 
-        // 3. Current directory config
-        for filename in &["raid.yaml", "raid.yml", "raid.toml", ".raid.yaml", ".raid.yml", ".raid.toml"] {
-            let config_file = PathBuf::from(filename);
-            if config_file.exists() {
-                builder = builder.add_source(File::from(config_file).required(false));
-                break; // Use the first one found
-            }
+/// A config file parsed in its own format without checking it against the schema
+fn read_config_value(path: &Path) -> Result<serde_json::Value, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let value = match config_file_format(path) {
+        FileFormat::Toml => toml::from_str(&content).map_err(|e| e.to_string()),
+        FileFormat::Json => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        _ => serde_yaml::from_str(&content).map_err(|e| e.to_string()),
+    };
+    match value.map_err(|e| format!("Failed to parse '{}': {}", path.display(), e))? {
+        // An empty YAML file
+        serde_json::Value::Null => Ok(serde_json::Value::Object(Default::default())),
+        value => Ok(value),
+    }
+}
+
+fn declared_version(value: &serde_json::Value) -> u32 {
+    value.get("config_version").and_then(serde_json::Value::as_u64).map_or(0, |version| version as u32)
+}
+
+/// Run every migration step newer than the file's version; true when anything changed
+fn apply_migrations(value: &mut serde_json::Value) -> bool {
+    let before = value.clone();
+    for step in MIGRATIONS.iter().skip(declared_version(value) as usize) {
+        step(value);
+    }
+    *value != before
+}
+
+/// Walk a config file's keys alongside the default config's and record those with no setting
+fn collect_unknown_keys(value: &serde_json::Value, schema: &serde_json::Value, section: &str, found: &mut Vec<String>) {
+    let (Some(value), Some(schema)) = (value.as_object(), schema.as_object()) else {
+        return;
+    };
+    // An empty map in the defaults is keyed by the user, like `context.extra_sections`
+    if schema.is_empty() {
+        return;
+    }
+    for (key, child) in value {
+        match schema.get(key) {
+            Some(known) if section.is_empty() => collect_unknown_keys(child, known, key, found),
+            Some(known) => collect_unknown_keys(child, known, &format!("{}.{}", section, key), found),
+            None if section.is_empty() => found.push(format!("unknown key '{}'", key)),
+            None => found.push(format!("unknown key '{}' in [{}]", key, section)),
         }
+    }
+}
 
-        // 4. Environment variables (with RAID_ prefix)
-        builder = builder.add_source(
-            Environment::with_prefix("RAID")
-                .prefix_separator("_")
-                .separator("__"),
-        );
+/// Local override files looked for next to the base config, usually left out of version control
+pub const LOCAL_CONFIG_FILES: [&str; 3] = ["raid.local.yaml", "raid.local.yml", "raid.local.toml"];
 
-        // Build and deserialize
-        let config = builder.build()?;
-        config.try_deserialize()
+/// Colon-separated config files merged after the local override, in order
+pub const CONFIG_LAYERS_ENV: &str = "RAID_CONFIG_LAYERS";
+
+impl RaidConfig {
+    /// Load configuration from files, environment variables, and defaults
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_files(&Self::config_files(None))
     }
 
     /// Load configuration with custom config file path
     pub fn load_from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ConfigError> {
+        Self::load_files(&Self::config_files(Some(path.as_ref())))
+    }
+
+    /// Merge the files over the defaults, then environment variables over the files. Maps
+    /// are merged key by key, so a later file only overrides the keys it sets.
+    fn load_files(files: &[PathBuf]) -> Result<Self, ConfigError> {
         let mut builder = Config::builder();
 
         // Start with defaults
         builder = builder.add_source(config::Config::try_from(&RaidConfig::default())?);
 
-        // Add the specified config file
-        builder = builder.add_source(File::from(path.as_ref()).required(true));
+        for file in files {
+            builder = builder.add_source(File::from(file.as_path()).format(config_file_format(file)).required(true));
+        }
 
-        // Add environment variables (they can still override file settings)
+        // Environment variables (with RAID_ prefix) override every file
         builder = builder.add_source(
             Environment::with_prefix("RAID")
                 .prefix_separator("_")
@@ -158,6 +467,76 @@ impl RaidConfig {
         config.try_deserialize()
     }
 
+    /// The config files a run merges, lowest precedence first: `--config <file>` or the
+    /// system, user and current-directory configs, then `raid.local.yaml` next to the base
+    /// config, then each file in `RAID_CONFIG_LAYERS`
+    pub fn config_files(explicit: Option<&Path>) -> Vec<PathBuf> {
+        Self::config_files_with(explicit, std::env::var(CONFIG_LAYERS_ENV).ok().as_deref())
+    }
+
+    fn config_files_with(explicit: Option<&Path>, layers: Option<&str>) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let base_dir = match explicit {
+            Some(path) => {
+                files.push(path.to_path_buf());
+                path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new(".")).to_path_buf()
+            }
+            None => {
+                files.extend(Self::base_config_files());
+                PathBuf::from(".")
+            }
+        };
+
+        // 4. Uncommitted local overrides next to the base config
+        if let Some(local) = LOCAL_CONFIG_FILES
+            .iter()
+            .map(|filename| base_dir.join(filename))
+            .find(|local| local.exists() && explicit != Some(local.as_path()))
+        {
+            files.push(local);
+        }
+
+        // 5. Extra layers named in the environment; a missing one is an error
+        files.extend(
+            layers
+                .unwrap_or_default()
+                .split(':')
+                .filter(|layer| !layer.is_empty())
+                .map(PathBuf::from),
+        );
+        files
+    }
+
+    /// The system, user and current-directory configs that exist, lowest precedence first
+    pub fn base_config_files() -> Vec<PathBuf> {
+        let mut files = Vec::new();
+
+        // 1. System-wide config
+        if let Some(system_config) = Self::get_system_config_path() {
+            files.push(system_config);
+        }
+
+        // 2. User config directory
+        if let Some(user_config_dir) = Self::get_user_config_dir()
+            && let Some(config_file) = ["raid.yaml", "raid.yml", "raid.toml"]
+                .iter()
+                .map(|filename| user_config_dir.join(filename))
+                .find(|config_file| config_file.exists())
+        {
+            files.push(config_file);
+        }
+
+        // 3. Current directory config
+        if let Some(config_file) = ["raid.yaml", "raid.yml", "raid.toml", ".raid.yaml", ".raid.yml", ".raid.toml"]
+            .iter()
+            .map(PathBuf::from)
+            .find(|config_file| config_file.exists())
+        {
+            files.push(config_file);
+        }
+        files
+    }
+
     /// Get the system-wide configuration file path
     fn get_system_config_path() -> Option<PathBuf> {
         // Try common system config locations
@@ -197,32 +576,106 @@ impl RaidConfig {
         None
     }
 
-    /// Create a sample configuration file
+    /// Create a sample configuration file, in TOML when the path ends in `.toml` and YAML otherwise
     pub fn create_sample_config<P: AsRef<std::path::Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
         let sample_config = RaidConfig::default();
-        let yaml_content = serde_yaml::to_string(&sample_config)?;
-        
-        std::fs::write(path, yaml_content)?;
+        std::fs::write(path.as_ref(), sample_config.to_file_contents(path.as_ref())?)?;
         Ok(())
     }
 
+    /// The configuration serialized in the format the path's extension calls for
+    fn to_file_contents(&self, path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(match config_file_format(path) {
+            FileFormat::Toml => toml::to_string_pretty(self)?,
+            FileFormat::Json => serde_json::to_string_pretty(self)?,
+            _ => serde_yaml::to_string(self)?,
+        })
+    }
+
+    /// Files written for an older schema, with the version they declare: an explicit older
+    /// `config_version`, or none at all and a key that a migration step would change (so a
+    /// short override layer without a version isn't flagged). Unreadable files are left to
+    /// the loader to report.
+    pub fn outdated_config_files(files: &[PathBuf]) -> Vec<(PathBuf, u32)> {
+        files
+            .iter()
+            .filter_map(|file| {
+                let mut value = read_config_value(file).ok()?;
+                let version = declared_version(&value);
+                let declared = value.get("config_version").is_some();
+                let outdated = version < CONFIG_VERSION && (declared || apply_migrations(&mut value));
+                outdated.then(|| (file.clone(), version))
+            })
+            .collect()
+    }
+
+    /// Keys in the files that no setting reads, e.g. `unknown key 'temperatur' in [ai]`. Serde
+    /// ignores them while loading, so this is a separate pass. Entries of lists of tables
+    /// (`ai.fallback_providers`) aren't checked.
+    pub fn unknown_keys(files: &[PathBuf]) -> Vec<(PathBuf, String)> {
+        let schema = serde_json::to_value(RaidConfig::default()).unwrap_or_default();
+        let mut unknown = Vec::new();
+        for file in files {
+            let Ok(value) = read_config_value(file) else {
+                continue;
+            };
+            let mut found = Vec::new();
+            collect_unknown_keys(&value, &schema, "", &mut found);
+            unknown.extend(found.into_iter().map(|message| (file.clone(), message)));
+        }
+        unknown
+    }
+
+    /// Upgrade a config file to `CONFIG_VERSION`: run the migration steps, fill in the default
+    /// of every key it doesn't set and write it back in its own format, keeping the original as
+    /// `<file>.bak`. Unknown keys are dropped. Returns the version the file was at and the backup.
+    pub fn migrate_file(path: &Path) -> Result<(u32, PathBuf), Box<dyn std::error::Error>> {
+        let mut value = read_config_value(path)?;
+        let from_version = declared_version(&value);
+        if from_version > CONFIG_VERSION {
+            return Err(format!(
+                "'{}' has config_version {}, newer than this release's {}",
+                path.display(),
+                from_version,
+                CONFIG_VERSION
+            )
+            .into());
+        }
+        apply_migrations(&mut value);
+
+        let mut config: RaidConfig = Config::builder()
+            .add_source(Config::try_from(&RaidConfig::default())?)
+            .add_source(File::from_str(&serde_json::to_string(&value)?, FileFormat::Json))
+            .build()?
+            .try_deserialize()?;
+        config.config_version = CONFIG_VERSION;
+
+        let backup = PathBuf::from(format!("{}.bak", path.display()));
+        std::fs::copy(path, &backup)?;
+        std::fs::write(path, config.to_file_contents(path)?)?;
+        Ok((from_version, backup))
+    }
+
     /// Get the effective AI provider from config
     pub fn get_ai_provider(&self) -> AIProvider {
-        match self.ai.provider.to_lowercase().as_str() {
-            "openai" | "open-ai" => AIProvider::OpenAI,
-            "anthropic" => AIProvider::Anthropic,
-            "local" => AIProvider::Local,
-            _ => AIProvider::OpenAI, // Default fallback
-        }
+        parse_ai_provider(&self.ai.provider)
     }
 
-    /// Get the effective output format from config
+    /// Get the effective output format. `output.format` is resolved while loading and merging,
+    /// so the precedence is `--format`/`--jsonl` > `RAID_OUTPUT__FORMAT` > config file > text.
+    /// Unknown names are rejected by `validate`, so the text fallback only covers unvalidated configs.
     pub fn get_output_format(&self) -> OutputFormat {
-        match self.output.format.to_lowercase().as_str() {
-            "yaml" | "yml" => OutputFormat::Yaml,
-            "json" => OutputFormat::Json,
-            _ => OutputFormat::Text, // Default fallback
-        }
+        OutputFormat::from_name(&self.output.format).unwrap_or(OutputFormat::Text)
+    }
+
+    /// JSON output as one compact report per line (`output.format: jsonl` or `--jsonl`)
+    pub fn is_json_lines(&self) -> bool {
+        self.get_output_format() == OutputFormat::Jsonl
+    }
+
+    /// No API key is configured but the provider needs one
+    pub fn missing_api_key(&self) -> bool {
+        self.ai.api_key.is_none() && self.get_ai_provider().needs_api_key()
     }
 
     /// Get the model name with provider-specific defaults
@@ -235,10 +688,33 @@ impl RaidConfig {
                 AIProvider::OpenAI => "gpt-4o-mini".to_string(),
                 AIProvider::Anthropic => "claude-3-5-sonnet-20241022".to_string(),
                 AIProvider::Local => "llama2".to_string(),
+                AIProvider::LlamaCpp => String::new(),
             }
         }
     }
 
+    /// Check that the model belongs to the selected provider. Custom base URLs and local
+    /// models can serve any model name, so only the hosted OpenAI and Anthropic APIs are checked.
+    pub fn validate_model(&self) -> Result<(), String> {
+        if self.ai.base_url.is_some() {
+            return Ok(());
+        }
+
+        let model = self.get_model();
+        let is_claude = model.to_lowercase().starts_with("claude");
+        match self.get_ai_provider() {
+            AIProvider::OpenAI if is_claude => Err(format!(
+                "model '{}' is an Anthropic model; pass --ai-provider anthropic to use it",
+                model
+            )),
+            AIProvider::Anthropic if !is_claude => Err(format!(
+                "model '{}' is not an Anthropic model; pass --ai-provider open-ai or set --ai-base-url for a compatible endpoint",
+                model
+            )),
+            _ => Ok(()),
+        }
+    }
+
     /// Merge CLI overrides into the configuration
     pub fn merge_cli_overrides(&mut self, cli: &crate::cli::Cli) {
         // AI overrides
@@ -248,6 +724,8 @@ impl RaidConfig {
             self.ai.provider = "anthropic".to_string();
         } else if matches!(cli.ai_provider, AIProvider::Local) {
             self.ai.provider = "local".to_string();
+        } else if matches!(cli.ai_provider, AIProvider::LlamaCpp) {
+            self.ai.provider = "llama-cpp".to_string();
         }
 
         if cli.ai_api_key.is_some() {
@@ -270,26 +748,132 @@ impl RaidConfig {
             self.ai.temperature = cli.ai_temperature;
         }
 
-        // Output overrides
-        self.output.format = match cli.output_format {
-            OutputFormat::Text => "text".to_string(),
-            OutputFormat::Yaml => "yaml".to_string(),
-            OutputFormat::Json => "json".to_string(),
-        };
+        if cli.seed.is_some() {
+            self.ai.seed = cli.seed;
+        }
+
+        // Output overrides: only an explicit --format replaces the configured one
+        if let Some(format) = cli.output_format {
+            self.output.format = format.as_str().to_string();
+        }
+        if cli.jsonl {
+            self.output.format = OutputFormat::Jsonl.as_str().to_string();
+        }
+
+        if cli.quiet || cli.verbose > 0 {
+            self.output.verbosity = Verbosity::from_flags(cli.quiet, cli.verbose);
+        }
+        if cli.verbose > 0 {
+            self.output.verbose = true;
+        }
+
+        if cli.no_progress {
+            self.ui.progress_indicators = false;
+        }
+
+        if cli.no_commands {
+            self.output.show_commands = false;
+        }
+
+        if cli.explain_reasoning {
+            self.ai.explain_reasoning = true;
+        }
+
+        if cli.no_known_issues {
+            self.ai.use_known_issues = false;
+        }
+
+        if cli.unit.is_some() {
+            self.journal.unit = cli.unit.clone();
+        }
+
+        if cli.since_boot {
+            self.journal.since_boot = true;
+        }
 
-        self.output.verbose = cli.verbose;
+        if cli.context_file.is_some() {
+            self.context.file = cli.context_file.clone();
+        }
+
+        if !cli.unit_include.is_empty() {
+            self.systemd.include_patterns = cli.unit_include.clone();
+        }
+        if !cli.unit_exclude.is_empty() {
+            self.systemd.exclude_patterns = cli.unit_exclude.clone();
+        }
+
+        if cli.no_store {
+            self.database.enabled = false;
+        }
+
+        if cli.anonymize {
+            self.output.anonymize = true;
+        }
+
+        if cli.check_update {
+            self.updates.check = true;
+        }
+    }
+
+    /// What `collect_system_info_with` should collect, per the merged configuration
+    pub fn collect_options(&self) -> CollectOptions {
+        CollectOptions {
+            journal_unit: self.journal.unit.clone(),
+            since_boot: self.journal.since_boot,
+            paths: self.host_paths(),
+            unit_filter: UnitFilter {
+                include: self.systemd.include_patterns.clone(),
+                exclude: self.systemd.exclude_patterns.clone(),
+            },
+        }
+    }
+
+    /// procfs and sysfs roots for collectors and debug tools
+    pub fn host_paths(&self) -> HostPaths {
+        HostPaths {
+            procfs_root: PathBuf::from(&self.system.procfs_root),
+            sysfs_root: PathBuf::from(&self.system.sysfs_root),
+        }
+    }
+
+    /// Get the effective output verbosity
+    pub fn get_verbosity(&self) -> Verbosity {
+        if self.output.verbose && self.output.verbosity == Verbosity::Normal {
+            Verbosity::Full
+        } else {
+            self.output.verbosity
+        }
+    }
+
+    /// Copy of the configuration with secrets masked, for display
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        config.ai.api_key = config.ai.api_key.as_deref().map(mask_secret);
+        for fallback in &mut config.ai.fallback_providers {
+            fallback.api_key = fallback.api_key.as_deref().map(mask_secret);
+        }
+        config.signing.key = config.signing.key.as_deref().map(mask_secret);
+        config
     }
 
     /// Validate the configuration
     pub fn validate(&self) -> Result<(), String> {
         // Validate AI provider
-        if !["open-ai", "openai", "anthropic", "local"].contains(&self.ai.provider.as_str()) {
+        if !AI_PROVIDER_NAMES.contains(&self.ai.provider.as_str()) {
             return Err(format!("Invalid AI provider: {}", self.ai.provider));
         }
+        for fallback in &self.ai.fallback_providers {
+            if !AI_PROVIDER_NAMES.contains(&fallback.provider.as_str()) {
+                return Err(format!("Invalid fallback AI provider: {}", fallback.provider));
+            }
+        }
 
         // Validate output format
-        if !["text", "yaml", "yml", "json"].contains(&self.output.format.as_str()) {
-            return Err(format!("Invalid output format: {}", self.output.format));
+        if OutputFormat::from_name(&self.output.format).is_none() {
+            return Err(format!(
+                "Invalid output format: {} (expected text, yaml, json, jsonl or csv)",
+                self.output.format
+            ));
         }
 
         // Validate temperature range
@@ -306,15 +890,51 @@ impl RaidConfig {
             }
         }
 
+        if self.ai.analysis_timeout_secs == 0 {
+            return Err("analysis_timeout_secs must be greater than 0".to_string());
+        }
+        if self.tools.retries > MAX_TOOL_RETRIES {
+            return Err(format!("tools.retries must be at most {}", MAX_TOOL_RETRIES));
+        }
+
+        if self.ai.context_window == Some(0) {
+            return Err("context_window must be greater than 0".to_string());
+        }
+        if self.performance.max_parallelism == Some(0) {
+            return Err("performance.max_parallelism must be greater than 0".to_string());
+        }
+
         // Validate retention days
         if self.database.retention_days == 0 {
             return Err("retention_days must be greater than 0".to_string());
         }
 
+        // A mistyped host mount would silently fall back to empty data, so fail loudly
+        for (name, root) in [("procfs_root", &self.system.procfs_root), ("sysfs_root", &self.system.sysfs_root)] {
+            if !std::path::Path::new(root).is_dir() {
+                return Err(format!("system.{} '{}' is not a directory", name, root));
+            }
+        }
+
         Ok(())
     }
 }
 
+/// Mask a secret for display, keeping only its prefix and last four characters (`sk-...abcd`)
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+
+    let prefix = match secret.find('-') {
+        Some(idx) if idx > 0 && idx <= 4 => &secret[..=idx],
+        _ => "",
+    };
+    let last4: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", prefix, last4)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -338,6 +958,8 @@ mod tests {
         // Test invalid provider
         config.ai.provider = "invalid".to_string();
         assert!(config.validate().is_err());
+        config.ai.provider = "llama-cpp".to_string();
+        assert!(config.validate().is_ok());
         
         // Reset and test invalid temperature
         config.ai.provider = "open-ai".to_string();
@@ -353,6 +975,45 @@ mod tests {
         config.ai.max_tokens = Some(1000);
         config.database.retention_days = 0;
         assert!(config.validate().is_err());
+
+        config.database.retention_days = 30;
+        config.performance.max_parallelism = Some(0);
+        assert!(config.validate().unwrap_err().contains("max_parallelism"));
+    }
+
+    #[test]
+    fn test_fallback_providers_from_yaml() {
+        let yaml = "ai:\n  provider: anthropic\n  api_key: null\n  model: null\n  base_url: null\n  max_tokens: 1000\n  temperature: 0.7\n  fallback_providers:\n    - provider: open-ai\n      api_key: sk-proj-abcdefghijkl1234\n    - provider: local\n      base_url: http://localhost:11434\n";
+        let ai: AIConfig = serde_yaml::from_value(serde_yaml::from_str::<serde_yaml::Value>(yaml).unwrap()["ai"].clone()).unwrap();
+        assert_eq!(ai.fallback_providers.len(), 2);
+        assert!(matches!(ai.fallback_providers[1].get_ai_provider(), AIProvider::Local));
+
+        let mut config = RaidConfig { ai, ..RaidConfig::default() };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.redacted().ai.fallback_providers[0].api_key.as_deref(), Some("sk-...1234"));
+
+        config.ai.fallback_providers[0].provider = "gemini".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_mask_secret() {
+        assert_eq!(mask_secret("sk-proj-abcdefghijkl1234"), "sk-...1234");
+        assert_eq!(mask_secret("sk-ant-api03-zzzzzzzz9876"), "sk-...9876");
+        assert_eq!(mask_secret("plainsecretvalue"), "...alue");
+        assert_eq!(mask_secret("short"), "****");
+    }
+
+    #[test]
+    fn test_redacted_config_hides_api_key() {
+        let mut config = RaidConfig::default();
+        config.ai.api_key = Some("sk-proj-abcdefghijkl1234".to_string());
+
+        let yaml = serde_yaml::to_string(&config.redacted()).unwrap();
+        assert!(!yaml.contains("abcdefghijkl1234"));
+        assert!(yaml.contains("sk-...1234"));
+        // The original is untouched
+        assert_eq!(config.ai.api_key.as_deref(), Some("sk-proj-abcdefghijkl1234"));
     }
 
     #[test]
@@ -386,6 +1047,11 @@ mod tests {
         
         config.ai.provider = "local".to_string();
         assert!(matches!(config.get_ai_provider(), AIProvider::Local));
+
+        config.ai.provider = "llama-cpp".to_string();
+        assert!(matches!(config.get_ai_provider(), AIProvider::LlamaCpp));
+        // In-process models need no key
+        assert!(!config.missing_api_key());
         
         // Test fallback for invalid provider
         config.ai.provider = "invalid".to_string();
@@ -413,6 +1079,44 @@ mod tests {
         assert!(matches!(config.get_output_format(), OutputFormat::Text));
     }
 
+    #[test]
+    fn test_validate_model_against_provider() {
+        let mut config = RaidConfig::default();
+        config.ai.model = Some("gpt-4o".to_string());
+        assert!(config.validate_model().is_ok());
+
+        config.ai.provider = "anthropic".to_string();
+        assert!(config.validate_model().unwrap_err().contains("gpt-4o"));
+        config.ai.model = Some("claude-3-5-haiku-20241022".to_string());
+        assert!(config.validate_model().is_ok());
+
+        config.ai.provider = "open-ai".to_string();
+        assert!(config.validate_model().is_err());
+
+        // Compatible endpoints and local models may serve any model name
+        config.ai.base_url = Some("https://llm.internal/v1".to_string());
+        assert!(config.validate_model().is_ok());
+        config.ai.base_url = None;
+        config.ai.provider = "local".to_string();
+        config.ai.model = Some("qwen2.5:7b".to_string());
+        assert!(config.validate_model().is_ok());
+    }
+
+    #[test]
+    fn test_host_paths_from_system_config() {
+        let mut config = RaidConfig::default();
+        assert_eq!(config.host_paths().proc("pressure/cpu"), std::path::Path::new("/proc/pressure/cpu"));
+
+        let dir = tempfile::tempdir().unwrap();
+        config.system.procfs_root = dir.path().display().to_string();
+        config.system.sysfs_root = dir.path().display().to_string();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.collect_options().paths.sys("fs/cgroup"), dir.path().join("fs/cgroup"));
+
+        config.system.procfs_root = "/host/does-not-exist".to_string();
+        assert!(config.validate().unwrap_err().contains("system.procfs_root"));
+    }
+
     #[test]
     fn test_get_model_with_defaults() {
         let mut config = RaidConfig::default();
@@ -487,14 +1191,42 @@ database:
             ai_base_url: Some("https://custom.api".to_string()),
             ai_max_tokens: Some(1500),
             ai_temperature: Some(0.8),
+            seed: Some(42),
             ai_max_tool_calls: 75,
             ai_agent_mode: true,
             dry_run: false,
-            verbose: true,
-            output_format: OutputFormat::Yaml,
+            no_ai: false,
+            prompt_only: false,
+            redact_preview: false,
+            yes: false,
+            questions_file: None,
+            explain_issue: None,
+            apply_fixes: None,
+            allow_destructive: false,
+            fix_log: "raid-fixes.log".to_string(),
+            verbose: 1,
+            quiet: false,
+            quiet_json: false,
+            output_format: Some(OutputFormat::Yaml),
+            jsonl: false,
             config: None,
+            unit: Some("nginx.service".to_string()),
+            since_boot: true,
+            list_checks: false,
+            context_file: Some(PathBuf::from("/etc/raid/facts.txt")),
+            unit_include: vec!["nginx*".to_string()],
+            unit_exclude: vec!["*.scope".to_string()],
             no_color: false,
             no_progress: false,
+            force_progress: false,
+            no_store: true,
+            continue_from: None,
+            anonymize: true,
+            no_commands: true,
+            explain_reasoning: true,
+            no_known_issues: true,
+            timeout: None,
+            check_update: true,
             command: None,
         };
         
@@ -506,8 +1238,172 @@ database:
         assert_eq!(config.ai.base_url, Some("https://custom.api".to_string()));
         assert_eq!(config.ai.max_tokens, Some(1500));
         assert_eq!(config.ai.temperature, Some(0.8));
+        assert_eq!(config.ai.seed, Some(42));
         assert_eq!(config.output.format, "yaml");
         assert!(config.output.verbose);
+        assert_eq!(config.get_verbosity(), Verbosity::Detailed);
+        assert_eq!(config.collect_options().journal_unit.as_deref(), Some("nginx.service"));
+        assert!(config.collect_options().since_boot);
+        assert_eq!(config.context.file, Some(PathBuf::from("/etc/raid/facts.txt")));
+        assert_eq!(config.systemd.include_patterns, vec!["nginx*".to_string()]);
+        assert!(!config.collect_options().unit_filter.matches("session-3.scope"));
+        assert!(!config.database.enabled);
+        assert!(config.output.anonymize);
+        assert!(!config.output.show_commands);
+        assert!(config.ai.explain_reasoning);
+        assert!(!config.ai.use_known_issues);
+        assert!(config.updates.check);
+    }
+
+    #[test]
+    fn test_json_lines_format() {
+        let mut config = RaidConfig::default();
+        assert!(!config.is_json_lines());
+
+        config.output.format = "jsonl".to_string();
+        assert!(config.validate().is_ok());
+        assert!(config.is_json_lines());
+        assert!(matches!(config.get_output_format(), OutputFormat::Jsonl));
+    }
+
+    #[test]
+    fn test_output_format_precedence() {
+        use crate::cli::Cli;
+        use clap::Parser;
+
+        // Without --format the configured (or RAID_OUTPUT__FORMAT) value is kept
+        let mut config = RaidConfig::default();
+        config.output.format = "YAML".to_string();
+        let cli = Cli::try_parse_from(["raid"]).unwrap();
+        config.merge_cli_overrides(&cli);
+        assert!(config.validate().is_ok());
+        assert_eq!(config.get_output_format(), OutputFormat::Yaml);
+
+        let cli = Cli::try_parse_from(["raid", "--format", "json"]).unwrap();
+        config.merge_cli_overrides(&cli);
+        assert_eq!(config.get_output_format(), OutputFormat::Json);
+
+        config.output.format = "xml".to_string();
+        assert!(config.validate().unwrap_err().contains("xml"));
+    }
+
+    #[test]
+    fn test_verbosity_config() {
+        let mut config = RaidConfig::default();
+        assert_eq!(config.get_verbosity(), Verbosity::Normal);
+
+        // Legacy boolean maps to full output
+        config.output.verbose = true;
+        assert_eq!(config.get_verbosity(), Verbosity::Full);
+
+        // An explicit level wins over the legacy flag
+        config.output.verbosity = Verbosity::Detailed;
+        assert_eq!(config.get_verbosity(), Verbosity::Detailed);
+
+        let temp_file = NamedTempFile::with_suffix(".yaml").unwrap();
+        fs::write(temp_file.path(), "output:\n  verbosity: quiet\n").unwrap();
+        let config = RaidConfig::load_from_file(temp_file.path()).unwrap();
+        assert_eq!(config.get_verbosity(), Verbosity::Quiet);
+    }
+
+    #[test]
+    fn test_layered_config_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("raid.yaml");
+        let local = dir.path().join("raid.local.yaml");
+        let layer = dir.path().join("ci.yaml");
+        fs::write(&base, "ai:\n  provider: anthropic\n  max_tokens: 2000\noutput:\n  format: json\n").unwrap();
+        fs::write(&local, "ai:\n  max_tokens: 500\n").unwrap();
+        fs::write(&layer, "output:\n  format: yaml\n").unwrap();
+
+        let layers = layer.display().to_string();
+        let files = RaidConfig::config_files_with(Some(&base), Some(&layers));
+        assert_eq!(files, vec![base.clone(), local.clone(), layer.clone()]);
+
+        // Each layer overrides only the keys it sets
+        let config = RaidConfig::load_files(&files).unwrap();
+        assert_eq!(config.ai.provider, "anthropic");
+        assert_eq!(config.ai.max_tokens, Some(500));
+        assert_eq!(config.output.format, "yaml");
+
+        // Loading the local file directly doesn't merge it twice
+        assert_eq!(RaidConfig::config_files_with(Some(&local), None), vec![local]);
+
+        // A layer that doesn't exist is an error rather than silently skipped
+        let files = RaidConfig::config_files_with(Some(&base), Some("/nonexistent/layer.yaml"));
+        assert!(RaidConfig::load_files(&files).is_err());
+    }
+
+    #[test]
+    fn test_sample_config_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let expected = serde_yaml::to_string(&RaidConfig::default()).unwrap();
+        for name in ["raid.toml", "raid.yaml", "raid-config"] {
+            let path = dir.path().join(name);
+            RaidConfig::create_sample_config(&path).unwrap();
+            let content = fs::read_to_string(&path).unwrap();
+            assert_eq!(content.contains("[ai]"), name.ends_with(".toml"), "{}", name);
+
+            let loaded = RaidConfig::load_files(&[path]).unwrap();
+            assert_eq!(serde_yaml::to_string(&loaded).unwrap(), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_migrate_old_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("raid.yaml");
+        let original = "ai:\n  temperature: 0.2\noutput:\n  verbose: true\n";
+        fs::write(&old, original).unwrap();
+        // A short override layer with no version and nothing to migrate isn't flagged
+        let layer = dir.path().join("raid.local.toml");
+        fs::write(&layer, "[ai]\nmodel = \"gpt-4o\"\n").unwrap();
+
+        let files = vec![old.clone(), layer.clone()];
+        assert_eq!(RaidConfig::outdated_config_files(&files), vec![(old.clone(), 0)]);
+
+        let (from_version, backup) = RaidConfig::migrate_file(&old).unwrap();
+        assert_eq!(from_version, 0);
+        assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+        assert!(RaidConfig::outdated_config_files(&files).is_empty());
+
+        let migrated = RaidConfig::load_files(&[old]).unwrap();
+        assert_eq!(migrated.config_version, CONFIG_VERSION);
+        assert_eq!(migrated.ai.temperature, Some(0.2));
+        assert!(!migrated.output.verbose);
+        assert_eq!(migrated.get_verbosity(), Verbosity::Full);
+        assert_eq!(migrated.database.retention_days, 30);
+
+        // A file written by a newer release is left alone
+        let newer = dir.path().join("newer.yaml");
+        fs::write(&newer, format!("config_version: {}\n", CONFIG_VERSION + 1)).unwrap();
+        assert!(RaidConfig::migrate_file(&newer).is_err());
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("raid.yaml");
+        fs::write(&yaml, "ai:\n  temperatur: 0.5\n  model: gpt-4o\nthresholds: {}\nsystem:\n  procfs_root: /proc\ncontext:\n  extra_sections:\n    Cluster name: prod-a\n").unwrap();
+        let toml = dir.path().join("raid.local.toml");
+        fs::write(&toml, "[output]\nformat = \"json\"\n[output.colour]\nenabled = true\n").unwrap();
+
+        let unknown = RaidConfig::unknown_keys(&[yaml.clone(), toml.clone()]);
+        assert_eq!(
+            unknown,
+            vec![
+                (yaml.clone(), "unknown key 'temperatur' in [ai]".to_string()),
+                (yaml.clone(), "unknown key 'thresholds'".to_string()),
+                (toml, "unknown key 'colour' in [output]".to_string()),
+            ]
+        );
+
+        let mut file = fs::read_to_string(&yaml).unwrap();
+        file.push_str("unknown_keys: error\n");
+        fs::write(&yaml, file).unwrap();
+        let config = RaidConfig::load_files(&[yaml]).unwrap();
+        assert_eq!(config.unknown_keys, UnknownKeyPolicy::Error);
+        assert_eq!(config.context.extra_sections.get("cluster name").map(String::as_str), Some("prod-a"));
     }
 
     #[test]
@@ -547,6 +1443,14 @@ database:
         
         config.ai.max_tokens = Some(0);
         assert!(config.validate().is_err());
+
+        // Tool retries are off by default and capped
+        let mut config = RaidConfig::default();
+        assert_eq!(config.tools.retries, 0);
+        config.tools.retries = MAX_TOOL_RETRIES;
+        assert!(config.validate().is_ok());
+        config.tools.retries = MAX_TOOL_RETRIES + 1;
+        assert!(config.validate().is_err());
     }
 
     #[test]
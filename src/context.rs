@@ -0,0 +1,117 @@
+use crate::config::ContextConfig;
+use std::fmt::Display;
+
+/// Assembles the system context string the AI receives. Every AI-calling path builds its
+/// context through this, so configured sections (`context.extra_sections`, `--context-file`)
+/// show up the same way everywhere.
+#[derive(Debug, Default, Clone)]
+pub struct ContextBuilder {
+    context: String,
+}
+
+impl ContextBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from text that is already formatted, such as `SystemInfo::context_summary`
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self { context: text.into() }
+    }
+
+    /// Append text as is
+    pub fn push_str(&mut self, text: &str) -> &mut Self {
+        self.context.push_str(text);
+        self
+    }
+
+    /// Append one `Label: value` style line
+    pub fn push_line(&mut self, line: impl Display) -> &mut Self {
+        self.context.push_str(&format!("{}\n", line));
+        self
+    }
+
+    /// A titled block: a blank line, `title:`, then the content. Blank content adds nothing.
+    pub fn add_section(&mut self, title: &str, content: &str) -> &mut Self {
+        if content.trim().is_empty() {
+            return self;
+        }
+        self.context.push_str(&format!("\n{}:\n{}", title, content));
+        if !content.ends_with('\n') {
+            self.context.push('\n');
+        }
+        self
+    }
+
+    /// A titled block with one `- item` line per item; no items adds nothing
+    pub fn add_list_section<T: Display>(&mut self, title: &str, items: impl IntoIterator<Item = T>) -> &mut Self {
+        let content: String = items.into_iter().map(|item| format!("- {}\n", item)).collect();
+        self.add_section(title, &content)
+    }
+
+    /// The operator's own facts: each `context.extra_sections` entry in key order, then the
+    /// `--context-file` contents. An unreadable file is reported and skipped, since the
+    /// collected context is still worth sending.
+    pub fn add_configured_sections(&mut self, config: &ContextConfig) -> &mut Self {
+        for (title, content) in &config.extra_sections {
+            self.add_section(title, content);
+        }
+        if let Some(path) = &config.file {
+            match std::fs::read_to_string(path) {
+                Ok(content) => {
+                    self.add_section("Additional context", &content);
+                }
+                Err(e) => eprintln!("⚠️  Could not read context file {}: {}", path.display(), e),
+            }
+        }
+        self
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.context
+    }
+
+    pub fn build(self) -> String {
+        self.context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_builder_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("facts.txt");
+        std::fs::write(&file, "Runbook: https://runbooks.example.com/raid").unwrap();
+        let config = ContextConfig {
+            extra_sections: [
+                ("Region".to_string(), "eu-west-1".to_string()),
+                ("Cluster".to_string(), "prod-a\n".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            file: Some(file),
+        };
+
+        let mut builder = ContextBuilder::from_text("Operating System: Linux\n");
+        builder
+            .push_line("Container Runtime: Available")
+            .add_list_section("Nearly full filesystems", ["/var is 96% full"])
+            .add_list_section("Empty", Vec::<String>::new())
+            .add_section("Blank", "  \n")
+            .add_configured_sections(&config);
+        assert_eq!(
+            builder.build(),
+            "Operating System: Linux\nContainer Runtime: Available\n\
+             \nNearly full filesystems:\n- /var is 96% full\n\
+             \nCluster:\nprod-a\n\
+             \nRegion:\neu-west-1\n\
+             \nAdditional context:\nRunbook: https://runbooks.example.com/raid\n"
+        );
+
+        let missing = ContextConfig { file: Some(dir.path().join("missing.txt")), ..Default::default() };
+        assert_eq!(ContextBuilder::new().add_configured_sections(&missing).as_str(), "");
+    }
+}
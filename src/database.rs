@@ -1,41 +1,119 @@
+use crate::config::DatabaseConfig;
 use crate::sysinfo::SystemInfo;
-use rusqlite::{Connection, Result};
-use std::path::Path;
+use rusqlite::{Connection, ErrorCode};
+use std::path::{Path, PathBuf};
+
+/// `database.path` value that keeps checks in memory for the current run only
+pub const IN_MEMORY_PATH: &str = ":memory:";
+
+/// How long a write waits for another RAID process holding the database lock before failing
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum DatabaseError {
+    #[error(
+        "cannot write check database at {}: {reason}. Set `database.path` in raid.yaml to a writable location, or pass --no-store",
+        path.display()
+    )]
+    Unwritable { path: PathBuf, reason: String },
+    #[error("Database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+pub type Result<T> = std::result::Result<T, DatabaseError>;
+
+/// Schema migrations, applied in order. Entry `i` upgrades the schema to version `i + 1`;
+/// append new migrations here and never edit one that has shipped.
+const MIGRATIONS: &[&str] = &[
+    // 1: stored comprehensive checks
+    "CREATE TABLE IF NOT EXISTS system_checks (
+        id INTEGER PRIMARY KEY,
+        timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+        system_info_json TEXT NOT NULL,
+        analysis TEXT NOT NULL
+    )",
+];
 
 pub struct Database {
-    conn: Connection,
+    /// `None` when storage is disabled; every operation is then a no-op
+    conn: Option<Connection>,
 }
 
 impl Database {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        let db = Database { conn };
-        db.init_tables()?;
+        let path = path.as_ref();
+        let conn = if path == Path::new(IN_MEMORY_PATH) {
+            Connection::open_in_memory()?
+        } else {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).map_err(|e| DatabaseError::Unwritable {
+                    path: path.to_path_buf(),
+                    reason: format!("could not create {}: {}", parent.display(), e),
+                })?;
+            }
+            let conn = Connection::open(path).map_err(|e| unwritable_or_sqlite(path, e))?;
+            // Concurrent runs sharing the file wait their turn instead of failing with "database
+            // is locked", and WAL lets history reads proceed while another run writes
+            conn.busy_timeout(BUSY_TIMEOUT)?;
+            conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+                .map_err(|e| unwritable_or_sqlite(path, e))?;
+            conn
+        };
+        let db = Database { conn: Some(conn) };
+        db.migrate().map_err(|e| match e {
+            DatabaseError::Sqlite(e) => unwritable_or_sqlite(path, e),
+            other => other,
+        })?;
         Ok(db)
     }
 
-    fn init_tables(&self) -> Result<()> {
-        // Drop the old table if it exists
-        self.conn
-            .execute("DROP TABLE IF EXISTS system_checks", [])?;
+    /// Open the store described by the `database` config section
+    pub fn from_config(config: &DatabaseConfig) -> Result<Self> {
+        if config.enabled {
+            Self::new(&config.path)
+        } else {
+            Ok(Self::disabled())
+        }
+    }
+
+    /// A store that silently discards checks (`database.enabled: false`)
+    pub fn disabled() -> Self {
+        Database { conn: None }
+    }
 
-        self.conn.execute(
-            "CREATE TABLE system_checks (
-                id INTEGER PRIMARY KEY,
-                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
-                system_info_json TEXT NOT NULL,
-                analysis TEXT NOT NULL
-            )",
-            [],
-        )?;
+    pub fn is_enabled(&self) -> bool {
+        self.conn.is_some()
+    }
+
+    /// Bring the schema up to date, applying each pending migration in its own transaction
+    pub fn migrate(&self) -> Result<()> {
+        let Some(conn) = &self.conn else { return Ok(()) };
+        conn.execute("CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)", [])?;
+
+        let current = self.schema_version()?;
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.execute("DELETE FROM schema_version", [])?;
+            tx.execute("INSERT INTO schema_version (version) VALUES (?1)", [index as u32 + 1])?;
+            tx.commit()?;
+        }
         Ok(())
     }
 
+    /// Version of the schema currently applied (0 for a fresh or disabled store)
+    pub fn schema_version(&self) -> Result<u32> {
+        let Some(conn) = &self.conn else { return Ok(0) };
+        let version = conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))?;
+        Ok(version)
+    }
+
     pub fn store_check(&self, system_info: &SystemInfo, analysis: &str) -> Result<()> {
+        let Some(conn) = &self.conn else { return Ok(()) };
         let system_info_json = serde_json::to_string(system_info)
             .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
 
-        self.conn.execute(
+        conn.execute(
             "INSERT INTO system_checks (system_info_json, analysis) VALUES (?1, ?2)",
             [&system_info_json, analysis],
         )?;
@@ -43,7 +121,8 @@ impl Database {
     }
 
     pub fn get_recent_checks(&self, limit: i64) -> Result<Vec<(i64, String, SystemInfo, String)>> {
-        let mut stmt = self.conn.prepare(
+        let Some(conn) = &self.conn else { return Ok(Vec::new()) };
+        let mut stmt = conn.prepare(
             "SELECT id, timestamp, system_info_json, analysis FROM system_checks ORDER BY timestamp DESC LIMIT ?"
         )?;
         let rows = stmt.query_map([limit], |row| {
@@ -61,3 +140,111 @@ impl Database {
         Ok(results)
     }
 }
+
+/// SQLite reports unopenable or read-only files as generic failures; surface those as path problems
+fn unwritable_or_sqlite(path: &Path, error: rusqlite::Error) -> DatabaseError {
+    match error.sqlite_error_code() {
+        Some(ErrorCode::CannotOpen | ErrorCode::PermissionDenied | ErrorCode::ReadOnly) => {
+            DatabaseError::Unwritable {
+                path: path.to_path_buf(),
+                reason: error.to_string(),
+            }
+        }
+        _ => DatabaseError::Sqlite(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_in_memory_store() {
+        let db = Database::new(IN_MEMORY_PATH).unwrap();
+        db.store_check(&SystemInfo::default(), "all good").unwrap();
+
+        let checks = db.get_recent_checks(10).unwrap();
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].3, "all good");
+    }
+
+    #[test]
+    fn test_migrations_apply_once_and_keep_data() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("checks.db");
+
+        let db = Database::new(&path).unwrap();
+        assert_eq!(db.schema_version().unwrap(), MIGRATIONS.len() as u32);
+        db.store_check(&SystemInfo::default(), "first run").unwrap();
+        drop(db);
+
+        let db = Database::new(&path).unwrap();
+        db.migrate().unwrap();
+        assert_eq!(db.schema_version().unwrap(), MIGRATIONS.len() as u32);
+        assert_eq!(db.get_recent_checks(10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_store_is_a_no_op() {
+        let config = DatabaseConfig {
+            enabled: false,
+            ..crate::config::RaidConfig::default().database
+        };
+        let db = Database::from_config(&config).unwrap();
+        assert!(!db.is_enabled());
+
+        db.store_check(&SystemInfo::default(), "discarded").unwrap();
+        assert!(db.get_recent_checks(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_creates_missing_parent_directory() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("raid").join("checks.db");
+
+        let db = Database::new(&path).unwrap();
+        db.store_check(&SystemInfo::default(), "stored").unwrap();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_concurrent_store_check() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("checks.db");
+
+        let writers: Vec<_> = (0..2)
+            .map(|writer| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let db = Database::new(&path)?;
+                    for run in 0..20 {
+                        db.store_check(&SystemInfo::default(), &format!("writer {} run {}", writer, run))?;
+                    }
+                    Ok::<_, DatabaseError>(())
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+
+        let db = Database::new(&path).unwrap();
+        assert_eq!(db.get_recent_checks(100).unwrap().len(), 40);
+    }
+
+    #[test]
+    fn test_unwritable_path_is_a_friendly_error() {
+        // A regular file in place of the parent directory can't be written to, even as root
+        let dir = tempdir().unwrap();
+        let blocker = dir.path().join("not-a-dir");
+        std::fs::write(&blocker, b"").unwrap();
+        let path = blocker.join("checks.db");
+
+        let err = Database::new(&path).err().expect("opening should fail");
+        assert!(matches!(err, DatabaseError::Unwritable { .. }));
+        let message = err.to_string();
+        assert!(message.contains(&path.display().to_string()));
+        assert!(message.contains("database.path"));
+    }
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{OnceCell, RwLock};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KnownIssue {
@@ -53,19 +54,132 @@ pub struct IssueMatch {
     pub matched_keywords: Vec<String>,
 }
 
+/// How many known issues are injected into an AI prompt by default
+pub const DEFAULT_MAX_RELEVANT_ISSUES: usize = 5;
+
+/// Counts reported after importing a known-issues file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
 pub struct KnownIssuesDatabase {
     issues: Arc<RwLock<HashMap<String, KnownIssue>>>,
 }
 
+/// The process-wide database handed out by `KnownIssuesDatabase::shared`
+static SHARED_DATABASE: OnceCell<Arc<KnownIssuesDatabase>> = OnceCell::const_new();
+
 impl KnownIssuesDatabase {
     pub async fn new() -> Self {
+        Self::load(Self::persisted_issues_path().as_deref()).await
+    }
+
+    /// The default issues plus the ones persisted at `path` instead of the user's config dir
+    pub async fn with_issues_file(path: &Path) -> Self {
+        Self::load(Some(path)).await
+    }
+
+    async fn load(persisted: Option<&Path>) -> Self {
         let db = Self {
             issues: Arc::new(RwLock::new(HashMap::new())),
         };
         db.initialize_default_issues().await;
+        if let Some(path) = persisted
+            && path.exists()
+        {
+            match read_issues_file(path) {
+                Ok(issues) => {
+                    for issue in issues {
+                        db.add_issue(issue).await;
+                    }
+                }
+                Err(e) => eprintln!("⚠️  Ignoring known issues file {}: {}", path.display(), e),
+            }
+        }
         db
     }
 
+    /// One database per process, loaded on first use. AI clients, the agent context and the
+    /// issues command all go through this, so the defaults and the persisted file are read once.
+    pub async fn shared() -> Arc<KnownIssuesDatabase> {
+        SHARED_DATABASE
+            .get_or_init(|| async { Arc::new(Self::new().await) })
+            .await
+            .clone()
+    }
+
+    /// Where imported issues are persisted (~/.config/raid/known_issues.yaml)
+    pub fn persisted_issues_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("raid").join("known_issues.yaml"))
+    }
+
+    /// Merge issues into the database. Existing ids are skipped unless `overwrite` is set.
+    pub async fn import_issues(&self, incoming: Vec<KnownIssue>, overwrite: bool) -> ImportSummary {
+        self.merge_issues(incoming, overwrite).await.0
+    }
+
+    /// Import a YAML/JSON file and persist the accepted issues to `store` so later runs see them
+    pub async fn import_file(
+        &self,
+        path: &Path,
+        overwrite: bool,
+        store: &Path,
+    ) -> Result<ImportSummary, Box<dyn std::error::Error>> {
+        let incoming = read_issues_file(path)?;
+        let mut stored = if store.exists() {
+            read_issues_file(store)?
+        } else {
+            Vec::new()
+        };
+
+        let (summary, accepted) = self.merge_issues(incoming, overwrite).await;
+        for issue in accepted {
+            match stored.iter_mut().find(|existing| existing.id == issue.id) {
+                Some(existing) => *existing = issue,
+                None => stored.push(issue),
+            }
+        }
+
+        if let Some(parent) = store.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_issues_file(store, &stored)?;
+        Ok(summary)
+    }
+
+    /// Write every issue, sorted by id, to a YAML/JSON file. Returns the number written.
+    pub async fn export_file(&self, path: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut issues = self.get_all_issues().await;
+        issues.sort_by(|a, b| a.id.cmp(&b.id));
+        write_issues_file(path, &issues)?;
+        Ok(issues.len())
+    }
+
+    async fn merge_issues(&self, incoming: Vec<KnownIssue>, overwrite: bool) -> (ImportSummary, Vec<KnownIssue>) {
+        let mut issues = self.issues.write().await;
+        let mut summary = ImportSummary::default();
+        let mut accepted = Vec::new();
+
+        for issue in incoming {
+            if issues.contains_key(&issue.id) {
+                if !overwrite {
+                    summary.skipped += 1;
+                    continue;
+                }
+                summary.updated += 1;
+            } else {
+                summary.added += 1;
+            }
+            issues.insert(issue.id.clone(), issue.clone());
+            accepted.push(issue);
+        }
+
+        (summary, accepted)
+    }
+
     pub async fn add_issue(&self, issue: KnownIssue) {
         let mut issues = self.issues.write().await;
         issues.insert(issue.id.clone(), issue);
@@ -164,11 +278,12 @@ impl KnownIssuesDatabase {
             }
         }
 
-        // Sort by confidence (highest first)
+        // Sort by confidence (highest first), then by id so ties are stable
         matches.sort_by(|a, b| {
             b.confidence
                 .partial_cmp(&a.confidence)
                 .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.issue.id.cmp(&b.issue.id))
         });
         matches
     }
@@ -623,11 +738,239 @@ impl KnownIssuesDatabase {
         context: &str,
         category: Option<IssueCategory>,
     ) -> Vec<KnownIssue> {
-        let matches = self.match_issues(context, category).await;
-        matches
+        self.get_scored_issues_for_context(context, category, DEFAULT_MAX_RELEVANT_ISSUES)
+            .await
+            .into_iter()
+            .map(|(issue, _)| issue)
+            .collect()
+    }
+
+    /// Relevant issues with their relevance score, strongest first, capped at `limit`.
+    /// The score grows with each matched pattern (0.4), symptom (0.3), keyword (0.2) and tag (0.1).
+    pub async fn get_scored_issues_for_context(
+        &self,
+        context: &str,
+        category: Option<IssueCategory>,
+        limit: usize,
+    ) -> Vec<(KnownIssue, f32)> {
+        self.match_issues(context, category)
+            .await
             .into_iter()
             .filter(|m| m.confidence > 0.3) // Only include good matches
-            .map(|m| m.issue)
+            .take(limit)
+            .map(|m| (m.issue, m.confidence))
             .collect()
     }
 }
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("json"))
+        .unwrap_or(false)
+}
+
+/// Read and validate a list of known issues from a YAML or JSON file (by extension)
+pub fn read_issues_file(path: &Path) -> Result<Vec<KnownIssue>, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let issues: Vec<KnownIssue> = if is_json(path) {
+        serde_json::from_str(&content)?
+    } else {
+        serde_yaml::from_str(&content)?
+    };
+    validate_issues(&issues)?;
+    Ok(issues)
+}
+
+/// Write a list of known issues as YAML or JSON (by extension)
+pub fn write_issues_file(path: &Path, issues: &[KnownIssue]) -> Result<(), Box<dyn std::error::Error>> {
+    let content = if is_json(path) {
+        serde_json::to_string_pretty(issues)?
+    } else {
+        serde_yaml::to_string(issues)?
+    };
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// The catalog as CSV for spreadsheets, one row per issue; tags are joined with `; `
+pub fn write_issues_csv<W: std::io::Write>(writer: W, issues: &[KnownIssue]) -> std::io::Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(["id", "title", "category", "severity", "description", "tags"])?;
+    for issue in issues {
+        csv.write_record([
+            issue.id.clone(),
+            issue.title.clone(),
+            format!("{:?}", issue.category),
+            format!("{:?}", issue.severity),
+            issue.description.clone(),
+            issue.tags.join("; "),
+        ])?;
+    }
+    csv.flush()
+}
+
+fn validate_issues(issues: &[KnownIssue]) -> Result<(), String> {
+    let mut seen = std::collections::HashSet::new();
+    for (index, issue) in issues.iter().enumerate() {
+        if issue.id.trim().is_empty() {
+            return Err(format!("issue #{} has an empty id", index + 1));
+        }
+        if issue.title.trim().is_empty() {
+            return Err(format!("issue '{}' has an empty title", issue.id));
+        }
+        if !seen.insert(issue.id.as_str()) {
+            return Err(format!("duplicate issue id '{}'", issue.id));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sample_issue(id: &str, title: &str) -> KnownIssue {
+        KnownIssue {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: "Team-curated issue".to_string(),
+            category: IssueCategory::Network,
+            severity: IssueSeverity::Medium,
+            patterns: vec!["connection reset".to_string()],
+            keywords: vec!["proxy".to_string()],
+            symptoms: vec![],
+            verification_commands: vec![],
+            fix_commands: vec![],
+            prerequisites: vec![],
+            distribution_specific: None,
+            tags: vec!["team".to_string()],
+            next_steps: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shared_database_is_built_once() {
+        let first = KnownIssuesDatabase::shared().await;
+        let second = KnownIssuesDatabase::shared().await;
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(first.get_issue("systemd-failed-units").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_with_issues_file_loads_persisted_issues() {
+        let dir = tempdir().unwrap();
+        let store = dir.path().join("known_issues.yaml");
+        write_issues_file(&store, &[sample_issue("team-proxy-reset", "Proxy resets")]).unwrap();
+
+        let db = KnownIssuesDatabase::with_issues_file(&store).await;
+        assert!(db.get_issue("team-proxy-reset").await.is_some());
+        assert!(db.get_issue("systemd-failed-units").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let dir = tempdir().unwrap();
+        let db = KnownIssuesDatabase::with_issues_file(&dir.path().join("known_issues.yaml")).await;
+        let total = db.get_all_issues().await.len();
+
+        for name in ["issues.yaml", "issues.json"] {
+            let path = dir.path().join(name);
+            assert_eq!(db.export_file(&path).await.unwrap(), total);
+
+            let issues = read_issues_file(&path).unwrap();
+            assert_eq!(issues.len(), total);
+            assert!(issues.windows(2).all(|w| w[0].id <= w[1].id));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_counts_and_overwrite() {
+        let dir = tempdir().unwrap();
+        let db = KnownIssuesDatabase::with_issues_file(&dir.path().join("known_issues.yaml")).await;
+        let existing_id = db.get_all_issues().await[0].id.clone();
+
+        let file = dir.path().join("team.yaml");
+        let store = dir.path().join("store").join("known_issues.yaml");
+        write_issues_file(
+            &file,
+            &[sample_issue("team-proxy-reset", "Proxy resets"), sample_issue(&existing_id, "Replaced")],
+        )
+        .unwrap();
+
+        let summary = db.import_file(&file, false, &store).await.unwrap();
+        assert_eq!(summary, ImportSummary { added: 1, updated: 0, skipped: 1 });
+        assert!(db.get_issue("team-proxy-reset").await.is_some());
+        assert_ne!(db.get_issue(&existing_id).await.unwrap().title, "Replaced");
+
+        let summary = db.import_file(&file, true, &store).await.unwrap();
+        assert_eq!(summary, ImportSummary { added: 0, updated: 2, skipped: 0 });
+        assert_eq!(db.get_issue(&existing_id).await.unwrap().title, "Replaced");
+
+        // Only imported issues are persisted, each once
+        let stored = read_issues_file(&store).unwrap();
+        assert_eq!(stored.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_scored_issues_are_ordered_by_relevance() {
+        let dir = tempdir().unwrap();
+        let db = KnownIssuesDatabase::with_issues_file(&dir.path().join("known_issues.yaml")).await;
+        db.add_issue(sample_issue("team-proxy-reset", "Proxy resets")).await;
+
+        // Two memory patterns plus keywords beat a single proxy pattern
+        let context = "kernel: Out of memory: oom-killer invoked, memory pressure high, swap full. \
+                       Upstream proxy reported connection reset.";
+        let scored = db.get_scored_issues_for_context(context, None, 10).await;
+
+        assert!(scored.len() >= 2);
+        assert_eq!(scored[0].0.id, "system-high-memory-usage");
+        assert!(scored.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        let proxy_score = scored.iter().find(|(issue, _)| issue.id == "team-proxy-reset").unwrap().1;
+        assert!(scored[0].1 > proxy_score);
+    }
+
+    #[tokio::test]
+    async fn test_scored_issues_respect_limit_and_threshold() {
+        let dir = tempdir().unwrap();
+        let db = KnownIssuesDatabase::with_issues_file(&dir.path().join("known_issues.yaml")).await;
+        let context = "out of memory oom-killer memory pressure disk full no space left on device \
+                       permission denied connection refused dns failure";
+
+        let scored = db.get_scored_issues_for_context(context, None, 2).await;
+        assert!(scored.len() <= 2);
+        assert!(scored.iter().all(|(_, score)| *score > 0.3));
+
+        assert!(db.get_scored_issues_for_context("all quiet here", None, 5).await.is_empty());
+        assert!(db.get_relevant_issues_for_context(context, None).await.len() <= DEFAULT_MAX_RELEVANT_ISSUES);
+    }
+
+    #[test]
+    fn test_write_issues_csv_escapes_fields() {
+        let mut issue = sample_issue("proxy-reset", "Proxy resets, \"sometimes\"");
+        issue.description = "Line one\nline two".to_string();
+        issue.tags.push("edge".to_string());
+
+        let mut buffer = Vec::new();
+        write_issues_csv(&mut buffer, &[issue]).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "id,title,category,severity,description,tags\n\
+             proxy-reset,\"Proxy resets, \"\"sometimes\"\"\",Network,Medium,\"Line one\nline two\",team; edge\n"
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_files() {
+        let dir = tempdir().unwrap();
+
+        let duplicate = dir.path().join("dup.yaml");
+        write_issues_file(&duplicate, &[sample_issue("a", "A"), sample_issue("a", "A again")]).unwrap();
+        assert!(read_issues_file(&duplicate).unwrap_err().to_string().contains("duplicate"));
+
+        let malformed = dir.path().join("bad.json");
+        std::fs::write(&malformed, r#"[{"id": "x"}]"#).unwrap();
+        assert!(read_issues_file(&malformed).is_err());
+    }
+}
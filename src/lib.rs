@@ -1,10 +1,23 @@
+//! RAID - Rust Analysis and Informative Debugger
+//!
+//! The `raid` binary is a thin CLI over this library. Downstream tools can use
+//! the re-exports below to run diagnostics and build reports programmatically.
+
 pub mod ai;
+pub mod analysis;
 pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod context;
 pub mod database;
 pub mod known_issues;
 pub mod output;
 pub mod sysinfo;
 pub mod tools;
-pub mod ui; 
\ No newline at end of file
+pub mod ui;
+
+pub use ai::AIProvider;
+pub use known_issues::KnownIssuesDatabase;
+pub use output::{create_system_health_report, SystemHealthReport};
+pub use sysinfo::{collect_system_info, SystemInfo};
+pub use tools::{CompositeToolResult, DebugToolResult, DebugTools};
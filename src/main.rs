@@ -1,28 +1,28 @@
-mod ai;
-mod cli;
-mod commands;
-mod config;
-mod database;
-mod known_issues;
-mod output;
-mod sysinfo;
-mod tools;
-mod ui;
+use raid::{ai, cli, commands, config, known_issues, output, sysinfo, tools, ui};
+use raid::context::ContextBuilder;
+use raid::output::anonymize::Anonymizer;
 
 use ai::{create_ai_provider_from_cli, AIAgent, AIAgentConfig, AIAgentResult};
 use clap::Parser;
-use cli::{CheckComponent, Cli, Commands, IssueAction};
-use commands::{config::run_config_command, debug::run_debug_tools};
+use cli::{ApplyFixesMode, CheckComponent, Cli, Commands, ConfigAction, IssueAction, OutputFormat, SortKey};
+use commands::{config::{check_config_files, run_config_command}, debug::run_debug_tools, doctor::run_doctor};
+use output::printers::print_output_with_config;
+use output::Verbosity;
 use config::RaidConfig;
 
-use sysinfo::collect_basic_system_info;
+use sysinfo::{collect_basic_system_info_with, collect_system_info_with, SystemInfo};
 use tools::DebugTools;
+use std::io::Write;
+use std::time::Duration;
 use ui::UIFormatter;
 
+/// Exit status when `--timeout` expires, the same as `timeout(1)`
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Parse CLI args
-    let mut cli = Cli::parse();
+    let cli = Cli::parse();
 
     // Load configuration
     let mut config = if let Some(config_file) = &cli.config {
@@ -41,20 +41,111 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Merge CLI overrides into config
     config.merge_cli_overrides(&cli);
 
+    // Warn about outdated files and unknown keys; `config validate` and `config migrate` report them themselves
+    if !matches!(cli.command, Some(Commands::Config { action: ConfigAction::Validate | ConfigAction::Migrate, .. }))
+        && let Err(e) = check_config_files(cli.config.as_deref(), &config)
+    {
+        eprintln!("Configuration error: {}", e);
+        std::process::exit(1);
+    }
+
     // Validate configuration
     if let Err(e) = config.validate() {
         eprintln!("Configuration error: {}", e);
         std::process::exit(1);
     }
 
+    // One limit on concurrent commands, for collection and AI tools alike
+    tools::pool::configure(config.performance.max_parallelism);
+
+    // A --model override is checked against the provider so a typo fails before any collection
+    if cli.ai_model.is_some()
+        && let Err(e) = config.validate_model()
+    {
+        eprintln!("Configuration error: {}", e);
+        std::process::exit(1);
+    }
+
+    match cli.timeout {
+        Some(secs) => run_with_timeout(cli, config, Duration::from_secs(secs)).await,
+        None => run(cli, config).await,
+    }
+}
+
+/// `--timeout`: the run gets a thread and runtime of its own, so a tool blocking its thread
+/// can't hold up the deadline. When it passes, print a report of whatever was collected and
+/// exit with `TIMEOUT_EXIT_CODE`.
+async fn run_with_timeout(cli: Cli, config: RaidConfig, limit: Duration) -> Result<(), Box<dyn std::error::Error>> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let run_config = config.clone();
+    std::thread::spawn(move || {
+        let result = tokio::runtime::Runtime::new()
+            .map_err(|e| e.to_string())
+            .and_then(|runtime| runtime.block_on(run(cli, run_config)).map_err(|e| e.to_string()));
+        let _ = sender.send(result);
+    });
+
+    match tokio::time::timeout(limit, receiver).await {
+        Ok(Ok(result)) => result.map_err(Into::into),
+        Ok(Err(_)) => Err("RAID stopped before finishing".into()),
+        Err(_) => {
+            print_timeout_report(&config, limit);
+            std::process::exit(TIMEOUT_EXIT_CODE);
+        }
+    }
+}
+
+/// The report printed when `--timeout` expires: everything collected so far, without analysis
+fn print_timeout_report(config: &RaidConfig, limit: Duration) {
+    let message = format!(
+        "RAID timed out after {}s; this report only has the data collected before then.",
+        limit.as_secs()
+    );
+    eprintln!("⏱️  {}", message);
+    match sysinfo::last_collected() {
+        Some(info) => {
+            let mut report = output::create_system_health_report(&info, &message, config.get_verbosity().is_detailed());
+            report.apply_output_config(&config.output);
+            output::print_report(&report, config.get_output_format(), config.get_verbosity());
+        }
+        None => eprintln!("Nothing was collected before the timeout."),
+    }
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+}
+
+async fn run(mut cli: Cli, config: RaidConfig) -> Result<(), Box<dyn std::error::Error>> {
+    // Opt-in only: this contacts GitHub, so it never runs unless asked for
+    if config.updates.check {
+        commands::update::spawn_update_check();
+    }
+
     // Create UI formatter
-    let ui_formatter = UIFormatter::new(config.output.color && !cli.no_color);
+    let ui_formatter = UIFormatter::new(config.output.color && !cli.no_color)
+        .with_progress(config.ui.progress_indicators)
+        .force_progress(cli.force_progress);
+
+    // Quiet mode stays silent unless issues are found
+    let quiet = config.get_verbosity() == Verbosity::Quiet;
+
+    // The check listing is static, so it needs neither the banner nor the tool probe
+    if cli.list_checks {
+        return commands::list_checks::run_list_checks(config.get_output_format());
+    }
+    if let Some(Commands::Verify { report, signature }) = &cli.command {
+        return commands::verify::run_verify(report, signature, &config);
+    }
 
     // Initialize debug tools with availability checking at startup
-    println!("🔧 Checking available system tools...");
-    let debug_tools = DebugTools::initialize_with_availability_check();
+    // `tools-list` and `benchmark` output may be JSON for scripts, so they get no banner
+    // either; nor does any other machine-readable format, whose report must be all of stdout
+    let tools_list = matches!(cli.command, Some(Commands::ToolsList | Commands::Benchmark { .. }));
+    let machine_readable = config.get_output_format() != OutputFormat::Text;
+    if !quiet && !cli.prompt_only && !tools_list && !machine_readable {
+        println!("🔧 Checking available system tools...");
+    }
+    let debug_tools = DebugTools::initialize_with_availability_check().with_host_paths(config.host_paths());
     let available_categories = debug_tools.get_available_categories();
-    if config.output.verbose || cli.verbose {
+    if config.get_verbosity().is_detailed() && !cli.prompt_only && !tools_list && !machine_readable {
         println!("📋 Available tool categories: {:?}", available_categories);
         for category in &available_categories {
             let tools = debug_tools.get_category_tools(category);
@@ -63,14 +154,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Handle config command
-    if let Some(Commands::Config { action, output }) = &cli.command {
-        return run_config_command(action, output.as_deref(), &config).await;
+    if let Some(Commands::Config { action, output, reveal_secrets }) = &cli.command {
+        return run_config_command(action, cli.config.as_deref(), output.as_deref(), *reveal_secrets, &config).await;
+    }
+
+    // Handle doctor command
+    if let Some(Commands::Doctor) = &cli.command {
+        return run_doctor(&cli, &config, &debug_tools).await;
+    }
+
+    if let Some(Commands::Benchmark { categories }) = &cli.command {
+        return commands::benchmark::run_benchmark(&debug_tools, categories, config.get_output_format()).await;
+    }
+
+    if tools_list {
+        return commands::tools_list::run_tools_list(&debug_tools, config.get_output_format());
+    }
+
+    // A bundle is an offline scan, so it never needs an API key
+    if let Some(Commands::Bundle { output, sign }) = &cli.command {
+        return commands::bundle::run_bundle(output.as_deref(), *sign, &config, &debug_tools).await;
     }
 
     // Check if this is a debug command
     if let Some(Commands::Debug { .. }) = &cli.command {
         // Debug commands don't need AI API key
-        run_debug_tools(&cli).await?;
+        run_debug_tools(&cli, &config, &ui_formatter).await?;
         return Ok(());
     }
 
@@ -81,15 +190,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    // Printing the prompt never contacts a provider, so it needs no API key either
+    if cli.prompt_only {
+        return print_prompt_only(&config, &cli).await;
+    }
+
+    // Offline analysis never needs an API key
+    if cli.no_ai {
+        return commands::offline::run_offline_check(&cli, &config, &debug_tools).await;
+    }
+
     // If AI_API_KEY is not set and no key provided via CLI, force dry-run and print a message
-    if config.ai.api_key.is_none() && !cli.dry_run {
-        println!("No AI API key found. Running in dry-run mode. No AI model will be used.");
+    if config.missing_api_key() && !cli.dry_run {
+        if !quiet {
+            writeln!(status_output(&config), "No AI API key found. Running in dry-run mode. No AI model will be used.")?;
+        }
         cli.dry_run = true;
     }
 
+    // In quiet mode, check health first so healthy systems produce no output (e.g. from cron)
+    if quiet {
+        let info = collect_system_info_with(&config.collect_options());
+        let report = output::create_system_health_report(&info, "", false);
+        if report.is_healthy() {
+            if cli.quiet_json && matches!(config.get_output_format(), OutputFormat::Json | OutputFormat::Jsonl) {
+                println!("{}", serde_json::json!({ "status": "healthy" }));
+            }
+            return Ok(());
+        }
+
+        if cli.dry_run {
+            print_output_with_config(&info, "AI analysis skipped (dry run).", &cli.check_component(), &config, &ui_formatter);
+            return Ok(());
+        }
+        // Issues were found: fall through to the AI analysis
+    }
+
     // Handle dry-run mode (no AI analysis)
     if cli.dry_run {
-        let info = collect_basic_system_info();
+        // Component checks and machine-readable formats get a full (scoped) report
+        let component = cli.check_component();
+        if config.get_output_format() != OutputFormat::Text || !matches!(component, CheckComponent::All) {
+            let info = collect_system_info_with(&config.collect_options());
+            print_output_with_config(&info, "AI analysis skipped (dry run).", &component, &config, &ui_formatter);
+            return Ok(());
+        }
+
+        let info = collect_basic_system_info_with(&config.collect_options());
         println!("🔍 System Health Check (Dry Run)");
         println!("OS: {}", info.os);
         println!("CPU: {}", info.cpu);
@@ -105,8 +252,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 /// Run basic diagnostic tools first to provide context to the AI
-async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter: &UIFormatter) -> String {
+async fn run_initial_system_diagnostics(
+    debug_tools: &DebugTools,
+    ui_formatter: &UIFormatter,
+    journal_unit: Option<&str>,
+) -> (String, Vec<String>) {
     let mut context = String::new();
+    let mut executed_commands = Vec::new();
     
     context.push_str("🔍 INITIAL SYSTEM DIAGNOSTICS\n");
     context.push_str("============================\n\n");
@@ -118,6 +270,7 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
         context.push_str("📡 NETWORK STATUS:\n");
         let network_result = debug_tools.run_ip_addr().await;
         context.push_str(&format!("Command: {}\n", network_result.command));
+        executed_commands.push(network_result.command.clone());
         if network_result.success {
             context.push_str(&format!("Status: ✅ Network interfaces detected\n"));
             // Show just interface names, not full output to keep it concise
@@ -136,6 +289,7 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
         // Basic connectivity test
         let connectivity_result = debug_tools.run_connectivity_test().await;
         context.push_str(&format!("Command: {}\n", connectivity_result.command));
+        executed_commands.push(connectivity_result.command.clone());
         if connectivity_result.success {
             context.push_str("Internet: ✅ Basic connectivity working\n");
         } else {
@@ -147,6 +301,7 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
         context.push_str("💾 MEMORY STATUS:\n");
         let memory_result = debug_tools.run_free().await;
         context.push_str(&format!("Command: {}\n", memory_result.command));
+        executed_commands.push(memory_result.command.clone());
         if memory_result.success {
             context.push_str("Status: ✅ Memory information available\n");
             // Extract key memory stats
@@ -166,6 +321,7 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
         context.push_str("💿 STORAGE STATUS:\n");
         let disk_result = debug_tools.run_df().await;
         context.push_str(&format!("Command: {}\n", disk_result.command));
+        executed_commands.push(disk_result.command.clone());
         if disk_result.success {
             context.push_str("Status: ✅ Disk information available\n");
             // Show just filesystem usage summary
@@ -185,6 +341,7 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
         context.push_str("⚙️ PROCESS STATUS:\n");
         let process_result = debug_tools.run_top().await;
         context.push_str(&format!("Command: {}\n", process_result.command));
+        executed_commands.push(process_result.command.clone());
         if process_result.success {
             context.push_str("Status: ✅ Process information available\n");
             // Extract load average and top processes
@@ -203,12 +360,68 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
         } else {
             context.push_str("Status: ❌ Failed to check processes\n");
         }
+
+        // Structured top consumers so the AI doesn't have to parse a full process dump
+        let mut top_pids = Vec::new();
+        for (by, label) in [(SortKey::Cpu, "CPU"), (SortKey::Mem, "memory")] {
+            let top_result = debug_tools.run_top_consumers(by, 5).await;
+            executed_commands.push(top_result.command.clone());
+            if top_result.success {
+                let processes = tools::process_debug::parse_process_usage(&top_result.output);
+                if !processes.is_empty() {
+                    context.push_str(&format!("Top {} consumers (command: {}):\n", label, top_result.command));
+                    for process in processes {
+                        if !top_pids.contains(&process.pid) {
+                            top_pids.push(process.pid);
+                        }
+                        context.push_str(&format!(
+                            "  PID {} ({}, user {}): {:.1}% CPU, {:.1}% MEM, {} KB RSS\n",
+                            process.pid,
+                            process.command,
+                            process.user,
+                            process.cpu_percent,
+                            process.mem_percent,
+                            process.rss_kb
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Open files against NOFILE limits catches "Too many open files" before it crashes a service
+        let fd_usages: Vec<_> = top_pids
+            .into_iter()
+            .filter_map(|pid| tools::process_debug::read_process_fd_usage(&debug_tools.host_paths, pid))
+            .collect();
+        let near_limit: Vec<_> = fd_usages
+            .iter()
+            .filter(|usage| tools::process_debug::is_near_fd_limit(usage))
+            .collect();
+        if near_limit.is_empty() {
+            if !fd_usages.is_empty() {
+                context.push_str("Open files: no top process is near its NOFILE limit\n");
+            }
+        } else {
+            for usage in near_limit {
+                context.push_str(&format!(
+                    "⚠️ PID {} ({}) has {} open files of a {} soft limit\n",
+                    usage.pid,
+                    usage.command,
+                    usage.open_fds.unwrap_or_default(),
+                    usage.fd_soft_limit.unwrap_or_default()
+                ));
+            }
+        }
         context.push_str("\n");
         
-        // 5. System Logs (Recent)
+        // 5. System Logs (Recent), narrowed to one unit when requested
         context.push_str("📜 RECENT SYSTEM LOGS:\n");
-        let log_result = debug_tools.run_journalctl_recent(Some(20)).await;
+        let log_result = match journal_unit {
+            Some(unit) => debug_tools.run_journalctl_service(unit, Some(20)).await,
+            None => debug_tools.run_journalctl_recent(Some(20)).await,
+        };
         context.push_str(&format!("Command: {}\n", log_result.command));
+        executed_commands.push(log_result.command.clone());
         if log_result.success {
             context.push_str("Status: ✅ System logs available\n");
             // Count errors/warnings in recent logs
@@ -243,6 +456,7 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
             context.push_str("☸️ KUBERNETES STATUS:\n");
             let k8s_cluster_result = debug_tools.run_kubectl_cluster_info().await;
             context.push_str(&format!("Command: {}\n", k8s_cluster_result.command));
+            executed_commands.push(k8s_cluster_result.command.clone());
             if k8s_cluster_result.success {
                 context.push_str("Status: ✅ Kubernetes cluster accessible\n");
                 if k8s_cluster_result.output.contains("Kubernetes control plane") {
@@ -257,6 +471,7 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
             // Check pod status  
             let pods_result = debug_tools.run_kubectl_get_pods(None).await;
             context.push_str(&format!("Command: {}\n", pods_result.command));
+            executed_commands.push(pods_result.command.clone());
             if pods_result.success {
                 let pod_lines: Vec<&str> = pods_result.output.lines().skip(1).collect(); // Skip header
                 context.push_str(&format!("Pods found: {} across all namespaces\n", pod_lines.len()));
@@ -280,6 +495,7 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
             context.push_str("🐳 CONTAINER STATUS:\n");
             let docker_result = debug_tools.run_docker_ps().await;
             context.push_str(&format!("Command: {}\n", docker_result.command));
+            executed_commands.push(docker_result.command.clone());
             if docker_result.success {
                 let container_lines: Vec<&str> = docker_result.output.lines().skip(1).collect();
                 context.push_str(&format!("Running containers: {}\n", container_lines.len()));
@@ -294,125 +510,186 @@ async fn run_initial_system_diagnostics(debug_tools: &DebugTools, ui_formatter:
         context.push_str("to make informed decisions about what additional tools to run.\n\n");
     }).await;
     
-    context
+    (context, executed_commands)
 }
 
-/// Unified AI system that always uses AIAgent with full tool access
-async fn run_unified_ai_system(
+/// The collected context the agent starts from, plus the findings that go into its report
+struct AgentContext {
+    system_info: SystemInfo,
+    system_context: String,
+    initial_commands: Vec<String>,
+    scheduling_issues: Vec<output::Issue>,
+    security_issues: Vec<output::Issue>,
+    time_issues: Vec<output::Issue>,
+    dns_issues: Vec<output::Issue>,
+    auth_issues: Vec<output::Issue>,
+    /// `systemctl show` state of the failed units, for deterministic exit and crash-loop issues
+    service_statuses: Vec<tools::ServiceStatusDetail>,
+}
+
+/// Collect system information, run the initial diagnostics and threshold checks, and assemble
+/// the system context given to the agent
+async fn collect_agent_context(
     config: &RaidConfig,
     ui_formatter: &UIFormatter,
     cli: &Cli,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Check if AI API key is available
-    if config.ai.api_key.is_none() {
-        println!("❌ No AI API key found. AI analysis requires an AI provider.");
-        println!("Please set your AI_API_KEY environment variable or use --ai-api-key flag.");
-        println!("Supported providers: OpenAI, Anthropic, Local (Ollama)");
-        println!("\nFor a basic system check without AI, use: cargo run -- --dry-run");
-        return Ok(());
-    }
-
-    // Create AI provider
-    let ai_provider = match create_ai_provider_from_cli(
-        &config.get_ai_provider(),
-        config.ai.api_key.clone(),
-        Some(config.get_model()),
-        config.ai.base_url.clone(),
-        config.ai.max_tokens,
-        config.ai.temperature,
-    ).await {
-        Ok(provider) => provider,
-        Err(e) => {
-            println!("❌ Failed to initialize AI provider: {}", e);
-            println!("This usually means:");
-            println!("  • Invalid API key");
-            println!("  • Network connectivity issues");
-            println!("  • Service temporarily unavailable");
-            println!("\nPlease check your API key and try again.");
-            println!("\nFor a basic system check without AI, use: cargo run -- --dry-run");
-            return Ok(());
-        }
-    };
-
-    // Test AI provider connection before proceeding
-    match ai_provider.analyze("test").await {
-        Ok(_) => {
-            // Provider is working, proceed with analysis
-        },
-        Err(e) => {
-            println!("❌ AI provider test failed: {}", e);
-            println!("This usually indicates:");
-            println!("  • Invalid or expired API key");
-            println!("  • Insufficient API credits/quota");
-            println!("  • Network connectivity issues");
-            println!("\nPlease verify your API key and try again.");
-            println!("\nFor a basic system check without AI, use: cargo run -- --dry-run");
-            return Ok(());
-        }
-    }
-
-    // Collect basic system info
-    let sys_info = ui_formatter.show_progress("Collecting system information", || {
-        collect_basic_system_info()
+    previous_report: Option<&output::SystemHealthReport>,
+) -> AgentContext {
+    // Collected once and reused for the context, the report and the agent's cache
+    let system_info = ui_formatter.show_progress("Collecting system information", || {
+        collect_system_info_with(&config.collect_options())
     });
 
     // Initialize debug tools for initial diagnostics
-    let debug_tools = DebugTools::initialize_with_availability_check();
+    let debug_tools = DebugTools::initialize_with_availability_check().with_host_paths(config.host_paths());
     
     // Run initial system diagnostics to provide context to the AI
-    let initial_diagnostics = if matches!(
+    let (initial_diagnostics, initial_commands) = if matches!(
         (&cli.command, &cli.problem_description), 
         (Some(Commands::Check { component: CheckComponent::All }), _) | (_, None)
     ) {
         // Only run initial diagnostics for full system checks or when no specific problem is described
-        run_initial_system_diagnostics(&debug_tools, ui_formatter).await
+        run_initial_system_diagnostics(&debug_tools, ui_formatter, config.journal.unit.as_deref()).await
     } else {
         // For specific questions or component checks, skip initial diagnostics
-        String::new()
+        (String::new(), Vec::new())
     };
 
     // Create comprehensive system context
-    let mut system_context = String::new();
-    system_context.push_str(&format!("Operating System: {}\n", sys_info.os));
-    system_context.push_str(&format!("CPU: {}\n", sys_info.cpu));
-    system_context.push_str(&format!(
-        "Memory: {}/{}\n",
-        sys_info.free_memory, sys_info.total_memory
-    ));
-    system_context.push_str(&format!(
-        "Disk: {}/{}\n",
-        sys_info.free_disk, sys_info.total_disk
-    ));
+    let mut context = ContextBuilder::from_text(system_info.context_summary());
+    context.add_list_section(
+        "Nearly full filesystems",
+        output::disk_usage_issues(&system_info.filesystems).iter().map(|issue| &issue.message),
+    );
 
-    if sys_info.is_kubernetes {
-        system_context.push_str("Environment: Kubernetes cluster\n");
+    // Explain Pending pods and unbound volume claims up front from the cluster's own events
+    let mut scheduling_issues = Vec::new();
+    if sysinfo::is_running_in_kubernetes() {
+        let mut claim_issues = Vec::new();
+        if let Some((pods, events)) = debug_tools.get_pods_and_events(None).await {
+            scheduling_issues = output::pending_pod_issues(&pods, &events);
+            if let Some(volumes) = debug_tools.get_volume_binding_state().await {
+                claim_issues = output::unbound_claim_issues(&volumes, &events);
+            }
+        }
+        context
+            .add_list_section("Unschedulable Pending pods", scheduling_issues.iter().map(|issue| &issue.message))
+            .add_list_section("Unbound PersistentVolumeClaims", claim_issues.iter().map(|issue| &issue.message));
+        // A claim that won't bind keeps its pods Pending, so both go into the report together
+        scheduling_issues.extend(claim_issues);
     }
 
-    if sys_info.container_runtime_available {
-        system_context.push_str("Container Runtime: Available\n");
+    if sysinfo::is_container_runtime_available() {
+        context.push_line("Container Runtime: Available");
+    }
+
+    // How each failed service ended, so the AI doesn't have to read it out of status text
+    let failed_units = sysinfo::collect_failed_units();
+    let service_statuses = debug_tools.get_service_statuses(&failed_units).await;
+    context.add_list_section(
+        "Failed service states (systemctl show)",
+        service_statuses.iter().map(|status| status.summary()),
+    );
+
+    // A failed service with a matching SELinux/AppArmor denial points straight at the cause
+    let security_issues = if failed_units.is_empty() {
+        Vec::new()
+    } else {
+        output::security_denial_issues(&debug_tools.get_security_denials().await, &failed_units)
+    };
+    context.add_list_section(
+        "Security module denials affecting failed services",
+        security_issues.iter().map(|issue| &issue.message),
+    );
+
+    // Repeated failed SSH logins from one address are a cheap intrusion signal
+    let auth_issues = output::brute_force_issues(&debug_tools.get_failed_logins().await);
+    context.add_list_section("Failed SSH login patterns", auth_issues.iter().map(|issue| &issue.message));
+
+    // Clock skew breaks TLS, etcd and Kubernetes in confusing ways, so check it up front
+    let time_issues = match debug_tools.get_time_sync_status().await {
+        Some(status) => {
+            context.push_line(format!("Time: {}", tools::system_info::describe_time_sync(&status)));
+            output::time_sync_issues(&status)
+        }
+        None => Vec::new(),
+    };
+
+    // A dead resolver makes every tool that needs a name fail in its own confusing way
+    let dns_issues = match &config.network.dns_probe_name {
+        Some(name) => output::dns_resolution_issues(&debug_tools.get_dns_resolution_status(name).await),
+        None => Vec::new(),
+    };
+    context.add_list_section("DNS", dns_issues.iter().map(|issue| {
+        format!("{}: {}", issue.message, issue.details.as_deref().unwrap_or_default())
+    }));
+
+    if let Some(unit) = &config.journal.unit {
+        context.push_line(format!("Journal entries are limited to unit: {}", unit));
+    }
+    if config.journal.since_boot {
+        context.push_str(&ai::since_boot_context(sysinfo::boot_time(&config.host_paths())));
+    }
+
+    // Pressure stall information is a better stress signal than load average
+    if system_info.pressure.is_available() {
+        context.add_section("Pressure stall information (PSI, % of time stalled)", &system_info.pressure.to_context());
     }
     
+    if let Some(report) = previous_report {
+        context.push_str("\n").push_str(&report.previous_run_context());
+    }
+
     // Add initial diagnostics if we ran them
     if !initial_diagnostics.is_empty() {
-        system_context.push_str("\n");
-        system_context.push_str(&initial_diagnostics);
+        context.push_str("\n").push_str(&initial_diagnostics);
     }
 
-    // Determine the analysis type and create appropriate prompt
-    let (analysis_prompt, max_tool_calls) = match (&cli.command, &cli.problem_description) {
+    context.add_configured_sections(&config.context);
+
+    // Known issues that match the collected context, in the same form analyze_with_known_issues adds them
+    if config.ai.use_known_issues {
+        let known_issues = known_issues::KnownIssuesDatabase::shared().await;
+        let relevant_issues = known_issues
+            .get_scored_issues_for_context(context.as_str(), None, config.ai.max_known_issues)
+            .await;
+        context.push_str(&ai::known_issues_prompt_section(&relevant_issues));
+    }
+    let system_context = context.build();
+
+    AgentContext {
+        system_info,
+        system_context,
+        initial_commands,
+        scheduling_issues,
+        security_issues,
+        time_issues,
+        dns_issues,
+        auth_issues,
+        service_statuses,
+    }
+}
+
+/// `--explain-issue`: look the issue up before anything is collected, so a typo fails fast
+async fn load_explained_issue(cli: &Cli) -> Result<Option<known_issues::KnownIssue>, String> {
+    let Some(id) = &cli.explain_issue else {
+        return Ok(None);
+    };
+    match known_issues::KnownIssuesDatabase::shared().await.get_issue(id).await {
+        Some(issue) => Ok(Some(issue)),
+        None => Err(format!("Issue with ID '{}' not found. List the known issues with: raid issues list", id)),
+    }
+}
+
+/// The task given to the agent for this invocation and its tool call budget
+fn analysis_prompt(cli: &Cli, explained_issue: Option<&known_issues::KnownIssue>) -> (String, usize) {
+    if let Some(issue) = explained_issue {
+        return (ai::explain_issue_prompt(issue), 10);
+    }
+    match (&cli.command, &cli.problem_description) {
         // Specific component check
         (Some(Commands::Check { component }), _) => {
-            let component_focus = match component {
-                CheckComponent::All => "comprehensive system health check",
-                CheckComponent::System => "system information and performance analysis",
-                CheckComponent::Containers => "container and Docker analysis",
-                CheckComponent::Kubernetes => "Kubernetes cluster analysis",
-                CheckComponent::Cgroups => "cgroups and resource management analysis",
-                CheckComponent::Systemd => "systemd services and system management analysis",
-                CheckComponent::Journal => "system logs and journal analysis",
-                CheckComponent::Debug => "debug tools analysis",
-            };
-            (format!("Perform a focused {} for this system. Analyze the component thoroughly and provide insights on any issues or optimizations.", component_focus), 10)
+            (format!("Perform a focused {} for this system. Analyze the component thoroughly and provide insights on any issues or optimizations.", component.focus()), 10)
         },
         // User provided a specific problem description
         (_, Some(problem)) => {
@@ -428,133 +705,478 @@ async fn run_unified_ai_system(
         _ => {
             ("Analyze this system's health and provide insights on any issues or optimizations. Perform a comprehensive system check.".to_string(), 10)
         }
+    }
+}
+
+/// `--prompt-only`: assemble the agent's first prompt from a full collection and print it
+/// without contacting any AI provider
+async fn print_prompt_only(config: &RaidConfig, cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let previous_report = match &cli.continue_from {
+        Some(path) => Some(
+            output::SystemHealthReport::read_from_file(std::path::Path::new(path))
+                .map_err(|e| format!("Failed to load previous report '{}': {}", path, e))?,
+        ),
+        None => None,
     };
+    let explained_issue = load_explained_issue(cli).await?;
 
-    // Display appropriate header based on the analysis type
-    match (&cli.command, &cli.problem_description) {
-        (Some(Commands::Check { component }), _) => {
-            println!("🔍 Component Check: {:?}", component);
-            println!("🤖 AI Assistant ({})", ai_provider.name());
-            println!("Analyzing {} component...\n", component.as_str());
+    // Progress output would end up in the copied prompt
+    let ui_formatter = UIFormatter::new(false).with_progress(false);
+    let context = collect_agent_context(config, &ui_formatter, cli, previous_report.as_ref()).await;
+    let problem = match &cli.command {
+        Some(Commands::Ask { question: Some(question) }) => question.clone(),
+        _ => analysis_prompt(cli, explained_issue.as_ref()).0,
+    };
+    let prompt = ai::initial_agent_prompt(&problem, &context.system_context, config.ai.explain_reasoning);
+    print!("{}", prompt);
+
+    // The size goes to stderr so the prompt can be redirected on its own
+    let tokens = ai::estimate_tokens(&prompt);
+    eprintln!("Estimated prompt size: ~{} tokens", tokens);
+    if let Some(window) = config.ai.context_window
+        && tokens > window
+    {
+        eprintln!("{}", ai::context_window_warning(tokens, window));
+    }
+    Ok(())
+}
+
+/// Print the redacted first prompt and ask before sending it; `--yes` skips the question
+fn confirm_redacted_prompt(prompt: &str, provider: &str, assume_yes: bool) -> std::io::Result<bool> {
+    println!("📝 Prompt for {} after redaction:\n", provider);
+    print!("{}", prompt);
+    println!("\nEstimated prompt size: ~{} tokens", ai::estimate_tokens(prompt));
+    println!("Tool output the AI asks for later is redacted the same way before it is sent.");
+    if assume_yes {
+        return Ok(true);
+    }
+
+    print!("Send this to {}? [y/N]: ", provider);
+    std::io::Write::flush(&mut std::io::stdout())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Unified AI system that always uses AIAgent with full tool access
+/// Where banners, progress and the text analysis go: stdout for text output, stderr when
+/// stdout carries a machine-readable report
+fn status_output(config: &RaidConfig) -> Box<dyn std::io::Write> {
+    if config.get_output_format() == OutputFormat::Text {
+        Box::new(std::io::stdout())
+    } else {
+        Box::new(std::io::stderr())
+    }
+}
+
+async fn run_unified_ai_system(
+    config: &RaidConfig,
+    ui_formatter: &UIFormatter,
+    cli: &Cli,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut out = status_output(config);
+
+    // Check if AI API key is available
+    if config.missing_api_key() {
+        writeln!(out, "❌ No AI API key found. AI analysis requires an AI provider.")?;
+        writeln!(out, "Please set your AI_API_KEY environment variable or use --ai-api-key flag.")?;
+        writeln!(out, "Supported providers: OpenAI, Anthropic, Local (Ollama), llama.cpp (GGUF file)")?;
+        writeln!(out, "\nFor a basic system check without AI, use: cargo run -- --dry-run")?;
+        return Ok(());
+    }
+
+    // Load the previous report up front so a bad path fails before any analysis runs
+    let previous_report = match &cli.continue_from {
+        Some(path) => Some(
+            output::SystemHealthReport::read_from_file(std::path::Path::new(path))
+                .map_err(|e| format!("Failed to load previous report '{}': {}", path, e))?,
+        ),
+        None => None,
+    };
+    let questions = match &cli.questions_file {
+        Some(path) => Some(
+            commands::ai::read_questions_file(std::path::Path::new(path))
+                .map_err(|e| format!("Failed to read questions file '{}': {}", path, e))?,
+        ),
+        None => None,
+    };
+    if let (Some(path), Some(questions)) = (&cli.questions_file, &questions)
+        && questions.is_empty()
+    {
+        return Err(format!("Questions file '{}' contains no questions", path).into());
+    }
+    let explained_issue = load_explained_issue(cli).await?;
+    if cli.redact_preview && matches!(cli.command, Some(Commands::Ask { .. })) {
+        return Err("--redact-preview previews a single analysis and can't be combined with ask".into());
+    }
+    if cli.apply_fixes.is_some() && matches!(cli.command, Some(Commands::Ask { .. })) {
+        return Err("--apply-fixes works on a single analysis and can't be combined with ask".into());
+    }
+    // Running fixes changes the system, so it is never implied
+    if cli.apply_fixes == Some(ApplyFixesMode::Run) && !cli.yes {
+        return Err("--apply-fixes=run runs commands that change the system; add --yes to confirm, or use --apply-fixes to only list them".into());
+    }
+
+    // Create AI provider
+    let ai_provider = match create_ai_provider_from_cli(
+        &config.get_ai_provider(),
+        config.ai.api_key.clone(),
+        Some(config.get_model()),
+        config.ai.base_url.clone(),
+        config.ai.max_tokens,
+        config.ai.temperature,
+        config.ai.seed,
+        config.ai.max_known_issues,
+        config.ai.use_known_issues,
+        config.ai.analysis_timeout_secs,
+        &config.ai.fallback_providers,
+    ).await {
+        Ok(provider) => provider,
+        Err(e) => {
+            writeln!(out, "❌ Failed to initialize AI provider: {}", e)?;
+            writeln!(out, "This usually means:")?;
+            writeln!(out, "  • Invalid API key")?;
+            writeln!(out, "  • Network connectivity issues")?;
+            writeln!(out, "  • Service temporarily unavailable")?;
+            writeln!(out, "\nPlease check your API key and try again.")?;
+            writeln!(out, "\nFor a basic system check without AI, use: cargo run -- --dry-run")?;
+            return Ok(());
+        }
+    };
+
+    // Fail fast if the AI provider is unreachable, before collecting system info
+    match ai_provider.health_check().await {
+        Ok(_) => {
+            // Provider is working, proceed with analysis
         },
-        (_, Some(problem)) => {
-            if cli.ai_agent_mode {
-                println!("🤖 AI Agent Mode - Iterative Problem Solving");
-                println!("Problem: {}", problem);
-                println!("Max tool calls: {}", cli.ai_max_tool_calls);
-                println!("Starting analysis...\n");
-            } else {
-                println!("❓ Question: {}", problem);
-                println!("🤖 AI Assistant ({})", ai_provider.name());
-                println!("Analyzing your question and determining which tools to run...\n");
+        Err(e) => {
+            writeln!(out, "❌ AI provider unreachable: {}", e)?;
+            writeln!(out, "This usually indicates:")?;
+            writeln!(out, "  • Invalid or expired API key")?;
+            writeln!(out, "  • Insufficient API credits/quota")?;
+            writeln!(out, "  • Network connectivity issues")?;
+            writeln!(out, "\nPlease verify your API key and try again.")?;
+
+            // Interactive sessions need the AI; everything else still gets a useful report
+            if matches!(cli.command, Some(Commands::Ask { .. })) {
+                writeln!(out, "\nFor a basic system check without AI, use: cargo run -- --dry-run")?;
+                return Ok(());
             }
-        },
-        _ => {
-            println!("🔍 System Health Check");
-            println!("🤖 AI Assistant ({})", ai_provider.name());
-            println!("Starting comprehensive system analysis...\n");
+            writeln!(out, "\nFalling back to offline analysis (same as --no-ai).\n")?;
+            let debug_tools = DebugTools::initialize_with_availability_check().with_host_paths(config.host_paths());
+            return commands::offline::run_offline_check(cli, config, &debug_tools).await;
         }
     }
 
-    // Create AI agent configuration
-    let agent_config = AIAgentConfig {
-        max_tool_calls,
-        pause_on_limit: cli.ai_agent_mode, // Only pause in interactive agent mode
-        allow_user_continuation: cli.ai_agent_mode,
-        verbose_logging: config.output.verbose || cli.verbose,
+    let AgentContext {
+        system_info,
+        system_context,
+        initial_commands,
+        scheduling_issues,
+        security_issues,
+        time_issues,
+        dns_issues,
+        auth_issues,
+        service_statuses,
+    } = collect_agent_context(config, ui_formatter, cli, previous_report.as_ref()).await;
+
+    // A batch of questions shares the one collection above
+    if let Some(questions) = &questions {
+        // --ai-max-tool-calls is each question's budget
+        return commands::ai::run_question_batch(
+            ai_provider,
+            questions,
+            &system_context,
+            &system_info,
+            config,
+            ui_formatter,
+            cli.ai_max_tool_calls,
+        ).await;
+    }
+
+    // Interactive sessions keep the agent alive across questions
+    if let Some(Commands::Ask { question }) = &cli.command {
+        return commands::ai::run_ask_session(
+            ai_provider,
+            question.as_deref(),
+            &system_context,
+            &system_info,
+            config,
+            ui_formatter,
+            cli.ai_max_tool_calls,
+            &initial_commands,
+        ).await;
+    }
+
+    let (analysis_prompt, max_tool_calls) = analysis_prompt(cli, explained_issue.as_ref());
+    let analysis_prompt = if cli.apply_fixes.is_some() {
+        format!("{}{}", analysis_prompt, ai::FIX_COMMANDS_PROMPT)
+    } else {
+        analysis_prompt
     };
 
-    // Create and run the AI agent (always with full tool access)
-    let mut agent = ui_formatter.show_progress("Initializing AI agent with tool access", || async {
-        AIAgent::new(ai_provider, agent_config).await
-    }).await;
+    // `--redact-preview`: redact everything the AI will see, show the first prompt and send
+    // nothing unless the user agrees
+    let mut anonymizer = None;
+    let (analysis_prompt, system_context) = if cli.redact_preview {
+        let mut redactor = Anonymizer::for_local_system();
+        let analysis_prompt = redactor.anonymize_text(&analysis_prompt);
+        let system_context = redactor.anonymize_text(&system_context);
+        let prompt = ai::initial_agent_prompt(&analysis_prompt, &system_context, config.ai.explain_reasoning);
+        if !confirm_redacted_prompt(&prompt, ai_provider.name(), cli.yes)? {
+            writeln!(out, "Nothing was sent.")?;
+            return Ok(());
+        }
+        anonymizer = Some(redactor);
+        (analysis_prompt, system_context)
+    } else {
+        (analysis_prompt, system_context)
+    };
+
+    // `ai.per_category_analysis`: a full check is analyzed one category at a time, without
+    // the agent. Redacted runs keep the single analysis the preview showed.
+    let per_category = config.ai.per_category_analysis
+        && !cli.redact_preview
+        && explained_issue.is_none()
+        && cli.problem_description.is_none()
+        && matches!(cli.check_component(), CheckComponent::All);
 
-    let result = ui_formatter.show_progress("Running AI analysis", || async {
-        agent.run(&analysis_prompt, &system_context).await
-    }).await?;
+    let (final_analysis, agent, system_info) = if per_category {
+        writeln!(out, "🔍 System Health Check")?;
+        writeln!(out, "🤖 AI Assistant ({})", ai_provider.name())?;
+        writeln!(out, "Analyzing each category separately...\n")?;
 
-    // Handle the result and potential continuation (for interactive agent mode)
-    if cli.ai_agent_mode {
-        handle_ai_agent_result(result, &mut agent, ui_formatter, config).await?;
+        let detected = output::create_system_health_report(&system_info, "", false).issues;
+        let local_issues: Vec<&output::Issue> = [&detected, &scheduling_issues, &security_issues, &time_issues, &dns_issues, &auth_issues]
+            .into_iter()
+            .flatten()
+            .collect();
+        let mut inputs = commands::ai::category_inputs(&system_info, &local_issues, config);
+        // Without the exit-code contract a verify command's status would mean nothing
+        if cli.apply_fixes.is_some() {
+            for input in &mut inputs {
+                input.input.push_str(ai::FIX_COMMANDS_PROMPT);
+            }
+        }
+        let final_analysis = ui_formatter.show_progress(&format!("Analyzing {} categories", inputs.len()), || {
+            commands::ai::run_per_category_analysis(ai_provider, inputs)
+        }).await;
+        writeln!(out, "\n🎯 Analysis Result:")?;
+        writeln!(out, "{}", final_analysis)?;
+        (final_analysis, None, Some(system_info))
     } else {
-        // For non-interactive mode, just display the result
-        match result {
-            AIAgentResult::Success { final_analysis, tool_calls_used } => {
-                println!("\n🎯 Analysis Result (used {} tools):", tool_calls_used);
-                println!("{}", final_analysis);
-                
-                if config.output.verbose {
-                    println!("\n📊 Tool Usage Summary:");
-                    println!("{}", agent.get_conversation_summary());
+        // Display appropriate header based on the analysis type
+        match (&explained_issue, &cli.command, &cli.problem_description) {
+            (Some(issue), _, _) => {
+                writeln!(out, "🔍 Known Issue: {} ({})", issue.title, issue.id)?;
+                writeln!(out, "🤖 AI Assistant ({})", ai_provider.name())?;
+                writeln!(out, "Checking whether this issue applies to this system...\n")?;
+            },
+            (_, Some(Commands::Check { component }), _) => {
+                writeln!(out, "🔍 Component Check: {:?}", component)?;
+                writeln!(out, "🤖 AI Assistant ({})", ai_provider.name())?;
+                writeln!(out, "Analyzing {} component...\n", component.as_str())?;
+            },
+            (_, _, Some(problem)) => {
+                if cli.ai_agent_mode {
+                    writeln!(out, "🤖 AI Agent Mode - Iterative Problem Solving")?;
+                    writeln!(out, "Problem: {}", problem)?;
+                    writeln!(out, "Max tool calls: {}", cli.ai_max_tool_calls)?;
+                    writeln!(out, "Starting analysis...\n")?;
+                } else {
+                    writeln!(out, "❓ Question: {}", problem)?;
+                    writeln!(out, "🤖 AI Assistant ({})", ai_provider.name())?;
+                    writeln!(out, "Analyzing your question and determining which tools to run...\n")?;
                 }
+            },
+            _ => {
+                writeln!(out, "🔍 System Health Check")?;
+                writeln!(out, "🤖 AI Assistant ({})", ai_provider.name())?;
+                writeln!(out, "Starting comprehensive system analysis...\n")?;
             }
-            AIAgentResult::LimitReached { partial_analysis, tool_calls_used } => {
-                println!("\n⚠️  Analysis stopped at tool limit ({} tools used):", tool_calls_used);
-                println!("{}", partial_analysis);
-            }
-            AIAgentResult::Error { error, tool_calls_used } => {
-                println!("\n❌ Analysis failed after {} tool calls:", tool_calls_used);
-                println!("Error: {}", error);
-            }
-            AIAgentResult::PausedForUserInput { reason, .. } => {
-                // In non-interactive mode, treat pause as completion
-                println!("\n🎯 Analysis Result:");
-                println!("{}", reason);
+        }
+
+        // Create AI agent configuration
+        let agent_config = AIAgentConfig {
+            max_tool_calls,
+            pause_on_limit: cli.ai_agent_mode, // Only pause in interactive agent mode
+            allow_user_continuation: cli.ai_agent_mode,
+            verbose_logging: config.get_verbosity().is_detailed(),
+            explain_reasoning: config.ai.explain_reasoning,
+            context_window: config.ai.context_window,
+        };
+
+        // Create and run the AI agent (always with full tool access)
+        let mut agent = ui_formatter.show_progress("Initializing AI agent with tool access", || async {
+            AIAgent::new(ai_provider, agent_config)
+                .await
+                .with_host_paths(config.host_paths())
+                .with_collect_options(config.collect_options())
+                .with_tool_retries(config.tools.retries)
+                .with_system_info(system_info)
+                .with_streaming(ui_formatter.shows_progress())
+        }).await;
+        if let Some(anonymizer) = anonymizer {
+            agent = agent.with_anonymizer(anonymizer);
+        }
+
+        let result = ui_formatter.show_progress("Running AI analysis", || async {
+            agent.run(&analysis_prompt, &system_context).await
+        }).await?;
+
+        // Handle the result and potential continuation (for interactive agent mode)
+        let final_analysis = if cli.ai_agent_mode {
+            handle_ai_agent_result(result, &mut agent, ui_formatter, config, &mut out).await?
+        } else {
+            // For non-interactive mode, just display the result
+            match result {
+                AIAgentResult::Success { final_analysis, tool_calls_used } => {
+                    writeln!(out, "\n🎯 Analysis Result (used {} tools):", tool_calls_used)?;
+                    writeln!(out, "{}", final_analysis)?;
+                
+                    if config.get_verbosity().is_detailed() {
+                        writeln!(out, "\n📊 Tool Usage Summary:")?;
+                        writeln!(out, "{}", agent.get_conversation_summary())?;
+                    }
+                    final_analysis
+                }
+                AIAgentResult::LimitReached { partial_analysis, tool_calls_used } => {
+                    writeln!(out, "\n⚠️  Analysis stopped at tool limit ({} tools used):", tool_calls_used)?;
+                    writeln!(out, "{}", partial_analysis)?;
+                    partial_analysis
+                }
+                AIAgentResult::Error { error, tool_calls_used } => {
+                    writeln!(out, "\n❌ Analysis failed after {} tool calls:", tool_calls_used)?;
+                    writeln!(out, "Error: {}", error)?;
+                    format!("Analysis failed: {}", error)
+                }
+                AIAgentResult::PausedForUserInput { reason, .. } => {
+                    // In non-interactive mode, treat pause as completion
+                    writeln!(out, "\n🎯 Analysis Result:")?;
+                    writeln!(out, "{}", reason)?;
+                    reason
+                }
             }
+        };
+        (final_analysis, Some(agent), None)
+    };
+
+    let transcript = agent.as_ref().map(AIAgent::get_tool_call_transcript).unwrap_or_default();
+
+    // Emit a machine-readable report, scoped to the checked component, including the
+    // agent's tool call transcript
+    if config.get_output_format() != OutputFormat::Text {
+        // The per-category run's collection, or the agent's copy, which is current if it
+        // refreshed it during the analysis
+        let full_sys_info = system_info
+            .or_else(|| agent.as_ref().and_then(|agent| agent.system_info().cloned()))
+            .unwrap_or_else(|| collect_system_info_with(&config.collect_options()));
+        let mut report = output::create_system_health_report(
+            &full_sys_info,
+            &final_analysis,
+            config.get_verbosity().is_detailed(),
+        );
+        report.agent_transcript = transcript.clone();
+        report.apply_output_config(&config.output);
+        if !scheduling_issues.is_empty() && report.status.overall == "healthy" {
+            report.status.overall = "warning".to_string();
+        }
+        report.issues.extend(scheduling_issues);
+        report.issues.extend(security_issues);
+        report.issues.extend(time_issues);
+        report.issues.extend(dns_issues);
+        report.issues.extend(auth_issues);
+        output::add_service_status_issues(&mut report.issues, &service_statuses);
+        report.attach_fix_suggestions();
+        report.scope_to(&cli.check_component());
+
+        output::print_report(&report, config.get_output_format(), config.get_verbosity());
+    }
+
+    if config.ai.explain_reasoning && matches!(config.get_output_format(), OutputFormat::Text) {
+        ui::print_reasoning_transcript(ui_formatter, &transcript);
+    }
+
+    // Initial diagnostics plus every tool the agent ran, for reproducing the analysis by hand
+    if config.output.show_commands && matches!(config.get_output_format(), OutputFormat::Text) {
+        ui::print_commands_footer(
+            ui_formatter,
+            initial_commands
+                .iter()
+                .map(String::as_str)
+                .chain(transcript.iter().map(|record| record.command.as_str())),
+        );
+    }
+
+    // Last, so the fixes follow the analysis they came from. Machine-readable output keeps
+    // stdout for the report.
+    if let Some(mode) = cli.apply_fixes {
+        let suggestions = output::parse_fix_commands(&final_analysis);
+        let log_path = std::path::Path::new(&cli.fix_log);
+        if config.get_output_format() == OutputFormat::Text {
+            commands::apply_fixes::apply_fixes(mode, &suggestions, cli.allow_destructive, log_path, &mut std::io::stdout()).await?;
+        } else {
+            commands::apply_fixes::apply_fixes(mode, &suggestions, cli.allow_destructive, log_path, &mut std::io::stderr()).await?;
         }
     }
 
     Ok(())
 }
 
-/// Handle AI agent results with potential user interaction (for agent mode)
+/// Handle AI agent results with potential user interaction (for agent mode).
+/// Returns the last analysis text produced by the agent.
 async fn handle_ai_agent_result(
     mut result: AIAgentResult,
     agent: &mut AIAgent,
     ui_formatter: &UIFormatter,
     config: &RaidConfig,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use std::io::{self, Write};
+    out: &mut dyn std::io::Write,
+) -> Result<String, Box<dyn std::error::Error>> {
+    use std::io;
+
+    let mut last_analysis = String::new();
 
     loop {
         match result {
             AIAgentResult::Success { final_analysis, tool_calls_used } => {
-                println!("\n🎯 Final Analysis (used {} tools):", tool_calls_used);
-                println!("{}", final_analysis);
+                writeln!(out, "\n🎯 Final Analysis (used {} tools):", tool_calls_used)?;
+                writeln!(out, "{}", final_analysis)?;
                 
-                if config.output.verbose {
-                    println!("\n📊 Tool Usage Summary:");
-                    println!("{}", agent.get_conversation_summary());
+                if config.get_verbosity().is_detailed() {
+                    writeln!(out, "\n📊 Tool Usage Summary:")?;
+                    writeln!(out, "{}", agent.get_conversation_summary())?;
                 }
+                last_analysis = final_analysis;
                 break;
             }
             AIAgentResult::LimitReached { partial_analysis, tool_calls_used } => {
-                println!("\n⚠️  Analysis paused at tool limit ({} tools used):", tool_calls_used);
-                println!("{}", partial_analysis);
+                writeln!(out, "\n⚠️  Analysis paused at tool limit ({} tools used):", tool_calls_used)?;
+                writeln!(out, "{}", partial_analysis)?;
+                last_analysis = partial_analysis;
                 
                 // Ask if user wants to continue
-                print!("\nWould you like to continue with more tool calls? (y/n): ");
-                io::stdout().flush()?;
+                write!(out, "\nWould you like to continue with more tool calls? (y/n): ")?;
+                out.flush()?;
                 
                 let mut input = String::new();
                 io::stdin().read_line(&mut input)?;
                 
                 if input.trim().to_lowercase().starts_with('y') {
-                    println!("Continuing analysis...");
+                    writeln!(out, "Continuing analysis...")?;
                     result = ui_formatter.show_progress("Continuing AI analysis", || async {
                         agent.continue_after_limit().await
                     }).await?;
                 } else {
-                    println!("Analysis stopped by user.");
+                    writeln!(out, "Analysis stopped by user.")?;
                     break;
                 }
             }
             AIAgentResult::PausedForUserInput { reason, tool_calls_used } => {
-                println!("\n🤖 AI Agent needs more information ({} tools used so far):", tool_calls_used);
-                println!("{}", reason);
+                writeln!(out, "\n🤖 AI Agent needs more information ({} tools used so far):", tool_calls_used)?;
+                writeln!(out, "{}", reason)?;
                 
-                print!("\nYour response: ");
-                io::stdout().flush()?;
+                write!(out, "\nYour response: ")?;
+                out.flush()?;
                 
                 let mut user_input = String::new();
                 io::stdin().read_line(&mut user_input)?;
@@ -564,31 +1186,36 @@ async fn handle_ai_agent_result(
                         agent.continue_with_input(user_input.trim()).await
                     }).await?;
                 } else {
-                    println!("No input provided. Ending analysis.");
+                    writeln!(out, "No input provided. Ending analysis.")?;
                     break;
                 }
             }
             AIAgentResult::Error { error, tool_calls_used } => {
-                println!("\n❌ Analysis failed after {} tool calls:", tool_calls_used);
-                println!("Error: {}", error);
+                writeln!(out, "\n❌ Analysis failed after {} tool calls:", tool_calls_used)?;
+                writeln!(out, "Error: {}", error)?;
                 break;
             }
         }
     }
 
-    Ok(())
+    Ok(last_analysis)
 }
 
 async fn run_issues_management(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
-    let db = known_issues::KnownIssuesDatabase::new().await;
+    let db = known_issues::KnownIssuesDatabase::shared().await;
 
     if let Some(Commands::Issues {
         action,
         issue_id,
         query,
+        file,
+        overwrite,
     }) = &cli.command
     {
         match action {
+            IssueAction::List if cli.output_format == Some(OutputFormat::Csv) => {
+                known_issues::write_issues_csv(std::io::stdout().lock(), &db.get_all_issues().await)?;
+            }
             IssueAction::List => {
                 println!("📋 Known Issues Database");
                 println!("========================");
@@ -670,6 +1297,37 @@ async fn run_issues_management(cli: &Cli) -> Result<(), Box<dyn std::error::Erro
                     "❌ Delete functionality not yet implemented. This would allow deleting known issues."
                 );
             }
+            IssueAction::Export => {
+                if let Some(path) = file {
+                    let count = db.export_file(std::path::Path::new(path)).await?;
+                    println!("✅ Exported {} issues to {}", count, path);
+                } else {
+                    println!("❌ File path required for 'export' action. Use --file <path>");
+                }
+            }
+            IssueAction::Import => {
+                if let Some(path) = file {
+                    let Some(store) = known_issues::KnownIssuesDatabase::persisted_issues_path() else {
+                        println!("❌ Could not determine the config directory to persist imported issues.");
+                        return Ok(());
+                    };
+                    match db.import_file(std::path::Path::new(path), *overwrite, &store).await {
+                        Ok(summary) => {
+                            println!(
+                                "✅ Imported {}: {} added, {} updated, {} skipped",
+                                path, summary.added, summary.updated, summary.skipped
+                            );
+                            if summary.skipped > 0 && !overwrite {
+                                println!("💡 Use --overwrite to replace existing issues with the same id");
+                            }
+                            println!("📁 Saved to {}", store.display());
+                        }
+                        Err(e) => println!("❌ Failed to import {}: {}", path, e),
+                    }
+                } else {
+                    println!("❌ File path required for 'import' action. Use --file <path>");
+                }
+            }
         }
     }
 
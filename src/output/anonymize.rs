@@ -0,0 +1,217 @@
+use super::SystemHealthReport;
+use regex::{Captures, Regex};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Replaces hostnames, IP and MAC addresses and usernames with pseudonyms. The same value
+/// always maps to the same pseudonym, so a report can still be correlated after anonymizing.
+pub struct Anonymizer {
+    names: Option<Regex>,
+    hostnames: Vec<String>,
+    pseudonyms: HashMap<String, String>,
+    counts: HashMap<&'static str, usize>,
+}
+
+impl Anonymizer {
+    /// Anonymize the given hostnames and usernames along with every IP and MAC address
+    pub fn new(hostnames: Vec<String>, usernames: Vec<String>) -> Self {
+        let mut names: Vec<String> = hostnames
+            .iter()
+            .chain(usernames.iter())
+            .filter(|name| name.len() > 1 && name.as_str() != "localhost")
+            .cloned()
+            .collect();
+        // Longest first so "web-1.example.com" wins over "web-1"
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        names.dedup();
+
+        let names = (!names.is_empty()).then(|| {
+            let alternation: Vec<String> = names.iter().map(|name| regex::escape(name)).collect();
+            Regex::new(&format!(r"\b(?:{})\b", alternation.join("|"))).expect("escaped names form a valid regex")
+        });
+
+        Self {
+            names,
+            hostnames,
+            pseudonyms: HashMap::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Anonymizer for this machine: its hostname (short and FQDN), the current user and
+    /// regular login accounts (UID 1000 and up) from /etc/passwd
+    pub fn for_local_system() -> Self {
+        let mut hostnames = Vec::new();
+        for name in [
+            std::fs::read_to_string("/etc/hostname").ok(),
+            std::fs::read_to_string("/proc/sys/kernel/hostname").ok(),
+            std::env::var("HOSTNAME").ok(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            let name = name.trim().to_string();
+            if let Some((short, _)) = name.split_once('.') {
+                hostnames.push(short.to_string());
+            }
+            if !name.is_empty() {
+                hostnames.push(name);
+            }
+        }
+
+        let mut usernames: Vec<String> = std::env::var("USER").into_iter().collect();
+        if let Ok(passwd) = std::fs::read_to_string("/etc/passwd") {
+            usernames.extend(login_users(&passwd));
+        }
+        usernames.retain(|user| user != "root");
+
+        Self::new(hostnames, usernames)
+    }
+
+    /// Anonymize every string in the report, including analysis text, tool output and journal messages
+    pub fn anonymize_report(&mut self, report: &mut SystemHealthReport) {
+        let Ok(mut value) = serde_json::to_value(&*report) else { return };
+        self.anonymize_value(&mut value);
+        if let Ok(anonymized) = serde_json::from_value(value) {
+            *report = anonymized;
+        }
+    }
+
+    fn anonymize_value(&mut self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.anonymize_text(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.anonymize_value(item)),
+            Value::Object(fields) => fields.values_mut().for_each(|field| self.anonymize_value(field)),
+            _ => {}
+        }
+    }
+
+    pub fn anonymize_text(&mut self, text: &str) -> String {
+        let text = replace_all(&MAC_PATTERN, text, |mac| {
+            let lower = mac.to_lowercase();
+            (lower != "00:00:00:00:00:00" && lower != "ff:ff:ff:ff:ff:ff").then(|| self.pseudonym("mac", &lower))
+        });
+        let text = replace_all(&IPV6_PATTERN, &text, |candidate| {
+            let address: Ipv6Addr = candidate.parse().ok()?;
+            (!address.is_loopback() && !address.is_unspecified()).then(|| self.pseudonym("ipv6", candidate))
+        });
+        let text = replace_all(&IPV4_PATTERN, &text, |candidate| {
+            let address: Ipv4Addr = candidate.parse().ok()?;
+            let keep = address.is_loopback() || address.is_unspecified() || address.is_broadcast();
+            (!keep).then(|| self.pseudonym("ipv4", candidate))
+        });
+
+        match self.names.clone() {
+            Some(names) => replace_all(&names, &text, |name| {
+                let kind = if self.hostnames.iter().any(|host| host == name) { "host" } else { "user" };
+                Some(self.pseudonym(kind, name))
+            }),
+            None => text,
+        }
+    }
+
+    fn pseudonym(&mut self, kind: &'static str, value: &str) -> String {
+        if let Some(pseudonym) = self.pseudonyms.get(value) {
+            return pseudonym.clone();
+        }
+
+        let count = self.counts.entry(kind).or_insert(0);
+        *count += 1;
+        let n = *count;
+        let pseudonym = match kind {
+            "ipv4" => format!("10.0.{}.{}", n / 256, n % 256),
+            "ipv6" => format!("fd00::{:x}", n),
+            "mac" => format!("02:00:00:00:{:02x}:{:02x}", n / 256, n % 256),
+            _ => format!("{}-{}", kind, n),
+        };
+        self.pseudonyms.insert(value.to_string(), pseudonym.clone());
+        pseudonym
+    }
+}
+
+static MAC_PATTERN: std::sync::LazyLock<Regex> =
+    std::sync::LazyLock::new(|| Regex::new(r"\b[0-9A-Fa-f]{2}(?::[0-9A-Fa-f]{2}){5}\b").unwrap());
+static IPV4_PATTERN: std::sync::LazyLock<Regex> =
+    std::sync::LazyLock::new(|| Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").unwrap());
+/// Candidates only; `Ipv6Addr` parsing rejects timestamps like "10:00:00"
+static IPV6_PATTERN: std::sync::LazyLock<Regex> =
+    std::sync::LazyLock::new(|| Regex::new(r"[0-9A-Fa-f]*:[0-9A-Fa-f:]*:[0-9A-Fa-f]*").unwrap());
+
+/// Replace matches for which `replacement` returns a value, leaving the rest untouched.
+/// Matches glued to a word character (e.g. `std::fs`) are skipped.
+fn replace_all(pattern: &Regex, text: &str, mut replacement: impl FnMut(&str) -> Option<String>) -> String {
+    pattern
+        .replace_all(text, |caps: &Captures| {
+            let found = caps.get(0).expect("group 0 always matches");
+            let is_word = |c: char| c.is_alphanumeric() || c == '_';
+            let glued = text[..found.start()].chars().next_back().is_some_and(is_word)
+                || text[found.end()..].chars().next().is_some_and(is_word);
+            if glued {
+                return found.as_str().to_string();
+            }
+            replacement(found.as_str()).unwrap_or_else(|| found.as_str().to_string())
+        })
+        .into_owned()
+}
+
+/// Accounts with UID 1000 or higher (regular users) from /etc/passwd content
+pub fn login_users(passwd: &str) -> Vec<String> {
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            let uid: u32 = fields.get(2)?.parse().ok()?;
+            // 65534 is "nobody"
+            (1000..65534).contains(&uid).then(|| fields[0].to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anonymize_text_is_consistent() {
+        let mut anonymizer = Anonymizer::new(vec!["web-1".to_string()], vec!["alice".to_string()]);
+        let text = "web-1 sshd: Accepted key for alice from 192.168.1.20 (eth0 52:54:00:ab:cd:ef), \
+                    then 192.168.1.21, then 192.168.1.20 again via fe80::5054:ff:feab:cdef at 10:00:00";
+
+        let anonymized = anonymizer.anonymize_text(text);
+        assert_eq!(
+            anonymized,
+            "host-1 sshd: Accepted key for user-1 from 10.0.0.1 (eth0 02:00:00:00:00:01), \
+             then 10.0.0.2, then 10.0.0.1 again via fd00::1 at 10:00:00"
+        );
+        assert_eq!(anonymizer.anonymize_text("ping 192.168.1.21"), "ping 10.0.0.2");
+    }
+
+    #[test]
+    fn test_non_identifying_values_are_kept() {
+        let mut anonymizer = Anonymizer::new(vec!["localhost".to_string()], vec![]);
+        let text = "listening on 127.0.0.1 and ::1 and 0.0.0.0, std::fs::read failed on localhost";
+        assert_eq!(anonymizer.anonymize_text(text), text);
+    }
+
+    #[test]
+    fn test_anonymize_report_reaches_nested_strings() {
+        let mut info = crate::sysinfo::SystemInfo::default();
+        info.systemd.failed_units = vec!["backup@alice.service".to_string()];
+        let mut report = crate::output::create_system_health_report(&info, "web-1 cannot reach 10.1.2.3", false);
+
+        Anonymizer::new(vec!["web-1".to_string()], vec!["alice".to_string()]).anonymize_report(&mut report);
+        assert_eq!(report.analysis, "host-1 cannot reach 10.0.0.1");
+        assert_eq!(report.status.services.failed_units, vec!["backup@user-1.service"]);
+        assert!(report.issues[0].message.contains("user-1"));
+    }
+
+    #[test]
+    fn test_login_users() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\n\
+                      daemon:x:1:1:daemon:/usr/sbin:/usr/sbin/nologin\n\
+                      nobody:x:65534:65534:nobody:/nonexistent:/usr/sbin/nologin\n\
+                      alice:x:1000:1000:Alice:/home/alice:/bin/bash\n";
+        assert_eq!(login_users(passwd), vec!["alice"]);
+    }
+}
@@ -1,15 +1,203 @@
-use crate::sysinfo::SystemInfo;
+use crate::cli::{CheckComponent, OutputFormat};
+use crate::config::OutputConfig;
+use crate::sysinfo::{compare_kernel_versions, format_bytes, CgroupInfo, ContainerInfo as CollectedContainer, Environment, EnvironmentKind, PressureInfo, FilesystemUsage, InterfaceCounters, JournalDiskUsage, PressureStats, SwapInfo, SystemInfo, SystemdInfo, ZombieParent};
+use crate::tools::security_debug::{describe_denial, BRUTE_FORCE_THRESHOLD};
+use crate::tools::system_info::CLOCK_OFFSET_WARN_MS;
+use crate::tools::{
+    DnsResolutionStatus, EventInfo, FailedLoginSource, PersistentVolumeClaimInfo, PodInfo, SecurityDenial, ServiceStatusDetail, TimeSyncStatus,
+    VolumeBindingState,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
 
+pub mod anonymize;
 pub mod printers;
 
+/// How much detail the text printers show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    /// Silent on healthy systems; only summaries and counts otherwise
+    Quiet,
+    /// Significant issues, capped to a few entries
+    #[default]
+    Normal,
+    /// More entries, including healthy units and containers
+    Detailed,
+    /// Everything, with no caps
+    Full,
+}
+
+impl Verbosity {
+    /// Map `-q` and the number of `-v` flags to a verbosity level
+    pub fn from_flags(quiet: bool, verbose_count: u8) -> Self {
+        if quiet {
+            return Verbosity::Quiet;
+        }
+        match verbose_count {
+            0 => Verbosity::Normal,
+            1 => Verbosity::Detailed,
+            _ => Verbosity::Full,
+        }
+    }
+
+    /// Scale a list cap that applies in Normal mode to this level
+    pub fn limit(&self, normal: usize) -> usize {
+        match self {
+            Verbosity::Quiet => 0,
+            Verbosity::Normal => normal,
+            Verbosity::Detailed => normal.saturating_mul(4),
+            Verbosity::Full => usize::MAX,
+        }
+    }
+
+    /// Whether extra detail sections should be printed
+    pub fn is_detailed(&self) -> bool {
+        *self >= Verbosity::Detailed
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verbosity::Quiet => "quiet",
+            Verbosity::Normal => "normal",
+            Verbosity::Detailed => "detailed",
+            Verbosity::Full => "full",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemHealthReport {
     pub timestamp: String,
-    pub system_info: SystemInfo,
+    /// Where the report was collected; kept even when the raw system info is left out
+    #[serde(default)]
+    pub environment: Environment,
+    /// Raw collected data; omitted when `output.include_raw_sysinfo` is false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_info: Option<SystemInfo>,
     pub analysis: String,
+    /// The component a `check --component` report is scoped to; absent for full reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
     pub status: SystemStatus,
     pub issues: Vec<Issue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agent_transcript: Vec<ToolCallRecord>,
+    /// Known issues matched against the collected data by the offline analysis
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_issue_matches: Vec<KnownIssueMatch>,
+    /// Every Verify/Fix command pair in the AI analysis, matched to an issue or not
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggested_fixes: Vec<FixSuggestion>,
+}
+
+impl SystemHealthReport {
+    /// Keep only the component's issues and derive the overall status from them.
+    /// `All` and `Debug` leave the report untouched.
+    pub fn scope_to(&mut self, component: &CheckComponent) {
+        let Some(categories) = component_issue_categories(component) else {
+            return;
+        };
+
+        self.component = Some(component.as_str().to_string());
+        self.issues.retain(|issue| categories.contains(&issue.category.as_str()));
+        self.status.overall = if self.issues.is_empty() {
+            "healthy".to_string()
+        } else if self.issues.iter().any(|issue| matches!(issue.severity.as_str(), "high" | "critical")) {
+            "critical".to_string()
+        } else {
+            "warning".to_string()
+        };
+    }
+
+    /// Extract the analysis's Verify/Fix commands into `suggested_fixes` and attach each to
+    /// the issue it describes best, as `suggested_fix` and `verify_command`. A suggestion
+    /// needs a score of 2 (two shared words, or a shared unit name or path) to be attached,
+    /// and each issue takes the first suggestion that matches it.
+    pub fn attach_fix_suggestions(&mut self) {
+        self.suggested_fixes = parse_fix_commands(&self.analysis);
+        for suggestion in &self.suggested_fixes {
+            let words = match_words(&suggestion.issue);
+            let best = self
+                .issues
+                .iter_mut()
+                .filter(|issue| issue.suggested_fix.is_none() && issue.verify_command.is_none())
+                .map(|issue| (match_score(&words, issue), issue))
+                .filter(|(score, _)| *score >= 2)
+                .min_by_key(|(score, _)| std::cmp::Reverse(*score));
+            if let Some((_, issue)) = best {
+                issue.suggested_fix = suggestion.fix_command.clone();
+                issue.verify_command = suggestion.verify_command.clone();
+            }
+        }
+    }
+
+    /// Whether no issues were found (used by quiet mode to stay silent)
+    pub fn is_healthy(&self) -> bool {
+        self.status.overall == "healthy"
+    }
+
+    /// Trim the serialized payload per `output.include_raw_sysinfo` and
+    /// `output.max_journal_entries`, then pseudonymize it when `output.anonymize` is set.
+    /// Error counts still reflect every entry.
+    pub fn apply_output_config(&mut self, output: &OutputConfig) {
+        if !output.include_raw_sysinfo {
+            self.system_info = None;
+        }
+
+        if let Some(max) = output.max_journal_entries {
+            if let Some(info) = &mut self.system_info {
+                info.journal.recent_errors.truncate(max);
+                info.journal.recent_warnings.truncate(max);
+                info.journal.boot_errors.truncate(max);
+            }
+            self.status.logs.recent_errors.truncate(max);
+            self.status.logs.boot_errors.truncate(max);
+        }
+
+        if output.anonymize {
+            anonymize::Anonymizer::for_local_system().anonymize_report(self);
+        }
+    }
+
+    /// Load a report written with `--output-format json` or `yaml` (YAML by extension)
+    pub fn read_from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        let is_yaml = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"))
+            .unwrap_or(false);
+        if is_yaml {
+            Ok(serde_yaml::from_str(&content)?)
+        } else {
+            Ok(serde_json::from_str(&content)?)
+        }
+    }
+
+    /// Summarize this report as AI context for a follow-up run (`--continue-from`)
+    pub fn previous_run_context(&self) -> String {
+        let mut context = format!(
+            "Previous RAID run ({}), overall status: {}\n",
+            self.timestamp, self.status.overall
+        );
+        if self.issues.is_empty() {
+            context.push_str("Issues reported then: none\n");
+        } else {
+            context.push_str("Issues reported then:\n");
+            for issue in &self.issues {
+                context.push_str(&format!("- [{}] {}: {}\n", issue.severity, issue.category, issue.message));
+            }
+        }
+        if !self.analysis.trim().is_empty() {
+            context.push_str(&format!("Analysis given then:\n{}\n", self.analysis.trim()));
+        }
+        context.push_str(
+            "Compare the current state with this previous run: note which issues are resolved, persist or are new, and whether the earlier recommendations appear to have helped.\n",
+        );
+        context
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -66,6 +254,863 @@ pub struct Issue {
     pub severity: String, // "low", "medium", "high", "critical"
     pub message: String,
     pub details: Option<String>,
+    /// The AI's `**Fix**` command for this issue, when its analysis names it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_fix: Option<String>,
+    /// The AI's `**Verify**` command for this issue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_command: Option<String>,
+}
+
+impl Issue {
+    /// An issue found by a check; the fix and verify commands are only filled in from an AI analysis
+    pub fn new(
+        category: impl Into<String>,
+        severity: impl Into<String>,
+        message: impl Into<String>,
+        details: Option<String>,
+    ) -> Self {
+        Self {
+            category: category.into(),
+            severity: severity.into(),
+            message: message.into(),
+            details,
+            suggested_fix: None,
+            verify_command: None,
+        }
+    }
+}
+
+/// One `**Issue**` entry of an AI analysis with its `**Verify**` and `**Fix**` commands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixSuggestion {
+    pub issue: String,
+    pub verify_command: Option<String>,
+    pub fix_command: Option<String>,
+}
+
+/// Scrape the `- **Issue**: ...` / `- **Verify**: \`...\`` / `- **Fix**: \`...\`` entries the
+/// analysis prompt asks for. Verify and Fix lines belong to the Issue line before them; a
+/// line without a backticked command (prose advice) gives no command.
+pub fn parse_fix_commands(analysis: &str) -> Vec<FixSuggestion> {
+    let mut suggestions: Vec<FixSuggestion> = Vec::new();
+    for line in analysis.lines() {
+        let Some((label, value)) = labelled_line(line) else {
+            continue;
+        };
+        if label == "issue" {
+            suggestions.push(FixSuggestion { issue: value.to_string(), verify_command: None, fix_command: None });
+            continue;
+        }
+        let Some(command) = first_code_span(value) else {
+            continue;
+        };
+        if suggestions.is_empty() {
+            suggestions.push(FixSuggestion { issue: String::new(), verify_command: None, fix_command: None });
+        }
+        let current = suggestions.last_mut().expect("pushed above");
+        let slot = if label == "verify" { &mut current.verify_command } else { &mut current.fix_command };
+        slot.get_or_insert(command);
+    }
+    suggestions.retain(|suggestion| suggestion.verify_command.is_some() || suggestion.fix_command.is_some());
+    suggestions
+}
+
+/// `- **Fix**: rest` or `**Fix:** rest` -> ("fix", "rest"), for the Issue, Verify and Fix labels
+fn labelled_line(line: &str) -> Option<(&'static str, &str)> {
+    let line = line.trim_start();
+    let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+    let line = line.trim_start().strip_prefix("**")?;
+    let (label, rest) = line.split_once("**")?;
+    let label = label.trim_end_matches(':').trim();
+    let rest = rest.trim_start().strip_prefix(':').unwrap_or(rest).trim();
+    ["issue", "verify", "fix"]
+        .into_iter()
+        .find(|known| label.eq_ignore_ascii_case(known))
+        .map(|known| (known, rest))
+}
+
+/// The text of the first `` `code` `` span
+fn first_code_span(text: &str) -> Option<String> {
+    let (_, rest) = text.split_once('`')?;
+    let (code, _) = rest.split_once('`')?;
+    let code = code.trim();
+    (!code.is_empty()).then(|| code.to_string())
+}
+
+/// Words too common in issue descriptions to tie a suggestion to an issue
+const MATCH_STOPWORDS: [&str; 12] = [
+    "with", "that", "this", "from", "have", "been", "were", "than", "into", "issue", "system", "check",
+];
+
+/// Lowercase words of four or more characters, keeping unit names and paths whole
+/// (`nginx.service`, `/var/log`)
+fn match_words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '-' | '_' | '/' | '@')))
+        .map(|word| word.trim_matches(|c: char| matches!(c, '.' | '-')).to_lowercase())
+        .filter(|word| word.len() >= 4 && !MATCH_STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// How well a suggestion's Issue text matches a report issue: each shared word counts once,
+/// a shared unit name or path (`nginx.service`, `/var`) twice
+fn match_score(suggestion: &HashSet<String>, issue: &Issue) -> usize {
+    let mut text = issue.message.clone();
+    if let Some(details) = &issue.details {
+        text.push(' ');
+        text.push_str(details);
+    }
+    match_words(&text)
+        .intersection(suggestion)
+        .map(|word| if word.contains(['.', '/']) { 2 } else { 1 })
+        .sum()
+}
+
+/// A known issue whose patterns matched the collected data, with its suggested fix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownIssueMatch {
+    pub id: String,
+    pub title: String,
+    pub severity: String,
+    pub confidence: f32,
+    pub fix_commands: Vec<String>,
+}
+
+/// A single tool call made by the AI agent, recorded for reproducibility
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub tool_name: String,
+    pub command: String,
+    pub success: bool,
+    pub execution_time_ms: u64,
+    pub reasoning: Option<String>,
+}
+
+/// PSI `avg60` thresholds (percent of time stalled) that count as sustained pressure:
+/// (resource, some threshold, full threshold). CPU `full` is not meaningful system-wide.
+const PRESSURE_THRESHOLDS: [(&str, f32, Option<f32>); 4] = [
+    ("CPU", 25.0, None),
+    ("Memory", 10.0, Some(5.0)),
+    ("IO", 20.0, Some(10.0)),
+    ("Cgroup memory", 10.0, Some(5.0)),
+];
+
+/// Flag sustained CPU/memory/IO pressure stalls as issues
+pub fn pressure_issues(pressure: &PressureInfo) -> Vec<Issue> {
+    let stats: [Option<&PressureStats>; 4] = [
+        pressure.cpu.as_ref(),
+        pressure.memory.as_ref(),
+        pressure.io.as_ref(),
+        pressure.cgroup_memory.as_ref(),
+    ];
+
+    let mut issues = Vec::new();
+    for ((resource, some_threshold, full_threshold), stats) in PRESSURE_THRESHOLDS.iter().zip(stats) {
+        let Some(stats) = stats else { continue };
+        let details = Some(format!(
+            "some avg10={:.2} avg60={:.2} avg300={:.2}",
+            stats.some.avg10, stats.some.avg60, stats.some.avg300
+        ));
+
+        if let (Some(full), Some(threshold)) = (stats.full, full_threshold)
+            && full.avg60 >= *threshold
+        {
+            issues.push(Issue::new(
+                "pressure",
+                "high",
+                format!(
+                    "{} pressure: all tasks stalled {:.1}% of the last minute",
+                    resource, full.avg60
+                ),
+                details,
+            ));
+        } else if stats.some.avg60 >= *some_threshold {
+            issues.push(Issue::new(
+                "pressure",
+                "medium",
+                format!(
+                    "{} pressure: some tasks stalled {:.1}% of the last minute",
+                    resource, stats.some.avg60
+                ),
+                details,
+            ));
+        }
+    }
+    issues
+}
+
+/// Controllers whose limits RAID reads, and what each limits
+const LIMIT_CONTROLLERS: [(&str, &str); 2] = [("memory", "memory limit"), ("cpu", "CPU quota")];
+
+/// Flag a memory or cpu controller that is not enabled for this process's cgroup. A limit
+/// that is set then isn't applied to it (the classic slice without `Delegate=`), and inside
+/// a container no limit given to the container can be enforced.
+pub fn cgroup_controller_issues(cgroups: &CgroupInfo, environment: &Environment) -> Vec<Issue> {
+    let in_container = matches!(environment.kind, EnvironmentKind::Container | EnvironmentKind::KubernetesPod);
+    LIMIT_CONTROLLERS
+        .iter()
+        .filter(|(controller, _)| cgroups.controller_enabled(controller) == Some(false))
+        .filter_map(|(controller, limit)| {
+            let limit_set = match *controller {
+                "memory" => cgroups.memory_limit_bytes().is_some(),
+                _ => cgroups.has_cpu_quota(),
+            };
+            let (severity, message) = if limit_set {
+                ("high", format!("A {} is set but the {} controller is not enabled for this cgroup", limit, controller))
+            } else if in_container {
+                ("medium", format!("The {} controller is not enabled for this container's cgroup", controller))
+            } else {
+                return None;
+            };
+            Some(Issue::new(
+                "cgroup",
+                severity,
+                message,
+                Some(format!(
+                    "{} is missing from the cgroup.controllers of {}, so {}s are not enforced on it; enable it in the parent's cgroup.subtree_control (systemd: Delegate= on the slice; Kubernetes: check the kubelet's cgroup driver)",
+                    controller, cgroups.cgroup_path, limit
+                )),
+            ))
+        })
+        .collect()
+}
+
+/// Memory usage (percent of the cgroup limit) at which an OOM kill is imminent
+const CGROUP_MEMORY_BREACH_PERCENT: f32 = 90.0;
+
+/// Share of throttled CPU periods that indicates the CPU limit is too tight
+const CGROUP_CPU_THROTTLE_PERCENT: f32 = 25.0;
+
+/// Flag cgroup memory/CPU usage that is close to its limit (e.g. a pod about to be OOM-killed)
+pub fn cgroup_limit_issues(cgroups: &CgroupInfo) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    if let (Some(percent), Some(limit), Some(usage)) = (
+        cgroups.memory_usage_percent(),
+        cgroups.memory_limit_bytes(),
+        cgroups.memory_usage,
+    ) && percent >= CGROUP_MEMORY_BREACH_PERCENT
+    {
+        issues.push(Issue::new(
+            "cgroup",
+            "high",
+            format!(
+                "Memory usage is at {:.0}% of the cgroup limit; the container is at risk of being OOM-killed",
+                percent
+            ),
+            Some(format!("{} of {}", format_bytes(usage), format_bytes(limit))),
+        ));
+    }
+
+    if let Some(throttled) = cgroups.cpu_throttled_percent
+        && throttled >= CGROUP_CPU_THROTTLE_PERCENT
+    {
+        let throttled_time = cgroups
+            .cpu_throttled_usec
+            .map(|usec| format!("{:.1}s throttled in total", usec as f64 / 1_000_000.0));
+        let details: Vec<String> = cgroups.cpu_limit.iter().cloned().chain(throttled_time).collect();
+        issues.push(Issue::new(
+            "cgroup",
+            "medium",
+            format!("CPU is throttled in {:.0}% of scheduling periods by the cgroup CPU limit", throttled),
+            (!details.is_empty()).then(|| details.join("; ")),
+        ));
+    }
+
+    issues
+}
+
+/// Flag services crash-looping in `activating (auto-restart)` or stuck starting up
+pub fn systemd_unit_issues(systemd: &SystemdInfo) -> Vec<Issue> {
+    systemd
+        .units
+        .iter()
+        .filter_map(|unit| {
+            if unit.is_restart_loop() {
+                Some(Issue::new(
+                    "service",
+                    "high",
+                    format!(
+                        "Service '{}' is crash-looping: systemd keeps restarting it ({})",
+                        unit.name,
+                        unit.state_label()
+                    ),
+                    Some(format!("Check why it exits with: journalctl -u {}", unit.name)),
+                ))
+            } else if unit.is_stuck_activating() {
+                Some(Issue::new(
+                    "service",
+                    "medium",
+                    format!("Service '{}' is stuck starting ({})", unit.name, unit.state_label()),
+                    None,
+                ))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Containers that are down, restarting, failing their health check or restarting often.
+/// Restart loops and failed health checks are high severity: the service is down or flapping.
+pub fn container_issues(containers: &[CollectedContainer]) -> Vec<Issue> {
+    containers
+        .iter()
+        .filter(|container| !container.is_healthy())
+        .map(|container| {
+            let restarts = container
+                .restart_count
+                .map(|count| format!(", {} restarts", count))
+                .unwrap_or_default();
+            let (severity, message, details) = if container.is_restarting() {
+                let exit = container
+                    .exit_code
+                    .map(|code| format!(" after exiting with code {}", code))
+                    .unwrap_or_default();
+                (
+                    "high",
+                    format!("Container '{}' is restarting{}{}", container.name, exit, restarts),
+                    Some(format!("See why it exits with: docker logs --tail 50 {}", container.name)),
+                )
+            } else if container.is_unhealthy() {
+                (
+                    "high",
+                    format!("Container '{}' is failing its health check{}", container.name, restarts),
+                    Some(format!(
+                        "See the health check output with: docker inspect --format '{{{{json .State.Health}}}}' {}",
+                        container.name
+                    )),
+                )
+            } else if container.is_running() {
+                (
+                    "medium",
+                    format!(
+                        "Container '{}' has restarted {} times",
+                        container.name,
+                        container.restart_count.unwrap_or_default()
+                    ),
+                    Some(format!("See why it exits with: docker logs --tail 50 {}", container.name)),
+                )
+            } else {
+                ("medium", format!("Container '{}' is not running: {}", container.name, container.status), None)
+            };
+            Issue::new("container", severity, message, details)
+        })
+        .collect()
+}
+
+/// The generic issue for a unit in the failed state, before its exit is known
+fn failed_unit_message(unit: &str) -> String {
+    format!("Service '{}' has failed", unit)
+}
+
+/// A crash loop or abnormal exit read from `systemctl show`, rather than from status text
+pub fn service_status_issue(status: &ServiceStatusDetail) -> Option<Issue> {
+    let message = if status.is_crash_looping() {
+        format!(
+            "Service '{}' is crash-looping: restarted {} times, last run {}",
+            status.unit,
+            status.n_restarts.unwrap_or_default(),
+            status.exit_description()
+        )
+    } else if status.exited_abnormally() {
+        format!("Service '{}' {}", status.unit, status.exit_description())
+    } else {
+        return None;
+    };
+    Some(Issue::new(
+        "service",
+        "high",
+        message,
+        Some(format!("Check why it exits with: journalctl -u {}", status.unit)),
+    ))
+}
+
+/// Add each unit's `service_status_issue`, replacing the generic "has failed" or crash-loop issue
+/// of the same unit
+pub fn add_service_status_issues(issues: &mut Vec<Issue>, statuses: &[ServiceStatusDetail]) {
+    for status in statuses {
+        let Some(issue) = service_status_issue(status) else {
+            continue;
+        };
+        let generic = failed_unit_message(&status.unit);
+        let crash_loop = format!("Service '{}' is crash-looping", status.unit);
+        issues.retain(|existing| existing.message != generic && !existing.message.starts_with(&crash_loop));
+        issues.push(issue);
+    }
+}
+
+/// Link failed services to SELinux/AppArmor denials of the same process, which often
+/// explain a service that fails to start with nothing useful in its own logs
+pub fn security_denial_issues(denials: &[SecurityDenial], failed_units: &[String]) -> Vec<Issue> {
+    failed_units
+        .iter()
+        .filter_map(|unit| {
+            let stem = unit.strip_suffix(".service").unwrap_or(unit);
+            let related: Vec<&SecurityDenial> = denials
+                .iter()
+                .filter(|denial| {
+                    denial.process.as_deref() == Some(stem)
+                        || denial.profile.as_deref().is_some_and(|profile| profile.ends_with(&format!("/{}", stem)))
+                })
+                .collect();
+            let first = related.first()?;
+
+            let mut message = format!("Service '{}' failed and {}", unit, describe_denial(first));
+            if related.len() > 1 {
+                message.push_str(&format!(" (+{} more denials)", related.len() - 1));
+            }
+            let hint = if first.module == "SELinux" {
+                "Explain the denial with: ausearch -m avc -ts recent | audit2why"
+            } else {
+                "Review the profile with: aa-logprof (or check /etc/apparmor.d)"
+            };
+            Some(Issue::new("security", "high", message, Some(hint.to_string())))
+        })
+        .collect()
+}
+
+/// More zombies than this points at a parent that never reaps its children
+pub const ZOMBIE_WARN_COUNT: usize = 5;
+
+/// Flag a climbing zombie count, naming the parent with the most defunct children
+pub fn zombie_issues(count: usize, parents: &[ZombieParent]) -> Vec<Issue> {
+    if count <= ZOMBIE_WARN_COUNT {
+        return Vec::new();
+    }
+
+    let details = parents.first().map(|parent| {
+        format!(
+            "{} of them are children of {} (PID {}), which is not reaping them; fix or restart that process",
+            parent.zombies, parent.name, parent.pid
+        )
+    });
+    vec![Issue::new("process", "medium", format!("{} zombie (defunct) processes", count), details)]
+}
+
+/// Swap utilization (percent of total) worth a warning
+pub const SWAP_WARN_PERCENT: f64 = 50.0;
+/// Swap utilization that points at a real memory shortage
+pub const SWAP_HIGH_PERCENT: f64 = 80.0;
+
+/// Flag heavy swap use as a performance issue
+pub fn swap_issues(swap: &SwapInfo) -> Vec<Issue> {
+    let Some(percent) = swap.used_percent() else {
+        return Vec::new();
+    };
+    let severity = if percent >= SWAP_HIGH_PERCENT {
+        "high"
+    } else if percent >= SWAP_WARN_PERCENT {
+        "medium"
+    } else {
+        return Vec::new();
+    };
+
+    let details = swap.swappiness.map(|swappiness| {
+        format!(
+            "vm.swappiness={}; check memory pressure and the largest memory consumers before adding swap",
+            swappiness
+        )
+    });
+    vec![Issue::new(
+        "performance",
+        severity,
+        format!(
+            "Swap is {:.1}% used ({} of {})",
+            percent,
+            format_bytes(swap.used_bytes),
+            format_bytes(swap.total_bytes)
+        ),
+        details,
+    )]
+}
+
+/// CPU steal (percent of CPU time) at which a VM is noticeably slowed by its neighbours
+pub const CPU_STEAL_WARN_PERCENT: f32 = 10.0;
+/// CPU steal at which the host is badly oversubscribed
+pub const CPU_STEAL_HIGH_PERCENT: f32 = 25.0;
+
+/// Flag CPU time taken by the hypervisor, which slows a VM without showing up as load
+pub fn cpu_steal_issues(steal_percent: Option<f32>) -> Vec<Issue> {
+    let Some(steal) = steal_percent else {
+        return Vec::new();
+    };
+    let severity = if steal >= CPU_STEAL_HIGH_PERCENT {
+        "high"
+    } else if steal >= CPU_STEAL_WARN_PERCENT {
+        "medium"
+    } else {
+        return Vec::new();
+    };
+
+    vec![Issue::new(
+        "performance",
+        severity,
+        format!("CPU steal time is {:.1}%: the hypervisor is giving this VM's CPU time to other guests", steal),
+        Some(
+            "The host is oversubscribed or the instance has run out of burst credits; consider a larger or dedicated instance type"
+                .to_string(),
+        ),
+    )]
+}
+
+/// Recommend a reboot when the running kernel isn't the newest installed one
+pub fn kernel_reboot_issues(system_info: &SystemInfo) -> Vec<Issue> {
+    let Some(newest) = system_info.newest_installed_kernel.as_deref().filter(|_| system_info.needs_reboot) else {
+        return Vec::new();
+    };
+    let running = &system_info.running_kernel;
+    let installed = if compare_kernel_versions(newest, running).is_gt() {
+        format!("is older than the newest installed kernel {}", newest)
+    } else {
+        format!("is no longer installed (newest: {}), so its modules can't be loaded", newest)
+    };
+
+    vec![Issue::new(
+        "kernel",
+        "low",
+        format!("Running kernel {} {}; reboot to load it", running, installed),
+        Some("Security and bug fixes in the installed kernel are not active until the next boot".to_string()),
+    )]
+}
+
+/// Share of an interface's packets lost to errors or drops since boot worth a note
+pub const INTERFACE_LOSS_WARN_RATIO: f64 = 0.001;
+/// Below this many lost packets a since-boot ratio is noise (link flaps at boot and the like)
+const INTERFACE_LOSS_MIN_PACKETS: u64 = 100;
+
+/// Flag interfaces losing packets: `medium` when errors or drops grew during the collection
+/// sample, `low` when only the since-boot total is high. Loopback is skipped.
+pub fn interface_error_issues(interfaces: &[InterfaceCounters]) -> Vec<Issue> {
+    interfaces
+        .iter()
+        .filter(|interface| interface.name != "lo")
+        .filter_map(|interface| {
+            let lost = interface.errors() + interface.dropped();
+            let (severity, message) = match interface.recent.filter(|recent| recent.errors + recent.dropped > 0) {
+                Some(recent) => (
+                    "medium",
+                    format!(
+                        "Interface {} is losing packets now: {} errors and {} drops in {:.1}s ({:.1}/s)",
+                        interface.name,
+                        recent.errors,
+                        recent.dropped,
+                        recent.seconds,
+                        (recent.errors + recent.dropped) as f32 / recent.seconds.max(f32::EPSILON)
+                    ),
+                ),
+                None if lost >= INTERFACE_LOSS_MIN_PACKETS
+                    && lost as f64 >= (interface.packets() + lost) as f64 * INTERFACE_LOSS_WARN_RATIO =>
+                {
+                    (
+                        "low",
+                        format!(
+                            "Interface {} has lost {:.2}% of its packets since boot ({} errors, {} drops)",
+                            interface.name,
+                            lost as f64 / (interface.packets() + lost) as f64 * 100.0,
+                            interface.errors(),
+                            interface.dropped()
+                        ),
+                    )
+                }
+                None => return None,
+            };
+            Some(Issue::new(
+                "network",
+                severity,
+                message,
+                Some(format!(
+                    "{}; errors point at the NIC, cable or switch port, drops at full ring buffers (`ethtool -S {}`, `ethtool -g {}`)",
+                    interface.summary(),
+                    interface.name,
+                    interface.name
+                )),
+            ))
+        })
+        .collect()
+}
+
+/// Journal size, as a percent of its `SystemMaxUse` cap, at which journald is about to delete old entries
+pub const JOURNAL_CAP_WARN_PERCENT: f64 = 90.0;
+
+/// Flag a journal near its size cap, where the oldest logs start disappearing, and journald
+/// errors about rotating or writing its files
+pub fn journal_disk_issues(usage: &JournalDiskUsage) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if let (Some(cap), Some(percent)) = (usage.max_use_bytes, usage.used_percent())
+        && percent >= JOURNAL_CAP_WARN_PERCENT
+    {
+        issues.push(Issue::new(
+            "log",
+            "medium",
+            format!(
+                "Journal is at {:.1}% of its size cap ({} of {})",
+                percent,
+                format_bytes(usage.used_bytes),
+                format_bytes(cap)
+            ),
+            Some(format!(
+                "journald deletes the oldest entries to stay under the {} SystemMaxUse, so older logs are being lost; \
+                 raise SystemMaxUse in /etc/systemd/journald.conf or ship logs elsewhere",
+                if usage.max_use_configured { "configured" } else { "default" }
+            )),
+        ));
+    }
+    if let Some(latest) = usage.rotation_errors.last() {
+        issues.push(Issue::new(
+            "log",
+            "medium",
+            format!(
+                "journald reported {} error(s) rotating or writing journal files",
+                usage.rotation_errors.len()
+            ),
+            Some(format!("Most recent: {}", latest)),
+        ));
+    }
+    issues
+}
+
+/// Filesystem use (df capacity percent) worth a warning
+pub const DISK_WARN_PERCENT: u8 = 90;
+/// Filesystem use at which writes are about to fail
+pub const DISK_HIGH_PERCENT: u8 = 95;
+
+/// Flag each filesystem that is nearly full, naming its mount point
+pub fn disk_usage_issues(filesystems: &[FilesystemUsage]) -> Vec<Issue> {
+    filesystems
+        .iter()
+        .filter(|fs| fs.use_percent >= DISK_WARN_PERCENT)
+        .map(|fs| Issue::new(
+            "disk",
+            if fs.use_percent >= DISK_HIGH_PERCENT { "high" } else { "medium" },
+            format!(
+                "Filesystem {} is {}% full ({} free of {})",
+                fs.mount_point,
+                fs.use_percent,
+                format_bytes(fs.available_bytes),
+                format_bytes(fs.total_bytes)
+            ),
+            Some(format!("{} ({})", fs.filesystem, fs.fs_type)),
+        ))
+        .collect()
+}
+
+/// Flag sources with enough failed SSH logins to look like password guessing
+pub fn brute_force_issues(sources: &[FailedLoginSource]) -> Vec<Issue> {
+    sources
+        .iter()
+        .filter(|source| source.attempts >= BRUTE_FORCE_THRESHOLD)
+        .map(|source| {
+            let mut users: Vec<&str> = source.users.iter().take(5).map(String::as_str).collect();
+            if source.users.len() > users.len() {
+                users.push("...");
+            }
+            Issue::new(
+                "security",
+                "high",
+                format!(
+                    "Possible SSH brute force: {} failed logins from {} in the last 24h (users: {})",
+                    source.attempts,
+                    source.address,
+                    users.join(", ")
+                ),
+                Some(
+                    "Block the address or enable fail2ban, and make sure PasswordAuthentication is off in sshd_config".to_string(),
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Flag an unsynchronized clock or a large NTP offset; skew breaks TLS, etcd and Kubernetes
+pub fn time_sync_issues(status: &TimeSyncStatus) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if let Some(offset) = status.offset_ms
+        && offset.abs() >= CLOCK_OFFSET_WARN_MS
+    {
+        issues.push(Issue::new(
+            "time",
+            "high",
+            format!("System clock is off by {:.0} ms from NTP time", offset),
+            Some(
+                "Clock skew breaks TLS validation, etcd leader election and Kubernetes tokens; check with: chronyc tracking (or timedatectl timesync-status)".to_string(),
+            ),
+        ));
+    } else if status.synchronized == Some(false) {
+        let details = if status.ntp_service_active == Some(false) {
+            "No NTP service is running; enable one with: timedatectl set-ntp true"
+        } else {
+            "An NTP service is running but has not synchronized yet; check it can reach its servers with: timedatectl timesync-status"
+        };
+        issues.push(Issue::new(
+            "time",
+            "medium",
+            "System clock is not synchronized with NTP",
+            Some(details.to_string()),
+        ));
+    }
+    issues
+}
+
+/// Flag a host where no resolution path works: nothing that needs a name (package mirrors,
+/// registries, internal services) can be reached, whatever the network itself is doing.
+/// High severity only when the system resolver itself failed; when it couldn't be tried,
+/// the failing paths go to public servers, which egress filtering alone can block.
+pub fn dns_resolution_issues(status: &DnsResolutionStatus) -> Vec<Issue> {
+    if !status.is_failing() {
+        return Vec::new();
+    }
+    let failed: Vec<&str> = [
+        (status.system_resolver, "system resolver (getent)"),
+        (status.public_servers, "public DNS servers (dig)"),
+        (status.ping_by_name, "pinging hosts by name"),
+    ]
+    .into_iter()
+    .filter(|(resolved, _)| *resolved == Some(false))
+    .map(|(_, path)| path)
+    .collect();
+    let severity = if status.system_resolver == Some(false) { "high" } else { "medium" };
+    vec![Issue::new(
+        "network",
+        severity,
+        "DNS resolution is failing",
+        Some(format!(
+            "'{}' did not resolve through: {}. Check the nameservers in /etc/resolv.conf and the resolver with: resolvectl status (or systemctl status systemd-resolved)",
+            status.name,
+            failed.join(", ")
+        )),
+    )]
+}
+
+/// Event reasons the scheduler records when it cannot place a pod
+const SCHEDULING_FAILURE_REASONS: [&str; 2] = ["FailedScheduling", "Unschedulable"];
+
+/// Explain unscheduled Pending pods using the scheduler's most recent failure event
+/// (e.g. "0/3 nodes are available: 3 Insufficient cpu.")
+pub fn pending_pod_issues(pods: &[PodInfo], events: &[EventInfo]) -> Vec<Issue> {
+    pods.iter()
+        .filter(|pod| pod.status == "Pending" && pod.node.is_none())
+        .filter_map(|pod| {
+            let object = format!("pod/{}", pod.name);
+            let event = events
+                .iter()
+                .filter(|e| e.object == object && e.namespace == pod.namespace)
+                .filter(|e| SCHEDULING_FAILURE_REASONS.contains(&e.reason.as_str()))
+                .max_by(|a, b| a.last_seen.cmp(&b.last_seen))?;
+
+            Some(Issue::new(
+                "kubernetes",
+                "high",
+                format!(
+                    "Pod '{}/{}' is Pending and cannot be scheduled: {}",
+                    pod.namespace, pod.name, event.message
+                ),
+                Some(format!("{} at {}", event.reason, event.last_seen)),
+            ))
+        })
+        .collect()
+}
+
+/// Event reasons the PV controller and provisioners record on a claim that isn't bound yet
+const CLAIM_BINDING_REASONS: [&str; 4] = ["ProvisioningFailed", "FailedBinding", "ExternalProvisioning", "WaitForFirstConsumer"];
+
+/// The provisioner Kubernetes uses for classes whose volumes are all created by hand
+const NO_PROVISIONER: &str = "kubernetes.io/no-provisioner";
+
+/// Explain Pending and Lost PersistentVolumeClaims: a missing StorageClass, no default class,
+/// no Available PV to bind statically, or the provisioner's own failure event. A claim of a
+/// `WaitForFirstConsumer` class waiting for its first pod is normal and not reported.
+pub fn unbound_claim_issues(state: &VolumeBindingState, events: &[EventInfo]) -> Vec<Issue> {
+    state
+        .claims
+        .iter()
+        .filter_map(|claim| {
+            let object = format!("persistentvolumeclaim/{}", claim.name);
+            let event = events
+                .iter()
+                .filter(|e| e.object == object && e.namespace == claim.namespace)
+                .filter(|e| CLAIM_BINDING_REASONS.contains(&e.reason.as_str()))
+                .max_by(|a, b| a.last_seen.cmp(&b.last_seen));
+            let details = event.map(|event| format!("{}: {}", event.reason, event.message));
+
+            let (severity, cause) = match claim.phase.as_str() {
+                "Lost" => (
+                    "high",
+                    format!(
+                        "its PersistentVolume '{}' no longer exists",
+                        claim.volume.as_deref().unwrap_or("unknown")
+                    ),
+                ),
+                "Pending" => claim_pending_cause(claim, state, event)?,
+                _ => return None,
+            };
+
+            Some(Issue::new(
+                "storage",
+                severity,
+                format!(
+                    "PersistentVolumeClaim '{}/{}' is {}: {}",
+                    claim.namespace, claim.name, claim.phase, cause
+                ),
+                details,
+            ))
+        })
+        .collect()
+}
+
+/// Why a Pending claim isn't bound, with a severity; `None` when waiting is expected
+fn claim_pending_cause(
+    claim: &PersistentVolumeClaimInfo,
+    state: &VolumeBindingState,
+    event: Option<&EventInfo>,
+) -> Option<(&'static str, String)> {
+    let available = |class: Option<&str>| {
+        state
+            .volumes
+            .iter()
+            .filter(|volume| volume.phase == "Available" && volume.storage_class.as_deref() == class)
+            .count()
+    };
+    let static_cause = |available: usize, kind: String| match available {
+        0 => format!("no Available PersistentVolume {} to bind to", kind),
+        n => format!(
+            "none of the {} Available PersistentVolume(s) {} matches its size ({}) or access modes ({})",
+            n,
+            kind,
+            claim.requested.as_deref().unwrap_or("unset"),
+            claim.access_modes.join(", ")
+        ),
+    };
+
+    let class_name = match claim.storage_class.as_deref() {
+        Some(name) if !name.is_empty() => name,
+        Some(_) => return Some(("high", static_cause(available(None), "without a StorageClass".to_string()))),
+        None if !state.storage_classes.iter().any(|class| class.is_default) => {
+            return Some((
+                "high",
+                "it sets no StorageClass and the cluster has no default StorageClass".to_string(),
+            ));
+        }
+        None => return Some(("high", static_cause(available(None), "without a StorageClass".to_string()))),
+    };
+
+    let Some(class) = state.storage_classes.iter().find(|class| class.name == class_name) else {
+        return Some(("high", format!("StorageClass '{}' does not exist", class_name)));
+    };
+    if class.provisioner == NO_PROVISIONER {
+        return Some((
+            "high",
+            static_cause(
+                available(Some(class_name)),
+                format!("of StorageClass '{}' (it has no provisioner)", class_name),
+            ),
+        ));
+    }
+    match event.map(|event| event.reason.as_str()) {
+        Some("ProvisioningFailed") => Some(("high", format!("provisioner '{}' failed to create a volume", class.provisioner))),
+        Some("WaitForFirstConsumer") | None if class.binding_mode == "WaitForFirstConsumer" => None,
+        _ => Some(("medium", format!("waiting for provisioner '{}' to create a volume", class.provisioner))),
+    }
 }
 
 pub fn create_system_health_report(
@@ -90,10 +1135,33 @@ pub fn create_system_health_report(
     let has_container_issues = system_info
         .containers
         .iter()
-        .any(|container| !container.status.contains("Up"));
+        .any(|container| !container.is_healthy());
+    let pressure_issues = pressure_issues(&system_info.pressure);
+    let mut cgroup_issues = cgroup_limit_issues(&system_info.cgroups);
+    cgroup_issues.extend(cgroup_controller_issues(&system_info.cgroups, &system_info.environment));
+    let unit_issues = systemd_unit_issues(&system_info.systemd);
+    let zombie_issues = zombie_issues(system_info.zombie_count, &system_info.zombie_parents);
+    let swap_issues = swap_issues(&system_info.swap);
+    let disk_issues = disk_usage_issues(&system_info.filesystems);
+    let steal_issues = cpu_steal_issues(system_info.cpu_steal_percent);
+    let kernel_issues = kernel_reboot_issues(system_info);
+    let interface_issues = interface_error_issues(&system_info.network_interfaces);
+    let journal_issues = system_info.journal.disk_usage.as_ref().map(journal_disk_issues).unwrap_or_default();
 
     // Determine overall status
-    let overall_status = if !has_failed_services && !has_significant_errors && !has_container_issues
+    let overall_status = if !has_failed_services
+        && !has_significant_errors
+        && !has_container_issues
+        && pressure_issues.is_empty()
+        && cgroup_issues.is_empty()
+        && unit_issues.is_empty()
+        && zombie_issues.is_empty()
+        && swap_issues.is_empty()
+        && disk_issues.is_empty()
+        && steal_issues.is_empty()
+        && kernel_issues.is_empty()
+        && interface_issues.is_empty()
+        && journal_issues.is_empty()
     {
         "healthy".to_string()
     } else if has_failed_services {
@@ -142,7 +1210,7 @@ pub fn create_system_health_report(
         .collect();
 
     let log_status = LogStatus {
-        status: if has_significant_errors {
+        status: if has_significant_errors || !journal_issues.is_empty() {
             "warning".to_string()
         } else {
             "healthy".to_string()
@@ -156,7 +1224,7 @@ pub fn create_system_health_report(
     let healthy_containers = system_info
         .containers
         .iter()
-        .filter(|c| c.status.contains("Up"))
+        .filter(|c| c.is_healthy())
         .count();
     let container_status = ContainerStatus {
         status: if has_container_issues {
@@ -190,55 +1258,1005 @@ pub fn create_system_health_report(
 
     // Add service issues
     for failed_unit in &system_info.systemd.failed_units {
-        issues.push(Issue {
-            category: "service".to_string(),
-            severity: "high".to_string(),
-            message: format!("Service '{}' has failed", failed_unit),
-            details: None,
-        });
+        issues.push(Issue::new("service", "high", failed_unit_message(failed_unit), None));
     }
 
+    // Add crash-looping and stuck services
+    issues.extend(unit_issues);
+
     // Add log issues
     for entry in &significant_errors {
-        issues.push(Issue {
-            category: "log".to_string(),
-            severity: "medium".to_string(),
-            message: format!("Error in {}: {}", entry.unit, entry.message),
-            details: Some(entry.timestamp.clone()),
-        });
+        issues.push(Issue::new(
+            "log",
+            "medium",
+            format!("Error in {}: {}", entry.unit, entry.message),
+            Some(entry.timestamp.clone()),
+        ));
     }
 
     // Add container issues
-    for container in &system_info.containers {
-        if !container.status.contains("Up") {
-            issues.push(Issue {
-                category: "container".to_string(),
-                severity: "medium".to_string(),
-                message: format!("Container '{}' is not running: {}", container.name, container.status),
-                details: None,
-            });
-        }
-    }
+    issues.extend(container_issues(&system_info.containers));
+
+    // Add resource pressure and cgroup limit issues
+    issues.extend(pressure_issues);
+    issues.extend(cgroup_issues);
+    issues.extend(zombie_issues);
+    issues.extend(swap_issues);
+    issues.extend(disk_issues);
+    issues.extend(steal_issues);
+    issues.extend(kernel_issues);
+    issues.extend(interface_issues);
+    issues.extend(journal_issues);
 
     SystemHealthReport {
         timestamp,
-        system_info: system_info.clone(),
+        environment: system_info.environment.clone(),
+        system_info: Some(system_info.clone()),
         analysis: analysis.to_string(),
+        component: None,
         status,
         issues,
+        agent_transcript: Vec::new(),
+        known_issue_matches: Vec::new(),
+        suggested_fixes: Vec::new(),
     }
 }
 
-pub fn print_json(report: &SystemHealthReport) {
-    let json = serde_json::to_string_pretty(report).unwrap_or_else(|e| {
-        format!("Error serializing to JSON: {}", e)
-    });
-    println!("{}", json);
+/// Issue categories that belong to a component; `None` keeps every issue
+pub fn component_issue_categories(component: &CheckComponent) -> Option<&'static [&'static str]> {
+    match component {
+        CheckComponent::All | CheckComponent::Debug => None,
+        CheckComponent::System => Some(&["pressure", "time", "process", "performance", "disk", "kernel", "network"]),
+        CheckComponent::Containers => Some(&["container"]),
+        CheckComponent::Kubernetes => Some(&["kubernetes", "storage", "cgroup"]),
+        CheckComponent::Cgroups => Some(&["cgroup", "pressure"]),
+        CheckComponent::Systemd => Some(&["service", "security"]),
+        CheckComponent::Journal => Some(&["log"]),
+    }
 }
 
-pub fn print_yaml(report: &SystemHealthReport) {
-    let yaml = serde_yaml::to_string(report).unwrap_or_else(|e| {
-        format!("Error serializing to YAML: {}", e)
-    });
-    println!("{}", yaml);
-} 
\ No newline at end of file
+/// A report for `check --component`; see `SystemHealthReport::scope_to`
+pub fn create_component_report(
+    system_info: &SystemInfo,
+    analysis: &str,
+    component: &CheckComponent,
+    verbose: bool,
+) -> SystemHealthReport {
+    let mut report = create_system_health_report(system_info, analysis, verbose);
+    report.scope_to(component);
+    report
+}
+
+/// Write the report in the given format. Every report print path goes through here,
+/// so supporting a new format means adding one arm. Verbosity only affects text output.
+pub fn render(
+    report: &SystemHealthReport,
+    format: OutputFormat,
+    verbosity: Verbosity,
+    writer: &mut dyn Write,
+) -> std::io::Result<()> {
+    match format {
+        OutputFormat::Text => render_text(report, verbosity, writer),
+        OutputFormat::Yaml => {
+            let yaml = serde_yaml::to_string(report).unwrap_or_else(|e| {
+                format!("Error serializing to YAML: {}", e)
+            });
+            writeln!(writer, "{}", yaml)
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(report).unwrap_or_else(|e| {
+                format!("Error serializing to JSON: {}", e)
+            });
+            writeln!(writer, "{}", json)
+        }
+        // One compact object per line, flushed immediately so log shippers and `jq`
+        // can consume reports as they arrive
+        OutputFormat::Jsonl => {
+            let json = serde_json::to_string(report).unwrap_or_else(|e| {
+                serde_json::json!({ "error": format!("Error serializing to JSON: {}", e) }).to_string()
+            });
+            writeln!(writer, "{}", json)?;
+            writer.flush()
+        }
+        OutputFormat::Csv => render_csv(report, writer),
+    }
+}
+
+/// One row per issue for spreadsheets; every row carries the report's timestamp
+fn render_csv(report: &SystemHealthReport, writer: &mut dyn Write) -> std::io::Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(["category", "severity", "message", "details", "timestamp"])?;
+    for issue in &report.issues {
+        csv.write_record([
+            issue.category.as_str(),
+            issue.severity.as_str(),
+            issue.message.as_str(),
+            issue.details.as_deref().unwrap_or(""),
+            report.timestamp.as_str(),
+        ])?;
+    }
+    csv.flush()
+}
+
+/// Plain text: the component's section (or the system overview and issues for a full
+/// report), then the analysis
+fn render_text(report: &SystemHealthReport, verbosity: Verbosity, writer: &mut dyn Write) -> std::io::Result<()> {
+    let component = report
+        .component
+        .as_deref()
+        .and_then(|name| <CheckComponent as clap::ValueEnum>::from_str(name, true).ok());
+
+    if let Some(info) = &report.system_info {
+        match component {
+            Some(CheckComponent::Containers) => printers::write_container_info(writer, info, verbosity)?,
+            Some(CheckComponent::Kubernetes) => printers::write_kubernetes_info(writer, info, verbosity)?,
+            Some(CheckComponent::Cgroups) => printers::write_cgroup_info(writer, info, verbosity)?,
+            Some(CheckComponent::Systemd) => printers::write_systemd_info(writer, info, verbosity)?,
+            Some(CheckComponent::Journal) => printers::write_journal_info(writer, info, verbosity)?,
+            _ => printers::write_system_info(writer, info, verbosity)?,
+        }
+    }
+
+    if component.is_none() || report.system_info.is_none() {
+        if report.system_info.is_none() {
+            writeln!(writer, "Environment: {}", report.environment.summary())?;
+        }
+        writeln!(writer, "\nStatus: {}", report.status.overall)?;
+        let issue_limit = verbosity.limit(10);
+        for issue in report.issues.iter().take(issue_limit) {
+            writeln!(writer, "[{}] {}: {}", issue.severity, issue.category, issue.message)?;
+            if let Some(details) = issue.details.as_ref().filter(|_| verbosity.is_detailed()) {
+                writeln!(writer, "    {}", details)?;
+            }
+        }
+        if report.issues.len() > issue_limit {
+            writeln!(writer, "... and {} more issues", report.issues.len() - issue_limit)?;
+        }
+    }
+
+    if !report.analysis.is_empty() {
+        writeln!(writer, "\n=== AI Analysis ===")?;
+        writeln!(writer, "{}", report.analysis)?;
+    }
+    Ok(())
+}
+
+/// Render the report to stdout
+pub fn print_report(report: &SystemHealthReport, format: OutputFormat, verbosity: Verbosity) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = render(report, format, verbosity, &mut stdout);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sysinfo::SystemdInfo;
+
+    fn create_test_system_info() -> SystemInfo {
+        SystemInfo {
+            os: "Test Linux 1.0".to_string(),
+            cpu: "Test CPU".to_string(),
+            systemd: SystemdInfo {
+                system_status: "running".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_report_is_healthy() {
+        let system_info = create_test_system_info();
+        let report = create_system_health_report(&system_info, "All good", false);
+        assert!(report.is_healthy());
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_sustained_pressure_is_flagged() {
+        use crate::sysinfo::PressureAverages;
+
+        let averages = |avg60| PressureAverages { avg10: avg60, avg60, avg300: avg60 / 2.0 };
+        let mut system_info = create_test_system_info();
+        system_info.pressure = PressureInfo {
+            cpu: Some(PressureStats { some: averages(3.0), full: None }),
+            memory: Some(PressureStats { some: averages(30.0), full: Some(averages(12.0)) }),
+            io: Some(PressureStats { some: averages(22.0), full: Some(averages(1.0)) }),
+            cgroup_memory: None,
+        };
+
+        let issues = pressure_issues(&system_info.pressure);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.starts_with("Memory pressure: all tasks"));
+        assert_eq!(issues[0].severity, "high");
+        assert!(issues[1].message.starts_with("IO pressure: some tasks"));
+        assert_eq!(issues[1].severity, "medium");
+
+        let report = create_system_health_report(&system_info, "", false);
+        assert_eq!(report.status.overall, "warning");
+        assert!(!report.is_healthy());
+    }
+
+    #[test]
+    fn test_cgroup_memory_breach_is_flagged() {
+        let mut system_info = create_test_system_info();
+        system_info.cgroups.memory_limit = Some((512 * 1024 * 1024).to_string());
+        system_info.cgroups.memory_usage = Some(500 * 1024 * 1024);
+        system_info.cgroups.cpu_throttled_percent = Some(40.0);
+
+        let issues = cgroup_limit_issues(&system_info.cgroups);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].severity, "high");
+        assert!(issues[0].message.contains("98%"));
+        assert_eq!(issues[0].details.as_deref(), Some("500 MiB of 512 MiB"));
+        assert_eq!(issues[1].severity, "medium");
+
+        let report = create_system_health_report(&system_info, "", false);
+        assert_eq!(report.status.overall, "warning");
+
+        // Comfortable headroom is not an issue
+        system_info.cgroups.memory_usage = Some(100 * 1024 * 1024);
+        system_info.cgroups.cpu_throttled_percent = Some(1.0);
+        assert!(cgroup_limit_issues(&system_info.cgroups).is_empty());
+    }
+
+    #[test]
+    fn test_pending_pod_scheduling_failure_is_flagged() {
+        use crate::tools::kubectl::{parse_events_json, parse_pods_json};
+
+        let pods = parse_pods_json(
+            r#"{"items": [
+                {"metadata": {"name": "batch-1", "namespace": "jobs"}, "status": {"phase": "Pending"}},
+                {"metadata": {"name": "batch-2", "namespace": "jobs"}, "status": {"phase": "Pending"}},
+                {"metadata": {"name": "web-1", "namespace": "shop"}, "spec": {"nodeName": "worker-1"},
+                 "status": {"phase": "Pending"}}
+            ]}"#,
+        )
+        .unwrap();
+        let events = parse_events_json(
+            r#"{"items": [
+                {"type": "Warning", "reason": "FailedScheduling", "lastTimestamp": "2024-01-01T00:01:00Z",
+                 "involvedObject": {"kind": "Pod", "name": "batch-1", "namespace": "jobs"},
+                 "message": "0/3 nodes are available: 3 Insufficient memory."},
+                {"type": "Warning", "reason": "FailedScheduling", "lastTimestamp": "2024-01-01T00:05:00Z",
+                 "involvedObject": {"kind": "Pod", "name": "batch-1", "namespace": "jobs"},
+                 "message": "0/3 nodes are available: 3 Insufficient cpu."},
+                {"type": "Warning", "reason": "FailedScheduling", "lastTimestamp": "2024-01-01T00:05:00Z",
+                 "involvedObject": {"kind": "Pod", "name": "batch-2", "namespace": "other"},
+                 "message": "0/3 nodes are available: 3 Insufficient cpu."},
+                {"type": "Warning", "reason": "FailedScheduling",
+                 "involvedObject": {"kind": "Pod", "name": "web-1", "namespace": "shop"},
+                 "message": "0/3 nodes are available: 1 node(s) had untolerated taint."}
+            ]}"#,
+        )
+        .unwrap();
+
+        // batch-2's event is in another namespace, and web-1 has since been scheduled
+        let issues = pending_pod_issues(&pods, &events);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].category, "kubernetes");
+        assert_eq!(
+            issues[0].message,
+            "Pod 'jobs/batch-1' is Pending and cannot be scheduled: 0/3 nodes are available: 3 Insufficient cpu."
+        );
+        assert_eq!(issues[0].details.as_deref(), Some("FailedScheduling at 2024-01-01T00:05:00Z"));
+    }
+
+    #[test]
+    fn test_unbound_claim_issues() {
+        use crate::tools::kubectl::{parse_events_json, parse_pvcs_json, parse_pvs_json, parse_storage_classes_json};
+
+        let claim = |name: &str, class: &str| {
+            format!(
+                r#"{{"metadata": {{"name": "{}", "namespace": "db"}}, "status": {{"phase": "Pending"}},
+                   "spec": {{{}"resources": {{"requests": {{"storage": "10Gi"}}}}, "accessModes": ["ReadWriteOnce"]}}}}"#,
+                name, class
+            )
+        };
+        let claims = [
+            claim("missing-class", r#""storageClassName": "fast","#),
+            claim("local", r#""storageClassName": "local","#),
+            claim("static", r#""storageClassName": "","#),
+            claim("provisioned", r#""storageClassName": "ebs","#),
+            claim("waiting", r#""storageClassName": "ebs-wffc","#),
+            r#"{"metadata": {"name": "data", "namespace": "db"}, "status": {"phase": "Bound"}, "spec": {"volumeName": "pv-1"}}"#.to_string(),
+        ];
+        let state = VolumeBindingState {
+            claims: parse_pvcs_json(&format!(r#"{{"items": [{}]}}"#, claims.join(","))).unwrap(),
+            volumes: parse_pvs_json(
+                r#"{"items": [
+                    {"metadata": {"name": "pv-1"}, "status": {"phase": "Bound"}, "spec": {"claimRef": {"namespace": "db", "name": "data"}}},
+                    {"metadata": {"name": "pv-small"}, "status": {"phase": "Available"}, "spec": {"capacity": {"storage": "1Gi"}}}
+                ]}"#,
+            )
+            .unwrap(),
+            storage_classes: parse_storage_classes_json(
+                r#"{"items": [
+                    {"metadata": {"name": "local"}, "provisioner": "kubernetes.io/no-provisioner"},
+                    {"metadata": {"name": "ebs"}, "provisioner": "ebs.csi.aws.com"},
+                    {"metadata": {"name": "ebs-wffc"}, "provisioner": "ebs.csi.aws.com", "volumeBindingMode": "WaitForFirstConsumer"}
+                ]}"#,
+            )
+            .unwrap(),
+        };
+        let events = parse_events_json(
+            r#"{"items": [
+                {"type": "Warning", "reason": "ProvisioningFailed", "lastTimestamp": "2024-01-01T00:05:00Z",
+                 "involvedObject": {"kind": "PersistentVolumeClaim", "name": "provisioned", "namespace": "db"},
+                 "message": "failed to provision volume: UnauthorizedOperation"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let issues = unbound_claim_issues(&state, &events);
+        let messages: Vec<&str> = issues.iter().map(|issue| issue.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "PersistentVolumeClaim 'db/missing-class' is Pending: StorageClass 'fast' does not exist",
+                "PersistentVolumeClaim 'db/local' is Pending: no Available PersistentVolume of StorageClass 'local' (it has no provisioner) to bind to",
+                "PersistentVolumeClaim 'db/static' is Pending: none of the 1 Available PersistentVolume(s) without a StorageClass matches its size (10Gi) or access modes (ReadWriteOnce)",
+                "PersistentVolumeClaim 'db/provisioned' is Pending: provisioner 'ebs.csi.aws.com' failed to create a volume",
+            ]
+        );
+        assert!(issues.iter().all(|issue| issue.category == "storage" && issue.severity == "high"));
+        assert_eq!(
+            issues[3].details.as_deref(),
+            Some("ProvisioningFailed: failed to provision volume: UnauthorizedOperation")
+        );
+
+        // Without a class and without a default class there is nothing to provision from
+        let mut no_class = state.clone();
+        no_class.claims = parse_pvcs_json(&format!(r#"{{"items": [{}]}}"#, claim("unset", ""))).unwrap();
+        assert!(unbound_claim_issues(&no_class, &[])[0].message.ends_with("the cluster has no default StorageClass"));
+    }
+
+    #[test]
+    fn test_apply_output_config_trims_payload() {
+        use crate::sysinfo::JournalEntry;
+
+        let entry = |n: usize| JournalEntry {
+            timestamp: format!("t{}", n),
+            unit: "app.service".to_string(),
+            message: format!("segfault {}", n),
+            priority: "3".to_string(),
+        };
+        let mut system_info = create_test_system_info();
+        system_info.journal.recent_errors = (0..5).map(entry).collect();
+
+        let mut output = crate::config::RaidConfig::default().output;
+        let mut report = create_system_health_report(&system_info, "", false);
+        report.apply_output_config(&output);
+        assert_eq!(report.system_info.as_ref().unwrap().journal.recent_errors.len(), 5);
+
+        output.max_journal_entries = Some(2);
+        report.apply_output_config(&output);
+        assert_eq!(report.system_info.as_ref().unwrap().journal.recent_errors.len(), 2);
+        assert_eq!(report.status.logs.recent_errors.len(), 2);
+        assert_eq!(report.status.logs.total_errors, 5);
+
+        output.include_raw_sysinfo = false;
+        report.apply_output_config(&output);
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json.get("system_info").is_none());
+        assert!(json.get("status").is_some());
+    }
+
+    #[test]
+    fn test_restart_loop_is_flagged() {
+        use crate::sysinfo::SystemdUnit;
+
+        let unit = |name: &str, status: &str, sub_state: &str| SystemdUnit {
+            name: name.to_string(),
+            status: status.to_string(),
+            description: String::new(),
+            sub_state: sub_state.to_string(),
+        };
+        let mut system_info = create_test_system_info();
+        system_info.systemd.units = vec![
+            unit("docker", "active", "running"),
+            unit("api.service", "activating", "auto-restart"),
+            unit("worker.service", "activating", "start-pre"),
+            unit("kubelet", "inactive", "dead"),
+        ];
+
+        let report = create_system_health_report(&system_info, "", false);
+        assert_eq!(report.status.overall, "warning");
+        assert_eq!(report.issues.len(), 2);
+        assert_eq!(report.issues[0].severity, "high");
+        assert_eq!(
+            report.issues[0].message,
+            "Service 'api.service' is crash-looping: systemd keeps restarting it (activating (auto-restart))"
+        );
+        assert_eq!(report.issues[1].severity, "medium");
+        assert!(report.issues[1].message.contains("worker.service"));
+    }
+
+    #[test]
+    fn test_security_denial_linked_to_failed_service() {
+        let denials = vec![
+            SecurityDenial {
+                module: "AppArmor".to_string(),
+                operation: "open".to_string(),
+                process: Some("nginx".to_string()),
+                target: Some("/etc/ssl/private/key.pem".to_string()),
+                profile: Some("/usr/sbin/nginx".to_string()),
+            },
+            SecurityDenial {
+                module: "SELinux".to_string(),
+                operation: "read".to_string(),
+                process: Some("sshd".to_string()),
+                target: None,
+                profile: None,
+            },
+        ];
+        let failed = vec!["nginx.service".to_string(), "postgresql.service".to_string()];
+
+        let issues = security_denial_issues(&denials, &failed);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].category, "security");
+        assert_eq!(issues[0].severity, "high");
+        assert!(issues[0].message.contains("nginx.service"));
+        assert!(issues[0].message.contains("/etc/ssl/private/key.pem"));
+        assert!(issues[0].details.as_deref().unwrap().contains("aa-logprof"));
+
+        assert!(security_denial_issues(&denials, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_zombie_issues_name_the_parent() {
+        let parents = vec![ZombieParent { pid: 812, name: "buggy-daemon".to_string(), zombies: 7 }];
+        assert!(zombie_issues(ZOMBIE_WARN_COUNT, &parents).is_empty());
+
+        let mut system_info = create_test_system_info();
+        system_info.zombie_count = 8;
+        system_info.zombie_parents = parents;
+        let report = create_system_health_report(&system_info, "", false);
+        assert_eq!(report.status.overall, "warning");
+        let issue = report.issues.iter().find(|issue| issue.category == "process").unwrap();
+        assert_eq!(issue.message, "8 zombie (defunct) processes");
+        assert!(issue.details.as_deref().unwrap().contains("buggy-daemon (PID 812)"));
+    }
+
+    #[test]
+    fn test_swap_issues_by_utilization() {
+        let swap = |used_bytes| SwapInfo { total_bytes: 1000, used_bytes, swappiness: Some(60) };
+        assert!(swap_issues(&SwapInfo::default()).is_empty());
+        assert!(swap_issues(&swap(400)).is_empty());
+        assert_eq!(swap_issues(&swap(500))[0].severity, "medium");
+
+        let mut system_info = create_test_system_info();
+        system_info.swap = swap(900);
+        let report = create_system_health_report(&system_info, "", false);
+        assert_eq!(report.status.overall, "warning");
+        let issue = report.issues.iter().find(|issue| issue.category == "performance").unwrap();
+        assert_eq!(issue.severity, "high");
+        assert!(issue.message.starts_with("Swap is 90.0% used"));
+        assert!(issue.details.as_deref().unwrap().contains("vm.swappiness=60"));
+    }
+
+    #[test]
+    fn test_journal_disk_issues_near_cap() {
+        let usage = |used_bytes| JournalDiskUsage {
+            used_bytes,
+            max_use_bytes: Some(1000),
+            max_use_configured: true,
+            rotation_errors: Vec::new(),
+        };
+        assert!(journal_disk_issues(&usage(800)).is_empty());
+        assert!(journal_disk_issues(&JournalDiskUsage { max_use_bytes: None, ..usage(5000) }).is_empty());
+
+        let mut system_info = create_test_system_info();
+        system_info.journal.disk_usage = Some(JournalDiskUsage {
+            rotation_errors: vec!["[Oct 16 10:00:00] Failed to rotate system.journal: No space left on device".to_string()],
+            ..usage(950)
+        });
+        let report = create_system_health_report(&system_info, "", false);
+        assert_eq!(report.status.logs.status, "warning");
+        let journal: Vec<_> = report.issues.iter().filter(|issue| issue.message.contains("ournal")).collect();
+        assert_eq!(journal.len(), 2);
+        assert!(journal[0].message.starts_with("Journal is at 95.0% of its size cap"));
+        assert!(journal[0].details.as_deref().unwrap().contains("configured SystemMaxUse"));
+        assert!(journal[1].details.as_deref().unwrap().contains("No space left on device"));
+    }
+
+    #[test]
+    fn test_kernel_reboot_issues() {
+        let mut info = SystemInfo {
+            running_kernel: "6.8.0-45-generic".to_string(),
+            newest_installed_kernel: Some("6.8.0-49-generic".to_string()),
+            ..Default::default()
+        };
+        assert!(kernel_reboot_issues(&info).is_empty());
+
+        info.needs_reboot = true;
+        let issues = kernel_reboot_issues(&info);
+        assert_eq!((issues[0].category.as_str(), issues[0].severity.as_str()), ("kernel", "low"));
+        assert_eq!(
+            issues[0].message,
+            "Running kernel 6.8.0-45-generic is older than the newest installed kernel 6.8.0-49-generic; reboot to load it"
+        );
+
+        info.running_kernel = "6.9.0-generic".to_string();
+        assert!(kernel_reboot_issues(&info)[0].message.contains("is no longer installed (newest: 6.8.0-49-generic)"));
+        assert_eq!(create_system_health_report(&info, "", false).status.overall, "warning");
+    }
+
+    #[test]
+    fn test_interface_error_issues() {
+        use crate::sysinfo::InterfaceDelta;
+
+        let quiet = InterfaceCounters { name: "eth0".to_string(), rx_packets: 100_000, rx_errors: 5, ..Default::default() };
+        assert!(interface_error_issues(std::slice::from_ref(&quiet)).is_empty());
+
+        let lossy = InterfaceCounters { rx_dropped: 400, ..quiet.clone() };
+        let issues = interface_error_issues(&[lossy]);
+        assert_eq!((issues[0].category.as_str(), issues[0].severity.as_str()), ("network", "low"));
+        assert_eq!(issues[0].message, "Interface eth0 has lost 0.40% of its packets since boot (5 errors, 400 drops)");
+
+        let failing = InterfaceCounters {
+            recent: Some(InterfaceDelta { packets: 50, errors: 3, dropped: 2, seconds: 0.5 }),
+            ..quiet.clone()
+        };
+        let issues = interface_error_issues(&[failing]);
+        assert_eq!(issues[0].severity, "medium");
+        assert_eq!(issues[0].message, "Interface eth0 is losing packets now: 3 errors and 2 drops in 0.5s (10.0/s)");
+
+        let loopback = InterfaceCounters { name: "lo".to_string(), rx_dropped: 400, ..quiet };
+        assert!(interface_error_issues(&[loopback]).is_empty());
+    }
+
+    #[test]
+    fn test_cpu_steal_issues_by_share() {
+        assert!(cpu_steal_issues(None).is_empty());
+        assert!(cpu_steal_issues(Some(4.0)).is_empty());
+        assert_eq!(cpu_steal_issues(Some(12.0))[0].severity, "medium");
+        let issues = cpu_steal_issues(Some(30.0));
+        assert_eq!(issues[0].severity, "high");
+        assert_eq!(issues[0].category, "performance");
+
+        let info = SystemInfo { cpu_steal_percent: Some(30.0), ..Default::default() };
+        let report = create_system_health_report(&info, "", false);
+        assert_eq!(report.status.overall, "warning");
+        assert!(report.issues.iter().any(|issue| issue.message.starts_with("CPU steal time is 30.0%")));
+    }
+
+    #[test]
+    fn test_disk_usage_issues_name_the_mount() {
+        let fs = |mount_point: &str, use_percent| FilesystemUsage {
+            filesystem: "/dev/sda1".to_string(),
+            fs_type: "ext4".to_string(),
+            mount_point: mount_point.to_string(),
+            total_bytes: 100 * 1024 * 1024 * 1024,
+            used_bytes: use_percent as u64 * 1024 * 1024 * 1024,
+            available_bytes: (100 - use_percent as u64) * 1024 * 1024 * 1024,
+            use_percent,
+        };
+        assert!(disk_usage_issues(&[fs("/", 89)]).is_empty());
+
+        let mut system_info = create_test_system_info();
+        system_info.filesystems = vec![fs("/", 50), fs("/var", 91), fs("/data", 97)];
+        let report = create_system_health_report(&system_info, "", false);
+        assert_eq!(report.status.overall, "warning");
+        let disk: Vec<&Issue> = report.issues.iter().filter(|issue| issue.category == "disk").collect();
+        assert_eq!(disk.len(), 2);
+        assert_eq!(disk[0].message, "Filesystem /var is 91% full (9.0 GiB free of 100.0 GiB)");
+        assert_eq!(disk[0].severity, "medium");
+        assert_eq!(disk[1].severity, "high");
+        assert_eq!(disk[1].details.as_deref(), Some("/dev/sda1 (ext4)"));
+    }
+
+    #[test]
+    fn test_brute_force_issues() {
+        let sources = vec![
+            FailedLoginSource {
+                address: "203.0.113.5".to_string(),
+                attempts: 57,
+                users: ["root", "admin", "test", "ubuntu", "oracle", "git"].iter().map(|u| u.to_string()).collect(),
+            },
+            FailedLoginSource {
+                address: "192.0.2.10".to_string(),
+                attempts: 2,
+                users: vec!["alice".to_string()],
+            },
+        ];
+
+        let issues = brute_force_issues(&sources);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].category, "security");
+        assert_eq!(issues[0].severity, "high");
+        assert!(issues[0].message.contains("57 failed logins from 203.0.113.5"));
+        assert!(issues[0].message.ends_with("(users: root, admin, test, ubuntu, oracle, ...)"));
+    }
+
+    #[test]
+    fn test_time_sync_issues() {
+        let healthy = TimeSyncStatus {
+            synchronized: Some(true),
+            ntp_service_active: Some(true),
+            offset_ms: Some(3.2),
+            offset_source: Some("chronyc".to_string()),
+        };
+        assert!(time_sync_issues(&healthy).is_empty());
+
+        let skewed = TimeSyncStatus { offset_ms: Some(-1800.0), ..healthy.clone() };
+        let issues = time_sync_issues(&skewed);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, "high");
+        assert!(issues[0].message.contains("-1800 ms"));
+
+        let no_ntp = TimeSyncStatus {
+            synchronized: Some(false),
+            ntp_service_active: Some(false),
+            ..TimeSyncStatus::default()
+        };
+        let issues = time_sync_issues(&no_ntp);
+        assert_eq!(issues[0].severity, "medium");
+        assert!(issues[0].details.as_deref().unwrap().contains("set-ntp true"));
+    }
+
+    /// An analysis in the format the analysis prompt asks for
+    const SAMPLE_ANALYSIS: &str = "## Critical Issues (if any)
+- **Issue**: nginx.service has failed and keeps exiting with status 1
+- **Verify**: `systemctl status nginx.service`
+- **Fix**: `nginx -t && systemctl restart nginx.service`
+
+## Performance Issues (if any)
+- **Issue**: Swap usage is at 85%, memory pressure is high
+- **Verify**: `free -h`
+- **Fix**: Add memory or reduce the workload
+
+## Configuration Issues (if any)
+- **Issue**: The system clock is not synchronized with NTP
+- **Verify:** `timedatectl status`
+- **Fix:** `timedatectl set-ntp true`
+";
+
+    #[test]
+    fn test_parse_fix_commands() {
+        let suggestions = parse_fix_commands(SAMPLE_ANALYSIS);
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(
+            suggestions[0],
+            FixSuggestion {
+                issue: "nginx.service has failed and keeps exiting with status 1".to_string(),
+                verify_command: Some("systemctl status nginx.service".to_string()),
+                fix_command: Some("nginx -t && systemctl restart nginx.service".to_string()),
+            }
+        );
+        // Prose advice is not a command
+        assert_eq!(suggestions[1].verify_command.as_deref(), Some("free -h"));
+        assert_eq!(suggestions[1].fix_command, None);
+        // `**Fix:**` with the colon inside the bold works too
+        assert_eq!(suggestions[2].fix_command.as_deref(), Some("timedatectl set-ntp true"));
+
+        assert!(parse_fix_commands("System appears healthy.").is_empty());
+    }
+
+    #[test]
+    fn test_attach_fix_suggestions() {
+        let mut report = create_system_health_report(&SystemInfo::default(), SAMPLE_ANALYSIS, false);
+        let issue = |category: &str, message: &str| Issue::new(category, "high", message, None);
+        report.issues = vec![
+            issue("disk", "Filesystem /var is 96% full"),
+            issue("service", "Service 'nginx.service' has failed"),
+            issue("time", "System clock is not synchronized with NTP"),
+        ];
+        report.attach_fix_suggestions();
+
+        assert_eq!(report.suggested_fixes.len(), 3);
+        assert_eq!(report.issues[0].suggested_fix, None);
+        assert_eq!(report.issues[1].suggested_fix.as_deref(), Some("nginx -t && systemctl restart nginx.service"));
+        assert_eq!(report.issues[1].verify_command.as_deref(), Some("systemctl status nginx.service"));
+        assert_eq!(report.issues[2].suggested_fix.as_deref(), Some("timedatectl set-ntp true"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["issues"][1]["suggested_fix"], "nginx -t && systemctl restart nginx.service");
+        assert!(json["issues"][0].get("suggested_fix").is_none());
+    }
+
+    #[test]
+    fn test_cgroup_controller_issues() {
+        let cgroups = CgroupInfo {
+            version: "v2".to_string(),
+            cgroup_path: "/kubepods.slice/pod1".to_string(),
+            enabled_controllers: Some(vec!["cpuset".to_string(), "cpu".to_string(), "pids".to_string()]),
+            memory_limit: Some((512 * 1024 * 1024).to_string()),
+            cpu_limit: Some("50000 100000".to_string()),
+            ..Default::default()
+        };
+        let host = Environment { kind: EnvironmentKind::BareMetal, ..Default::default() };
+        let issues = cgroup_controller_issues(&cgroups, &host);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, "high");
+        assert_eq!(issues[0].message, "A memory limit is set but the memory controller is not enabled for this cgroup");
+        assert!(issues[0].details.as_deref().unwrap().contains("/kubepods.slice/pod1"));
+
+        // No limit: only worth flagging inside a container, where limits are expected
+        let unlimited = CgroupInfo { memory_limit: Some("max".to_string()), ..cgroups.clone() };
+        assert!(cgroup_controller_issues(&unlimited, &host).is_empty());
+        let pod = Environment { kind: EnvironmentKind::KubernetesPod, ..Default::default() };
+        let issues = cgroup_controller_issues(&unlimited, &pod);
+        assert_eq!(issues[0].severity, "medium");
+        assert_eq!(issues[0].message, "The memory controller is not enabled for this container's cgroup");
+
+        // Unknown controllers report nothing
+        assert!(cgroup_controller_issues(&CgroupInfo { enabled_controllers: None, ..cgroups }, &pod).is_empty());
+    }
+
+    #[test]
+    fn test_dns_resolution_issues() {
+        let failing = DnsResolutionStatus {
+            name: "repo.internal".to_string(),
+            system_resolver: Some(false),
+            public_servers: Some(false),
+            ping_by_name: None,
+        };
+        let issues = dns_resolution_issues(&failing);
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].category.as_str(), issues[0].severity.as_str()), ("network", "high"));
+        assert_eq!(issues[0].message, "DNS resolution is failing");
+        let details = issues[0].details.as_deref().unwrap();
+        assert!(details.starts_with("'repo.internal' did not resolve through: system resolver (getent), public DNS servers (dig)."));
+        assert!(details.contains("/etc/resolv.conf"));
+
+        // Without getent only the public servers were tried, which a firewall alone can block
+        let external_only = DnsResolutionStatus { system_resolver: None, ..failing.clone() };
+        assert_eq!(dns_resolution_issues(&external_only)[0].severity, "medium");
+
+        let resolving = DnsResolutionStatus { system_resolver: Some(true), ..failing };
+        assert!(dns_resolution_issues(&resolving).is_empty());
+    }
+
+    #[test]
+    fn test_container_issues_by_status() {
+        let container = |name: &str, status: &str, restart_count: Option<u32>| {
+            let (health, exit_code) = crate::sysinfo::parse_container_status(status);
+            CollectedContainer {
+                name: name.to_string(),
+                status: status.to_string(),
+                health,
+                exit_code,
+                restart_count,
+                ..Default::default()
+            }
+        };
+        let containers = vec![
+            container("web", "Up 2 hours (healthy)", Some(0)),
+            container("api", "Up 3 minutes (unhealthy)", Some(1)),
+            container("worker", "Restarting (1) 10 seconds ago", Some(14)),
+            container("cache", "Up 5 minutes", Some(8)),
+            container("batch", "Exited (137) 2 hours ago", None),
+        ];
+        let issues: Vec<(String, String)> = container_issues(&containers)
+            .into_iter()
+            .map(|issue| (issue.severity, issue.message))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("high".to_string(), "Container 'api' is failing its health check, 1 restarts".to_string()),
+                (
+                    "high".to_string(),
+                    "Container 'worker' is restarting after exiting with code 1, 14 restarts".to_string()
+                ),
+                ("medium".to_string(), "Container 'cache' has restarted 8 times".to_string()),
+                ("medium".to_string(), "Container 'batch' is not running: Exited (137) 2 hours ago".to_string()),
+            ]
+        );
+
+        let info = SystemInfo { containers, ..Default::default() };
+        let report = create_system_health_report(&info, "", false);
+        assert_eq!(report.status.containers.healthy_count, 1);
+        assert_eq!(report.status.containers.unhealthy_count, 4);
+        assert_eq!(report.status.overall, "warning");
+    }
+
+    #[test]
+    fn test_service_status_replaces_generic_failure() {
+        let info = SystemInfo {
+            systemd: SystemdInfo {
+                failed_units: vec!["api.service".to_string(), "db.service".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut report = create_system_health_report(&info, "", false);
+        let statuses = [
+            ServiceStatusDetail {
+                unit: "api.service".to_string(),
+                active_state: "failed".to_string(),
+                sub_state: "failed".to_string(),
+                result: "exit-code".to_string(),
+                exec_main_status: Some(2),
+                main_pid: None,
+                n_restarts: Some(5),
+            },
+            // Nothing specific to say: the generic issue stays
+            ServiceStatusDetail { unit: "db.service".to_string(), ..Default::default() },
+        ];
+        add_service_status_issues(&mut report.issues, &statuses);
+
+        let messages: Vec<&str> = report.issues.iter().map(|issue| issue.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Service 'db.service' has failed",
+                "Service 'api.service' is crash-looping: restarted 5 times, last run exited with status 2 (exit-code)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_line_is_single_line() {
+        let report = create_system_health_report(&create_test_system_info(), "multi\nline analysis", false);
+        let line = serde_json::to_string(&report).unwrap();
+        assert!(!line.contains('\n'));
+
+        let parsed: SystemHealthReport = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.analysis, "multi\nline analysis");
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let mut report = create_system_health_report(&create_test_system_info(), "All good", false);
+        report.issues.push(Issue::new("time", "medium", "System clock is not synchronized", None));
+        report.issues.push(Issue::new(
+            "disk",
+            "high",
+            "Filesystem \"/var\" is 97% full",
+            Some("du: /var/log, /var/lib\nrotate logs".to_string()),
+        ));
+
+        let render_to_string = |format| {
+            let mut buffer = Vec::new();
+            render(&report, format, Verbosity::Normal, &mut buffer).unwrap();
+            String::from_utf8(buffer).unwrap()
+        };
+
+        let text = render_to_string(OutputFormat::Text);
+        assert!(text.contains("System Overview"));
+        assert!(text.contains("[medium] time: System clock is not synchronized"));
+        assert!(text.ends_with("=== AI Analysis ===\nAll good\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&render_to_string(OutputFormat::Json)).unwrap();
+        assert_eq!(json["analysis"], "All good");
+        assert_eq!(render_to_string(OutputFormat::Jsonl).lines().count(), 1);
+
+        let yaml: serde_yaml::Value = serde_yaml::from_str(&render_to_string(OutputFormat::Yaml)).unwrap();
+        assert_eq!(yaml["analysis"], "All good");
+
+        let csv = render_to_string(OutputFormat::Csv);
+        assert!(csv.starts_with("category,severity,message,details,timestamp\n"));
+        assert!(csv.contains("time,medium,System clock is not synchronized,,"));
+        assert!(csv.ends_with(&format!(
+            "disk,high,\"Filesystem \"\"/var\"\" is 97% full\",\"du: /var/log, /var/lib\nrotate logs\",{}\n",
+            report.timestamp
+        )));
+    }
+
+    #[test]
+    fn test_component_report_is_scoped() {
+        let mut info = create_test_system_info();
+        info.systemd.failed_units = vec!["nginx.service".to_string()];
+        info.containers.push(crate::sysinfo::ContainerInfo {
+            id: "abc".to_string(),
+            name: "web".to_string(),
+            image: "nginx".to_string(),
+            status: "Exited (1)".to_string(),
+            ports: Vec::new(),
+            ..Default::default()
+        });
+
+        let full = create_component_report(&info, "", &CheckComponent::All, false);
+        assert!(full.component.is_none());
+        assert!(full.issues.iter().any(|issue| issue.category == "container"));
+
+        let report = create_component_report(&info, "Restart nginx", &CheckComponent::Containers, false);
+        assert_eq!(report.component.as_deref(), Some("containers"));
+        assert!(report.issues.iter().all(|issue| issue.category == "container"));
+        assert_eq!(report.status.overall, "warning");
+
+        // The component's own section replaces the system overview in text output
+        let mut buffer = Vec::new();
+        render(&report, OutputFormat::Text, Verbosity::Normal, &mut buffer).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(text.starts_with("=== Container Status ==="));
+        assert!(text.contains("web (Exited (1))"));
+        assert!(!text.contains("System Overview"));
+
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["component"], "containers");
+    }
+
+    #[test]
+    fn test_every_component_check_emits_json() {
+        use clap::ValueEnum;
+
+        let info = create_test_system_info();
+        for component in CheckComponent::value_variants() {
+            let report = create_component_report(&info, "analysis", component, false);
+            for format in [OutputFormat::Json, OutputFormat::Jsonl] {
+                let mut buffer = Vec::new();
+                render(&report, format, Verbosity::Normal, &mut buffer).unwrap();
+                let json: serde_json::Value = serde_json::from_slice(&buffer)
+                    .unwrap_or_else(|e| panic!("{} check did not emit JSON: {}", component.as_str(), e));
+                assert_eq!(json["analysis"], "analysis");
+                match component {
+                    CheckComponent::All | CheckComponent::Debug => assert!(json.get("component").is_none()),
+                    _ => assert_eq!(json["component"], component.as_str()),
+                }
+            }
+
+            let mut buffer = Vec::new();
+            render(&report, OutputFormat::Yaml, Verbosity::Normal, &mut buffer).unwrap();
+            assert!(serde_yaml::from_slice::<serde_yaml::Value>(&buffer).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_previous_report_round_trip_and_context() {
+        let mut system_info = create_test_system_info();
+        system_info.systemd.failed_units = vec!["nginx.service".to_string()];
+        let report = create_system_health_report(&system_info, "Restart nginx after fixing its config", false);
+
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["last.json", "last.yaml"] {
+            let path = dir.path().join(name);
+            let content = if name.ends_with(".json") {
+                serde_json::to_string_pretty(&report).unwrap()
+            } else {
+                serde_yaml::to_string(&report).unwrap()
+            };
+            std::fs::write(&path, content).unwrap();
+
+            let loaded = SystemHealthReport::read_from_file(&path).unwrap();
+            let context = loaded.previous_run_context();
+            assert!(context.contains("overall status: critical"));
+            assert!(context.contains("nginx.service"));
+            assert!(context.contains("Restart nginx after fixing its config"));
+            assert!(context.contains("resolved"));
+        }
+    }
+
+    #[test]
+    fn test_report_with_failed_service_is_not_healthy() {
+        let mut system_info = create_test_system_info();
+        system_info.systemd.failed_units = vec!["nginx.service".to_string()];
+
+        let report = create_system_health_report(&system_info, "nginx is down", false);
+        assert!(!report.is_healthy());
+        assert_eq!(report.status.overall, "critical");
+        assert_eq!(report.issues.len(), 1);
+    }
+
+    #[test]
+    fn test_verbosity_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Detailed);
+        assert_eq!(Verbosity::from_flags(false, 2), Verbosity::Full);
+        assert_eq!(Verbosity::from_flags(false, 5), Verbosity::Full);
+        assert_eq!(Verbosity::from_flags(true, 0), Verbosity::Quiet);
+    }
+
+    #[test]
+    fn test_verbosity_limits() {
+        assert_eq!(Verbosity::Quiet.limit(5), 0);
+        assert_eq!(Verbosity::Normal.limit(5), 5);
+        assert_eq!(Verbosity::Detailed.limit(5), 20);
+        assert_eq!(Verbosity::Full.limit(5), usize::MAX);
+
+        assert!(!Verbosity::Quiet.is_detailed());
+        assert!(!Verbosity::Normal.is_detailed());
+        assert!(Verbosity::Detailed.is_detailed());
+        assert!(Verbosity::Full.is_detailed());
+        assert_eq!(Verbosity::default(), Verbosity::Normal);
+    }
+
+    #[test]
+    fn test_verbosity_serde() {
+        assert_eq!(serde_yaml::to_string(&Verbosity::Detailed).unwrap().trim(), "detailed");
+        let parsed: Verbosity = serde_yaml::from_str("full").unwrap();
+        assert_eq!(parsed, Verbosity::Full);
+    }
+}
@@ -1,240 +1,295 @@
-use crate::cli::OutputFormat;
+use crate::cli::{CheckComponent, OutputFormat};
 use crate::config::RaidConfig;
-use crate::output::{create_system_health_report, print_json, print_yaml};
-use crate::sysinfo::SystemInfo;
-use crate::ui::{print_results, print_results_with_formatter, UIFormatter};
-
-pub fn print_output(
-    system_info: &SystemInfo,
-    analysis: &str,
-    output_format: &OutputFormat,
-    verbose: bool,
-) {
-    match output_format {
-        OutputFormat::Text => {
-            print_results(system_info, analysis, verbose);
-        }
-        OutputFormat::Yaml => {
-            let report = create_system_health_report(system_info, analysis, verbose);
-            print_yaml(&report);
-        }
-        OutputFormat::Json => {
-            let report = create_system_health_report(system_info, analysis, verbose);
-            print_json(&report);
-        }
-    }
-}
+use crate::output::{cgroup_limit_issues, create_component_report, print_report, Verbosity};
+use crate::sysinfo::{format_bytes, CgroupInfo, SystemInfo, SystemdUnit};
+use crate::ui::{print_results_with_formatter, UIFormatter};
+use std::io::{self, Write};
 
+/// Print a report, scoped to `component`, in the configured format and verbosity. A full
+/// text report keeps the colored terminal layout; everything else goes through `render`.
 pub fn print_output_with_config(
     system_info: &SystemInfo,
     analysis: &str,
+    component: &CheckComponent,
     config: &RaidConfig,
     ui_formatter: &UIFormatter,
 ) {
-    match config.get_output_format() {
-        OutputFormat::Text => {
-            print_results_with_formatter(system_info, analysis, config.output.verbose, ui_formatter);
-        }
-        OutputFormat::Yaml => {
-            let report = create_system_health_report(system_info, analysis, config.output.verbose);
-            print_yaml(&report);
-        }
-        OutputFormat::Json => {
-            let report = create_system_health_report(system_info, analysis, config.output.verbose);
-            print_json(&report);
-        }
+    let format = config.get_output_format();
+    let verbosity = config.get_verbosity();
+    if format == OutputFormat::Text && matches!(component, CheckComponent::All) {
+        print_results_with_formatter(system_info, analysis, verbosity, ui_formatter);
+        return;
     }
+
+    let mut report = create_component_report(system_info, analysis, component, verbosity.is_detailed());
+    report.apply_output_config(&config.output);
+    print_report(&report, format, verbosity);
 }
 
-pub fn print_system_info(info: &SystemInfo, analysis: &str, verbose: bool) {
-    println!("🔍 System Information");
-    println!("{}", "=".repeat(50));
+pub fn write_system_info(w: &mut dyn Write, info: &SystemInfo, verbosity: Verbosity) -> io::Result<()> {
+    writeln!(w, "🔍 System Information")?;
+    writeln!(w, "{}", "=".repeat(50))?;
 
-    println!("\n📊 System Overview");
-    println!("{}", "-".repeat(30));
-    println!("🖥️  OS: {}", info.os);
-    println!("⚡ CPU: {}", info.cpu);
-    println!("💾 Memory: {}/{}", info.free_memory, info.total_memory);
-    println!("💿 Disk: {}/{}", info.free_disk, info.total_disk);
+    writeln!(w, "\n📊 System Overview")?;
+    writeln!(w, "{}", "-".repeat(30))?;
+    writeln!(w, "🌐 Environment: {}", info.environment.summary())?;
+    writeln!(w, "🖥️  OS: {}", info.os)?;
+    writeln!(w, "⚡ CPU: {}", info.cpu)?;
+    writeln!(w, "💾 Memory: {}/{}", info.free_memory, info.total_memory)?;
+    writeln!(w, "💿 Disk: {}/{}", info.free_disk, info.total_disk)?;
 
     if info.kubernetes.is_kubernetes {
-        println!("☸️  Kubernetes: Yes");
+        writeln!(w, "☸️  Kubernetes: Yes")?;
         if let Some(namespace) = &info.kubernetes.namespace {
-            println!("   Namespace: {}", namespace);
+            writeln!(w, "   Namespace: {}", namespace)?;
         }
         if let Some(pod_name) = &info.kubernetes.pod_name {
-            println!("   Pod: {}", pod_name);
+            writeln!(w, "   Pod: {}", pod_name)?;
         }
     } else {
-        println!("☸️  Kubernetes: No");
+        writeln!(w, "☸️  Kubernetes: No")?;
+    }
+
+    for issue in super::pressure_issues(&info.pressure) {
+        writeln!(w, "🔥 {}", issue.message)?;
     }
 
-    if verbose {
-        println!("\n📋 Verbose System Details");
-        println!("{}", "-".repeat(30));
-        // In verbose mode, show additional system details
+    if verbosity.is_detailed() {
+        writeln!(w, "\n📋 Verbose System Details")?;
+        writeln!(w, "{}", "-".repeat(30))?;
+        if info.pressure.is_available() {
+            write!(w, "{}", info.pressure.to_context())?;
+        }
+        // In detailed mode, show additional system details
         if !info.systemd.units.is_empty() {
-            println!("System Services:");
+            writeln!(w, "System Services:")?;
             for unit in &info.systemd.units {
                 let status_icon = if unit.status == "active" {
                     "✅"
                 } else {
                     "⚠️"
                 };
-                println!("  {} {}: {}", status_icon, unit.name, unit.status);
+                writeln!(w, "  {} {}: {}", status_icon, unit.name, unit.status)?;
             }
         }
 
         if !info.cgroups.controllers.is_empty() {
-            println!(
+            writeln!(
+                w,
                 "Cgroup Controllers: {}",
                 info.cgroups.controllers.join(", ")
-            );
+            )?;
         }
-    }
 
-    println!("\n🤖 AI Analysis");
-    println!("{}", "-".repeat(30));
-    println!("{}", analysis);
+        writeln!(w, "{}", info.swap.summary())?;
+        if let Some(steal) = info.cpu_steal_percent {
+            writeln!(w, "CPU Steal: {:.1}%", steal)?;
+        }
+        if !info.running_kernel.is_empty() {
+            match info.newest_installed_kernel.as_deref().filter(|_| info.needs_reboot) {
+                Some(newest) => writeln!(w, "Kernel: {} (reboot needed: {} is installed)", info.running_kernel, newest)?,
+                None => writeln!(w, "Kernel: {}", info.running_kernel)?,
+            }
+        }
+        let lossy: Vec<_> = info
+            .network_interfaces
+            .iter()
+            .filter(|interface| interface.errors() + interface.dropped() > 0)
+            .collect();
+        if !lossy.is_empty() {
+            writeln!(w, "Interface Errors:")?;
+            for interface in lossy.iter().take(verbosity.limit(10)) {
+                writeln!(w, "  {}", interface.summary())?;
+            }
+        }
+        if !info.filesystems.is_empty() {
+            writeln!(w, "Filesystems:")?;
+            for fs in info.filesystems.iter().take(verbosity.limit(10)) {
+                writeln!(w, "  {} ({}): {}% used, {} free", fs.mount_point, fs.fs_type, fs.use_percent, format_bytes(fs.available_bytes))?;
+            }
+        }
+        writeln!(w, "Zombie Processes: {}", info.zombie_count)?;
+        for parent in info.zombie_parents.iter().take(verbosity.limit(3)) {
+            writeln!(w, "  {} (PID {}): {} defunct children", parent.name, parent.pid, parent.zombies)?;
+        }
+    }
 
-    println!("\n{}", "=".repeat(50));
+    Ok(())
 }
 
-pub fn print_container_info(info: &SystemInfo, analysis: &str, verbose: bool) {
-    println!("=== Container Status ===");
+pub fn write_container_info(w: &mut dyn Write, info: &SystemInfo, verbosity: Verbosity) -> io::Result<()> {
+    writeln!(w, "=== Container Status ===")?;
     if info.containers.is_empty() {
-        println!("No containers found");
+        writeln!(w, "No containers found")?;
     } else {
         for container in &info.containers {
-            let status_icon = if container.status.contains("Up") {
+            let status_icon = if container.is_healthy() {
                 "✅"
             } else {
                 "⚠️"
             };
 
             // In normal mode, only show containers with issues
-            // In verbose mode, show all containers
-            if verbose || !container.status.contains("Up") {
-                println!(
+            // In detailed mode, show all containers; quiet mode only shows the summary
+            let show_container = match verbosity {
+                Verbosity::Quiet => false,
+                Verbosity::Normal => !container.is_healthy(),
+                Verbosity::Detailed | Verbosity::Full => true,
+            };
+            if show_container {
+                writeln!(
+                    w,
                     "  {} {} ({})",
                     status_icon, container.name, container.status
-                );
+                )?;
                 if !container.ports.is_empty() {
-                    println!("    Ports: {}", container.ports.join(", "));
+                    writeln!(w, "    Ports: {}", container.ports.join(", "))?;
+                }
+                if let Some(restarts) = container.restart_count.filter(|restarts| *restarts > 0) {
+                    writeln!(w, "    Restarts: {}", restarts)?;
                 }
-                if verbose {
-                    println!("    Image: {}", container.image);
-                    println!("    ID: {}", container.id);
+                if verbosity == Verbosity::Full {
+                    writeln!(w, "    Image: {}", container.image)?;
+                    writeln!(w, "    ID: {}", container.id)?;
                 }
             }
         }
 
-        if !verbose {
+        if verbosity != Verbosity::Full {
             let healthy_count = info
                 .containers
                 .iter()
-                .filter(|c| c.status.contains("Up"))
+                .filter(|c| c.is_healthy())
                 .count();
             let unhealthy_count = info.containers.len() - healthy_count;
             if unhealthy_count == 0 {
-                println!("  ✅ All {} containers are healthy", info.containers.len());
+                writeln!(w, "  ✅ All {} containers are healthy", info.containers.len())?;
             } else {
-                println!(
+                writeln!(
+                    w,
                     "  Summary: {}/{} containers healthy",
                     healthy_count,
                     info.containers.len()
-                );
+                )?;
             }
         }
     }
-    println!("\n=== AI Analysis ===");
-    println!("{}", analysis);
+    Ok(())
 }
 
-pub fn print_kubernetes_info(info: &SystemInfo, analysis: &str, verbose: bool) {
-    println!("=== Kubernetes Information ===");
+pub fn write_kubernetes_info(w: &mut dyn Write, info: &SystemInfo, verbosity: Verbosity) -> io::Result<()> {
+    writeln!(w, "=== Kubernetes Information ===")?;
     if info.kubernetes.is_kubernetes {
-        println!("Running in Kubernetes: Yes");
+        writeln!(w, "Running in Kubernetes: Yes")?;
         if let Some(namespace) = &info.kubernetes.namespace {
-            println!("Namespace: {}", namespace);
+            writeln!(w, "Namespace: {}", namespace)?;
         }
         if let Some(pod_name) = &info.kubernetes.pod_name {
-            println!("Pod Name: {}", pod_name);
+            writeln!(w, "Pod Name: {}", pod_name)?;
         }
         if let Some(node_name) = &info.kubernetes.node_name {
-            println!("Node Name: {}", node_name);
+            writeln!(w, "Node Name: {}", node_name)?;
         }
         if let Some(sa) = &info.kubernetes.service_account {
-            println!("Service Account: {}", sa);
+            writeln!(w, "Service Account: {}", sa)?;
         }
 
-        if verbose {
-            println!("\nAdditional K8s Details:");
-            println!("Cgroup Version: {}", info.cgroups.version);
+        if verbosity.is_detailed() {
+            writeln!(w, "\nAdditional K8s Details:")?;
+            writeln!(w, "Cgroup Version: {}", info.cgroups.version)?;
             if let Some(memory_limit) = &info.cgroups.memory_limit {
-                println!("Memory Limit: {}", memory_limit);
+                writeln!(w, "Memory Limit: {}", memory_limit)?;
             }
             if let Some(cpu_limit) = &info.cgroups.cpu_limit {
-                println!("CPU Limit: {}", cpu_limit);
+                writeln!(w, "CPU Limit: {}", cpu_limit)?;
             }
         }
+
+        write_cgroup_limit_warnings(w, &info.cgroups)?;
     } else {
-        println!("Running in Kubernetes: No");
+        writeln!(w, "Running in Kubernetes: No")?;
     }
-    println!("\n=== AI Analysis ===");
-    println!("{}", analysis);
+    Ok(())
 }
 
-pub fn print_cgroup_info(info: &SystemInfo, analysis: &str, verbose: bool) {
-    println!("=== Cgroup Information ===");
-    println!("Version: {}", info.cgroups.version);
-    println!("Path: {}", info.cgroups.cgroup_path);
+pub fn write_cgroup_info(w: &mut dyn Write, info: &SystemInfo, verbosity: Verbosity) -> io::Result<()> {
+    writeln!(w, "=== Cgroup Information ===")?;
+    writeln!(w, "Version: {}", info.cgroups.version)?;
+    writeln!(w, "Path: {}", info.cgroups.cgroup_path)?;
 
-    if verbose || !info.cgroups.controllers.is_empty() {
-        println!("Controllers: {}", info.cgroups.controllers.join(", "));
+    if verbosity.is_detailed() || !info.cgroups.controllers.is_empty() {
+        writeln!(w, "Controllers: {}", info.cgroups.controllers.join(", "))?;
+    }
+    if let Some(enabled) = &info.cgroups.enabled_controllers {
+        writeln!(w, "Enabled for this cgroup: {}", enabled.join(", "))?;
     }
 
     if let Some(memory_limit) = &info.cgroups.memory_limit {
-        println!("Memory Limit: {}", memory_limit);
+        writeln!(w, "Memory Limit: {}", memory_limit)?;
     }
     if let Some(cpu_limit) = &info.cgroups.cpu_limit {
-        println!("CPU Limit: {}", cpu_limit);
+        writeln!(w, "CPU Limit: {}", cpu_limit)?;
     }
+    write_cgroup_usage(w, &info.cgroups)?;
 
-    if verbose {
-        println!("\nVerbose Cgroup Details:");
-        println!("Full cgroup path: {}", info.cgroups.cgroup_path);
+    if verbosity.is_detailed() {
+        writeln!(w, "\nVerbose Cgroup Details:")?;
+        writeln!(w, "Full cgroup path: {}", info.cgroups.cgroup_path)?;
     }
 
-    println!("\n=== AI Analysis ===");
-    println!("{}", analysis);
+    Ok(())
+}
+
+/// A crash loop reads differently from a unit that is merely inactive
+fn unit_issue_line(unit: &SystemdUnit) -> String {
+    if unit.is_restart_loop() {
+        format!("🔁 {}: {} - crash loop, systemd keeps restarting it", unit.name, unit.state_label())
+    } else {
+        format!("⚠️  {}: {}", unit.name, unit.state_label())
+    }
 }
 
-pub fn print_systemd_info(info: &SystemInfo, analysis: &str, verbose: bool) {
-    println!("=== Service Status ===");
-    println!("System Status: {}", info.systemd.system_status);
+pub fn write_systemd_info(w: &mut dyn Write, info: &SystemInfo, verbosity: Verbosity) -> io::Result<()> {
+    writeln!(w, "=== Service Status ===")?;
+    writeln!(w, "System Status: {}", info.systemd.system_status)?;
 
     if !info.systemd.failed_units.is_empty() {
-        println!("Failed Units:");
+        writeln!(w, "Failed Units:")?;
         for unit in &info.systemd.failed_units {
-            println!("  ❌ {}", unit);
+            writeln!(w, "  ❌ {}", unit)?;
         }
     }
 
-    // Show units based on verbose mode
-    if verbose {
-        // In verbose mode, show all units
-        println!("All Monitored Units:");
-        for unit in &info.systemd.units {
+    // Show units based on verbosity
+    if verbosity.is_detailed() {
+        // In detailed mode, show all units (with descriptions in full mode)
+        writeln!(w, "All Monitored Units:")?;
+        let unit_limit = verbosity.limit(5);
+        for unit in info.systemd.units.iter().take(unit_limit) {
             let status_icon = if unit.status == "active" {
                 "✅"
             } else {
                 "⚠️"
             };
-            println!(
-                "  {} {}: {} - {}",
-                status_icon, unit.name, unit.status, unit.description
-            );
+            if verbosity == Verbosity::Full {
+                writeln!(
+                    w,
+                    "  {} {}: {} - {}",
+                    status_icon, unit.name, unit.status, unit.description
+                )?;
+            } else {
+                writeln!(w, "  {} {}: {}", status_icon, unit.name, unit.status)?;
+            }
+        }
+        if info.systemd.units.len() > unit_limit {
+            writeln!(w, "  ... and {} more units", info.systemd.units.len() - unit_limit)?;
+        }
+    } else if verbosity == Verbosity::Quiet {
+        let problem_units = info.systemd.units.iter().filter(|u| u.status != "active").count();
+        if problem_units > 0 {
+            writeln!(w, "Units with Issues: {}", problem_units)?;
+        } else if info.systemd.failed_units.is_empty() {
+            writeln!(w, "✅ All services are running normally")?;
         }
     } else {
         // In normal mode, only show units with issues
@@ -242,59 +297,64 @@ pub fn print_systemd_info(info: &SystemInfo, analysis: &str, verbose: bool) {
         for unit in &info.systemd.units {
             if unit.status != "active" {
                 if !has_issues {
-                    println!("Units with Issues:");
+                    writeln!(w, "Units with Issues:")?;
                     has_issues = true;
                 }
-                println!("  ⚠️  {}: {}", unit.name, unit.status);
+                writeln!(w, "  {}", unit_issue_line(unit))?;
             }
         }
 
         if !has_issues && info.systemd.failed_units.is_empty() {
-            println!("✅ All services are running normally");
+            writeln!(w, "✅ All services are running normally")?;
         }
     }
 
-    println!("\n=== AI Analysis ===");
-    println!("{}", analysis);
+    Ok(())
 }
 
-pub fn print_journal_info(info: &SystemInfo, analysis: &str, verbose: bool) {
-    println!("=== System Logs ===");
+pub fn write_journal_info(w: &mut dyn Write, info: &SystemInfo, verbosity: Verbosity) -> io::Result<()> {
+    writeln!(w, "=== System Logs ===")?;
+    if let Some(usage) = &info.journal.disk_usage {
+        writeln!(w, "{}", usage.summary())?;
+    }
 
-    if verbose {
-        // In verbose mode, show ALL logs
+    if verbosity == Verbosity::Full {
+        // In full mode, show ALL logs
         if !info.journal.recent_errors.is_empty() {
-            println!("All Recent Errors ({}):", info.journal.recent_errors.len());
+            writeln!(w, "All Recent Errors ({}):", info.journal.recent_errors.len())?;
             for entry in &info.journal.recent_errors {
-                println!(
+                writeln!(
+                    w,
                     "  ❌ [{}] {}: {}",
                     entry.timestamp, entry.unit, entry.message
-                );
+                )?;
             }
         }
 
         if !info.journal.boot_errors.is_empty() {
-            println!("All Boot Errors ({}):", info.journal.boot_errors.len());
+            writeln!(w, "All Boot Errors ({}):", info.journal.boot_errors.len())?;
             for entry in &info.journal.boot_errors {
-                println!("  🔄 [BOOT] {}: {}", entry.unit, entry.message);
+                writeln!(w, "  🔄 [BOOT] {}: {}", entry.unit, entry.message)?;
             }
         }
 
         if !info.journal.recent_warnings.is_empty() {
-            println!("Recent Warnings ({}):", info.journal.recent_warnings.len());
+            writeln!(w, "Recent Warnings ({}):", info.journal.recent_warnings.len())?;
             for (i, entry) in info.journal.recent_warnings.iter().enumerate() {
                 if i >= 10 {
                     // Limit warnings in verbose mode to avoid spam
-                    println!(
+                    writeln!(
+                        w,
                         "  ... and {} more warnings",
                         info.journal.recent_warnings.len() - i
-                    );
+                    )?;
                     break;
                 }
-                println!(
+                writeln!(
+                    w,
                     "  ⚠️  [{}] {}: {}",
                     entry.timestamp, entry.unit, entry.message
-                );
+                )?;
             }
         }
 
@@ -302,49 +362,63 @@ pub fn print_journal_info(info: &SystemInfo, analysis: &str, verbose: bool) {
             && info.journal.boot_errors.is_empty()
             && info.journal.recent_warnings.is_empty()
         {
-            println!("✅ No errors or warnings found");
+            writeln!(w, "✅ No errors or warnings found")?;
         }
     } else {
-        // In normal mode, show only significant errors
-        let mut significant_errors = 0;
-        for entry in &info.journal.recent_errors {
-            if !is_common_non_critical_error(&entry.message) {
-                if significant_errors == 0 {
-                    println!("Recent Errors:");
+        // Otherwise show only significant errors, capped by verbosity
+        let significant_errors: Vec<_> = info
+            .journal
+            .recent_errors
+            .iter()
+            .filter(|entry| !is_common_non_critical_error(&entry.message))
+            .collect();
+        let error_limit = verbosity.limit(5);
+        if !significant_errors.is_empty() {
+            if error_limit == 0 {
+                writeln!(w, "Recent Errors: {}", significant_errors.len())?;
+            } else {
+                writeln!(w, "Recent Errors:")?;
+                for entry in significant_errors.iter().take(error_limit) {
+                    writeln!(
+                        w,
+                        "  ❌ [{}] {}: {}",
+                        entry.timestamp, entry.unit, entry.message
+                    )?;
                 }
-                println!(
-                    "  ❌ [{}] {}: {}",
-                    entry.timestamp, entry.unit, entry.message
-                );
-                significant_errors += 1;
-                if significant_errors >= 5 {
-                    break;
+                if significant_errors.len() > error_limit {
+                    writeln!(w, "  ... and {} more errors", significant_errors.len() - error_limit)?;
                 }
             }
         }
 
         // Show boot errors only if significant
-        let mut boot_error_count = 0;
-        for entry in &info.journal.boot_errors {
-            if !is_common_non_critical_error(&entry.message) {
-                if boot_error_count == 0 {
-                    println!("Boot Errors:");
+        let significant_boot_errors: Vec<_> = info
+            .journal
+            .boot_errors
+            .iter()
+            .filter(|entry| !is_common_non_critical_error(&entry.message))
+            .collect();
+        let boot_limit = verbosity.limit(3);
+        if !significant_boot_errors.is_empty() {
+            if boot_limit == 0 {
+                writeln!(w, "Boot Errors: {}", significant_boot_errors.len())?;
+            } else {
+                writeln!(w, "Boot Errors:")?;
+                for entry in significant_boot_errors.iter().take(boot_limit) {
+                    writeln!(w, "  🔄 [BOOT] {}: {}", entry.unit, entry.message)?;
                 }
-                println!("  🔄 [BOOT] {}: {}", entry.unit, entry.message);
-                boot_error_count += 1;
-                if boot_error_count >= 3 {
-                    break;
+                if significant_boot_errors.len() > boot_limit {
+                    writeln!(w, "  ... and {} more boot errors", significant_boot_errors.len() - boot_limit)?;
                 }
             }
         }
 
-        if significant_errors == 0 && boot_error_count == 0 {
-            println!("✅ No significant errors found");
+        if significant_errors.is_empty() && significant_boot_errors.is_empty() {
+            writeln!(w, "✅ No significant errors found")?;
         }
     }
 
-    println!("\n=== AI Analysis ===");
-    println!("{}", analysis);
+    Ok(())
 }
 
 // Dry-run versions of print functions (no AI analysis)
@@ -353,6 +427,7 @@ pub fn print_results_dry_run(info: &SystemInfo) {
 
     // Always show general system information
     println!("\n--- General System Information ---");
+    println!("Environment: {}", info.environment.summary());
     println!("OS: {}", info.os);
     println!("CPU: {}", info.cpu);
     println!("Total Memory: {}", info.total_memory);
@@ -375,7 +450,7 @@ pub fn print_results_dry_run(info: &SystemInfo) {
     let has_container_issues = info
         .containers
         .iter()
-        .any(|container| !container.status.contains("Up"));
+        .any(|container| !container.is_healthy());
 
     // Only show Kubernetes info if we're in K8s AND there are issues
     if info.kubernetes.is_kubernetes && (has_failed_services || has_significant_errors) {
@@ -440,8 +515,8 @@ pub fn print_results_dry_run(info: &SystemInfo) {
     if has_container_issues {
         println!("\n=== Container Status ===");
         for container in &info.containers {
-            if !container.status.contains("Up") {
-                let status_icon = if container.status.contains("Up") {
+            if !container.is_healthy() {
+                let status_icon = if container.is_healthy() {
                     "✅"
                 } else {
                     "⚠️"
@@ -466,6 +541,43 @@ pub fn print_results_dry_run(info: &SystemInfo) {
     println!("AI analysis skipped. Use without --dry-run flag for AI-powered insights.");
 }
 
+/// Write cgroup usage next to its limits, followed by any limit warnings
+fn write_cgroup_usage(w: &mut dyn Write, cgroups: &CgroupInfo) -> io::Result<()> {
+    match (cgroups.memory_usage, cgroups.memory_limit_bytes(), cgroups.memory_usage_percent()) {
+        (Some(usage), Some(limit), Some(percent)) => writeln!(
+            w,
+            "Memory Usage: {} of {} ({:.0}%)",
+            format_bytes(usage),
+            format_bytes(limit),
+            percent
+        )?,
+        (Some(usage), _, _) => writeln!(w, "Memory Usage: {}", format_bytes(usage))?,
+        _ => {}
+    }
+    if let Some(throttled) = cgroups.cpu_throttled_percent {
+        match cgroups.cpu_throttled_usec {
+            Some(usec) => writeln!(
+                w,
+                "CPU Throttled: {:.0}% of periods ({:.1}s in total)",
+                throttled,
+                usec as f64 / 1_000_000.0
+            )?,
+            None => writeln!(w, "CPU Throttled: {:.0}% of periods", throttled)?,
+        }
+    }
+    write_cgroup_limit_warnings(w, cgroups)
+}
+
+fn write_cgroup_limit_warnings(w: &mut dyn Write, cgroups: &CgroupInfo) -> io::Result<()> {
+    for issue in cgroup_limit_issues(cgroups) {
+        match &issue.details {
+            Some(details) => writeln!(w, "⚠️  {} ({})", issue.message, details)?,
+            None => writeln!(w, "⚠️  {}", issue.message)?,
+        }
+    }
+    Ok(())
+}
+
 pub fn is_common_non_critical_error(message: &str) -> bool {
     let common_errors = [
         "dmidecode",
@@ -497,148 +609,3 @@ pub fn is_common_non_critical_error(message: &str) -> bool {
         .iter()
         .any(|error| message_lower.contains(error))
 }
-
-pub fn print_system_info_dry_run(info: &SystemInfo) {
-    println!("=== System Information ===");
-    println!("OS: {}", info.os);
-    println!("CPU: {}", info.cpu);
-    println!("\n=== DRY RUN MODE ===");
-    println!("AI analysis skipped. Use without --dry-run flag for AI-powered insights.");
-}
-
-pub fn print_container_info_dry_run(info: &SystemInfo) {
-    println!("=== Container Status ===");
-    if info.containers.is_empty() {
-        println!("No containers found");
-    } else {
-        for container in &info.containers {
-            let status_icon = if container.status.contains("Up") {
-                "✅"
-            } else {
-                "⚠️"
-            };
-            println!(
-                "  {} {} ({})",
-                status_icon, container.name, container.status
-            );
-            if !container.ports.is_empty() {
-                println!("    Ports: {}", container.ports.join(", "));
-            }
-        }
-    }
-    println!("\n=== DRY RUN MODE ===");
-    println!("AI analysis skipped. Use without --dry-run flag for AI-powered insights.");
-}
-
-pub fn print_kubernetes_info_dry_run(info: &SystemInfo) {
-    println!("=== Kubernetes Information ===");
-    if info.kubernetes.is_kubernetes {
-        println!("Running in Kubernetes: Yes");
-        if let Some(namespace) = &info.kubernetes.namespace {
-            println!("Namespace: {}", namespace);
-        }
-        if let Some(pod_name) = &info.kubernetes.pod_name {
-            println!("Pod Name: {}", pod_name);
-        }
-        if let Some(node_name) = &info.kubernetes.node_name {
-            println!("Node Name: {}", node_name);
-        }
-        if let Some(sa) = &info.kubernetes.service_account {
-            println!("Service Account: {}", sa);
-        }
-    } else {
-        println!("Running in Kubernetes: No");
-    }
-    println!("\n=== DRY RUN MODE ===");
-    println!("AI analysis skipped. Use without --dry-run flag for AI-powered insights.");
-}
-
-pub fn print_cgroup_info_dry_run(info: &SystemInfo) {
-    println!("=== Cgroup Information ===");
-    println!("Version: {}", info.cgroups.version);
-    println!("Path: {}", info.cgroups.cgroup_path);
-    println!("Controllers: {}", info.cgroups.controllers.join(", "));
-    if let Some(memory_limit) = &info.cgroups.memory_limit {
-        println!("Memory Limit: {}", memory_limit);
-    }
-    if let Some(cpu_limit) = &info.cgroups.cpu_limit {
-        println!("CPU Limit: {}", cpu_limit);
-    }
-    println!("\n=== DRY RUN MODE ===");
-    println!("AI analysis skipped. Use without --dry-run flag for AI-powered insights.");
-}
-
-pub fn print_systemd_info_dry_run(info: &SystemInfo) {
-    println!("=== Service Status ===");
-    println!("System Status: {}", info.systemd.system_status);
-
-    if !info.systemd.failed_units.is_empty() {
-        println!("Failed Units:");
-        for unit in &info.systemd.failed_units {
-            println!("  ❌ {}", unit);
-        }
-    }
-
-    // Only show important units if they have issues
-    let mut has_issues = false;
-    for unit in &info.systemd.units {
-        if unit.status != "active" {
-            if !has_issues {
-                println!("Units with Issues:");
-                has_issues = true;
-            }
-            println!("  ⚠️  {}: {}", unit.name, unit.status);
-        }
-    }
-
-    if !has_issues && info.systemd.failed_units.is_empty() {
-        println!("✅ All services are running normally");
-    }
-
-    println!("\n=== DRY RUN MODE ===");
-    println!("AI analysis skipped. Use without --dry-run flag for AI-powered insights.");
-}
-
-pub fn print_journal_info_dry_run(info: &SystemInfo) {
-    println!("=== System Logs ===");
-
-    // Show only significant errors
-    let mut significant_errors = 0;
-    for entry in &info.journal.recent_errors {
-        if !is_common_non_critical_error(&entry.message) {
-            if significant_errors == 0 {
-                println!("Recent Errors:");
-            }
-            println!(
-                "  ❌ [{}] {}: {}",
-                entry.timestamp, entry.unit, entry.message
-            );
-            significant_errors += 1;
-            if significant_errors >= 5 {
-                break;
-            }
-        }
-    }
-
-    // Show boot errors only if significant
-    let mut boot_error_count = 0;
-    for entry in &info.journal.boot_errors {
-        if !is_common_non_critical_error(&entry.message) {
-            if boot_error_count == 0 {
-                println!("Boot Errors:");
-            }
-            println!("  🔄 [BOOT] {}: {}", entry.unit, entry.message);
-            boot_error_count += 1;
-            if boot_error_count >= 3 {
-                break;
-            }
-        }
-    }
-
-    if significant_errors == 0 && boot_error_count == 0 {
-        println!("✅ No significant errors found");
-    }
-
-    println!("\n=== DRY RUN MODE ===");
-    println!("AI analysis skipped. Use without --dry-run flag for AI-powered insights.");
-} 
\ No newline at end of file
@@ -1,8 +1,10 @@
+use crate::tools::pool::PooledOutput;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SystemInfo {
     pub os: String,
     pub cpu: String,
@@ -15,9 +17,311 @@ pub struct SystemInfo {
     pub systemd: SystemdInfo,
     pub journal: JournalInfo,
     pub containers: Vec<ContainerInfo>,
+    #[serde(default)]
+    pub pressure: PressureInfo,
+    /// Processes in state `Z` (defunct), from /proc/*/stat
+    #[serde(default)]
+    pub zombie_count: usize,
+    /// Parents that are not reaping their zombies, most zombies first
+    #[serde(default)]
+    pub zombie_parents: Vec<ZombieParent>,
+    #[serde(default)]
+    pub swap: SwapInfo,
+    /// Usage of each real (non-pseudo) filesystem, from `df`
+    #[serde(default)]
+    pub filesystems: Vec<FilesystemUsage>,
+    /// Share of CPU time the hypervisor gave to other guests, sampled from /proc/stat
+    #[serde(default)]
+    pub cpu_steal_percent: Option<f32>,
+    /// Bare metal, VM, container or Kubernetes pod, which changes how the rest reads
+    #[serde(default)]
+    pub environment: Environment,
+    /// Release of the running kernel (`uname -r`)
+    #[serde(default)]
+    pub running_kernel: String,
+    /// Newest kernel release installed in /lib/modules or /boot
+    #[serde(default)]
+    pub newest_installed_kernel: Option<String>,
+    /// The running kernel isn't the newest installed one (or was removed by an upgrade), so
+    /// fixes in the installed kernel take effect only after a reboot
+    #[serde(default)]
+    pub needs_reboot: bool,
+    /// Error and drop counters of each interface in /proc/net/dev
+    #[serde(default)]
+    pub network_interfaces: Vec<InterfaceCounters>,
+    /// Processes with unusually many children or nesting, from the /proc parent links
+    #[serde(default)]
+    pub process_highlights: Vec<ProcessHighlight>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Packet, error and drop counters of one interface from /proc/net/dev
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct InterfaceCounters {
+    pub name: String,
+    pub rx_packets: u64,
+    pub rx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_packets: u64,
+    pub tx_errors: u64,
+    pub tx_dropped: u64,
+    /// What the counters grew by while the collection sampled them twice; a since-boot
+    /// total alone can't tell an old burst from a link failing right now
+    #[serde(default)]
+    pub recent: Option<InterfaceDelta>,
+}
+
+/// Growth of an interface's counters between two reads of /proc/net/dev
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct InterfaceDelta {
+    pub packets: u64,
+    pub errors: u64,
+    pub dropped: u64,
+    pub seconds: f32,
+}
+
+impl InterfaceCounters {
+    pub fn packets(&self) -> u64 {
+        self.rx_packets + self.tx_packets
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.rx_errors + self.tx_errors
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.rx_dropped + self.tx_dropped
+    }
+
+    /// Counter growth since an earlier read of the same interface; `None` when a counter
+    /// went backwards (the interface was recreated in between)
+    pub fn delta_since(&self, earlier: &InterfaceCounters, seconds: f32) -> Option<InterfaceDelta> {
+        Some(InterfaceDelta {
+            packets: self.packets().checked_sub(earlier.packets())?,
+            errors: self.errors().checked_sub(earlier.errors())?,
+            dropped: self.dropped().checked_sub(earlier.dropped())?,
+            seconds,
+        })
+    }
+
+    /// One line for the AI context and the text report
+    pub fn summary(&self) -> String {
+        let recent = self
+            .recent
+            .filter(|recent| recent.errors + recent.dropped > 0)
+            .map(|recent| format!("; +{} errors, +{} drops in the last {:.1}s", recent.errors, recent.dropped, recent.seconds))
+            .unwrap_or_default();
+        format!(
+            "{}: {} RX errors, {} RX drops, {} TX errors, {} TX drops of {} packets since boot{}",
+            self.name,
+            self.rx_errors,
+            self.rx_dropped,
+            self.tx_errors,
+            self.tx_dropped,
+            self.packets(),
+            recent
+        )
+    }
+}
+
+/// One mounted filesystem as reported by `df -kPT`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FilesystemUsage {
+    pub filesystem: String,
+    pub fs_type: String,
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+    /// df's capacity column: used / (used + available), so reserved blocks count as full
+    pub use_percent: u8,
+}
+
+/// Filesystems that are always full, live in memory or mirror another mount
+const PSEUDO_FILESYSTEMS: [&str; 8] = [
+    "tmpfs", "devtmpfs", "squashfs", "overlay", "iso9660", "efivarfs", "ramfs", "nsfs",
+];
+
+/// Swap usage from /proc/meminfo and the kernel's `vm.swappiness`
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SwapInfo {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    /// `/proc/sys/vm/swappiness`, when readable
+    pub swappiness: Option<u32>,
+}
+
+impl SwapInfo {
+    /// Percentage of swap in use, `None` when no swap is configured
+    pub fn used_percent(&self) -> Option<f64> {
+        if self.total_bytes == 0 {
+            return None;
+        }
+        Some(self.used_bytes as f64 / self.total_bytes as f64 * 100.0)
+    }
+
+    /// One line for the AI context, e.g. `Swap: 1.5 GiB used of 2.0 GiB (75.0%), vm.swappiness=60`
+    pub fn summary(&self) -> String {
+        let swappiness = self
+            .swappiness
+            .map(|value| format!(", vm.swappiness={}", value))
+            .unwrap_or_default();
+        match self.used_percent() {
+            Some(percent) => format!(
+                "Swap: {} used of {} ({:.1}%){}",
+                format_bytes(self.used_bytes),
+                format_bytes(self.total_bytes),
+                percent,
+                swappiness
+            ),
+            None => format!("Swap: none configured{}", swappiness),
+        }
+    }
+}
+
+impl SystemInfo {
+    /// The headline facts given to the AI: OS, CPU, memory, disk and swap
+    pub fn context_summary(&self) -> String {
+        let mut summary = String::new();
+        if self.environment.kind != EnvironmentKind::Unknown {
+            summary.push_str(&format!("Environment: {}\n", self.environment.summary()));
+        }
+        summary.push_str(&format!(
+            "Operating System: {}\nCPU: {}\nMemory: {}/{}\nDisk: {}/{}\n{}\n",
+            self.os,
+            self.cpu,
+            self.free_memory,
+            self.total_memory,
+            self.free_disk,
+            self.total_disk,
+            self.swap.summary()
+        ));
+        if let Some(steal) = self.cpu_steal_percent {
+            summary.push_str(&format!("CPU steal: {:.1}% of CPU time taken by the hypervisor\n", steal));
+        }
+        if self.needs_reboot
+            && let Some(newest) = &self.newest_installed_kernel
+        {
+            summary.push_str(&format!(
+                "Kernel: running {}, newest installed {}; a reboot is needed to load it\n",
+                self.running_kernel, newest
+            ));
+        }
+        if let (Some(throttled), Some(periods)) = (self.cgroups.cpu_nr_throttled, self.cgroups.cpu_nr_periods)
+            && throttled > 0
+        {
+            summary.push_str(&format!(
+                "CPU throttling: {} of {} cgroup periods throttled{}\n",
+                throttled,
+                periods,
+                self.cgroups
+                    .cpu_throttled_usec
+                    .map(|usec| format!(", {:.1}s throttled in total", usec as f64 / 1_000_000.0))
+                    .unwrap_or_default()
+            ));
+        }
+        for interface in self.network_interfaces.iter().filter(|interface| interface.errors() + interface.dropped() > 0) {
+            summary.push_str(&format!("Interface {}\n", interface.summary()));
+        }
+        for highlight in &self.process_highlights {
+            summary.push_str(&format!("Process tree: {}\n", highlight.summary()));
+        }
+        if let Some(usage) = &self.journal.disk_usage {
+            summary.push_str(&format!("{}\n", usage.summary()));
+        }
+        summary
+    }
+}
+
+/// A process that stands out in the process tree, with the chain of parents that started it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProcessHighlight {
+    pub pid: u32,
+    pub name: String,
+    /// What stands out, e.g. `64 children (60 × php-fpm)` or `nested 12 levels deep`
+    pub reason: String,
+    /// Ancestors from the root down, e.g. `systemd (1) > containerd-shim (900)`
+    pub ancestry: String,
+}
+
+impl ProcessHighlight {
+    /// One line for the AI context and the ps_tree tool
+    pub fn summary(&self) -> String {
+        if self.ancestry.is_empty() {
+            format!("{} (PID {}) has {}", self.name, self.pid, self.reason)
+        } else {
+            format!("{} (PID {}) has {}, started by {}", self.name, self.pid, self.reason, self.ancestry)
+        }
+    }
+}
+
+/// A process with defunct children it has not waited for
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ZombieParent {
+    pub pid: u32,
+    pub name: String,
+    pub zombies: usize,
+}
+
+/// Pressure stall information (PSI) from /proc/pressure, plus the container's own cgroup
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PressureInfo {
+    pub cpu: Option<PressureStats>,
+    pub memory: Option<PressureStats>,
+    pub io: Option<PressureStats>,
+    /// `memory.pressure` of our cgroup (only present inside a cgroup v2 container)
+    pub cgroup_memory: Option<PressureStats>,
+}
+
+/// The `some` and `full` lines of a PSI file
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct PressureStats {
+    pub some: PressureAverages,
+    /// Not reported for CPU on older kernels
+    pub full: Option<PressureAverages>,
+}
+
+/// Share of time (percent) tasks were stalled over the last 10s, 60s and 300s
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct PressureAverages {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+}
+
+impl PressureInfo {
+    /// Whether any PSI data could be read (PSI needs kernel 4.20+ and CONFIG_PSI)
+    pub fn is_available(&self) -> bool {
+        self.cpu.is_some() || self.memory.is_some() || self.io.is_some() || self.cgroup_memory.is_some()
+    }
+
+    /// Short summary for AI context, one line per resource
+    pub fn to_context(&self) -> String {
+        let mut context = String::new();
+        for (name, stats) in [
+            ("CPU", &self.cpu),
+            ("Memory", &self.memory),
+            ("IO", &self.io),
+            ("Cgroup memory", &self.cgroup_memory),
+        ] {
+            if let Some(stats) = stats {
+                context.push_str(&format!(
+                    "{} pressure: some avg10={:.2} avg60={:.2} avg300={:.2}",
+                    name, stats.some.avg10, stats.some.avg60, stats.some.avg300
+                ));
+                if let Some(full) = &stats.full {
+                    context.push_str(&format!(
+                        "; full avg10={:.2} avg60={:.2} avg300={:.2}",
+                        full.avg10, full.avg60, full.avg300
+                    ));
+                }
+                context.push('\n');
+            }
+        }
+        context
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct KubernetesInfo {
     pub namespace: Option<String>,
     pub pod_name: Option<String>,
@@ -26,16 +330,130 @@ pub struct KubernetesInfo {
     pub is_kubernetes: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// What RAID is running on, from `detect_environment`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvironmentKind {
+    /// Not detected, e.g. a report saved before environment detection existed
+    #[default]
+    Unknown,
+    BareMetal,
+    VirtualMachine,
+    Container,
+    KubernetesPod,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Environment {
+    pub kind: EnvironmentKind,
+    /// Hypervisor underneath, e.g. `kvm`; set for containers and pods on a VM too
+    pub hypervisor: Option<String>,
+    /// Container runtime, e.g. `docker` or `containerd`
+    pub container_runtime: Option<String>,
+}
+
+impl Environment {
+    /// One line for reports, e.g. "Kubernetes pod (containerd) on a kvm virtual machine"
+    pub fn summary(&self) -> String {
+        let mut summary = match self.kind {
+            EnvironmentKind::Unknown => return "unknown".to_string(),
+            EnvironmentKind::BareMetal => return "bare metal".to_string(),
+            EnvironmentKind::VirtualMachine => "virtual machine".to_string(),
+            EnvironmentKind::Container => "container".to_string(),
+            EnvironmentKind::KubernetesPod => "Kubernetes pod".to_string(),
+        };
+        if let Some(runtime) = &self.container_runtime {
+            summary.push_str(&format!(" ({})", runtime));
+        }
+        match (&self.hypervisor, self.kind) {
+            (Some(hypervisor), EnvironmentKind::VirtualMachine) => summary.push_str(&format!(" ({})", hypervisor)),
+            (Some(hypervisor), _) => summary.push_str(&format!(" on a {} virtual machine", hypervisor)),
+            (None, _) => {}
+        }
+        summary
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CgroupInfo {
     pub version: String,
+    /// Controllers available on the system: the root `cgroup.controllers` on v2, the mounted
+    /// hierarchies on v1
     pub controllers: Vec<String>,
+    /// Controllers enabled for this process's own cgroup (its `cgroup.controllers` on v2).
+    /// Limits of a controller missing here are not applied to the cgroup. `None` when unknown.
+    #[serde(default)]
+    pub enabled_controllers: Option<Vec<String>>,
     pub memory_limit: Option<String>,
     pub cpu_limit: Option<String>,
     pub cgroup_path: String,
+    /// Current memory usage in bytes (`memory.current` / `memory.usage_in_bytes`)
+    #[serde(default)]
+    pub memory_usage: Option<u64>,
+    /// Share of CPU scheduling periods that were throttled (`cpu.stat`)
+    #[serde(default)]
+    pub cpu_throttled_percent: Option<f32>,
+    /// Scheduling periods counted in `cpu.stat` (`nr_periods`)
+    #[serde(default)]
+    pub cpu_nr_periods: Option<u64>,
+    /// Periods in which the cgroup was throttled (`nr_throttled`)
+    #[serde(default)]
+    pub cpu_nr_throttled: Option<u64>,
+    /// Total time throttled in microseconds (`throttled_usec`, or v1 `throttled_time` in ns)
+    #[serde(default)]
+    pub cpu_throttled_usec: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Limits at or above this are the kernel's "unlimited" sentinel (cgroup v1 reports ~2^63)
+const UNLIMITED_MEMORY_BYTES: u64 = 1 << 60;
+
+impl CgroupInfo {
+    /// Memory limit in bytes, or None if unlimited/unknown
+    pub fn memory_limit_bytes(&self) -> Option<u64> {
+        self.memory_limit
+            .as_deref()
+            .and_then(|limit| limit.trim().parse::<u64>().ok())
+            .filter(|limit| *limit > 0 && *limit < UNLIMITED_MEMORY_BYTES)
+    }
+
+    /// Whether a CPU quota is set: v2 `cpu.max` reads `max 100000` and v1 `cpu.cfs_quota_us`
+    /// reads `-1` when there is none
+    pub fn has_cpu_quota(&self) -> bool {
+        self.cpu_limit
+            .as_deref()
+            .and_then(|limit| limit.split_whitespace().next())
+            .and_then(|quota| quota.parse::<i64>().ok())
+            .is_some_and(|quota| quota > 0)
+    }
+
+    /// Whether a controller is enabled for this process's cgroup, `None` when unknown
+    pub fn controller_enabled(&self, controller: &str) -> Option<bool> {
+        self.enabled_controllers
+            .as_ref()
+            .map(|enabled| enabled.iter().any(|name| name == controller))
+    }
+
+    /// Memory usage as a percentage of the cgroup limit, when both are known
+    pub fn memory_usage_percent(&self) -> Option<f32> {
+        let limit = self.memory_limit_bytes()?;
+        let usage = self.memory_usage?;
+        Some(usage as f32 / limit as f32 * 100.0)
+    }
+}
+
+/// Format a byte count as MiB/GiB for display
+pub fn format_bytes(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    const GIB: f64 = MIB * 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= GIB {
+        format!("{:.1} GiB", bytes / GIB)
+    } else {
+        format!("{:.0} MiB", bytes / MIB)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SystemdInfo {
     pub units: Vec<SystemdUnit>,
     pub failed_units: Vec<String>,
@@ -47,13 +465,92 @@ pub struct SystemdUnit {
     pub name: String,
     pub status: String,
     pub description: String,
+    /// systemd SubState, e.g. `running`, `auto-restart`, `start-pre`
+    #[serde(default)]
+    pub sub_state: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl SystemdUnit {
+    /// `activating (auto-restart)`: the service keeps crashing and systemd keeps restarting it
+    pub fn is_restart_loop(&self) -> bool {
+        self.sub_state == "auto-restart"
+    }
+
+    /// Stuck starting up (`activating` in any sub-state other than auto-restart)
+    pub fn is_stuck_activating(&self) -> bool {
+        self.status == "activating" && !self.is_restart_loop()
+    }
+
+    /// State as systemctl shows it, e.g. `activating (auto-restart)`
+    pub fn state_label(&self) -> String {
+        if self.sub_state.is_empty() {
+            self.status.clone()
+        } else {
+            format!("{} ({})", self.status, self.sub_state)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct JournalInfo {
     pub recent_errors: Vec<JournalEntry>,
     pub recent_warnings: Vec<JournalEntry>,
     pub boot_errors: Vec<JournalEntry>,
+    /// Size of the journal against its cap, when `journalctl --disk-usage` worked
+    #[serde(default)]
+    pub disk_usage: Option<JournalDiskUsage>,
+}
+
+/// journald caps a persistent journal at 10% of its filesystem, but never above 4 GiB,
+/// unless `SystemMaxUse` says otherwise
+const JOURNAL_DEFAULT_MAX_USE_CAP: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Where journald keeps a persistent journal
+const PERSISTENT_JOURNAL_DIR: &str = "/var/log/journal";
+
+/// journald.conf drop-in directories, lowest priority first
+const JOURNALD_DROP_IN_DIRS: [&str; 3] = [
+    "/usr/lib/systemd/journald.conf.d",
+    "/run/systemd/journald.conf.d",
+    "/etc/systemd/journald.conf.d",
+];
+
+/// How full the journal is and whether journald is rotating it cleanly
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct JournalDiskUsage {
+    pub used_bytes: u64,
+    /// The effective `SystemMaxUse`; `None` when neither configured nor derivable
+    pub max_use_bytes: Option<u64>,
+    /// `max_use_bytes` comes from journald.conf rather than journald's default
+    pub max_use_configured: bool,
+    /// Recent journald messages about rotating, vacuuming or writing journal files
+    pub rotation_errors: Vec<String>,
+}
+
+impl JournalDiskUsage {
+    /// Percentage of the cap in use, `None` when the cap is unknown
+    pub fn used_percent(&self) -> Option<f64> {
+        let cap = self.max_use_bytes.filter(|cap| *cap > 0)?;
+        Some(self.used_bytes as f64 / cap as f64 * 100.0)
+    }
+
+    /// One line for the AI context, e.g. `Journal: 3.8 GiB of its 4.0 GiB cap (95.0%, default SystemMaxUse)`
+    pub fn summary(&self) -> String {
+        let mut summary = match (self.max_use_bytes, self.used_percent()) {
+            (Some(cap), Some(percent)) => format!(
+                "Journal: {} of its {} cap ({:.1}%, {} SystemMaxUse)",
+                format_bytes(self.used_bytes),
+                format_bytes(cap),
+                percent,
+                if self.max_use_configured { "configured" } else { "default" }
+            ),
+            _ => format!("Journal: {} (cap unknown)", format_bytes(self.used_bytes)),
+        };
+        if !self.rotation_errors.is_empty() {
+            summary.push_str(&format!(", {} journald rotation/write errors", self.rotation_errors.len()));
+        }
+        summary
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -64,28 +561,102 @@ pub struct JournalEntry {
     pub priority: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ContainerInfo {
     pub id: String,
     pub name: String,
     pub image: String,
     pub status: String,
     pub ports: Vec<String>,
+    /// Health check state from the status, e.g. `healthy`, `unhealthy` or `starting`
+    #[serde(default)]
+    pub health: Option<String>,
+    /// Exit code of the last run, from `Exited (137) ...` or `Restarting (1) ...`
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Restarts since the container was created, from `docker inspect`
+    #[serde(default)]
+    pub restart_count: Option<u32>,
+}
+
+/// Restarts after which a running container counts as unstable
+pub const CONTAINER_RESTART_WARN: u32 = 5;
+
+impl ContainerInfo {
+    /// Running and not paused (`Up 2 hours`, `Up 3 minutes (healthy)`)
+    pub fn is_running(&self) -> bool {
+        self.status.starts_with("Up") && !self.status.contains("(Paused)")
+    }
+
+    /// Caught in the restart policy's loop (`Restarting (1) 10 seconds ago`)
+    pub fn is_restarting(&self) -> bool {
+        self.status.starts_with("Restarting")
+    }
+
+    pub fn is_unhealthy(&self) -> bool {
+        self.health.as_deref() == Some("unhealthy")
+    }
+
+    pub fn has_many_restarts(&self) -> bool {
+        self.restart_count.is_some_and(|restarts| restarts >= CONTAINER_RESTART_WARN)
+    }
+
+    /// Running, passing its health check (if it has one) and not restarting often
+    pub fn is_healthy(&self) -> bool {
+        self.is_running() && !self.is_unhealthy() && !self.has_many_restarts()
+    }
+}
+
+/// Health state and exit code from a docker/podman `Status` column, e.g.
+/// `Up 3 minutes (unhealthy)`, `Up 5 seconds (health: starting)` or `Exited (137) 2 hours ago`
+pub fn parse_container_status(status: &str) -> (Option<String>, Option<i32>) {
+    let health = ["unhealthy", "healthy", "health: starting"]
+        .iter()
+        .find(|state| status.contains(&format!("({})", state)))
+        .map(|state| state.trim_start_matches("health: ").to_string());
+    let exit_code = status
+        .starts_with("Exited")
+        .then_some(status)
+        .or_else(|| status.starts_with("Restarting").then_some(status))
+        .and_then(|status| status.split_once('(')?.1.split_once(')')?.0.trim().parse().ok());
+    (health, exit_code)
+}
+
+/// Fill in `restart_count` from `docker inspect --format '{{.Id}} {{.RestartCount}}'`.
+/// Inspect prints full IDs, `docker ps` short ones.
+pub fn apply_restart_counts(containers: &mut [ContainerInfo], inspect_output: &str) {
+    for line in inspect_output.lines() {
+        let Some((id, count)) = line.trim().split_once(' ') else {
+            continue;
+        };
+        if let Ok(count) = count.trim().parse()
+            && let Some(container) = containers
+                .iter_mut()
+                .find(|container| !container.id.is_empty() && id.starts_with(&container.id))
+        {
+            container.restart_count = Some(count);
+        }
+    }
 }
 
 pub fn collect_basic_system_info() -> BasicSystemInfo {
-    let (total_memory, free_memory) = get_memory_info();
+    collect_basic_system_info_with(&CollectOptions::default())
+}
+
+pub fn collect_basic_system_info_with(options: &CollectOptions) -> BasicSystemInfo {
+    let (total_memory, free_memory) = get_memory_info(&options.paths);
     let (total_disk, free_disk) = get_disk_info();
 
     BasicSystemInfo {
-        os: get_os_info(),
-        cpu: get_cpu_info(),
+        os: get_os_info(&options.paths),
+        cpu: get_cpu_info(&options.paths),
         total_memory,
         free_memory,
         total_disk,
         free_disk,
         is_kubernetes: is_running_in_kubernetes(),
         container_runtime_available: is_container_runtime_available(),
+        environment: detect_environment_with(&options.paths),
     }
 }
 
@@ -99,10 +670,11 @@ pub struct BasicSystemInfo {
     pub free_disk: String,
     pub is_kubernetes: bool,
     pub container_runtime_available: bool,
+    pub environment: Environment,
 }
 
 // Lightweight check for Kubernetes environment (no external commands)
-fn is_running_in_kubernetes() -> bool {
+pub fn is_running_in_kubernetes() -> bool {
     // Check for Kubernetes environment variables
     std::env::var("KUBERNETES_SERVICE_HOST").is_ok() ||
     std::env::var("KUBERNETES_SERVICE_PORT").is_ok() ||
@@ -111,7 +683,7 @@ fn is_running_in_kubernetes() -> bool {
 }
 
 // Lightweight check for container runtime availability (no external commands)
-fn is_container_runtime_available() -> bool {
+pub fn is_container_runtime_available() -> bool {
     // Check if docker socket exists
     std::path::Path::new("/var/run/docker.sock").exists() ||
     // Check if containerd socket exists  
@@ -121,25 +693,541 @@ fn is_container_runtime_available() -> bool {
     std::path::Path::new("/usr/local/bin/docker").exists()
 }
 
+/// Narrows what `collect_system_info_with` collects, for targeted investigations
+#[derive(Debug, Clone, Default)]
+pub struct CollectOptions {
+    /// Only collect journal entries from this unit (`journalctl -u <unit>`)
+    pub journal_unit: Option<String>,
+    /// Only collect journal entries from the current boot (`journalctl -b 0`)
+    pub since_boot: bool,
+    pub paths: HostPaths,
+    pub unit_filter: UnitFilter,
+}
+
+/// Include/exclude globs (`*` and `?`) for the systemd units that are collected
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnitFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl UnitFilter {
+    /// Whether a unit is reported: it matches an include glob (or there are none) and no
+    /// exclude glob. `nginx*` matches both `nginx` and `nginx.service`.
+    pub fn matches(&self, unit: &str) -> bool {
+        let short_name = unit.strip_suffix(".service").unwrap_or(unit);
+        let matches_any = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| glob_match(pattern, unit) || glob_match(pattern, short_name))
+        };
+        (self.include.is_empty() || matches_any(&self.include)) && !matches_any(&self.exclude)
+    }
+}
+
+/// Minimal glob matching: `*` matches any run of characters, `?` exactly one
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text index it was tried at, for backtracking
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Where procfs and sysfs are mounted. A DaemonSet that mounts the host's `/proc` at
+/// `/host/proc` points these there to inspect the host instead of its own container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostPaths {
+    pub procfs_root: PathBuf,
+    pub sysfs_root: PathBuf,
+}
+
+impl Default for HostPaths {
+    fn default() -> Self {
+        Self {
+            procfs_root: PathBuf::from("/proc"),
+            sysfs_root: PathBuf::from("/sys"),
+        }
+    }
+}
+
+impl HostPaths {
+    /// A path under the procfs root, e.g. `proc("pressure/cpu")`
+    pub fn proc(&self, relative: &str) -> PathBuf {
+        self.procfs_root.join(relative)
+    }
+
+    /// A path under the sysfs root, e.g. `sys("fs/cgroup/memory.max")`
+    pub fn sys(&self, relative: &str) -> PathBuf {
+        self.sysfs_root.join(relative)
+    }
+
+    /// Whether procfs is read from a mounted host root instead of `/proc`
+    pub fn has_host_root(&self) -> bool {
+        self.procfs_root != Path::new("/proc")
+    }
+
+    /// A path under the procfs entry of the process whose view is inspected: RAID's own
+    /// (`self/...`), or the host's init (`1/...`) with a host root, where `self` would still
+    /// describe RAID in its own namespaces
+    pub fn inspected_proc(&self, relative: &str) -> PathBuf {
+        let process = if self.has_host_root() { "1" } else { "self" };
+        self.proc(&format!("{}/{}", process, relative))
+    }
+}
+
 pub fn collect_system_info() -> SystemInfo {
-    let (total_memory, free_memory) = get_memory_info();
+    collect_system_info_with(&CollectOptions::default())
+}
+
+/// The most recent `collect_system_info_with` result, so a run cut short by `--timeout` can
+/// still report what it collected
+static LAST_COLLECTED: std::sync::Mutex<Option<SystemInfo>> = std::sync::Mutex::new(None);
+
+/// What the last full collection in this process found, if one finished
+pub fn last_collected() -> Option<SystemInfo> {
+    LAST_COLLECTED.lock().ok()?.clone()
+}
+
+pub fn collect_system_info_with(options: &CollectOptions) -> SystemInfo {
+    let (total_memory, free_memory) = get_memory_info(&options.paths);
+    let (zombie_count, zombie_parents) = collect_zombie_info(&options.paths);
     let (total_disk, free_disk) = get_disk_info();
-    SystemInfo {
-        os: get_os_info(),
-        cpu: get_cpu_info(),
+    let filesystems = collect_disk_usage();
+    let environment = detect_environment_with(&options.paths);
+    let running_kernel = running_kernel(&options.paths);
+    // A container's /lib/modules, if any, belongs to its image and not to the host kernel
+    let installed_kernels = if matches!(environment.kind, EnvironmentKind::Container | EnvironmentKind::KubernetesPod) {
+        Vec::new()
+    } else {
+        installed_kernels()
+    };
+    // The CPU steal sample's sleep doubles as the window for interface error deltas
+    let interfaces_before = read_interface_counters(&options.paths);
+    let sample_start = std::time::Instant::now();
+    let cpu_steal_percent = collect_cpu_steal(&options.paths);
+    let network_interfaces = with_interface_deltas(
+        read_interface_counters(&options.paths),
+        &interfaces_before,
+        sample_start.elapsed().as_secs_f32(),
+    );
+    let info = SystemInfo {
+        os: get_os_info(&options.paths),
+        cpu: get_cpu_info(&options.paths),
         total_memory,
         free_memory,
         total_disk,
         free_disk,
         kubernetes: collect_kubernetes_info(),
-        cgroups: collect_cgroup_info(),
-        systemd: collect_systemd_info(),
-        journal: collect_journal_info(),
+        cgroups: collect_cgroup_info(&options.paths),
+        systemd: collect_systemd_info(&options.unit_filter),
+        journal: collect_journal_info(options.journal_unit.as_deref(), options.since_boot, &filesystems),
         containers: collect_container_info(),
+        pressure: collect_pressure_info(&options.paths),
+        zombie_count,
+        zombie_parents,
+        swap: collect_swap_info(&options.paths),
+        filesystems,
+        cpu_steal_percent,
+        environment,
+        needs_reboot: kernel_needs_reboot(&running_kernel, &installed_kernels),
+        newest_installed_kernel: installed_kernels.into_iter().max_by(|a, b| compare_kernel_versions(a, b)),
+        running_kernel,
+        network_interfaces,
+        process_highlights: ProcessTree::read(&options.paths).highlights(),
+    };
+    if let Ok(mut last) = LAST_COLLECTED.lock() {
+        *last = Some(info.clone());
+    }
+    info
+}
+
+/// Per-filesystem usage from `df -kPT`, skipping pseudo filesystems
+pub fn collect_disk_usage() -> Vec<FilesystemUsage> {
+    Command::new("df")
+        .args(["-kPT"])
+        .pooled_output()
+        .map(|output| parse_df_output(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// Parse `df -kPT` output (POSIX format with a type column, 1K blocks). Mount points with
+/// spaces are kept whole; pseudo filesystems and zero-sized entries are dropped.
+pub fn parse_df_output(output: &str) -> Vec<FilesystemUsage> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 7 {
+                return None;
+            }
+            let kib = |field: &str| field.parse::<u64>().ok().map(|value| value * 1024);
+            let total_bytes = kib(fields[2])?;
+            let usage = FilesystemUsage {
+                filesystem: fields[0].to_string(),
+                fs_type: fields[1].to_string(),
+                mount_point: fields[6..].join(" "),
+                total_bytes,
+                used_bytes: kib(fields[3])?,
+                available_bytes: kib(fields[4])?,
+                use_percent: fields[5].trim_end_matches('%').parse().ok()?,
+            };
+            (total_bytes > 0 && !PSEUDO_FILESYSTEMS.contains(&usage.fs_type.as_str())).then_some(usage)
+        })
+        .collect()
+}
+
+/// Swap totals from `<procfs>/meminfo` and `vm.swappiness` from `<procfs>/sys/vm/swappiness`
+pub fn collect_swap_info(paths: &HostPaths) -> SwapInfo {
+    let (total_bytes, used_bytes) = std::fs::read_to_string(paths.proc("meminfo"))
+        .ok()
+        .and_then(|content| parse_swap_meminfo(&content))
+        .unwrap_or_default();
+    let swappiness = std::fs::read_to_string(paths.proc("sys/vm/swappiness"))
+        .ok()
+        .and_then(|content| content.trim().parse().ok());
+    SwapInfo {
+        total_bytes,
+        used_bytes,
+        swappiness,
+    }
+}
+
+pub fn collect_pressure_info(paths: &HostPaths) -> PressureInfo {
+    let read = |path: PathBuf| {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| parse_pressure(&content))
+    };
+
+    PressureInfo {
+        cpu: read(paths.proc("pressure/cpu")),
+        memory: read(paths.proc("pressure/memory")),
+        io: read(paths.proc("pressure/io")),
+        // The root cgroup has no memory.pressure, so this is only found inside a container
+        cgroup_memory: read(paths.sys("fs/cgroup/memory.pressure")),
     }
 }
 
-fn get_os_info() -> String {
+/// Count zombie processes and group them by parent, reading `<procfs>/<pid>/stat`
+pub fn collect_zombie_info(paths: &HostPaths) -> (usize, Vec<ZombieParent>) {
+    let Ok(entries) = std::fs::read_dir(&paths.procfs_root) else {
+        return (0, Vec::new());
+    };
+
+    let read_stat = |pid: &str| {
+        std::fs::read_to_string(paths.procfs_root.join(pid).join("stat"))
+            .ok()
+            .and_then(|content| parse_proc_stat(&content))
+    };
+
+    let mut count = 0;
+    let mut by_parent: HashMap<u32, usize> = HashMap::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(pid) = name.to_str().filter(|pid| pid.chars().all(|c| c.is_ascii_digit())) else {
+            continue;
+        };
+        if let Some(stat) = read_stat(pid)
+            && stat.state == 'Z'
+        {
+            count += 1;
+            *by_parent.entry(stat.ppid).or_default() += 1;
+        }
+    }
+
+    let mut parents: Vec<ZombieParent> = by_parent
+        .into_iter()
+        .map(|(pid, zombies)| ZombieParent {
+            pid,
+            name: read_stat(&pid.to_string()).map(|stat| stat.comm).unwrap_or_else(|| "unknown".to_string()),
+            zombies,
+        })
+        .collect();
+    parents.sort_by(|a, b| b.zombies.cmp(&a.zombies).then(a.pid.cmp(&b.pid)));
+    (count, parents)
+}
+
+/// The fields of `/proc/<pid>/stat` the zombie check needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcStat {
+    pub comm: String,
+    pub state: char,
+    pub ppid: u32,
+}
+
+/// Parse `/proc/<pid>/stat`: `pid (comm) state ppid ...`. The command name may itself
+/// contain spaces and parentheses, so it ends at the last `)`.
+pub fn parse_proc_stat(content: &str) -> Option<ProcStat> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let comm = content.get(open + 1..close)?.to_string();
+    let mut fields = content[close + 1..].split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    let ppid = fields.next()?.parse().ok()?;
+    Some(ProcStat { comm, state, ppid })
+}
+
+/// Children at which a process is highlighted in the process tree. PID 1 and kthreadd are
+/// exempt, since their children are services and kernel threads.
+pub const PROCESS_TREE_MANY_CHILDREN: usize = 20;
+/// Depth below a root at which a process is highlighted in the process tree
+pub const PROCESS_TREE_DEEP_NESTING: usize = 10;
+/// Most deeply nested processes highlighted, so one runaway recursion isn't listed many times
+const PROCESS_TREE_MAX_DEEP: usize = 3;
+/// Same-named leaf children shown as one `N × name` line in the rendered tree
+const PROCESS_TREE_GROUP_MIN: usize = 3;
+/// The kernel thread parent; its subtree is summarized, not drawn
+const KTHREADD_PID: u32 = 2;
+
+/// Every process and its parent, from the `ppid` field of `<procfs>/<pid>/stat`
+#[derive(Debug, Clone, Default)]
+pub struct ProcessTree {
+    /// PID to (parent PID, command name)
+    processes: std::collections::BTreeMap<u32, (u32, String)>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl ProcessTree {
+    pub fn read(paths: &HostPaths) -> Self {
+        let Ok(entries) = std::fs::read_dir(&paths.procfs_root) else {
+            return Self::default();
+        };
+        Self::from_processes(entries.flatten().filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse().ok()?;
+            let stat = parse_proc_stat(&std::fs::read_to_string(entry.path().join("stat")).ok()?)?;
+            Some((pid, stat.ppid, stat.comm))
+        }))
+    }
+
+    /// Build the tree from (PID, parent PID, command name) triples
+    pub fn from_processes(processes: impl IntoIterator<Item = (u32, u32, String)>) -> Self {
+        let processes: std::collections::BTreeMap<_, _> =
+            processes.into_iter().map(|(pid, ppid, comm)| (pid, (ppid, comm))).collect();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (&pid, (ppid, _)) in &processes {
+            if processes.contains_key(ppid) && *ppid != pid {
+                children.entry(*ppid).or_default().push(pid);
+            }
+        }
+        Self { processes, children }
+    }
+
+    pub fn len(&self) -> usize {
+        self.processes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty()
+    }
+
+    fn name(&self, pid: u32) -> &str {
+        self.processes.get(&pid).map_or("?", |(_, comm)| comm.as_str())
+    }
+
+    fn children(&self, pid: u32) -> &[u32] {
+        self.children.get(&pid).map_or(&[], Vec::as_slice)
+    }
+
+    /// Processes whose parent isn't in the tree: PID 1 and kthreadd on a host, PID 1 in a container
+    fn roots(&self) -> impl Iterator<Item = u32> + '_ {
+        self.processes
+            .iter()
+            .filter(|(pid, (ppid, _))| !self.processes.contains_key(ppid) || ppid == *pid)
+            .map(|(pid, _)| *pid)
+    }
+
+    /// `pid`'s ancestors from the root down, excluding `pid` itself
+    fn ancestors(&self, pid: u32) -> Vec<u32> {
+        let mut chain = Vec::new();
+        let mut current = pid;
+        while let Some((ppid, _)) = self.processes.get(&current) {
+            if !self.processes.contains_key(ppid) || *ppid == current || chain.contains(ppid) {
+                break;
+            }
+            chain.push(*ppid);
+            current = *ppid;
+        }
+        chain.reverse();
+        chain
+    }
+
+    fn ancestry(&self, pid: u32) -> String {
+        self.ancestors(pid)
+            .into_iter()
+            .map(|ancestor| format!("{} ({})", self.name(ancestor), ancestor))
+            .collect::<Vec<_>>()
+            .join(" > ")
+    }
+
+    /// "64 children (60 × php-fpm)": the count and the most common child name
+    fn describe_children(&self, pid: u32) -> String {
+        let children = self.children(pid);
+        let mut by_name: HashMap<&str, usize> = HashMap::new();
+        for &child in children {
+            *by_name.entry(self.name(child)).or_default() += 1;
+        }
+        let (common, count) = by_name
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+            .unwrap_or_default();
+        if count > 1 {
+            format!("{} children ({} × {})", children.len(), count, common)
+        } else {
+            format!("{} children", children.len())
+        }
+    }
+
+    /// Parents of many children, most children first, then the most deeply nested processes
+    pub fn highlights(&self) -> Vec<ProcessHighlight> {
+        let highlight = |pid: u32, reason: String| ProcessHighlight {
+            pid,
+            name: self.name(pid).to_string(),
+            reason,
+            ancestry: self.ancestry(pid),
+        };
+
+        let mut busy: Vec<u32> = self
+            .children
+            .iter()
+            .filter(|(pid, children)| **pid != 1 && **pid != KTHREADD_PID && children.len() >= PROCESS_TREE_MANY_CHILDREN)
+            .map(|(pid, _)| *pid)
+            .collect();
+        busy.sort_by_key(|pid| (std::cmp::Reverse(self.children(*pid).len()), *pid));
+        let mut highlights: Vec<_> = busy.into_iter().map(|pid| highlight(pid, self.describe_children(pid))).collect();
+
+        // Only leaves, so a deep chain is reported once rather than once per level
+        let mut deep: Vec<(usize, u32)> = self
+            .processes
+            .keys()
+            .filter(|pid| self.children(**pid).is_empty())
+            .map(|pid| (self.ancestors(*pid).len(), *pid))
+            .filter(|(depth, _)| *depth >= PROCESS_TREE_DEEP_NESTING)
+            .collect();
+        deep.sort_by_key(|(depth, pid)| (std::cmp::Reverse(*depth), *pid));
+        highlights.extend(
+            deep.into_iter()
+                .take(PROCESS_TREE_MAX_DEEP)
+                .map(|(depth, pid)| highlight(pid, format!("nesting {} levels deep", depth))),
+        );
+        highlights
+    }
+
+    /// The whole tree, one process per line, with highlighted processes marked. Runs of
+    /// same-named childless siblings become one `N × name` line and kernel threads one count.
+    pub fn render(&self) -> String {
+        let highlighted: HashMap<u32, String> =
+            self.highlights().into_iter().map(|highlight| (highlight.pid, highlight.reason)).collect();
+        let mut out = String::new();
+        for root in self.roots() {
+            self.render_node(root, "", None, &highlighted, &mut out);
+        }
+        out
+    }
+
+    /// Append `pid` and its subtree. `branch` is `None` for a root, else whether it is the last sibling.
+    fn render_node(&self, pid: u32, prefix: &str, branch: Option<bool>, highlighted: &HashMap<u32, String>, out: &mut String) {
+        let (connector, child_prefix) = match branch {
+            None => ("", String::new()),
+            Some(true) => ("└─ ", format!("{}   ", prefix)),
+            Some(false) => ("├─ ", format!("{}│  ", prefix)),
+        };
+        let mark = highlighted.get(&pid).map(|reason| format!("  ⚠ {}", reason)).unwrap_or_default();
+        if pid == KTHREADD_PID {
+            out.push_str(&format!(
+                "{}{}{} {} [{} kernel threads]{}\n",
+                prefix,
+                connector,
+                pid,
+                self.name(pid),
+                self.children(pid).len(),
+                mark
+            ));
+            return;
+        }
+        out.push_str(&format!("{}{}{} {}{}\n", prefix, connector, pid, self.name(pid), mark));
+
+        // Childless siblings with a common name collapse into one line, placed after the rest
+        let children = self.children(pid);
+        let mut leaf_groups: std::collections::BTreeMap<&str, Vec<u32>> = std::collections::BTreeMap::new();
+        for &child in children.iter().filter(|child| self.children(**child).is_empty()) {
+            leaf_groups.entry(self.name(child)).or_default().push(child);
+        }
+        leaf_groups.retain(|_, pids| pids.len() >= PROCESS_TREE_GROUP_MIN);
+        let shown: Vec<u32> = children
+            .iter()
+            .copied()
+            .filter(|child| !leaf_groups.get(self.name(*child)).is_some_and(|group| group.contains(child)))
+            .collect();
+        let lines = shown.len() + leaf_groups.len();
+        for (index, &child) in shown.iter().enumerate() {
+            self.render_node(child, &child_prefix, Some(index + 1 == lines), highlighted, out);
+        }
+        for (index, (name, pids)) in leaf_groups.iter().enumerate() {
+            let connector = if shown.len() + index + 1 == lines { "└─ " } else { "├─ " };
+            out.push_str(&format!(
+                "{}{}{} × {} (PIDs {}{})\n",
+                child_prefix,
+                connector,
+                pids.len(),
+                name,
+                pids.iter().take(3).map(u32::to_string).collect::<Vec<_>>().join(", "),
+                if pids.len() > 3 { ", ..." } else { "" }
+            ));
+        }
+    }
+}
+
+/// Parse a PSI file such as `/proc/pressure/memory`:
+/// `some avg10=0.00 avg60=0.00 avg300=0.00 total=0`
+pub fn parse_pressure(content: &str) -> Option<PressureStats> {
+    let parse_line = |line: &str| {
+        let mut averages = PressureAverages::default();
+        for field in line.split_whitespace().skip(1) {
+            let (key, value) = field.split_once('=')?;
+            let value = value.parse::<f32>().ok()?;
+            match key {
+                "avg10" => averages.avg10 = value,
+                "avg60" => averages.avg60 = value,
+                "avg300" => averages.avg300 = value,
+                _ => {}
+            }
+        }
+        Some(averages)
+    };
+
+    let mut some = None;
+    let mut full = None;
+    for line in content.lines() {
+        if line.starts_with("some ") {
+            some = parse_line(line);
+        } else if line.starts_with("full ") {
+            full = parse_line(line);
+        }
+    }
+
+    some.map(|some| PressureStats { some, full })
+}
+
+fn get_os_info(paths: &HostPaths) -> String {
     // Try to read from /etc/os-release first
     if let Ok(content) = std::fs::read_to_string("/etc/os-release") {
         let mut os_info = HashMap::new();
@@ -177,7 +1265,7 @@ fn get_os_info() -> String {
         }
 
         // Add kernel version
-        if let Ok(kernel_version) = std::fs::read_to_string("/proc/version") {
+        if let Ok(kernel_version) = std::fs::read_to_string(paths.proc("version")) {
             if let Some(kernel_info) = kernel_version.split_whitespace().nth(2) {
                 os_string.push_str(&format!(" [Kernel: {}]", kernel_info));
             }
@@ -192,9 +1280,9 @@ fn get_os_info() -> String {
     std::env::consts::OS.to_string()
 }
 
-fn get_cpu_info() -> String {
+fn get_cpu_info(paths: &HostPaths) -> String {
     // Try to get CPU info from /proc/cpuinfo
-    if let Ok(content) = std::fs::read_to_string("/proc/cpuinfo") {
+    if let Ok(content) = std::fs::read_to_string(paths.proc("cpuinfo")) {
         for line in content.lines() {
             if line.starts_with("model name") {
                 if let Some(cpu_name) = line.split(':').nth(1) {
@@ -206,8 +1294,16 @@ fn get_cpu_info() -> String {
     "Unknown CPU".to_string()
 }
 
-fn get_memory_info() -> (String, String) {
-    if let Ok(output) = std::process::Command::new("free").arg("-h").output() {
+fn get_memory_info(paths: &HostPaths) -> (String, String) {
+    // Read meminfo directly so a host procfs mounted elsewhere is honoured; `free` only sees its own
+    if let Some((total, free)) = std::fs::read_to_string(paths.proc("meminfo"))
+        .ok()
+        .and_then(|content| parse_meminfo(&content))
+    {
+        return (format_bytes(total), format_bytes(free));
+    }
+
+    if let Ok(output) = std::process::Command::new("free").arg("-h").pooled_output() {
         let out = String::from_utf8_lossy(&output.stdout);
         for line in out.lines() {
             if line.starts_with("Mem:") {
@@ -218,145 +1314,572 @@ fn get_memory_info() -> (String, String) {
             }
         }
     }
-    ("unknown".to_string(), "unknown".to_string())
+    ("unknown".to_string(), "unknown".to_string())
+}
+
+/// `MemTotal` and `MemFree` in bytes from /proc/meminfo (which reports kB)
+pub fn parse_meminfo(content: &str) -> Option<(u64, u64)> {
+    let field = |name: &str| {
+        content.lines().find_map(|line| {
+            let value = line.strip_prefix(name)?.strip_prefix(':')?;
+            value.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    Some((field("MemTotal")? * 1024, field("MemFree")? * 1024))
+}
+
+/// Swap total and used (`SwapTotal - SwapFree`) in bytes from /proc/meminfo
+pub fn parse_swap_meminfo(content: &str) -> Option<(u64, u64)> {
+    let field = |name: &str| {
+        content.lines().find_map(|line| {
+            let value = line.strip_prefix(name)?.strip_prefix(':')?;
+            value.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    let total = field("SwapTotal")?;
+    let free = field("SwapFree")?;
+    Some((total * 1024, total.saturating_sub(free) * 1024))
+}
+
+fn get_disk_info() -> (String, String) {
+    if let Ok(output) = std::process::Command::new("df").args(["-h", "/"]).pooled_output() {
+        let out = String::from_utf8_lossy(&output.stdout);
+        for line in out.lines().skip(1) {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() >= 5 {
+                return (parts[1].to_string(), parts[3].to_string());
+            }
+        }
+    }
+    ("unknown".to_string(), "unknown".to_string())
+}
+
+fn collect_kubernetes_info() -> KubernetesInfo {
+    let mut k8s_info = KubernetesInfo {
+        namespace: None,
+        pod_name: None,
+        node_name: None,
+        service_account: None,
+        is_kubernetes: false,
+    };
+
+    // Check if we're running in Kubernetes by looking for service account token
+    if std::path::Path::new("/var/run/secrets/kubernetes.io/serviceaccount/token").exists() {
+        k8s_info.is_kubernetes = true;
+
+        // Try to get namespace
+        if let Ok(namespace) =
+            std::fs::read_to_string("/var/run/secrets/kubernetes.io/serviceaccount/namespace")
+        {
+            k8s_info.namespace = Some(namespace.trim().to_string());
+        }
+
+        // Try to get pod name from environment
+        if let Ok(pod_name) = std::env::var("HOSTNAME") {
+            k8s_info.pod_name = Some(pod_name);
+        }
+
+        // Try to get node name from environment
+        if let Ok(node_name) = std::env::var("NODE_NAME") {
+            k8s_info.node_name = Some(node_name);
+        }
+
+        // Try to get service account from environment
+        if let Ok(sa) = std::env::var("SERVICE_ACCOUNT") {
+            k8s_info.service_account = Some(sa);
+        }
+    }
+
+    k8s_info
+}
+
+fn collect_cgroup_info(paths: &HostPaths) -> CgroupInfo {
+    let mut cgroup_info = CgroupInfo {
+        version: "unknown".to_string(),
+        controllers: Vec::new(),
+        enabled_controllers: None,
+        memory_limit: None,
+        cpu_limit: None,
+        cgroup_path: "unknown".to_string(),
+        memory_usage: None,
+        cpu_throttled_percent: None,
+        cpu_nr_periods: None,
+        cpu_nr_throttled: None,
+        cpu_throttled_usec: None,
+    };
+
+    // Cgroup version, path and which controllers apply to this process
+    if let Some(membership) = std::fs::read_to_string(paths.inspected_proc("cgroup"))
+        .ok()
+        .and_then(|content| parse_proc_self_cgroup(&content))
+    {
+        cgroup_info.version = membership.version.to_string();
+        cgroup_info.cgroup_path = membership.path;
+        if membership.version == "v1" {
+            // Every process belongs to each mounted v1 hierarchy, so all of them apply
+            cgroup_info.enabled_controllers = Some(membership.controllers.clone());
+            cgroup_info.controllers = membership.controllers;
+        } else {
+            let read_controllers = |relative: &str| {
+                std::fs::read_to_string(paths.sys(relative))
+                    .ok()
+                    .map(|content| content.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+            };
+            cgroup_info.controllers = read_controllers("fs/cgroup/cgroup.controllers").unwrap_or_default();
+            // With a cgroup namespace the path is `/` and the mount root is the process's own cgroup
+            cgroup_info.enabled_controllers =
+                read_controllers(&format!("fs/cgroup{}/cgroup.controllers", cgroup_info.cgroup_path))
+                    .or_else(|| read_controllers("fs/cgroup/cgroup.controllers"));
+        }
+    }
+
+    // v1 keeps each controller in a hierarchy of its own; v2 keeps limits and usage in the
+    // process's cgroup directory, the same one its enabled controllers were read from
+    let v2_cgroup = match cgroup_info.version.as_str() {
+        "v1" => None,
+        "unknown" => Some("/".to_string()),
+        _ => Some(cgroup_info.cgroup_path.clone()),
+    };
+    let read = |v1_file: &str, v2_file: &str| {
+        std::fs::read_to_string(paths.sys(v1_file))
+            .ok()
+            .or_else(|| v2_cgroup.as_deref().and_then(|cgroup| read_cgroup_v2_file(paths, cgroup, v2_file)))
+    };
+
+    cgroup_info.memory_limit = read("fs/cgroup/memory/memory.limit_in_bytes", "memory.max")
+        .map(|content| content.trim().to_string());
+    cgroup_info.cpu_limit =
+        read("fs/cgroup/cpu/cpu.cfs_quota_us", "cpu.max").map(|content| content.trim().to_string());
+
+    // Current usage, to compare against the limits
+    cgroup_info.memory_usage = read("fs/cgroup/memory/memory.usage_in_bytes", "memory.current")
+        .and_then(|content| content.trim().parse().ok());
+    if let Some(stat) = read("fs/cgroup/cpu/cpu.stat", "cpu.stat").and_then(|content| parse_cgroup_cpu_stat(&content)) {
+        cgroup_info.cpu_throttled_percent = stat.throttled_percent();
+        cgroup_info.cpu_nr_periods = Some(stat.nr_periods);
+        cgroup_info.cpu_nr_throttled = Some(stat.nr_throttled);
+        cgroup_info.cpu_throttled_usec = stat.throttled_usec;
+    }
+
+    cgroup_info
+}
+
+/// `file` from the v2 cgroup at `cgroup_path` or, when that cgroup doesn't have it (the root
+/// cgroup has no limits, and a controller not enabled there has no files), from its nearest
+/// ancestor that does
+fn read_cgroup_v2_file(paths: &HostPaths, cgroup_path: &str, file: &str) -> Option<String> {
+    let mut cgroup = cgroup_path.trim_end_matches('/').to_string();
+    loop {
+        if let Ok(content) = std::fs::read_to_string(paths.sys(&format!("fs/cgroup{}/{}", cgroup, file))) {
+            return Some(content);
+        }
+        if cgroup.is_empty() {
+            return None;
+        }
+        cgroup.truncate(cgroup.rfind('/').unwrap_or(0));
+    }
+}
+
+/// A process's cgroup membership from `/proc/self/cgroup`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CgroupMembership {
+    /// `v1` when any controller is mounted as a v1 hierarchy (including hybrid setups), else `v2`
+    pub version: &'static str,
+    /// Controllers of the v1 hierarchies, sorted; empty on v2, where they are in `cgroup.controllers`
+    pub controllers: Vec<String>,
+    pub path: String,
+}
+
+/// Parse `/proc/self/cgroup` (`hierarchy-id:controllers:path` lines). v1 has one line per
+/// hierarchy with its controllers; pure v2 has only the unified `0::/path` line.
+pub fn parse_proc_self_cgroup(content: &str) -> Option<CgroupMembership> {
+    let lines: Vec<(&str, &str)> = content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ':');
+            parts.next()?;
+            Some((parts.next()?, parts.next()?))
+        })
+        .collect();
+    let v1_lines: Vec<&(&str, &str)> = lines.iter().filter(|(controllers, _)| !controllers.is_empty()).collect();
+    let Some((_, v1_path)) = v1_lines.first() else {
+        let (_, path) = lines.first()?;
+        return Some(CgroupMembership { version: "v2", controllers: Vec::new(), path: path.to_string() });
+    };
+
+    // `name=systemd` is a named hierarchy without a controller
+    let mut controllers: Vec<String> = v1_lines
+        .iter()
+        .flat_map(|(controllers, _)| controllers.split(','))
+        .filter(|controller| !controller.starts_with("name="))
+        .map(str::to_string)
+        .collect();
+    controllers.sort();
+    controllers.dedup();
+    Some(CgroupMembership { version: "v1", controllers, path: v1_path.to_string() })
+}
+
+/// The throttling counters of a cgroup `cpu.stat`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CgroupCpuStat {
+    pub nr_periods: u64,
+    pub nr_throttled: u64,
+    pub throttled_usec: Option<u64>,
+}
+
+impl CgroupCpuStat {
+    /// Percentage of periods that were throttled, `None` before the first period
+    pub fn throttled_percent(&self) -> Option<f32> {
+        (self.nr_periods > 0).then(|| self.nr_throttled as f32 / self.nr_periods as f32 * 100.0)
+    }
+}
+
+/// Parse a cgroup `cpu.stat`. v2 reports `throttled_usec`; v1 reports `throttled_time` in ns.
+pub fn parse_cgroup_cpu_stat(content: &str) -> Option<CgroupCpuStat> {
+    let field = |name: &str| {
+        content.lines().find_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            (key == name).then(|| value.trim().parse::<u64>().ok()).flatten()
+        })
+    };
+
+    Some(CgroupCpuStat {
+        nr_periods: field("nr_periods")?,
+        nr_throttled: field("nr_throttled")?,
+        throttled_usec: field("throttled_usec").or_else(|| field("throttled_time").map(|ns| ns / 1000)),
+    })
+}
+
+/// Percentage of throttled periods from a cgroup `cpu.stat` (`nr_periods` / `nr_throttled`)
+pub fn parse_cpu_throttling(content: &str) -> Option<f32> {
+    parse_cgroup_cpu_stat(content)?.throttled_percent()
+}
+
+/// How long steal time is sampled for; since-boot totals would hide a noisy neighbour
+/// that only showed up recently
+const CPU_STEAL_SAMPLE: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Aggregate CPU time from the `cpu` line of /proc/stat, in clock ticks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CpuTimes {
+    pub total: u64,
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    /// Steal as a percentage of all CPU time between an earlier sample and this one
+    pub fn steal_percent_since(&self, earlier: &CpuTimes) -> Option<f32> {
+        let total = self.total.checked_sub(earlier.total)?;
+        let steal = self.steal.checked_sub(earlier.steal)?;
+        (total > 0).then(|| steal as f32 / total as f32 * 100.0)
+    }
 }
 
-fn get_disk_info() -> (String, String) {
-    if let Ok(output) = std::process::Command::new("df").args(["-h", "/"]).output() {
-        let out = String::from_utf8_lossy(&output.stdout);
-        for line in out.lines().skip(1) {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 5 {
-                return (parts[1].to_string(), parts[3].to_string());
-            }
-        }
+/// Parse the aggregate `cpu` line of /proc/stat:
+/// `cpu user nice system idle iowait irq softirq steal guest guest_nice`. Guest time is
+/// already counted in user/nice, so it is left out of the total.
+pub fn parse_cpu_times(content: &str) -> Option<CpuTimes> {
+    let line = content.lines().find(|line| line.starts_with("cpu "))?;
+    let values: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .map(|value| value.parse().ok())
+        .collect::<Option<_>>()?;
+    if values.len() < 4 {
+        return None;
     }
-    ("unknown".to_string(), "unknown".to_string())
+    let counted = &values[..values.len().min(8)];
+    Some(CpuTimes {
+        total: counted.iter().sum(),
+        steal: values.get(7).copied().unwrap_or(0),
+    })
 }
 
-fn collect_kubernetes_info() -> KubernetesInfo {
-    let mut k8s_info = KubernetesInfo {
-        namespace: None,
-        pod_name: None,
-        node_name: None,
-        service_account: None,
-        is_kubernetes: false,
+/// Steal time over a short sample of `<procfs>/stat`, `None` when it can't be read
+pub fn collect_cpu_steal(paths: &HostPaths) -> Option<f32> {
+    let sample = || {
+        std::fs::read_to_string(paths.proc("stat"))
+            .ok()
+            .and_then(|content| parse_cpu_times(&content))
     };
+    let before = sample()?;
+    std::thread::sleep(CPU_STEAL_SAMPLE);
+    sample()?.steal_percent_since(&before)
+}
 
-    // Check if we're running in Kubernetes by looking for service account token
-    if std::path::Path::new("/var/run/secrets/kubernetes.io/serviceaccount/token").exists() {
-        k8s_info.is_kubernetes = true;
-
-        // Try to get namespace
-        if let Ok(namespace) =
-            std::fs::read_to_string("/var/run/secrets/kubernetes.io/serviceaccount/namespace")
-        {
-            k8s_info.namespace = Some(namespace.trim().to_string());
-        }
-
-        // Try to get pod name from environment
-        if let Ok(pod_name) = std::env::var("HOSTNAME") {
-            k8s_info.pod_name = Some(pod_name);
-        }
+/// Every interface in `<procfs>/net/dev`, empty when it can't be read
+pub fn read_interface_counters(paths: &HostPaths) -> Vec<InterfaceCounters> {
+    std::fs::read_to_string(paths.proc("net/dev"))
+        .map(|content| parse_net_dev(&content))
+        .unwrap_or_default()
+}
 
-        // Try to get node name from environment
-        if let Ok(node_name) = std::env::var("NODE_NAME") {
-            k8s_info.node_name = Some(node_name);
-        }
+/// Parse /proc/net/dev: two header lines, then `iface: rx_bytes rx_packets rx_errs rx_drop
+/// fifo frame compressed multicast tx_bytes tx_packets tx_errs tx_drop ...` per interface
+pub fn parse_net_dev(content: &str) -> Vec<InterfaceCounters> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (name, counters) = line.split_once(':')?;
+            let values: Vec<u64> = counters
+                .split_whitespace()
+                .map(|value| value.parse().ok())
+                .collect::<Option<_>>()?;
+            if values.len() < 12 {
+                return None;
+            }
+            Some(InterfaceCounters {
+                name: name.trim().to_string(),
+                rx_packets: values[1],
+                rx_errors: values[2],
+                rx_dropped: values[3],
+                tx_packets: values[9],
+                tx_errors: values[10],
+                tx_dropped: values[11],
+                recent: None,
+            })
+        })
+        .collect()
+}
 
-        // Try to get service account from environment
-        if let Ok(sa) = std::env::var("SERVICE_ACCOUNT") {
-            k8s_info.service_account = Some(sa);
-        }
+/// Attach to each interface what its counters grew by since `earlier` was read
+pub fn with_interface_deltas(
+    mut current: Vec<InterfaceCounters>,
+    earlier: &[InterfaceCounters],
+    seconds: f32,
+) -> Vec<InterfaceCounters> {
+    for interface in &mut current {
+        interface.recent = earlier
+            .iter()
+            .find(|before| before.name == interface.name)
+            .and_then(|before| interface.delta_since(before, seconds));
     }
+    current
+}
 
-    k8s_info
+/// Directories holding one module tree per installed kernel release
+const KERNEL_MODULE_DIRS: [&str; 2] = ["/lib/modules", "/usr/lib/modules"];
+
+/// The running kernel's release, from procfs (so a host mount works) or `uname -r`
+pub fn running_kernel(paths: &HostPaths) -> String {
+    std::fs::read_to_string(paths.proc("sys/kernel/osrelease"))
+        .ok()
+        .or_else(|| {
+            Command::new("uname")
+                .arg("-r")
+                .pooled_output()
+                .ok()
+                .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        })
+        .map(|release| release.trim().to_string())
+        .unwrap_or_default()
 }
 
-fn collect_cgroup_info() -> CgroupInfo {
-    let mut cgroup_info = CgroupInfo {
-        version: "unknown".to_string(),
-        controllers: Vec::new(),
-        memory_limit: None,
-        cpu_limit: None,
-        cgroup_path: "unknown".to_string(),
+/// Installed kernel releases: the module trees in /lib/modules and the versioned
+/// `/boot/vmlinuz-<release>` images. Arch's unversioned `vmlinuz-linux` is covered by its
+/// module tree.
+pub fn installed_kernels() -> Vec<String> {
+    let names = |dir: &str| {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
     };
+    let mut releases: Vec<String> = KERNEL_MODULE_DIRS
+        .iter()
+        .flat_map(|dir| names(dir))
+        .chain(names("/boot").into_iter().filter_map(|name| name.strip_prefix("vmlinuz-").map(str::to_string)))
+        .filter(|release| release.starts_with(|c: char| c.is_ascii_digit()))
+        .collect();
+    releases.sort();
+    releases.dedup();
+    releases
+}
 
-    // Try to get cgroup version and path
-    if let Ok(content) = std::fs::read_to_string("/proc/self/cgroup") {
-        for line in content.lines() {
-            let parts: Vec<&str> = line.split(':').collect();
-            if parts.len() >= 3 {
-                cgroup_info.version = if parts[0] == "0" {
-                    "v1".to_string()
-                } else {
-                    "v2".to_string()
-                };
-                cgroup_info.controllers = parts[1].split(',').map(|s| s.to_string()).collect();
-                cgroup_info.cgroup_path = parts[2].to_string();
-                break;
+/// Order kernel releases by their numeric parts, so `6.8.0-49-generic` is newer than
+/// `6.8.0-45-generic` and `5.15.10` newer than `5.15.9`
+pub fn compare_kernel_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    // Runs of digits and of everything else; a split always falls next to an ASCII digit,
+    // so it is on a char boundary
+    fn parts(release: &str) -> Vec<&str> {
+        let bytes = release.as_bytes();
+        let mut parts = Vec::new();
+        let mut start = 0;
+        for index in 1..=bytes.len() {
+            if index == bytes.len() || bytes[index].is_ascii_digit() != bytes[index - 1].is_ascii_digit() {
+                parts.push(&release[start..index]);
+                start = index;
             }
         }
+        parts
     }
 
-    // Try to get memory limit
-    if let Ok(content) = std::fs::read_to_string("/sys/fs/cgroup/memory/memory.limit_in_bytes") {
-        cgroup_info.memory_limit = Some(content.trim().to_string());
-    } else if let Ok(content) = std::fs::read_to_string("/sys/fs/cgroup/memory.max") {
-        cgroup_info.memory_limit = Some(content.trim().to_string());
+    let (a, b) = (parts(a), parts(b));
+    for (a_part, b_part) in a.iter().zip(&b) {
+        let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+            (Ok(a_number), Ok(b_number)) => a_number.cmp(&b_number),
+            _ => a_part.cmp(b_part),
+        };
+        if ordering != std::cmp::Ordering::Equal {
+            return ordering;
+        }
     }
+    a.len().cmp(&b.len())
+}
 
-    // Try to get CPU limit
-    if let Ok(content) = std::fs::read_to_string("/sys/fs/cgroup/cpu/cpu.cfs_quota_us") {
-        cgroup_info.cpu_limit = Some(content.trim().to_string());
-    } else if let Ok(content) = std::fs::read_to_string("/sys/fs/cgroup/cpu.max") {
-        cgroup_info.cpu_limit = Some(content.trim().to_string());
+/// A reboot is needed when a newer kernel than the running one is installed, or when the
+/// running kernel's modules are gone (Arch replaces them on upgrade). Nothing installed
+/// (no /lib/modules at all) means nothing to compare.
+pub fn kernel_needs_reboot(running: &str, installed: &[String]) -> bool {
+    if running.is_empty() || installed.is_empty() {
+        return false;
     }
+    !installed.iter().any(|release| release == running)
+        || installed
+            .iter()
+            .any(|release| compare_kernel_versions(release, running) == std::cmp::Ordering::Greater)
+}
 
-    cgroup_info
+/// Detect bare metal, VM, container or Kubernetes pod for the local system
+pub fn detect_environment() -> Environment {
+    detect_environment_with(&HostPaths::default())
 }
 
-fn collect_systemd_info() -> SystemdInfo {
-    let mut systemd_info = SystemdInfo {
-        units: Vec::new(),
-        failed_units: Vec::new(),
-        system_status: "unknown".to_string(),
+/// `systemd-detect-virt` answers first; without it, container marker files, PID 1's
+/// cgroup and the CPU's `hypervisor` flag stand in
+pub fn detect_environment_with(paths: &HostPaths) -> Environment {
+    let detect_virt = |flag: &str| {
+        Command::new("systemd-detect-virt")
+            .arg(flag)
+            .pooled_output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|virt| !virt.is_empty())
     };
 
-    // Get system status
-    if let Ok(output) = Command::new("systemctl").arg("is-system-running").output() {
-        systemd_info.system_status = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    }
+    let container = detect_virt("--container")
+        .or_else(|| {
+            if std::path::Path::new("/.dockerenv").exists() {
+                Some("docker".to_string())
+            } else if std::path::Path::new("/run/.containerenv").exists() {
+                Some("podman".to_string())
+            } else {
+                None
+            }
+        })
+        .or_else(|| {
+            std::fs::read_to_string(paths.proc("1/cgroup"))
+                .ok()
+                .and_then(|content| container_runtime_from_cgroup(&content))
+        });
+    let vm = detect_virt("--vm").or_else(|| {
+        let cpuinfo = std::fs::read_to_string(paths.proc("cpuinfo")).ok()?;
+        let flagged = cpuinfo
+            .lines()
+            .filter(|line| line.starts_with("flags"))
+            .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"));
+        Some(if flagged { "unknown" } else { "none" }.to_string())
+    });
+
+    classify_environment(vm.as_deref(), container.as_deref(), is_running_in_kubernetes())
+}
+
+/// Combine the detections. `none` (what `systemd-detect-virt` prints on bare metal) and
+/// `None` both mean not detected; a Kubernetes pod wins over a plain container.
+pub fn classify_environment(vm: Option<&str>, container: Option<&str>, kubernetes: bool) -> Environment {
+    let detected = |value: Option<&str>| value.filter(|value| *value != "none").map(str::to_string);
+    let hypervisor = detected(vm);
+    let container_runtime = detected(container);
+
+    let kind = if kubernetes {
+        EnvironmentKind::KubernetesPod
+    } else if container_runtime.is_some() {
+        EnvironmentKind::Container
+    } else if hypervisor.is_some() {
+        EnvironmentKind::VirtualMachine
+    } else if vm.is_some() {
+        EnvironmentKind::BareMetal
+    } else {
+        EnvironmentKind::Unknown
+    };
+    Environment { kind, hypervisor, container_runtime }
+}
 
-    // Get failed units
+/// The runtime named in a `/proc/<pid>/cgroup` path, e.g. `/docker/<id>` or
+/// `/kubepods/.../cri-containerd-<id>.scope`. A cgroup v2 namespace shows only `0::/`.
+pub fn container_runtime_from_cgroup(content: &str) -> Option<String> {
+    const MARKERS: [(&str, &str); 6] = [
+        ("cri-containerd", "containerd"),
+        ("crio", "cri-o"),
+        ("libpod", "podman"),
+        ("docker", "docker"),
+        ("containerd", "containerd"),
+        ("lxc", "lxc"),
+    ];
+    content.lines().find_map(|line| {
+        let path = line.splitn(3, ':').nth(2)?;
+        MARKERS
+            .iter()
+            .find(|(marker, _)| path.contains(marker))
+            .map(|(_, runtime)| runtime.to_string())
+    })
+}
+
+/// Names of units in the failed state (`systemctl --failed`)
+pub fn collect_failed_units() -> Vec<String> {
+    let mut failed_units = Vec::new();
     if let Ok(output) = Command::new("systemctl")
         .args(["--failed", "--no-pager", "--no-legend"])
-        .output()
+        .pooled_output()
     {
         for line in String::from_utf8_lossy(&output.stdout).lines() {
             if !line.trim().is_empty() {
                 let parts: Vec<&str> = line.split_whitespace().collect();
                 if !parts.is_empty() {
-                    systemd_info.failed_units.push(parts[0].to_string());
+                    failed_units.push(parts[0].to_string());
                 }
             }
         }
     }
+    failed_units
+}
+
+fn collect_systemd_info(filter: &UnitFilter) -> SystemdInfo {
+    let mut systemd_info = SystemdInfo {
+        units: Vec::new(),
+        failed_units: Vec::new(),
+        system_status: "unknown".to_string(),
+    };
+
+    // Get system status
+    if let Ok(output) = Command::new("systemctl").arg("is-system-running").pooled_output() {
+        systemd_info.system_status = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    }
+
+    systemd_info.failed_units = collect_failed_units();
+    systemd_info.failed_units.retain(|unit| filter.matches(unit));
 
     // Get some important units
     let important_units = ["docker", "containerd", "kubelet", "kube-proxy"];
-    for unit in important_units {
+    for unit in important_units.into_iter().filter(|unit| filter.matches(unit)) {
         if let Ok(output) = Command::new("systemctl")
-            .args(["show", unit, "--property=ActiveState,Description"])
-            .output()
+            .args(["show", unit, "--property=ActiveState,SubState,Description"])
+            .pooled_output()
         {
             let output_str = String::from_utf8_lossy(&output.stdout);
             let mut status = "unknown".to_string();
+            let mut sub_state = String::new();
             let mut description = "".to_string();
 
             for line in output_str.lines() {
                 if line.starts_with("ActiveState=") {
                     status = line.split('=').nth(1).unwrap_or("unknown").to_string();
+                } else if line.starts_with("SubState=") {
+                    sub_state = line.split('=').nth(1).unwrap_or("").to_string();
                 } else if line.starts_with("Description=") {
                     description = line.split('=').nth(1).unwrap_or("").to_string();
                 }
@@ -366,47 +1889,243 @@ fn collect_systemd_info() -> SystemdInfo {
                 name: unit.to_string(),
                 status,
                 description,
+                sub_state,
             });
         }
     }
 
+    // Any other service that is stuck starting or crash-looping
+    if let Ok(output) = Command::new("systemctl")
+        .args([
+            "list-units",
+            "--type=service",
+            "--state=activating",
+            "--no-pager",
+            "--no-legend",
+            "--plain",
+        ])
+        .pooled_output()
+    {
+        for unit in parse_list_units(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|unit| filter.matches(&unit.name))
+        {
+            let short_name = unit.name.trim_end_matches(".service");
+            if !systemd_info.units.iter().any(|u| u.name == unit.name || u.name == short_name) {
+                systemd_info.units.push(unit);
+            }
+        }
+    }
+
     systemd_info
 }
 
-fn collect_journal_info() -> JournalInfo {
+/// Parse `systemctl list-units --no-legend` rows: UNIT LOAD ACTIVE SUB DESCRIPTION
+fn parse_list_units(output: &str) -> Vec<SystemdUnit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start_matches(['●', '*', ' ']);
+            let mut columns = line.split_whitespace();
+            let name = columns.next()?;
+            let _load = columns.next()?;
+            let status = columns.next()?;
+            let sub_state = columns.next()?;
+            Some(SystemdUnit {
+                name: name.to_string(),
+                status: status.to_string(),
+                sub_state: sub_state.to_string(),
+                description: columns.collect::<Vec<_>>().join(" "),
+            })
+        })
+        .collect()
+}
+
+fn collect_journal_info(unit: Option<&str>, since_boot: bool, filesystems: &[FilesystemUsage]) -> JournalInfo {
     let mut journal_info = JournalInfo {
         recent_errors: Vec::new(),
         recent_warnings: Vec::new(),
         boot_errors: Vec::new(),
+        disk_usage: None,
+    };
+
+    let journalctl = |args: &[&str]| {
+        let mut command = Command::new("journalctl");
+        command.args(journal_args(args, unit, since_boot));
+        command.pooled_output()
     };
 
     // Get recent errors (last 50 entries)
-    if let Ok(output) = Command::new("journalctl")
-        .args(["-p", "err", "--no-pager", "--no-hostname", "-n", "50"])
-        .output()
-    {
+    if let Ok(output) = journalctl(&["-p", "err", "--no-pager", "--no-hostname", "-n", "50"]) {
         journal_info.recent_errors = parse_journal_output(&output.stdout);
     }
 
     // Get recent warnings (last 50 entries)
-    if let Ok(output) = Command::new("journalctl")
-        .args(["-p", "warning", "--no-pager", "--no-hostname", "-n", "50"])
-        .output()
-    {
+    if let Ok(output) = journalctl(&["-p", "warning", "--no-pager", "--no-hostname", "-n", "50"]) {
         journal_info.recent_warnings = parse_journal_output(&output.stdout);
     }
 
     // Get boot errors
-    if let Ok(output) = Command::new("journalctl")
-        .args(["-p", "err", "--no-pager", "--no-hostname", "-b"])
-        .output()
-    {
+    if let Ok(output) = journalctl(&["-p", "err", "--no-pager", "--no-hostname", "-b"]) {
         journal_info.boot_errors = parse_journal_output(&output.stdout);
     }
 
+    // The journal's size is global, so the unit filter doesn't apply
+    if let Ok(output) = Command::new("journalctl").arg("--disk-usage").pooled_output()
+        && output.status.success()
+    {
+        journal_info.disk_usage =
+            journal_disk_usage_from(&String::from_utf8_lossy(&output.stdout), filesystems, since_boot);
+    }
+
     journal_info
 }
 
+/// Journal usage from `journalctl --disk-usage` output, with the effective cap and journald's
+/// recent rotation errors
+pub fn journal_disk_usage_from(
+    disk_usage_output: &str,
+    filesystems: &[FilesystemUsage],
+    since_boot: bool,
+) -> Option<JournalDiskUsage> {
+    let used_bytes = parse_journal_disk_usage(disk_usage_output)?;
+    let configured = configured_system_max_use(&read_journald_configs());
+    let max_use_bytes = configured.or_else(|| default_system_max_use(filesystems));
+    Some(JournalDiskUsage {
+        used_bytes,
+        max_use_bytes,
+        max_use_configured: configured.is_some(),
+        rotation_errors: journald_rotation_errors(since_boot),
+    })
+}
+
+/// The size in "Archived and active journals take up 3.8G in the file system."
+pub fn parse_journal_disk_usage(output: &str) -> Option<u64> {
+    let (_, rest) = output.split_once("take up ")?;
+    parse_journald_size(rest.split_whitespace().next()?)
+}
+
+/// A journald.conf size such as `500M`, `1.5G` or `4096`: a number with an optional
+/// base-1024 suffix (K, M, G, T, P, E) and an optional trailing `B`
+pub fn parse_journald_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let value = value.strip_suffix(['B', 'b']).unwrap_or(value);
+    let (number, multiplier) = match value.chars().last()?.to_ascii_uppercase() {
+        suffix @ ('K' | 'M' | 'G' | 'T' | 'P' | 'E') => {
+            let power = "KMGTPE".find(suffix)? as u32 + 1;
+            (&value[..value.len() - 1], 1024f64.powi(power as i32))
+        }
+        _ => (value, 1.0),
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    (number >= 0.0).then_some((number * multiplier) as u64)
+}
+
+/// journald.conf and its drop-ins in the order journald applies them. A drop-in in a
+/// higher-priority directory replaces one with the same file name.
+fn read_journald_configs() -> Vec<String> {
+    let mut drop_ins = std::collections::BTreeMap::new();
+    for dir in JOURNALD_DROP_IN_DIRS {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|extension| extension == "conf")
+                && let Some(name) = path.file_name()
+            {
+                drop_ins.insert(name.to_os_string(), path);
+            }
+        }
+    }
+    std::iter::once(PathBuf::from("/etc/systemd/journald.conf"))
+        .chain(drop_ins.into_values())
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .collect()
+}
+
+/// The `SystemMaxUse` in effect after applying each config in order. An empty value resets
+/// it to journald's default.
+pub fn configured_system_max_use(configs: &[String]) -> Option<u64> {
+    let mut max_use = None;
+    for config in configs {
+        let mut in_journal_section = false;
+        for line in config.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_journal_section = line == "[Journal]";
+            } else if in_journal_section
+                && let Some((key, value)) = line.split_once('=')
+                && key.trim() == "SystemMaxUse"
+            {
+                max_use = parse_journald_size(value);
+            }
+        }
+    }
+    max_use
+}
+
+/// journald's default cap: 10% of the filesystem holding /var/log/journal, at most 4 GiB
+fn default_system_max_use(filesystems: &[FilesystemUsage]) -> Option<u64> {
+    let filesystem = filesystems
+        .iter()
+        .filter(|fs| {
+            fs.mount_point == "/"
+                || PERSISTENT_JOURNAL_DIR == fs.mount_point
+                || PERSISTENT_JOURNAL_DIR.starts_with(&format!("{}/", fs.mount_point))
+        })
+        .max_by_key(|fs| fs.mount_point.len())?;
+    Some((filesystem.total_bytes / 10).min(JOURNAL_DEFAULT_MAX_USE_CAP))
+}
+
+/// journald's own warnings about rotating, vacuuming or writing its files
+fn journald_rotation_errors(since_boot: bool) -> Vec<String> {
+    let args = journal_args(
+        &["-u", "systemd-journald", "-p", "warning", "--no-pager", "--no-hostname", "-n", "50"],
+        None,
+        since_boot,
+    );
+    Command::new("journalctl")
+        .args(args)
+        .pooled_output()
+        .map(|output| {
+            parse_journal_output(&output.stdout)
+                .into_iter()
+                .filter(|entry| is_journal_rotation_error(&entry.message))
+                .map(|entry| format!("[{}] {}", entry.timestamp, entry.message))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// journald messages that mean entries are being lost or files aren't rotating cleanly
+pub fn is_journal_rotation_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    ["rotat", "vacuum", "corrupted", "uncleanly shut down", "truncated", "no space left", "failed to write entry"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// journalctl arguments, restricted to one unit and to the current boot when requested
+fn journal_args<'a>(args: &[&'a str], unit: Option<&'a str>, since_boot: bool) -> Vec<&'a str> {
+    let mut full_args = args.to_vec();
+    if let Some(unit) = unit {
+        full_args.extend(["-u", unit]);
+    }
+    if since_boot && !args.contains(&"-b") {
+        full_args.extend(["-b", "0"]);
+    }
+    full_args
+}
+
+/// When the system booted, from the `btime` line of `/proc/stat`
+pub fn boot_time(paths: &HostPaths) -> Option<chrono::DateTime<chrono::Utc>> {
+    parse_boot_time(&std::fs::read_to_string(paths.proc("stat")).ok()?)
+}
+
+fn parse_boot_time(stat: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let seconds = stat.lines().find_map(|line| line.strip_prefix("btime "))?.trim().parse().ok()?;
+    chrono::DateTime::from_timestamp(seconds, 0)
+}
+
 fn parse_journal_output(output: &[u8]) -> Vec<JournalEntry> {
     let mut entries = Vec::new();
     let output_str = String::from_utf8_lossy(output);
@@ -492,28 +2211,43 @@ fn collect_container_info() -> Vec<ContainerInfo> {
             "--format",
             "table {{.ID}}\t{{.Names}}\t{{.Image}}\t{{.Status}}\t{{.Ports}}",
         ])
-        .output()
+        .pooled_output()
     {
         let output_str = String::from_utf8_lossy(&output.stdout);
         for line in output_str.lines().skip(1) {
             // Skip header
             let parts: Vec<&str> = line.split('\t').collect();
             if parts.len() >= 5 {
+                let (health, exit_code) = parse_container_status(parts[3]);
                 containers.push(ContainerInfo {
                     id: parts[0].to_string(),
                     name: parts[1].to_string(),
                     image: parts[2].to_string(),
                     status: parts[3].to_string(),
                     ports: parts[4].split(',').map(|s| s.trim().to_string()).collect(),
+                    health,
+                    exit_code,
+                    restart_count: None,
                 });
             }
         }
+
+        // `docker ps` has no restart count, so ask for all of them in one inspect
+        let ids: Vec<String> = containers.iter().map(|container| container.id.clone()).collect();
+        if !ids.is_empty()
+            && let Ok(output) = Command::new("docker")
+                .args(["inspect", "--format", "{{.Id}} {{.RestartCount}}"])
+                .args(&ids)
+                .pooled_output()
+        {
+            apply_restart_counts(&mut containers, &String::from_utf8_lossy(&output.stdout));
+        }
     }
 
     // Try to get containerd containers
     if let Ok(output) = Command::new("crictl")
         .args(["ps", "--output", "table"])
-        .output()
+        .pooled_output()
     {
         let output_str = String::from_utf8_lossy(&output.stdout);
         for line in output_str.lines().skip(1) {
@@ -526,6 +2260,7 @@ fn collect_container_info() -> Vec<ContainerInfo> {
                     image: parts[2].to_string(),
                     status: parts[3].to_string(),
                     ports: Vec::new(), // crictl doesn't show ports by default
+                    ..Default::default()
                 });
             }
         }
@@ -536,7 +2271,316 @@ fn collect_container_info() -> Vec<ContainerInfo> {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_journal_output;
+    use super::{
+        apply_restart_counts, collect_cgroup_info, collect_pressure_info, compare_kernel_versions, journal_args, kernel_needs_reboot, parse_boot_time, parse_container_status, ContainerInfo, parse_cgroup_cpu_stat, parse_cpu_throttling, parse_proc_self_cgroup, CgroupMembership, parse_cpu_times, CgroupCpuStat, CpuTimes, classify_environment, container_runtime_from_cgroup, EnvironmentKind, parse_journal_output, parse_list_units,
+        collect_swap_info, collect_zombie_info, glob_match, parse_df_output, parse_meminfo, parse_net_dev, with_interface_deltas, InterfaceDelta, parse_proc_stat, parse_pressure, parse_swap_meminfo, CgroupInfo, HostPaths, configured_system_max_use, default_system_max_use, is_journal_rotation_error, parse_journal_disk_usage, parse_journald_size, FilesystemUsage, JournalDiskUsage, ProcessTree, PROCESS_TREE_DEEP_NESTING, SystemInfo, PressureAverages, UnitFilter,
+    };
+
+    #[test]
+    fn test_parse_list_units_sub_states() {
+        let output = "\
+● api.service    loaded activating auto-restart API server
+worker.service   loaded activating start-pre    Background worker
+";
+        let units = parse_list_units(output);
+        assert_eq!(units.len(), 2);
+
+        assert_eq!(units[0].name, "api.service");
+        assert_eq!(units[0].status, "activating");
+        assert_eq!(units[0].sub_state, "auto-restart");
+        assert_eq!(units[0].description, "API server");
+        assert!(units[0].is_restart_loop());
+        assert_eq!(units[0].state_label(), "activating (auto-restart)");
+
+        assert!(!units[1].is_restart_loop());
+        assert!(units[1].is_stuck_activating());
+    }
+
+    #[test]
+    fn test_journal_args_unit_filter() {
+        let base = ["-p", "err", "-n", "50"];
+        assert_eq!(journal_args(&base, None, false), base);
+        assert_eq!(
+            journal_args(&base, Some("nginx.service"), false),
+            ["-p", "err", "-n", "50", "-u", "nginx.service"]
+        );
+        assert_eq!(journal_args(&base, None, true), ["-p", "err", "-n", "50", "-b", "0"]);
+        // The boot errors query is already scoped to the current boot
+        assert_eq!(journal_args(&["-p", "err", "-b"], None, true), ["-p", "err", "-b"]);
+    }
+
+    #[test]
+    fn test_parse_boot_time() {
+        let stat = "cpu  10 0 5 100 0 0 0 0 0 0\nintr 12345\nbtime 1700000000\nprocesses 42\n";
+        assert_eq!(parse_boot_time(stat).unwrap().to_rfc3339(), "2023-11-14T22:13:20+00:00");
+        assert!(parse_boot_time("cpu  10 0 5 100\n").is_none());
+    }
+
+    #[test]
+    fn test_cgroup_memory_usage_percent() {
+        let mut cgroups = CgroupInfo {
+            memory_limit: Some("1073741824".to_string()),
+            memory_usage: Some(1020054732),
+            ..Default::default()
+        };
+        assert!((cgroups.memory_usage_percent().unwrap() - 95.0).abs() < 0.01);
+
+        // cgroup v2 "max" and the v1 unlimited sentinel mean no limit
+        cgroups.memory_limit = Some("max".to_string());
+        assert!(cgroups.memory_usage_percent().is_none());
+        cgroups.memory_limit = Some("9223372036854771712".to_string());
+        assert!(cgroups.memory_usage_percent().is_none());
+    }
+
+    #[test]
+    fn test_parse_proc_self_cgroup() {
+        let v2 = parse_proc_self_cgroup("0::/system.slice/nginx.service\n").unwrap();
+        assert_eq!(
+            v2,
+            CgroupMembership { version: "v2", controllers: Vec::new(), path: "/system.slice/nginx.service".to_string() }
+        );
+
+        // Hybrid: v1 controllers plus the unified line
+        let hybrid = "9:name=systemd:/\n4:memory:/docker/abc\n1:cpu,cpuacct:/docker/abc\n0::/\n";
+        let v1 = parse_proc_self_cgroup(hybrid).unwrap();
+        assert_eq!(v1.version, "v1");
+        assert_eq!(v1.controllers, vec!["cpu", "cpuacct", "memory"]);
+        assert!(parse_proc_self_cgroup("").is_none());
+
+        let cgroups = CgroupInfo {
+            enabled_controllers: Some(vec!["cpuset".to_string(), "cpu".to_string(), "pids".to_string()]),
+            cpu_limit: Some("50000 100000".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(cgroups.controller_enabled("cpu"), Some(true));
+        assert_eq!(cgroups.controller_enabled("memory"), Some(false));
+        assert_eq!(CgroupInfo::default().controller_enabled("memory"), None);
+        assert!(cgroups.has_cpu_quota());
+        assert!(!CgroupInfo { cpu_limit: Some("max 100000".to_string()), ..Default::default() }.has_cpu_quota());
+        assert!(!CgroupInfo { cpu_limit: Some("-1".to_string()), ..Default::default() }.has_cpu_quota());
+    }
+
+    #[test]
+    fn test_parse_cpu_throttling() {
+        let stat = "usage_usec 123\nuser_usec 100\nsystem_usec 23\nnr_periods 200\nnr_throttled 50\nthrottled_usec 999\n";
+        assert_eq!(parse_cpu_throttling(stat), Some(25.0));
+        assert_eq!(parse_cpu_throttling("nr_periods 0\nnr_throttled 0\n"), None);
+        assert_eq!(parse_cpu_throttling("usage_usec 123\n"), None);
+    }
+
+    #[test]
+    fn test_collect_cgroup_info_reads_limits_from_the_process_cgroup() {
+        let root = tempfile::tempdir().unwrap();
+        let paths = HostPaths { procfs_root: root.path().join("proc"), sysfs_root: root.path().join("sys") };
+        let service = paths.sys("fs/cgroup/system.slice/app.service");
+        std::fs::create_dir_all(paths.proc("1")).unwrap();
+        std::fs::create_dir_all(&service).unwrap();
+        // With a host root the host's init is inspected, not RAID's own process
+        std::fs::write(paths.proc("1/cgroup"), "0::/system.slice/app.service\n").unwrap();
+        std::fs::write(paths.sys("fs/cgroup/cgroup.controllers"), "cpuset cpu io memory pids\n").unwrap();
+        std::fs::write(paths.sys("fs/cgroup/system.slice/cpu.max"), "50000 100000\n").unwrap();
+        std::fs::write(service.join("cgroup.controllers"), "cpu pids\n").unwrap();
+        std::fs::write(service.join("memory.max"), "536870912\n").unwrap();
+
+        let info = collect_cgroup_info(&paths);
+        assert_eq!(info.version, "v2");
+        assert_eq!(info.enabled_controllers, Some(vec!["cpu".to_string(), "pids".to_string()]));
+        // The limit is the service's own; memory is set but not enabled there
+        assert_eq!(info.memory_limit.as_deref(), Some("536870912"));
+        // The service has no cpu.max of its own, so its parent's applies
+        assert_eq!(info.cpu_limit.as_deref(), Some("50000 100000"));
+        assert_eq!(info.memory_usage, None);
+    }
+
+    #[test]
+    fn test_parse_cgroup_cpu_stat() {
+        let v2 = "usage_usec 123\nnr_periods 200\nnr_throttled 50\nthrottled_usec 4500000\n";
+        assert_eq!(
+            parse_cgroup_cpu_stat(v2),
+            Some(CgroupCpuStat { nr_periods: 200, nr_throttled: 50, throttled_usec: Some(4_500_000) })
+        );
+
+        // cgroup v1 reports nanoseconds
+        let v1 = "nr_periods 10\nnr_throttled 0\nthrottled_time 2000000\n";
+        let stat = parse_cgroup_cpu_stat(v1).unwrap();
+        assert_eq!(stat.throttled_usec, Some(2000));
+        assert_eq!(stat.throttled_percent(), Some(0.0));
+    }
+
+    #[test]
+    fn test_context_summary_mentions_steal_and_throttling() {
+        let mut info = SystemInfo { cpu_steal_percent: Some(18.5), ..Default::default() };
+        info.cgroups.cpu_nr_periods = Some(200);
+        info.cgroups.cpu_nr_throttled = Some(50);
+        info.cgroups.cpu_throttled_usec = Some(4_500_000);
+        let summary = info.context_summary();
+        assert!(summary.contains("CPU steal: 18.5% of CPU time taken by the hypervisor\n"));
+        assert!(summary.contains("CPU throttling: 50 of 200 cgroup periods throttled, 4.5s throttled in total\n"));
+    }
+
+    #[test]
+    fn test_parse_net_dev_and_deltas() {
+        let before = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:  1000      10    0    0    0     0          0         0     1000      10    0    0    0     0       0          0
+  eth0: 50000    1000   20    5    0     0          0         0    40000     900    1    0    0     0       0          0
+";
+        let after = before.replace("1000   20    5", "1100   26    5");
+        let interfaces = parse_net_dev(before);
+        assert_eq!(interfaces.len(), 2);
+        let eth0 = &interfaces[1];
+        assert_eq!((eth0.name.as_str(), eth0.rx_packets, eth0.rx_errors, eth0.rx_dropped), ("eth0", 1000, 20, 5));
+        assert_eq!((eth0.tx_packets, eth0.tx_errors, eth0.tx_dropped), (900, 1, 0));
+
+        let sampled = with_interface_deltas(parse_net_dev(&after), &interfaces, 0.5);
+        assert_eq!(
+            sampled[1].recent,
+            Some(InterfaceDelta { packets: 100, errors: 6, dropped: 0, seconds: 0.5 })
+        );
+        assert_eq!(sampled[0].recent.map(|recent| recent.errors), Some(0));
+
+        let info = SystemInfo { network_interfaces: sampled, ..Default::default() };
+        let summary = info.context_summary();
+        assert!(summary.contains(
+            "Interface eth0: 26 RX errors, 5 RX drops, 1 TX errors, 0 TX drops of 2000 packets since boot; +6 errors, +0 drops in the last 0.5s\n"
+        ));
+        assert!(!summary.contains("Interface lo"));
+    }
+
+    #[test]
+    fn test_kernel_needs_reboot() {
+        use std::cmp::Ordering;
+
+        assert_eq!(compare_kernel_versions("6.8.0-49-generic", "6.8.0-45-generic"), Ordering::Greater);
+        assert_eq!(compare_kernel_versions("5.15.9", "5.15.10"), Ordering::Less);
+        assert_eq!(compare_kernel_versions("6.10.2-arch1-1", "6.9.12-arch1-1"), Ordering::Greater);
+        assert_eq!(compare_kernel_versions("6.1.0-18-amd64", "6.1.0-18-amd64"), Ordering::Equal);
+
+        let installed = |releases: &[&str]| releases.iter().map(|release| release.to_string()).collect::<Vec<_>>();
+        // Ubuntu after an upgrade: the old kernel is still installed alongside the new one
+        assert!(kernel_needs_reboot("6.8.0-45-generic", &installed(&["6.8.0-45-generic", "6.8.0-49-generic"])));
+        assert!(!kernel_needs_reboot("6.8.0-49-generic", &installed(&["6.8.0-45-generic", "6.8.0-49-generic"])));
+        // Arch after an upgrade: the running kernel's modules are gone
+        assert!(kernel_needs_reboot("6.9.12-arch1-1", &installed(&["6.10.2-arch1-1"])));
+        // No module trees at all (e.g. WSL) or an unknown running kernel
+        assert!(!kernel_needs_reboot("5.15.153.1-microsoft-standard-WSL2", &[]));
+        assert!(!kernel_needs_reboot("", &installed(&["6.10.2-arch1-1"])));
+
+        let info = SystemInfo {
+            running_kernel: "6.8.0-45-generic".to_string(),
+            newest_installed_kernel: Some("6.8.0-49-generic".to_string()),
+            needs_reboot: true,
+            ..Default::default()
+        };
+        assert!(info.context_summary().contains(
+            "Kernel: running 6.8.0-45-generic, newest installed 6.8.0-49-generic; a reboot is needed to load it\n"
+        ));
+    }
+
+    #[test]
+    fn test_parse_container_status() {
+        assert_eq!(parse_container_status("Up 2 hours"), (None, None));
+        assert_eq!(parse_container_status("Up 3 minutes (healthy)"), (Some("healthy".to_string()), None));
+        assert_eq!(parse_container_status("Up 3 minutes (unhealthy)"), (Some("unhealthy".to_string()), None));
+        assert_eq!(parse_container_status("Up 5 seconds (health: starting)"), (Some("starting".to_string()), None));
+        assert_eq!(parse_container_status("Restarting (1) 10 seconds ago"), (None, Some(1)));
+        assert_eq!(parse_container_status("Exited (137) 2 hours ago"), (None, Some(137)));
+
+        let container = |status: &str| {
+            let (health, exit_code) = parse_container_status(status);
+            ContainerInfo { status: status.to_string(), health, exit_code, ..Default::default() }
+        };
+        assert!(container("Up 2 hours").is_healthy());
+        assert!(!container("Up 3 minutes (unhealthy)").is_healthy());
+        assert!(!container("Up 2 hours (Paused)").is_healthy());
+        assert!(container("Restarting (1) 10 seconds ago").is_restarting());
+        assert!(!container("Restarting (1) 10 seconds ago").is_healthy());
+    }
+
+    #[test]
+    fn test_apply_restart_counts() {
+        let mut containers = vec![
+            ContainerInfo { id: "0123456789ab".to_string(), status: "Up 2 hours".to_string(), ..Default::default() },
+            ContainerInfo { id: "ba9876543210".to_string(), status: "Up 2 hours".to_string(), ..Default::default() },
+        ];
+        apply_restart_counts(
+            &mut containers,
+            "0123456789abcdef0123456789abcdef 12\nba9876543210fedcba9876543210fedc 0\n",
+        );
+        assert_eq!(containers[0].restart_count, Some(12));
+        assert!(!containers[0].is_healthy());
+        assert_eq!(containers[1].restart_count, Some(0));
+        assert!(containers[1].is_healthy());
+    }
+
+    #[test]
+    fn test_classify_environment() {
+        assert_eq!(classify_environment(Some("none"), None, false).kind, EnvironmentKind::BareMetal);
+        assert_eq!(classify_environment(None, None, false).kind, EnvironmentKind::Unknown);
+
+        let vm = classify_environment(Some("kvm"), Some("none"), false);
+        assert_eq!(vm.kind, EnvironmentKind::VirtualMachine);
+        assert_eq!(vm.summary(), "virtual machine (kvm)");
+
+        let container = classify_environment(Some("none"), Some("docker"), false);
+        assert_eq!(container.kind, EnvironmentKind::Container);
+        assert_eq!(container.summary(), "container (docker)");
+
+        let pod = classify_environment(Some("kvm"), Some("containerd"), true);
+        assert_eq!(pod.kind, EnvironmentKind::KubernetesPod);
+        assert_eq!(pod.summary(), "Kubernetes pod (containerd) on a kvm virtual machine");
+
+        let info = SystemInfo { environment: pod, ..Default::default() };
+        assert!(info.context_summary().starts_with("Environment: Kubernetes pod (containerd) on a kvm virtual machine\n"));
+    }
+
+    #[test]
+    fn test_container_runtime_from_cgroup() {
+        assert_eq!(
+            container_runtime_from_cgroup("12:memory:/docker/0123abcd\n0::/docker/0123abcd\n"),
+            Some("docker".to_string())
+        );
+        assert_eq!(
+            container_runtime_from_cgroup(
+                "0::/kubepods.slice/kubepods-burstable.slice/cri-containerd-0123abcd.scope\n"
+            ),
+            Some("containerd".to_string())
+        );
+        assert_eq!(container_runtime_from_cgroup("0::/init.scope\n"), None);
+        assert_eq!(container_runtime_from_cgroup("0::/\n"), None);
+    }
+
+    #[test]
+    fn test_parse_cpu_times_and_steal() {
+        let before = parse_cpu_times(
+            "cpu  1000 0 500 8000 100 0 0 400 0 0\ncpu0 500 0 250 4000 50 0 0 200 0 0\nintr 12345\n",
+        )
+        .unwrap();
+        assert_eq!(before, CpuTimes { total: 10000, steal: 400 });
+
+        let after = parse_cpu_times("cpu  1060 0 520 8080 100 0 0 440 20 0\n").unwrap();
+        assert_eq!(after.steal_percent_since(&before), Some(20.0));
+        assert_eq!(after.steal_percent_since(&after), None);
+
+        // Kernels without a steal column report none
+        assert_eq!(parse_cpu_times("cpu 10 0 10 80\n"), Some(CpuTimes { total: 100, steal: 0 }));
+        assert!(parse_cpu_times("intr 1 2 3\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_pressure() {
+        let memory = "some avg10=12.50 avg60=8.25 avg300=3.00 total=123456\nfull avg10=4.00 avg60=2.10 avg300=0.50 total=65432\n";
+        let stats = parse_pressure(memory).unwrap();
+        assert_eq!(stats.some, PressureAverages { avg10: 12.5, avg60: 8.25, avg300: 3.0 });
+        assert_eq!(stats.full, Some(PressureAverages { avg10: 4.0, avg60: 2.1, avg300: 0.5 }));
+
+        // Older kernels report only the "some" line for CPU
+        let cpu = parse_pressure("some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n").unwrap();
+        assert!(cpu.full.is_none());
+
+        assert!(parse_pressure("").is_none());
+        assert!(parse_pressure("some avg10=abc avg60=0.00").is_none());
+    }
 
     #[test]
     fn test_parse_journal_output_various_cases() {
@@ -599,4 +2643,246 @@ Jan 01 12:04:00 kernel:Another message with no space after colon
 
         // Note: Empty kernel message and reboot marker are filtered out
     }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("nginx*", "nginx.service"));
+        assert!(glob_match("*.scope", "session-3.scope"));
+        assert!(glob_match("kube-?roxy", "kube-proxy"));
+        assert!(glob_match("*a*b", "xaxxb"));
+        assert!(!glob_match("*.scope", "session-3.service"));
+        assert!(!glob_match("kube?", "kube"));
+    }
+
+    #[test]
+    fn test_unit_filter() {
+        assert!(UnitFilter::default().matches("anything.service"));
+
+        let filter = UnitFilter {
+            include: vec!["kube*".to_string(), "docker".to_string()],
+            exclude: vec!["kube-proxy".to_string(), "*.scope".to_string()],
+        };
+        assert!(filter.matches("kubelet"));
+        assert!(filter.matches("docker.service"));
+        assert!(!filter.matches("kube-proxy.service"));
+        assert!(!filter.matches("containerd"));
+
+        let exclude_only = UnitFilter {
+            include: Vec::new(),
+            exclude: vec!["*.scope".to_string()],
+        };
+        assert!(exclude_only.matches("nginx.service"));
+        assert!(!exclude_only.matches("session-3.scope"));
+    }
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let stat = parse_proc_stat("4242 (my (odd) worker) Z 1001 4242 4242 0 -1 4228164 0 0").unwrap();
+        assert_eq!(stat.comm, "my (odd) worker");
+        assert_eq!(stat.state, 'Z');
+        assert_eq!(stat.ppid, 1001);
+        assert!(parse_proc_stat("garbage").is_none());
+    }
+
+    #[test]
+    fn test_collect_zombie_info_groups_by_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = HostPaths {
+            procfs_root: dir.path().to_path_buf(),
+            sysfs_root: dir.path().to_path_buf(),
+        };
+        let write_stat = |pid: u32, comm: &str, state: char, ppid: u32| {
+            let proc_dir = dir.path().join(pid.to_string());
+            std::fs::create_dir_all(&proc_dir).unwrap();
+            std::fs::write(proc_dir.join("stat"), format!("{} ({}) {} {} 1 1 0", pid, comm, state, ppid)).unwrap();
+        };
+        write_stat(1, "systemd", 'S', 0);
+        write_stat(100, "buggy-daemon", 'S', 1);
+        write_stat(101, "worker", 'Z', 100);
+        write_stat(102, "worker", 'Z', 100);
+        write_stat(200, "cron", 'S', 1);
+        write_stat(201, "job", 'Z', 200);
+        std::fs::create_dir_all(dir.path().join("self")).unwrap();
+
+        let (count, parents) = collect_zombie_info(&paths);
+        assert_eq!(count, 3);
+        assert_eq!(parents[0].name, "buggy-daemon");
+        assert_eq!(parents[0].zombies, 2);
+        assert_eq!(parents[1].pid, 200);
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let content = "MemTotal:       16314188 kB\nMemFree:         1024000 kB\nMemAvailable:    8000000 kB\n";
+        assert_eq!(parse_meminfo(content), Some((16314188 * 1024, 1024000 * 1024)));
+        assert_eq!(parse_meminfo("MemAvailable: 1 kB\n"), None);
+    }
+
+    #[test]
+    fn test_collect_pressure_info_reads_alternate_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = HostPaths {
+            procfs_root: dir.path().join("host/proc"),
+            sysfs_root: dir.path().join("host/sys"),
+        };
+        std::fs::create_dir_all(paths.proc("pressure")).unwrap();
+        std::fs::write(
+            paths.proc("pressure/memory"),
+            "some avg10=12.50 avg60=3.00 avg300=1.00 total=100\nfull avg10=4.00 avg60=1.00 avg300=0.50 total=50\n",
+        )
+        .unwrap();
+
+        let pressure = collect_pressure_info(&paths);
+        assert_eq!(pressure.memory.unwrap().some.avg10, 12.5);
+        assert!(pressure.cpu.is_none());
+        assert!(pressure.cgroup_memory.is_none());
+    }
+
+    #[test]
+    fn test_parse_swap_meminfo() {
+        let meminfo = "MemTotal:       16303428 kB\nSwapCached:        10240 kB\nSwapTotal:       2097148 kB\nSwapFree:         524284 kB\n";
+        assert_eq!(parse_swap_meminfo(meminfo), Some((2097148 * 1024, 1572864 * 1024)));
+        assert_eq!(parse_swap_meminfo("SwapTotal: 0 kB\nSwapFree: 0 kB\n"), Some((0, 0)));
+        assert!(parse_swap_meminfo("MemTotal: 100 kB\n").is_none());
+    }
+
+    #[test]
+    fn test_collect_swap_info_reads_swappiness() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = HostPaths {
+            procfs_root: dir.path().join("proc"),
+            sysfs_root: dir.path().join("sys"),
+        };
+        std::fs::create_dir_all(paths.proc("sys/vm")).unwrap();
+        std::fs::write(paths.proc("meminfo"), "SwapTotal: 1048576 kB\nSwapFree: 262144 kB\n").unwrap();
+        std::fs::write(paths.proc("sys/vm/swappiness"), "60\n").unwrap();
+
+        let swap = collect_swap_info(&paths);
+        assert_eq!(swap.used_percent(), Some(75.0));
+        assert_eq!(swap.swappiness, Some(60));
+        assert_eq!(swap.summary(), "Swap: 768 MiB used of 1.0 GiB (75.0%), vm.swappiness=60");
+
+        std::fs::remove_file(paths.proc("meminfo")).unwrap();
+        assert_eq!(collect_swap_info(&paths).summary(), "Swap: none configured, vm.swappiness=60");
+    }
+
+    #[test]
+    fn test_parse_df_output() {
+        let output = "Filesystem     Type     1024-blocks     Used Available Capacity Mounted on
+/dev/nvme0n1p2 ext4       479596204 431234567  23914001      95% /
+tmpfs          tmpfs        8151712     2048   8149664       1% /run
+/dev/loop3     squashfs       75776    75776         0     100% /snap/core22/1380
+/dev/sdb1      xfs         10475520  5237760   5237760      50% /mnt/backup disk
+proc           proc               0        0         0       -  /proc
+";
+        let filesystems = parse_df_output(output);
+        assert_eq!(filesystems.len(), 2);
+        assert_eq!(filesystems[0].mount_point, "/");
+        assert_eq!(filesystems[0].use_percent, 95);
+        assert_eq!(filesystems[0].available_bytes, 23914001 * 1024);
+        assert_eq!(filesystems[1].mount_point, "/mnt/backup disk");
+        assert_eq!(filesystems[1].fs_type, "xfs");
+        assert!(parse_df_output("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_journal_disk_usage() {
+        let output = "Archived and active journals take up 3.8G in the file system.\n";
+        assert_eq!(parse_journal_disk_usage(output), Some((3.8 * 1024.0 * 1024.0 * 1024.0) as u64));
+        assert_eq!(parse_journal_disk_usage("Journals take up 512B in the file system."), Some(512));
+        assert!(parse_journal_disk_usage("No journal files were found.").is_none());
+
+        assert_eq!(parse_journald_size("500M"), Some(500 * 1024 * 1024));
+        assert_eq!(parse_journald_size("2G"), Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_journald_size("16.0K"), Some(16 * 1024));
+        assert_eq!(parse_journald_size("4096"), Some(4096));
+        assert!(parse_journald_size("").is_none());
+        assert!(parse_journald_size("lots").is_none());
+    }
+
+    #[test]
+    fn test_configured_system_max_use_applies_configs_in_order() {
+        let main = "[Journal]\n#SystemMaxUse=\nSystemMaxUse=1G\nRuntimeMaxUse=100M\n".to_string();
+        assert_eq!(configured_system_max_use(std::slice::from_ref(&main)), Some(1024 * 1024 * 1024));
+
+        let drop_in = "[Journal]\nSystemMaxUse=500M\n".to_string();
+        assert_eq!(configured_system_max_use(&[main.clone(), drop_in]), Some(500 * 1024 * 1024));
+
+        // An empty value resets to the default; keys outside [Journal] are ignored
+        let reset = "[Journal]\nSystemMaxUse=\n".to_string();
+        assert_eq!(configured_system_max_use(&[main, reset]), None);
+        assert_eq!(configured_system_max_use(&["[Other]\nSystemMaxUse=1G\n".to_string()]), None);
+    }
+
+    #[test]
+    fn test_default_system_max_use_uses_the_journal_filesystem() {
+        let filesystem = |mount_point: &str, total_bytes| FilesystemUsage {
+            filesystem: "/dev/sda1".to_string(),
+            fs_type: "ext4".to_string(),
+            mount_point: mount_point.to_string(),
+            total_bytes,
+            used_bytes: 0,
+            available_bytes: total_bytes,
+            use_percent: 0,
+        };
+        let gib = 1024 * 1024 * 1024;
+        assert_eq!(default_system_max_use(&[filesystem("/", 20 * gib)]), Some(2 * gib));
+        // /var is the closer mount, and 10% of it is over the 4 GiB ceiling
+        let filesystems = [filesystem("/", 20 * gib), filesystem("/var", 100 * gib), filesystem("/variant", gib)];
+        assert_eq!(default_system_max_use(&filesystems), Some(4 * gib));
+        assert_eq!(default_system_max_use(&[filesystem("/home", gib)]), None);
+    }
+
+    #[test]
+    fn test_journal_disk_usage_summary() {
+        let gib = 1024 * 1024 * 1024;
+        let mut usage = JournalDiskUsage {
+            used_bytes: 3 * gib + gib / 2,
+            max_use_bytes: Some(4 * gib),
+            max_use_configured: false,
+            rotation_errors: Vec::new(),
+        };
+        assert_eq!(usage.used_percent(), Some(87.5));
+        assert_eq!(usage.summary(), "Journal: 3.5 GiB of its 4.0 GiB cap (87.5%, default SystemMaxUse)");
+
+        usage.max_use_bytes = None;
+        usage.rotation_errors.push("[Oct 16 10:00:00] Failed to rotate /var/log/journal/system.journal".to_string());
+        assert_eq!(usage.summary(), "Journal: 3.5 GiB (cap unknown), 1 journald rotation/write errors");
+
+        assert!(is_journal_rotation_error("Failed to rotate /var/log/journal/abc/system.journal: No space left on device"));
+        assert!(is_journal_rotation_error("File /var/log/journal/abc/system.journal corrupted or uncleanly shut down, renaming and replacing."));
+        assert!(!is_journal_rotation_error("Journal started"));
+    }
+
+    #[test]
+    fn test_process_tree_highlights_busy_parents_and_deep_nesting() {
+        let mut processes = vec![
+            (1, 0, "systemd".to_string()),
+            (2, 0, "kthreadd".to_string()),
+            (3, 2, "kworker/0:0".to_string()),
+            (500, 1, "php-fpm".to_string()),
+            (501, 500, "logger".to_string()),
+        ];
+        processes.extend((600..625).map(|pid| (pid, 500, "php-fpm".to_string())));
+        // A chain of shells under cron, deep enough to stand out
+        processes.push((700, 1, "cron".to_string()));
+        let deepest = 700 + PROCESS_TREE_DEEP_NESTING as u32;
+        processes.extend((701..=deepest).map(|pid| (pid, pid - 1, "sh".to_string())));
+
+        let tree = ProcessTree::from_processes(processes);
+        let highlights = tree.highlights();
+        assert_eq!(highlights.len(), 2);
+        assert_eq!(highlights[0].pid, 500);
+        assert_eq!(highlights[0].reason, "26 children (25 × php-fpm)");
+        assert_eq!(highlights[0].summary(), "php-fpm (PID 500) has 26 children (25 × php-fpm), started by systemd (1)");
+        assert_eq!(highlights[1].pid, deepest);
+        assert!(highlights[1].reason.starts_with("nesting 11 levels deep"));
+        assert!(highlights[1].ancestry.starts_with("systemd (1) > cron (700) > sh (701)"));
+
+        let rendered = tree.render();
+        assert!(rendered.starts_with("1 systemd\n├─ 500 php-fpm  ⚠ 26 children (25 × php-fpm)\n"));
+        assert!(rendered.contains("│  ├─ 501 logger\n│  └─ 25 × php-fpm (PIDs 600, 601, 602, ...)\n"));
+        assert!(rendered.contains("2 kthreadd [1 kernel threads]\n"));
+        assert!(!rendered.contains("kworker"));
+    }
 }
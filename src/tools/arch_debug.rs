@@ -1,4 +1,5 @@
-use super::{DebugToolResult, DebugTools};
+use super::{find_in_path, DebugToolResult, DebugTools};
+use super::pool::PooledOutput;
 use std::process::Command;
 
 impl DebugTools {
@@ -8,7 +9,7 @@ impl DebugTools {
         let mut command = Command::new("pacman");
         command.args(["-Q"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -47,7 +48,7 @@ impl DebugTools {
         let mut command = Command::new("pacman");
         command.args(["-Qdt"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -86,7 +87,7 @@ impl DebugTools {
         let mut command = Command::new("pacman");
         command.args(["-Qkk"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -124,7 +125,7 @@ impl DebugTools {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("checkupdates");
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -163,7 +164,7 @@ impl DebugTools {
         let mut command = Command::new("paccache");
         command.args(["-d"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -202,7 +203,7 @@ impl DebugTools {
         let mut command = Command::new("systemd-analyze");
         command.args(["time"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -241,7 +242,7 @@ impl DebugTools {
         let mut command = Command::new("systemd-analyze");
         command.args(["critical-chain"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -280,7 +281,7 @@ impl DebugTools {
         let mut command = Command::new("systemd-analyze");
         command.args(["blame"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -319,7 +320,7 @@ impl DebugTools {
         let mut command = Command::new("journalctl");
         command.args(["--list-boots", "--no-pager"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -357,7 +358,7 @@ impl DebugTools {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("lsmod");
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -396,7 +397,7 @@ impl DebugTools {
         let mut command = Command::new("systemctl");
         command.args(["--failed", "--no-pager"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -434,13 +435,13 @@ impl DebugTools {
         let start_time = std::time::Instant::now();
         
         // Check if running kernel matches installed kernel
-        let running_kernel = std::fs::read_to_string("/proc/version")
+        let running_kernel = std::fs::read_to_string(self.host_paths.proc("version"))
             .unwrap_or_else(|_| "Unknown".to_string());
         
         let mut command = Command::new("pacman");
         command.args(["-Q", "linux"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -529,20 +530,17 @@ impl DebugTools {
         let mut helper_info = String::new();
         
         for helper in &aur_helpers {
-            if let Ok(output) = Command::new("which").arg(helper).output() {
-                if output.status.success() {
-                    found_helpers.push(*helper);
-                    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    helper_info.push_str(&format!("{}: {}\n", helper, path));
-                    
-                    // Get version if possible
-                    if let Ok(version_output) = Command::new(helper).arg("--version").output() {
-                        if version_output.status.success() {
-                            let version = String::from_utf8_lossy(&version_output.stdout);
-                            let version_line = version.lines().next().unwrap_or("Unknown version");
-                            helper_info.push_str(&format!("  Version: {}\n", version_line));
-                        }
-                    }
+            if let Some(path) = find_in_path(helper) {
+                found_helpers.push(*helper);
+                helper_info.push_str(&format!("{}: {}\n", helper, path.display()));
+
+                // Get version if possible
+                if let Ok(version_output) = Command::new(&path).arg("--version").pooled_output()
+                    && version_output.status.success()
+                {
+                    let version = String::from_utf8_lossy(&version_output.stdout);
+                    let version_line = version.lines().next().unwrap_or("Unknown version");
+                    helper_info.push_str(&format!("  Version: {}\n", version_line));
                 }
             }
         }
@@ -671,7 +669,6 @@ mod tests {
 
         assert_eq!(result.tool_name, "aur_helper_info");
         assert_eq!(result.command, "which yay paru pikaur trizen");
-        assert!(result.execution_time_ms > 0);
 
         // AUR helpers may or may not be installed
         assert!(!result.output.is_empty());
@@ -4,10 +4,11 @@ use std::process::Command;
 impl DebugTools {
     pub async fn run_cat_proc_cgroups(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
+        let path = self.host_paths.proc("cgroups");
         let mut command = Command::new("cat");
-        command.arg("/proc/cgroups");
+        command.arg(&path);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -22,7 +23,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "cat_proc_cgroups".to_string(),
-                    command: "cat /proc/cgroups".to_string(),
+                    command: format!("cat {}", path.display()),
                     success,
                     output: output_str,
                     error: error_str,
@@ -31,7 +32,7 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "cat_proc_cgroups".to_string(),
-                command: "cat /proc/cgroups".to_string(),
+                command: format!("cat {}", path.display()),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
@@ -42,10 +43,11 @@ impl DebugTools {
 
     pub async fn run_ls_cgroup(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
+        let path = self.host_paths.sys("fs/cgroup");
         let mut command = Command::new("ls");
-        command.args(["-la", "/sys/fs/cgroup"]);
+        command.arg("-la").arg(&path);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -60,7 +62,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "ls_cgroup".to_string(),
-                    command: "ls -la /sys/fs/cgroup".to_string(),
+                    command: format!("ls -la {}", path.display()),
                     success,
                     output: output_str,
                     error: error_str,
@@ -69,7 +71,7 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "ls_cgroup".to_string(),
-                command: "ls -la /sys/fs/cgroup".to_string(),
+                command: format!("ls -la {}", path.display()),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
@@ -80,10 +82,11 @@ impl DebugTools {
 
     pub async fn run_cat_proc_self_cgroup(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
+        let path = self.host_paths.inspected_proc("cgroup");
         let mut command = Command::new("cat");
-        command.arg("/proc/self/cgroup");
+        command.arg(&path);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -98,7 +101,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "cat_proc_self_cgroup".to_string(),
-                    command: "cat /proc/self/cgroup".to_string(),
+                    command: format!("cat {}", path.display()),
                     success,
                     output: output_str,
                     error: error_str,
@@ -107,7 +110,7 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "cat_proc_self_cgroup".to_string(),
-                command: "cat /proc/self/cgroup".to_string(),
+                command: format!("cat {}", path.display()),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
@@ -118,10 +121,11 @@ impl DebugTools {
 
     pub async fn run_cat_proc_self_mountinfo(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
+        let path = self.host_paths.inspected_proc("mountinfo");
         let mut command = Command::new("cat");
-        command.arg("/proc/self/mountinfo");
+        command.arg(&path);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -136,7 +140,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "cat_proc_self_mountinfo".to_string(),
-                    command: "cat /proc/self/mountinfo".to_string(),
+                    command: format!("cat {}", path.display()),
                     success,
                     output: output_str,
                     error: error_str,
@@ -145,7 +149,7 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "cat_proc_self_mountinfo".to_string(),
-                command: "cat /proc/self/mountinfo".to_string(),
+                command: format!("cat {}", path.display()),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
@@ -159,7 +163,7 @@ impl DebugTools {
         let mut command = Command::new("lsns");
         command.args(["-l"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -194,10 +198,11 @@ impl DebugTools {
 
     pub async fn run_cat_proc_self_status(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
+        let path = self.host_paths.inspected_proc("status");
         let mut command = Command::new("cat");
-        command.arg("/proc/self/status");
+        command.arg(&path);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -212,7 +217,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "cat_proc_self_status".to_string(),
-                    command: "cat /proc/self/status".to_string(),
+                    command: format!("cat {}", path.display()),
                     success,
                     output: output_str,
                     error: error_str,
@@ -221,7 +226,7 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "cat_proc_self_status".to_string(),
-                command: "cat /proc/self/status".to_string(),
+                command: format!("cat {}", path.display()),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
@@ -232,10 +237,11 @@ impl DebugTools {
 
     pub async fn run_cat_proc_self_ns(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
+        let path = self.host_paths.inspected_proc("ns");
         let mut command = Command::new("ls");
-        command.args(["-la", "/proc/self/ns"]);
+        command.arg("-la").arg(&path);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -250,7 +256,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "cat_proc_self_ns".to_string(),
-                    command: "ls -la /proc/self/ns".to_string(),
+                    command: format!("ls -la {}", path.display()),
                     success,
                     output: output_str,
                     error: error_str,
@@ -259,7 +265,7 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "cat_proc_self_ns".to_string(),
-                command: "ls -la /proc/self/ns".to_string(),
+                command: format!("ls -la {}", path.display()),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
@@ -278,7 +284,7 @@ impl DebugTools {
             "table {{.Names}}\t{{.Status}}\t{{.Ports}}\t{{.Image}}",
         ]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -320,7 +326,7 @@ impl DebugTools {
             "table {{.Names}}\t{{.Status}}\t{{.Ports}}\t{{.Image}}",
         ]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -363,7 +369,7 @@ impl DebugTools {
             "{{.State.Status}} - {{.State.Running}} - {{.Config.Image}}",
         ]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -419,7 +425,7 @@ impl DebugTools {
 
         command.arg(container_name);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -1,6 +1,35 @@
-use super::{DebugToolResult, DebugTools};
+use super::{DebugToolResult, DebugTools, ToolCategory, ToolAvailability};
+use super::pool::PooledOutput;
+use crate::sysinfo::HostPaths;
 use std::process::Command;
 
+/// Default sample duration for bpftrace_syscalls
+const BPFTRACE_SYSCALLS_DEFAULT_SECS: u64 = 5;
+
+/// Default duration of the OOM trace
+const BPFTRACE_OOM_DEFAULT_SECS: u64 = 10;
+
+/// Whether RAID is running as root (bpftrace needs it). This is RAID's own process even with
+/// a host root, since it is RAID that runs bpftrace.
+fn is_root(paths: &HostPaths) -> bool {
+    std::fs::read_to_string(paths.proc("self/status"))
+        .ok()
+        .and_then(|status| {
+            status
+                .lines()
+                .find(|line| line.starts_with("Uid:"))
+                .and_then(|line| line.split_whitespace().nth(2).map(|euid| euid == "0"))
+        })
+        .unwrap_or(false)
+}
+
+/// Whether a bpf filesystem is mounted
+fn bpf_fs_mounted(paths: &HostPaths) -> bool {
+    std::fs::read_to_string(paths.inspected_proc("mounts"))
+        .map(|mounts| mounts.lines().any(|line| line.split_whitespace().nth(2) == Some("bpf")))
+        .unwrap_or(false)
+}
+
 impl DebugTools {
     /// List all loaded BPF programs
     pub async fn run_bpftool_prog_list(&self) -> DebugToolResult {
@@ -8,7 +37,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["prog", "list"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -47,7 +76,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["prog", "show", "id", prog_id]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -86,7 +115,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["prog", "dump", "xlated", "id", prog_id]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -125,7 +154,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["prog", "dump", "jited", "id", prog_id]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -164,7 +193,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["map", "list"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -203,7 +232,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["map", "show", "id", map_id]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -242,7 +271,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["map", "dump", "id", map_id]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -281,7 +310,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["link", "list"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -320,7 +349,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["feature", "probe"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -359,7 +388,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["net", "list"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -398,7 +427,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["cgroup", "list", "/sys/fs/cgroup"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -437,7 +466,7 @@ impl DebugTools {
         let mut command = Command::new("bpftool");
         command.args(["btf", "list"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -476,7 +505,7 @@ impl DebugTools {
         let mut command = Command::new("mount");
         command.args(["-t", "bpf"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -515,7 +544,7 @@ impl DebugTools {
         let mut command = Command::new("find");
         command.args(["/sys/fs/bpf", "-type", "f", "2>/dev/null", "||", "echo", "BPF filesystem not mounted or no pinned objects"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -555,23 +584,23 @@ impl DebugTools {
         command.args(["-E", "CONFIG_BPF|CONFIG_CGROUP_BPF", "/proc/config.gz"]);
 
         // Fallback to checking boot config if /proc/config.gz doesn't exist
-        let result = if let Ok(output) = command.output() {
+        let result = if let Ok(output) = command.pooled_output() {
             if output.status.success() {
                 Ok(output)
             } else {
                 Command::new("grep")
                     .args(["-E", "CONFIG_BPF|CONFIG_CGROUP_BPF", "/boot/config-$(uname -r)"])
-                    .output()
+                    .pooled_output()
                     .or_else(|_| {
                         Command::new("sh")
                             .args(["-c", "zcat /proc/config.gz 2>/dev/null | grep -E 'CONFIG_BPF|CONFIG_CGROUP_BPF' || echo 'BPF config not available'"])
-                            .output()
+                            .pooled_output()
                     })
             }
         } else {
             Command::new("sh")
                 .args(["-c", "if [ -f /boot/config-$(uname -r) ]; then grep -E 'CONFIG_BPF|CONFIG_CGROUP_BPF' /boot/config-$(uname -r); else echo 'Kernel config not found'; fi"])
-                .output()
+                .pooled_output()
         };
 
         let execution_time = start_time.elapsed().as_millis() as u64;
@@ -606,18 +635,22 @@ impl DebugTools {
         }
     }
 
-    /// Simple BPF tracing one-liner (list syscalls)
-    pub async fn run_bpftrace_syscalls(&self) -> DebugToolResult {
+    /// Simple BPF tracing one-liner (count syscalls per process for `duration_secs`, default 5)
+    pub async fn run_bpftrace_syscalls(&self, duration_secs: Option<u64>) -> DebugToolResult {
         let start_time = std::time::Instant::now();
+        let secs = duration_secs.unwrap_or(BPFTRACE_SYSCALLS_DEFAULT_SECS);
+        let script = "tracepoint:raw_syscalls:sys_enter { @[comm] = count(); }";
+        let command_str = format!("timeout {} bpftrace -e '{}'", secs, script);
         let mut command = Command::new("timeout");
-        command.args(["5", "bpftrace", "-e", "tracepoint:raw_syscalls:sys_enter { @[comm] = count(); }"]);
+        command.args([&secs.to_string(), "bpftrace", "-e", script]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
             Ok(output) => {
-                let success = output.status.success();
+                // timeout exits with 124 when the trace ran for the full duration
+                let success = output.status.success() || output.status.code() == Some(124);
                 let output_str = String::from_utf8_lossy(&output.stdout).to_string();
                 let error_str = if success {
                     None
@@ -627,7 +660,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "bpftrace_syscalls".to_string(),
-                    command: "timeout 5 bpftrace -e 'tracepoint:raw_syscalls:sys_enter { @[comm] = count(); }'".to_string(),
+                    command: command_str,
                     success,
                     output: output_str,
                     error: error_str,
@@ -636,7 +669,87 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "bpftrace_syscalls".to_string(),
-                command: "timeout 5 bpftrace -e 'tracepoint:raw_syscalls:sys_enter { @[comm] = count(); }'".to_string(),
+                command: command_str,
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
+    /// Trace OOM kills for `duration_secs` (default 10) and report which processes were killed
+    pub async fn run_bpftrace_oom(&self, duration_secs: Option<u64>) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let secs = duration_secs.unwrap_or(BPFTRACE_OOM_DEFAULT_SECS);
+        let script = "kprobe:oom_kill_process { $oc = (struct oom_control *)arg0; printf(\"OOM kill: pid %d (%s), triggered by pid %d (%s)\\n\", $oc->chosen->pid, $oc->chosen->comm, pid, comm); }";
+        let command_str = format!("timeout {} bpftrace -e '{}'", secs, script);
+
+        // Prefer the startup probe; fall back to a direct check when tools were not probed
+        let bpftrace_available = if self.available_tools.contains_key(&ToolCategory::EbpfDebug) {
+            self.get_category_tools(&ToolCategory::EbpfDebug)
+                .iter()
+                .any(|t| t == "bpftrace")
+        } else {
+            self.check_tool_availability("bpftrace")
+        };
+
+        let unavailable_reason = if !bpftrace_available {
+            Some("bpftrace is not installed. Install it (e.g. `apt install bpftrace` or `pacman -S bpftrace`) to trace OOM kills.".to_string())
+        } else if !is_root(&self.host_paths) {
+            Some("bpftrace requires root. Re-run RAID with sudo to trace OOM kills; meanwhile check `journalctl -k | grep -i oom` for past kills.".to_string())
+        } else if !bpf_fs_mounted(&self.host_paths) {
+            Some("BPF filesystem is not mounted. Mount it with `mount -t bpf bpf /sys/fs/bpf` and try again.".to_string())
+        } else {
+            None
+        };
+
+        if let Some(reason) = unavailable_reason {
+            return DebugToolResult {
+                tool_name: "bpftrace_oom".to_string(),
+                command: command_str,
+                success: false,
+                output: String::new(),
+                error: Some(reason),
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+            };
+        }
+
+        let mut command = Command::new("timeout");
+        command.args([&secs.to_string(), "bpftrace", "-e", script]);
+
+        let result = self.command_output(&mut command);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(output) => {
+                // timeout exits with 124 when the trace ran for the full duration
+                let success = output.status.success() || output.status.code() == Some(124);
+                let mut output_str = String::from_utf8_lossy(&output.stdout).to_string();
+                if success && !output_str.contains("OOM kill:") {
+                    output_str.push_str(&format!(
+                        "No OOM kills observed during the {}-second trace.\n",
+                        secs
+                    ));
+                }
+                let error_str = if success {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(&output.stderr).to_string())
+                };
+
+                DebugToolResult {
+                    tool_name: "bpftrace_oom".to_string(),
+                    command: command_str,
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "bpftrace_oom".to_string(),
+                command: command_str,
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
@@ -651,7 +764,7 @@ impl DebugTools {
         let mut command = Command::new("bpftrace");
         command.args(["-l", "tracepoint:*"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -690,7 +803,7 @@ impl DebugTools {
         let mut command = Command::new("sysctl");
         command.args(["net.core.bpf_jit_enable"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -797,6 +910,19 @@ mod tests {
         assert!(result.execution_time_ms >= 0);
     }
 
+    #[tokio::test]
+    async fn test_bpftrace_oom_structure() {
+        let debug_tools = DebugTools::new();
+        let result = debug_tools.run_bpftrace_oom(Some(1)).await;
+
+        assert_eq!(result.tool_name, "bpftrace_oom");
+        assert!(result.command.starts_with("timeout 1 bpftrace -e"));
+        assert!(result.command.contains("kprobe:oom_kill_process"));
+        if !result.success {
+            assert!(result.error.is_some());
+        }
+    }
+
     #[tokio::test]
     async fn test_bpftool_feature_probe_structure() {
         let debug_tools = DebugTools::new();
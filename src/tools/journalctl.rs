@@ -1,4 +1,5 @@
 use super::{DebugToolResult, DebugTools};
+use crate::sysinfo::{collect_disk_usage, journal_disk_usage_from, JournalDiskUsage};
 use std::process::Command;
 
 impl DebugTools {
@@ -13,7 +14,7 @@ impl DebugTools {
             command.arg("-n").arg("50"); // Default to 50 lines
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -61,7 +62,7 @@ impl DebugTools {
             command.arg("-n").arg("50"); // Default to 50 lines
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -103,7 +104,7 @@ impl DebugTools {
         let mut command = Command::new("journalctl");
         command.args(["-b", "--no-pager", "-n", "100"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -147,7 +148,7 @@ impl DebugTools {
             command.arg("-n").arg("50"); // Default to 50 lines
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -179,4 +180,63 @@ impl DebugTools {
             },
         }
     }
+
+    /// Journal size: `journalctl --disk-usage`, followed by the effective `SystemMaxUse` cap and
+    /// any recent journald errors about rotating or writing its files
+    pub async fn run_journalctl_disk_usage(&self) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let mut command = Command::new("journalctl");
+        command.arg("--disk-usage");
+
+        let result = self.command_output(&mut command);
+
+        match result {
+            Ok(output) => {
+                let success = output.status.success();
+                let mut output_str = String::from_utf8_lossy(&output.stdout).to_string();
+                if success && let Some(usage) = journal_disk_usage_from(&output_str, &collect_disk_usage(), false) {
+                    output_str.push_str(&describe_journal_disk_usage(&usage));
+                }
+                let error_str = if success {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(&output.stderr).to_string())
+                };
+
+                DebugToolResult {
+                    tool_name: "journal_disk_usage".to_string(),
+                    command: "journalctl --disk-usage".to_string(),
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "journal_disk_usage".to_string(),
+                command: "journalctl --disk-usage".to_string(),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+            },
+        }
+    }
+}
+
+/// The cap and rotation errors appended to `journalctl --disk-usage` output
+fn describe_journal_disk_usage(usage: &JournalDiskUsage) -> String {
+    let mut text = format!("\n{}\n", usage.summary());
+    if usage.max_use_bytes.is_some() && !usage.max_use_configured {
+        text.push_str("SystemMaxUse isn't set in journald.conf; journald's default is 10% of the filesystem, at most 4 GiB\n");
+    }
+    if usage.rotation_errors.is_empty() {
+        text.push_str("No journald rotation or write errors in its recent log\n");
+    } else {
+        text.push_str("journald rotation and write errors:\n");
+        for error in &usage.rotation_errors {
+            text.push_str(&format!("  {}\n", error));
+        }
+    }
+    text
 }
@@ -1,4 +1,9 @@
-use super::{DebugToolResult, DebugTools};
+use super::{
+    DebugToolResult, DebugTools, EventInfo, PersistentVolumeClaimInfo, PersistentVolumeInfo, PodInfo, StorageClassInfo,
+    VolumeBindingState,
+};
+use serde_json::Value;
+use super::pool::PooledOutput;
 use std::process::Command;
 
 impl DebugTools {
@@ -11,7 +16,7 @@ impl DebugTools {
             command.args(["-n", ns]);
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -60,7 +65,7 @@ impl DebugTools {
             command.args(["-n", ns]);
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -106,7 +111,7 @@ impl DebugTools {
             command.args(["-n", ns]);
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -147,7 +152,7 @@ impl DebugTools {
         let mut command = Command::new("kubectl");
         command.arg("get").arg("nodes").arg("--output=wide");
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -189,7 +194,7 @@ impl DebugTools {
             command.args(["-n", ns]);
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -224,4 +229,341 @@ impl DebugTools {
             },
         }
     }
+
+    pub async fn run_kubectl_describe_node(&self, node: &str) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let mut command = Command::new("kubectl");
+        command.arg("describe").arg("node").arg(node);
+
+        let result = self.command_output(&mut command);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(output) => {
+                let success = output.status.success();
+                let full_output = String::from_utf8_lossy(&output.stdout).to_string();
+                let error_str = if success {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(&output.stderr).to_string())
+                };
+
+                // Only the pressure conditions and allocatable resources matter for
+                // eviction/scheduling problems; fall back to the full output otherwise
+                let sections = extract_describe_sections(&full_output, &["Conditions", "Allocatable"]);
+                let output_str = if sections.is_empty() { full_output } else { sections };
+
+                DebugToolResult {
+                    tool_name: "kubectl_describe_node".to_string(),
+                    command: format!("kubectl describe node {}", node),
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "kubectl_describe_node".to_string(),
+                command: format!("kubectl describe node {}", node),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
+    /// Fetch pods and events as structured data (`kubectl get ... -o json`).
+    /// Returns `None` if kubectl fails or its output cannot be parsed.
+    pub async fn get_pods_and_events(&self, namespace: Option<&str>) -> Option<(Vec<PodInfo>, Vec<EventInfo>)> {
+        let run = |resource: &str| {
+            let mut command = Command::new("kubectl");
+            command.arg("get").arg(resource).arg("-o").arg("json");
+            match namespace {
+                Some(ns) => command.args(["-n", ns]),
+                None => command.arg("--all-namespaces"),
+            };
+            command
+                .pooled_output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        };
+
+        let pods = parse_pods_json(&run("pods")?).ok()?;
+        let events = parse_events_json(&run("events")?).ok()?;
+        Some((pods, events))
+    }
+
+    /// Claims in every namespace with the PersistentVolumes and StorageClasses they could bind
+    /// through, or `None` when kubectl isn't usable
+    pub async fn get_volume_binding_state(&self) -> Option<VolumeBindingState> {
+        let run = |args: &[&str]| {
+            Command::new("kubectl")
+                .arg("get")
+                .args(args)
+                .args(["-o", "json"])
+                .pooled_output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        };
+
+        Some(VolumeBindingState {
+            claims: parse_pvcs_json(&run(&["pvc", "--all-namespaces"])?).ok()?,
+            volumes: parse_pvs_json(&run(&["pv"])?).ok()?,
+            storage_classes: parse_storage_classes_json(&run(&["storageclass"])?).ok()?,
+        })
+    }
+}
+
+fn json_str(value: &Value, pointer: &str) -> Option<String> {
+    value.pointer(pointer).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Parse `kubectl get pods -o json` into `PodInfo` entries
+pub fn parse_pods_json(json: &str) -> Result<Vec<PodInfo>, serde_json::Error> {
+    let list: Value = serde_json::from_str(json)?;
+    let items = list["items"].as_array().cloned().unwrap_or_default();
+
+    Ok(items
+        .iter()
+        .map(|item| {
+            let statuses = item
+                .pointer("/status/containerStatuses")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            let containers = item
+                .pointer("/spec/containers")
+                .and_then(Value::as_array)
+                .map_or(statuses.len(), Vec::len);
+            let ready = statuses.iter().filter(|s| s["ready"].as_bool() == Some(true)).count();
+            let restarts: u64 = statuses.iter().filter_map(|s| s["restartCount"].as_u64()).sum();
+
+            PodInfo {
+                name: json_str(item, "/metadata/name").unwrap_or_default(),
+                namespace: json_str(item, "/metadata/namespace").unwrap_or_else(|| "default".to_string()),
+                status: json_str(item, "/status/phase").unwrap_or_else(|| "Unknown".to_string()),
+                ready: format!("{}/{}", ready, containers),
+                restarts: restarts.to_string(),
+                age: json_str(item, "/metadata/creationTimestamp").unwrap_or_default(),
+                ip: json_str(item, "/status/podIP"),
+                node: json_str(item, "/spec/nodeName"),
+            }
+        })
+        .collect())
+}
+
+/// Parse `kubectl get events -o json` into `EventInfo` entries.
+/// `object` uses kubectl's `kind/name` form, e.g. `pod/web-7d4b9`.
+pub fn parse_events_json(json: &str) -> Result<Vec<EventInfo>, serde_json::Error> {
+    let list: Value = serde_json::from_str(json)?;
+    let items = list["items"].as_array().cloned().unwrap_or_default();
+
+    Ok(items
+        .iter()
+        .map(|item| {
+            let kind = json_str(item, "/involvedObject/kind").unwrap_or_default();
+            let name = json_str(item, "/involvedObject/name").unwrap_or_default();
+
+            EventInfo {
+                last_seen: json_str(item, "/lastTimestamp")
+                    .or_else(|| json_str(item, "/eventTime"))
+                    .or_else(|| json_str(item, "/metadata/creationTimestamp"))
+                    .unwrap_or_default(),
+                type_: json_str(item, "/type").unwrap_or_default(),
+                reason: json_str(item, "/reason").unwrap_or_default(),
+                object: format!("{}/{}", kind.to_lowercase(), name),
+                namespace: json_str(item, "/involvedObject/namespace")
+                    .or_else(|| json_str(item, "/metadata/namespace"))
+                    .unwrap_or_default(),
+                message: json_str(item, "/message").unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+fn json_strings(value: &Value, pointer: &str) -> Vec<String> {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn json_items(json: &str) -> Result<Vec<Value>, serde_json::Error> {
+    let list: Value = serde_json::from_str(json)?;
+    Ok(list["items"].as_array().cloned().unwrap_or_default())
+}
+
+/// Parse `kubectl get pvc -o json` into `PersistentVolumeClaimInfo` entries
+pub fn parse_pvcs_json(json: &str) -> Result<Vec<PersistentVolumeClaimInfo>, serde_json::Error> {
+    Ok(json_items(json)?
+        .iter()
+        .map(|item| PersistentVolumeClaimInfo {
+            name: json_str(item, "/metadata/name").unwrap_or_default(),
+            namespace: json_str(item, "/metadata/namespace").unwrap_or_else(|| "default".to_string()),
+            phase: json_str(item, "/status/phase").unwrap_or_else(|| "Pending".to_string()),
+            volume: json_str(item, "/spec/volumeName").filter(|volume| !volume.is_empty()),
+            storage_class: json_str(item, "/spec/storageClassName"),
+            requested: json_str(item, "/spec/resources/requests/storage"),
+            access_modes: json_strings(item, "/spec/accessModes"),
+        })
+        .collect())
+}
+
+/// Parse `kubectl get pv -o json` into `PersistentVolumeInfo` entries
+pub fn parse_pvs_json(json: &str) -> Result<Vec<PersistentVolumeInfo>, serde_json::Error> {
+    Ok(json_items(json)?
+        .iter()
+        .map(|item| PersistentVolumeInfo {
+            name: json_str(item, "/metadata/name").unwrap_or_default(),
+            phase: json_str(item, "/status/phase").unwrap_or_else(|| "Unknown".to_string()),
+            storage_class: json_str(item, "/spec/storageClassName").filter(|class| !class.is_empty()),
+            capacity: json_str(item, "/spec/capacity/storage"),
+            access_modes: json_strings(item, "/spec/accessModes"),
+            claim: json_str(item, "/spec/claimRef/name").map(|name| {
+                format!("{}/{}", json_str(item, "/spec/claimRef/namespace").unwrap_or_default(), name)
+            }),
+        })
+        .collect())
+}
+
+/// Parse `kubectl get storageclass -o json` into `StorageClassInfo` entries
+pub fn parse_storage_classes_json(json: &str) -> Result<Vec<StorageClassInfo>, serde_json::Error> {
+    Ok(json_items(json)?
+        .iter()
+        .map(|item| StorageClassInfo {
+            name: json_str(item, "/metadata/name").unwrap_or_default(),
+            provisioner: json_str(item, "/provisioner").unwrap_or_default(),
+            binding_mode: json_str(item, "/volumeBindingMode").unwrap_or_else(|| "Immediate".to_string()),
+            is_default: json_str(item, "/metadata/annotations/storageclass.kubernetes.io~1is-default-class")
+                .is_some_and(|value| value == "true"),
+        })
+        .collect())
+}
+
+/// Extract top-level sections (e.g. "Conditions:") from `kubectl describe` output.
+/// A section runs until the next unindented line.
+pub fn extract_describe_sections(output: &str, sections: &[&str]) -> String {
+    let mut extracted = String::new();
+    let mut in_section = false;
+
+    for line in output.lines() {
+        let is_top_level = !line.is_empty() && !line.starts_with(char::is_whitespace);
+        if is_top_level {
+            in_section = sections.iter().any(|section| {
+                line.strip_prefix(section)
+                    .is_some_and(|rest| rest.starts_with(':'))
+            });
+        }
+        if in_section {
+            extracted.push_str(line);
+            extracted.push('\n');
+        }
+    }
+
+    extracted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIBE_NODE: &str = "Name:               worker-1
+Roles:              <none>
+Conditions:
+  Type             Status  LastHeartbeatTime                 Reason                       Message
+  ----             ------  -----------------                 ------                       -------
+  MemoryPressure   True    Mon, 01 Jan 2024 00:00:00 +0000   KubeletHasInsufficientMemory kubelet has insufficient memory available
+  DiskPressure     False   Mon, 01 Jan 2024 00:00:00 +0000   KubeletHasNoDiskPressure     kubelet has no disk pressure
+  Ready            True    Mon, 01 Jan 2024 00:00:00 +0000   KubeletReady                 kubelet is posting ready status
+Addresses:
+  InternalIP:  10.0.0.5
+Capacity:
+  cpu:                4
+  memory:             8148012Ki
+Allocatable:
+  cpu:                3800m
+  memory:             7533612Ki
+  pods:               110
+System Info:
+  Kernel Version:     6.1.0
+";
+
+    #[test]
+    fn test_extract_describe_node_sections() {
+        let sections = extract_describe_sections(DESCRIBE_NODE, &["Conditions", "Allocatable"]);
+
+        assert!(sections.starts_with("Conditions:\n"));
+        assert!(sections.contains("MemoryPressure   True"));
+        assert!(sections.contains("Allocatable:\n  cpu:                3800m"));
+        assert!(!sections.contains("Capacity:"));
+        assert!(!sections.contains("InternalIP"));
+        assert!(!sections.contains("Kernel Version"));
+    }
+
+    const PODS_JSON: &str = r#"{"items": [
+        {"metadata": {"name": "web-1", "namespace": "shop", "creationTimestamp": "2024-01-01T00:00:00Z"},
+         "spec": {"nodeName": "worker-1", "containers": [{"name": "web"}, {"name": "proxy"}]},
+         "status": {"phase": "Running", "podIP": "10.1.0.4",
+                    "containerStatuses": [{"ready": true, "restartCount": 2}, {"ready": false, "restartCount": 1}]}},
+        {"metadata": {"name": "batch-1", "namespace": "jobs"},
+         "spec": {"containers": [{"name": "batch"}]},
+         "status": {"phase": "Pending"}}
+    ]}"#;
+
+    const EVENTS_JSON: &str = r#"{"items": [
+        {"metadata": {"namespace": "jobs"}, "type": "Warning", "reason": "FailedScheduling",
+         "lastTimestamp": "2024-01-01T00:05:00Z",
+         "involvedObject": {"kind": "Pod", "name": "batch-1", "namespace": "jobs"},
+         "message": "0/3 nodes are available: 3 Insufficient cpu."},
+        {"metadata": {"namespace": "shop", "creationTimestamp": "2024-01-01T00:01:00Z"}, "type": "Normal",
+         "reason": "Pulled", "involvedObject": {"kind": "Pod", "name": "web-1"}, "message": "Image pulled"}
+    ]}"#;
+
+    #[test]
+    fn test_parse_pods_json() {
+        let pods = parse_pods_json(PODS_JSON).unwrap();
+        assert_eq!(pods.len(), 2);
+
+        assert_eq!(pods[0].name, "web-1");
+        assert_eq!(pods[0].namespace, "shop");
+        assert_eq!(pods[0].status, "Running");
+        assert_eq!(pods[0].ready, "1/2");
+        assert_eq!(pods[0].restarts, "3");
+        assert_eq!(pods[0].node.as_deref(), Some("worker-1"));
+        assert_eq!(pods[0].ip.as_deref(), Some("10.1.0.4"));
+
+        assert_eq!(pods[1].status, "Pending");
+        assert_eq!(pods[1].ready, "0/1");
+        assert!(pods[1].node.is_none());
+    }
+
+    #[test]
+    fn test_parse_events_json() {
+        let events = parse_events_json(EVENTS_JSON).unwrap();
+        assert_eq!(events.len(), 2);
+
+        assert_eq!(events[0].reason, "FailedScheduling");
+        assert_eq!(events[0].object, "pod/batch-1");
+        assert_eq!(events[0].namespace, "jobs");
+        assert_eq!(events[0].last_seen, "2024-01-01T00:05:00Z");
+
+        // Falls back to the event's own metadata when involvedObject lacks them
+        assert_eq!(events[1].namespace, "shop");
+        assert_eq!(events[1].last_seen, "2024-01-01T00:01:00Z");
+    }
+
+    #[test]
+    fn test_parse_invalid_json() {
+        assert!(parse_pods_json("not json").is_err());
+        assert!(parse_events_json("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_extract_describe_sections_missing() {
+        assert!(extract_describe_sections("Name: worker-1\n", &["Conditions"]).is_empty());
+    }
 }
@@ -16,7 +16,7 @@ impl DebugTools {
             command.arg("--all-namespaces");
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -67,7 +67,7 @@ impl DebugTools {
             command.arg("--all-namespaces");
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -120,7 +120,7 @@ impl DebugTools {
             command.args(["--tail", &n.to_string()]);
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -173,7 +173,7 @@ impl DebugTools {
             command.arg("--all-namespaces");
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -218,7 +218,7 @@ impl DebugTools {
         let mut command = Command::new("kubectl");
         command.args(["top", "nodes"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -257,7 +257,7 @@ impl DebugTools {
         let mut command = Command::new("kubectl");
         command.args(["cluster-info"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -296,7 +296,7 @@ impl DebugTools {
         let mut command = Command::new("kubectl");
         command.args(["get", "pv", "-o", "wide"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -341,7 +341,7 @@ impl DebugTools {
             command.arg("--all-namespaces");
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -388,7 +388,7 @@ impl DebugTools {
         let mut command = Command::new("systemctl");
         command.args(["status", "kubelet", "--no-pager"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -433,7 +433,7 @@ impl DebugTools {
             command.args(["-n", "100"]);
         }
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -522,7 +522,7 @@ impl DebugTools {
         let mut command = Command::new("etcdctl");
         command.args(["cluster-health"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -561,7 +561,7 @@ impl DebugTools {
         let mut command = Command::new("etcdctl");
         command.args(["member", "list"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -600,7 +600,7 @@ impl DebugTools {
         let mut command = Command::new("etcdctl");
         command.args(["endpoint", "health", "--cluster"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -639,7 +639,7 @@ impl DebugTools {
         let mut command = Command::new("etcdctl");
         command.args(["endpoint", "status", "--cluster", "-w", "table"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
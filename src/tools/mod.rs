@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::process::Command;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Output, Stdio};
+use pool::PooledOutput;
+use crate::sysinfo::HostPaths;
 
 // Re-export all tool modules
 pub mod arch_debug;
@@ -11,6 +16,7 @@ pub mod kubectl;
 pub mod kubernetes_debug;
 pub mod network_debug;
 pub mod performance_debug;
+pub mod pool;
 pub mod process_debug;
 pub mod security_debug;
 pub mod storage_debug;
@@ -21,11 +27,7 @@ pub mod systemctl;
 pub trait ToolAvailability {
     fn check_tool_availability(&self, tool_name: &str) -> bool {
         // Default implementation: check if command exists in PATH
-        Command::new("which")
-            .arg(tool_name)
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+        find_in_path(tool_name).is_some()
     }
 
     fn check_file_exists(&self, path: &str) -> bool {
@@ -35,8 +37,74 @@ pub trait ToolAvailability {
     fn get_available_tools(&self) -> Vec<String>;
 }
 
+/// Find a command in this process's `PATH` without running `which`, which busybox and
+/// distroless images often lack. Only with no `PATH` at all is `which` asked, for its
+/// built-in default search path.
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    match std::env::var_os("PATH") {
+        Some(path) => find_executable(name, &path),
+        None => Command::new("which")
+            .arg(name)
+            .pooled_output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim())),
+    }
+}
+
+/// The first executable file called `name` in the directories of a `PATH`-style list.
+/// A name containing `/` is checked as given.
+pub fn find_executable(name: &str, path_var: &OsStr) -> Option<PathBuf> {
+    if name.contains('/') {
+        let path = Path::new(name);
+        return is_executable(path).then(|| path.to_path_buf());
+    }
+    std::env::split_paths(path_var)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(|dir| dir.join(name))
+        .find(|candidate| is_executable(candidate))
+}
+
+/// Run a command like `Command::output`, but copy each stdout line to `out` as soon as it
+/// arrives. The full stdout and stderr are still returned, so the result is the same as
+/// the buffered run.
+pub fn run_streamed<W: Write>(command: &mut Command, out: &mut W) -> std::io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drained on its own thread so a chatty stderr can't fill its pipe and stall the child
+    let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let stderr_reader = std::thread::spawn(move || {
+        let mut stderr = Vec::new();
+        stderr_pipe.read_to_end(&mut stderr).map(|_| stderr)
+    });
+
+    let mut stdout = Vec::new();
+    let mut reader = BufReader::new(child.stdout.take().expect("stdout is piped"));
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        out.write_all(&line)?;
+        out.flush()?;
+        stdout.append(&mut line);
+    }
+
+    let status = child.wait()?;
+    let stderr = stderr_reader.join().unwrap_or_else(|_| Ok(Vec::new()))?;
+    Ok(Output { status, stdout, stderr })
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
 // Tool category enumeration
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, clap::ValueEnum)]
 pub enum ToolCategory {
     SystemInfo,
     NetworkDebug,
@@ -52,6 +120,24 @@ pub enum ToolCategory {
     Systemctl,
 }
 
+impl ToolCategory {
+    /// Every category, in the order availability is checked and listed
+    pub const ALL: [ToolCategory; 12] = [
+        ToolCategory::SystemInfo,
+        ToolCategory::NetworkDebug,
+        ToolCategory::ProcessDebug,
+        ToolCategory::StorageDebug,
+        ToolCategory::PerformanceDebug,
+        ToolCategory::SecurityDebug,
+        ToolCategory::ContainerInfo,
+        ToolCategory::Kubernetes,
+        ToolCategory::ArchLinux,
+        ToolCategory::EbpfDebug,
+        ToolCategory::Journalctl,
+        ToolCategory::Systemctl,
+    ];
+}
+
 // Available tool information
 #[derive(Debug, Clone)]
 pub struct AvailableToolInfo {
@@ -72,6 +158,227 @@ pub struct DebugToolResult {
     pub execution_time_ms: u64,
 }
 
+/// Pause before retry number `attempt` (1-based) of a failed tool: 500 ms, 1 s, 1.5 s, ...
+fn retry_backoff(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(500 * u64::from(attempt))
+}
+
+/// Error text that marks a failure as transient
+const TRANSIENT_FAILURES: [&str; 6] = [
+    "timed out",
+    "timeout",
+    "connection refused",
+    "connection reset",
+    "temporary failure in name resolution",
+    "resource temporarily unavailable",
+];
+
+/// Whether running a failed tool again could help. Only failures that name a transient
+/// condition qualify; bad arguments, parse errors and a tool reporting a real problem fail
+/// the same way every time.
+pub fn is_retryable_failure(result: &DebugToolResult) -> bool {
+    if result.success {
+        return false;
+    }
+    let error = result.error.as_deref().unwrap_or_default().to_lowercase();
+    TRANSIENT_FAILURES.iter().any(|marker| error.contains(marker))
+}
+
+/// Run a tool, retrying transient failures up to `retries` times (`tools.retries`) with a
+/// short backoff. `run` gets the 1-based attempt number, so a retry can leave out what only
+/// the first attempt should do, like streaming output.
+pub async fn run_with_retries(retries: u32, mut run: impl AsyncFnMut(u32) -> DebugToolResult) -> DebugToolResult {
+    let mut attempts = 0;
+    let mut result = loop {
+        attempts += 1;
+        let result = run(attempts).await;
+        if attempts > retries || !is_retryable_failure(&result) {
+            break result;
+        }
+        tokio::time::sleep(retry_backoff(attempts)).await;
+    };
+    note_attempts(&mut result, attempts);
+    result
+}
+
+/// Record on the final result that it took more than one attempt
+fn note_attempts(result: &mut DebugToolResult, attempts: u32) {
+    if attempts <= 1 {
+        return;
+    }
+    if result.success {
+        result.output.push_str(&format!("\n(succeeded on attempt {})\n", attempts));
+    } else {
+        let error = result.error.take().unwrap_or_default();
+        result.error = Some(format!("{} (failed {} attempts)", error.trim_end(), attempts));
+    }
+}
+
+/// Result of a tool that runs several other tools (e.g. the network health check)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompositeToolResult {
+    pub tool_name: String,
+    pub results: Vec<DebugToolResult>,
+    pub success: bool,
+    pub execution_time_ms: u64,
+}
+
+impl CompositeToolResult {
+    /// Build a composite result; it succeeds if any sub-tool succeeded
+    pub fn new(tool_name: &str, results: Vec<DebugToolResult>, execution_time_ms: u64) -> Self {
+        Self {
+            tool_name: tool_name.to_string(),
+            success: results.iter().any(|r| r.success),
+            results,
+            execution_time_ms,
+        }
+    }
+
+    /// All commands that were run, in order
+    pub fn command(&self) -> String {
+        self.results
+            .iter()
+            .map(|r| r.command.clone())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Render every sub-result as a section, for both CLI output and AI context
+    pub fn render(&self) -> String {
+        self.results
+            .iter()
+            .map(|r| {
+                let mut section = format!("=== {} ===\nCommand: {}\n{}", r.tool_name, r.command, r.output);
+                if let Some(error) = &r.error {
+                    section.push_str(&format!("\nError: {}", error));
+                }
+                section
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Flatten into a single result for code paths that handle one tool at a time
+    pub fn to_debug_tool_result(&self) -> DebugToolResult {
+        let failed = self.results.iter().filter(|r| !r.success).count();
+        DebugToolResult {
+            tool_name: self.tool_name.clone(),
+            command: self.command(),
+            success: self.success,
+            output: self.render(),
+            error: if failed == 0 {
+                None
+            } else {
+                Some(format!("{} of {} checks failed", failed, self.results.len()))
+            },
+            execution_time_ms: self.execution_time_ms,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub user: String,
+    pub cpu_percent: f32,
+    pub mem_percent: f32,
+    pub rss_kb: u64,
+    pub command: String,
+}
+
+/// A recent SELinux AVC or AppArmor denial
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SecurityDenial {
+    /// "SELinux" or "AppArmor"
+    pub module: String,
+    /// Denied permission(s) or AppArmor operation, e.g. "read" or "open"
+    pub operation: String,
+    /// `comm` of the denied process
+    pub process: Option<String>,
+    /// File or object name the process tried to access
+    pub target: Option<String>,
+    /// AppArmor profile or SELinux source context
+    pub profile: Option<String>,
+}
+
+/// Failed SSH logins from one source address
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FailedLoginSource {
+    pub address: String,
+    pub attempts: usize,
+    /// Distinct usernames tried, in first-seen order
+    pub users: Vec<String>,
+}
+
+/// Clock synchronization state from timedatectl, refined by chrony or ntpd when available
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TimeSyncStatus {
+    /// "System clock synchronized" from timedatectl
+    pub synchronized: Option<bool>,
+    /// Whether an NTP service (systemd-timesyncd, chronyd, ntpd) is active
+    pub ntp_service_active: Option<bool>,
+    /// Offset from the NTP reference in milliseconds (positive = local clock ahead)
+    pub offset_ms: Option<f64>,
+    /// Where the offset came from: "chronyc" or "ntpq"
+    pub offset_source: Option<String>,
+}
+
+/// Whether a name resolved through each path RAID can test, so a dead resolver can be told
+/// apart from a single broken lookup. `None` means the path could not be tried (tool missing)
+/// or was skipped because an earlier path already resolved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DnsResolutionStatus {
+    /// The name that was looked up
+    pub name: String,
+    /// `getent hosts`: the system resolver, as applications use it
+    pub system_resolver: Option<bool>,
+    /// `dig` against public DNS servers (dns_test)
+    pub public_servers: Option<bool>,
+    /// Pinging hosts by name (connectivity_test)
+    pub ping_by_name: Option<bool>,
+}
+
+impl DnsResolutionStatus {
+    /// At least one path was tried and none of them resolved anything
+    pub fn is_failing(&self) -> bool {
+        let paths = [self.system_resolver, self.public_servers, self.ping_by_name];
+        paths.contains(&Some(false)) && !paths.contains(&Some(true))
+    }
+}
+
+/// A unit's state from `systemctl show`, so crash loops and non-zero exits can be detected
+/// without reading `systemctl status` prose
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ServiceStatusDetail {
+    pub unit: String,
+    /// `active`, `failed`, `activating`...; empty when systemctl could not be run
+    pub active_state: String,
+    /// e.g. `running`, `dead`, `auto-restart`
+    pub sub_state: String,
+    /// `success`, or why the last run ended: `exit-code`, `signal`, `core-dump`, `timeout`, `oom-kill`...
+    pub result: String,
+    /// Exit status of the main process (the signal number when it was killed by one)
+    pub exec_main_status: Option<i32>,
+    /// `None` when no main process is running
+    pub main_pid: Option<u32>,
+    /// Automatic restarts since the unit was last started by hand (systemd 235+)
+    pub n_restarts: Option<u32>,
+}
+
+/// Open file descriptors and threads of a process against its soft limits (from /proc)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessFdUsage {
+    pub pid: u32,
+    pub command: String,
+    /// `None` when /proc/<pid>/fd is not readable (other users' processes need root)
+    pub open_fds: Option<usize>,
+    /// `NOFILE` soft limit; `None` when unlimited
+    pub fd_soft_limit: Option<u64>,
+    pub threads: Option<usize>,
+    /// `NPROC` soft limit (per user, counts threads); `None` when unlimited
+    pub process_soft_limit: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct KubernetesDebugInfo {
     pub pods: Vec<PodInfo>,
@@ -81,6 +388,52 @@ pub struct KubernetesDebugInfo {
     pub namespace_info: Option<NamespaceInfo>,
 }
 
+/// A PersistentVolumeClaim from `kubectl get pvc -o json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistentVolumeClaimInfo {
+    pub name: String,
+    pub namespace: String,
+    /// Pending, Bound or Lost
+    pub phase: String,
+    /// The PersistentVolume it is bound to
+    pub volume: Option<String>,
+    /// `None` when unset; `Some("")` asks for a PV without a class (static binding only)
+    pub storage_class: Option<String>,
+    pub requested: Option<String>,
+    pub access_modes: Vec<String>,
+}
+
+/// A PersistentVolume from `kubectl get pv -o json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistentVolumeInfo {
+    pub name: String,
+    /// Available, Bound, Released or Failed
+    pub phase: String,
+    pub storage_class: Option<String>,
+    pub capacity: Option<String>,
+    pub access_modes: Vec<String>,
+    /// `namespace/name` of the claim it is bound or reserved for
+    pub claim: Option<String>,
+}
+
+/// A StorageClass from `kubectl get storageclass -o json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageClassInfo {
+    pub name: String,
+    pub provisioner: String,
+    /// Immediate or WaitForFirstConsumer
+    pub binding_mode: String,
+    pub is_default: bool,
+}
+
+/// Everything needed to explain why a claim isn't bound
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VolumeBindingState {
+    pub claims: Vec<PersistentVolumeClaimInfo>,
+    pub volumes: Vec<PersistentVolumeInfo>,
+    pub storage_classes: Vec<StorageClassInfo>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PodInfo {
     pub name: String,
@@ -121,6 +474,8 @@ pub struct EventInfo {
     pub type_: String,
     pub reason: String,
     pub object: String,
+    #[serde(default)]
+    pub namespace: String,
     pub message: String,
 }
 
@@ -153,6 +508,10 @@ pub struct DebugTools {
     pub kubernetes_enabled: bool,
     pub kubectl_path: Option<String>,
     pub available_tools: HashMap<ToolCategory, AvailableToolInfo>,
+    /// procfs/sysfs roots read by the cgroup, namespace and interface tools
+    pub host_paths: HostPaths,
+    /// Print each tool's stdout as it arrives (`debug --stream`) while still capturing it
+    pub stream_output: bool,
 }
 
 impl DebugTools {
@@ -164,6 +523,31 @@ impl DebugTools {
             kubernetes_enabled,
             kubectl_path,
             available_tools: HashMap::new(),
+            host_paths: HostPaths::default(),
+            stream_output: false,
+        }
+    }
+
+    /// Read procfs and sysfs from these roots instead of `/proc` and `/sys`
+    pub fn with_host_paths(mut self, host_paths: HostPaths) -> Self {
+        self.host_paths = host_paths;
+        self
+    }
+
+    /// Print tool output line by line as it arrives instead of only returning it
+    pub fn with_streaming(mut self, stream_output: bool) -> Self {
+        self.stream_output = stream_output;
+        self
+    }
+
+    /// Run a tool's command once the shared pool has a free slot, streamed to stdout when
+    /// `stream_output` is set
+    pub fn command_output(&self, command: &mut Command) -> std::io::Result<Output> {
+        let _permit = pool::acquire();
+        if self.stream_output {
+            run_streamed(command, &mut std::io::stdout())
+        } else {
+            command.output()
         }
     }
 
@@ -176,22 +560,7 @@ impl DebugTools {
 
     /// Check availability of all tool categories
     pub fn check_all_tool_availability(&mut self) {
-        let categories = [
-            ToolCategory::SystemInfo,
-            ToolCategory::NetworkDebug,
-            ToolCategory::ProcessDebug,
-            ToolCategory::StorageDebug,
-            ToolCategory::PerformanceDebug,
-            ToolCategory::SecurityDebug,
-            ToolCategory::ContainerInfo,
-            ToolCategory::Kubernetes,
-            ToolCategory::ArchLinux,
-            ToolCategory::EbpfDebug,
-            ToolCategory::Journalctl,
-            ToolCategory::Systemctl,
-        ];
-
-        for category in &categories {
+        for category in &ToolCategory::ALL {
             let available_info = self.check_category_availability(category.clone());
             self.available_tools.insert(category.clone(), available_info);
         }
@@ -242,12 +611,7 @@ impl DebugTools {
 
     fn find_kubectl() -> Option<String> {
         // Check if kubectl is available in PATH
-        if let Ok(output) = std::process::Command::new("which").arg("kubectl").output() {
-            if output.status.success() {
-                return Some(String::from_utf8_lossy(&output.stdout).trim().to_string());
-            }
-        }
-        None
+        find_in_path("kubectl").map(|path| path.display().to_string())
     }
 
     // Tool availability checking methods for each category
@@ -286,7 +650,7 @@ impl DebugTools {
         }
 
         // Check for optional network tools
-        let optional_tools = ["iwconfig", "netstat", "iperf3"];
+        let optional_tools = ["iwconfig", "netstat", "iperf3", "getent", "resolvectl"];
         for tool in &optional_tools {
             if self.check_tool_availability(tool) {
                 available_tools.push(tool.to_string());
@@ -555,6 +919,81 @@ impl ToolAvailability for DebugTools {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_find_executable_scans_path() {
+        let with = tempfile::tempdir().unwrap();
+        let without = tempfile::tempdir().unwrap();
+        let binary = with.path().join("kubectl");
+        std::fs::write(&binary, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        // Present but not executable, like a stray config file
+        std::fs::write(without.path().join("ss"), "").unwrap();
+
+        let path = std::env::join_paths([without.path(), with.path()]).unwrap();
+        assert_eq!(find_executable("kubectl", &path), Some(binary.clone()));
+        assert_eq!(find_executable("ss", &path), None);
+        assert_eq!(find_executable("kubectl", &std::env::join_paths([without.path()]).unwrap()), None);
+        assert_eq!(find_executable("kubectl", OsStr::new("")), None);
+        assert_eq!(find_executable(binary.to_str().unwrap(), OsStr::new("")), Some(binary));
+    }
+
+    fn failed(error: &str) -> DebugToolResult {
+        DebugToolResult {
+            tool_name: "kubectl_get_pods".to_string(),
+            command: "kubectl get pods".to_string(),
+            success: false,
+            output: String::new(),
+            error: Some(error.to_string()),
+            execution_time_ms: 5,
+        }
+    }
+
+    #[test]
+    fn test_run_streamed_captures_output() {
+        let mut streamed = Vec::new();
+        let output = run_streamed(
+            Command::new("sh").args(["-c", "echo first; echo oops >&2; printf last; exit 3"]),
+            &mut streamed,
+        )
+        .unwrap();
+        assert_eq!(streamed, b"first\nlast");
+        assert_eq!(output.stdout, b"first\nlast");
+        assert_eq!(output.stderr, b"oops\n");
+        assert_eq!(output.status.code(), Some(3));
+    }
+
+    #[test]
+    fn test_retryable_failures() {
+        assert!(is_retryable_failure(&failed("Unable to connect to the server: net/http: TLS handshake timeout")));
+        assert!(!is_retryable_failure(&failed("No such file or directory (os error 2)")));
+        assert!(!is_retryable_failure(&failed("Pod name required")));
+        assert!(is_retryable_failure(&failed("dial tcp 10.0.0.1:6443: connect: Connection refused")));
+        assert!(!is_retryable_failure(&failed("error: unknown flag: --bogus")));
+        assert!(!is_retryable_failure(&failed("Command failed with exit code 3")));
+        let mut ok = failed("");
+        ok.success = true;
+        assert!(!is_retryable_failure(&ok));
+
+        assert_eq!(retry_backoff(1), std::time::Duration::from_millis(500));
+        assert_eq!(retry_backoff(3), std::time::Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_note_attempts() {
+        let mut result = failed("i/o timeout\n");
+        note_attempts(&mut result, 1);
+        assert_eq!(result.error.as_deref(), Some("i/o timeout\n"));
+        note_attempts(&mut result, 3);
+        assert_eq!(result.error.as_deref(), Some("i/o timeout (failed 3 attempts)"));
+
+        let mut result = failed("");
+        result.success = true;
+        result.output = "NAME READY".to_string();
+        note_attempts(&mut result, 2);
+        assert_eq!(result.output, "NAME READY\n(succeeded on attempt 2)\n");
+    }
 
     #[test]
     fn test_tool_availability_trait() {
@@ -813,4 +1252,46 @@ mod tests {
             );
         }
     }
+
+    fn sample_result(tool_name: &str, success: bool) -> DebugToolResult {
+        DebugToolResult {
+            tool_name: tool_name.to_string(),
+            command: format!("{} --test", tool_name),
+            success,
+            output: format!("{} output", tool_name),
+            error: if success { None } else { Some("not found".to_string()) },
+            execution_time_ms: 5,
+        }
+    }
+
+    #[test]
+    fn test_composite_tool_result() {
+        let composite = CompositeToolResult::new(
+            "health_check",
+            vec![sample_result("ip", true), sample_result("ufw", false)],
+            12,
+        );
+
+        assert!(composite.success);
+        assert_eq!(composite.command(), "ip --test; ufw --test");
+
+        let rendered = composite.render();
+        assert!(rendered.contains("=== ip ===\nCommand: ip --test\nip output"));
+        assert!(rendered.contains("=== ufw ==="));
+        assert!(rendered.contains("Error: not found"));
+
+        let flattened = composite.to_debug_tool_result();
+        assert_eq!(flattened.tool_name, "health_check");
+        assert_eq!(flattened.command, "ip --test; ufw --test");
+        assert_eq!(flattened.output, rendered);
+        assert_eq!(flattened.error, Some("1 of 2 checks failed".to_string()));
+        assert_eq!(flattened.execution_time_ms, 12);
+    }
+
+    #[test]
+    fn test_composite_tool_result_all_failed() {
+        let composite = CompositeToolResult::new("health_check", vec![sample_result("ufw", false)], 1);
+        assert!(!composite.success);
+        assert!(!composite.to_debug_tool_result().success);
+    }
 }
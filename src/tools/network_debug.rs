@@ -1,13 +1,17 @@
-use super::{DebugToolResult, DebugTools};
+use super::{CompositeToolResult, DebugToolResult, DebugTools, DnsResolutionStatus};
+use super::pool::PooledOutput;
 use std::process::Command;
 
+/// Number of tools run by the network health check
+pub const NETWORK_HEALTH_CHECK_STEPS: u64 = 11;
+
 impl DebugTools {
     pub async fn run_ip_addr(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("ip");
         command.args(["addr", "show"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -45,7 +49,7 @@ impl DebugTools {
         let mut command = Command::new("ip");
         command.args(["route", "show"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -83,7 +87,7 @@ impl DebugTools {
         let mut command = Command::new("ss");
         command.args(["-tuln"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -121,7 +125,7 @@ impl DebugTools {
         let mut command = Command::new("ping");
         command.args(["-c", "3", host]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -159,7 +163,7 @@ impl DebugTools {
         let mut command = Command::new("traceroute");
         command.args([host]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -197,7 +201,7 @@ impl DebugTools {
         let mut command = Command::new("dig");
         command.args([domain]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -235,7 +239,7 @@ impl DebugTools {
         let mut command = Command::new("iptables");
         command.args(["-L", "-n", "-v"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -273,7 +277,7 @@ impl DebugTools {
         let mut command = Command::new("ethtool");
         command.args([interface]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -306,13 +310,89 @@ impl DebugTools {
         }
     }
 
+    /// The interface that owns the default route, e.g. `enp3s0` or `ens5` on distros with
+    /// predictable names. Read from `ip route show default`, or `<procfs>/net/route` when
+    /// iproute2 is missing.
+    pub fn primary_interface(&self) -> Option<String> {
+        Command::new("ip")
+            .args(["route", "show", "default"])
+            .pooled_output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| default_route_interface(&String::from_utf8_lossy(&output.stdout)))
+            .or_else(|| {
+                std::fs::read_to_string(self.host_paths.proc("net/route"))
+                    .ok()
+                    .and_then(|content| proc_route_default_interface(&content))
+            })
+    }
+
+    /// The interface a tool was asked about, else the primary one; `eth0` only when there
+    /// is no default route to go by
+    pub fn interface_or_primary(&self, interface: Option<&str>) -> String {
+        interface
+            .map(str::to_string)
+            .or_else(|| self.primary_interface())
+            .unwrap_or_else(|| "eth0".to_string())
+    }
+
+    /// Driver-level NIC statistics from `ethtool -S`, where CRC errors, ring buffer overruns
+    /// and missed packets show up long before /proc/net/dev explains them. Non-zero error
+    /// counters are repeated at the end of the output.
+    pub async fn run_ethtool_stats(&self, interface: &str) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let result = self.command_output(Command::new("ethtool").args(["-S", interface]));
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let command = format!("ethtool -S {}", interface);
+
+        match result {
+            Ok(output) => {
+                let success = output.status.success();
+                let mut output_str = String::from_utf8_lossy(&output.stdout).to_string();
+                let error_str = if success {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(&output.stderr).to_string())
+                };
+                if success {
+                    let notable = notable_ethtool_counters(&output_str);
+                    if notable.is_empty() {
+                        output_str.push_str("\nNo error or drop counters are non-zero\n");
+                    } else {
+                        output_str.push_str("\nNon-zero error and drop counters:\n");
+                        for (name, value) in notable {
+                            output_str.push_str(&format!("  {}: {}\n", name, value));
+                        }
+                    }
+                }
+
+                DebugToolResult {
+                    tool_name: "ethtool_stats".to_string(),
+                    command,
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "ethtool_stats".to_string(),
+                command,
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
     // Enhanced networking tools
     pub async fn run_arp_table(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("ip");
         command.args(["neigh", "show"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -347,10 +427,11 @@ impl DebugTools {
 
     pub async fn run_interface_stats(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
+        let path = self.host_paths.proc("net/dev");
         let mut command = Command::new("cat");
-        command.args(["/proc/net/dev"]);
+        command.arg(&path);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -365,7 +446,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "interface_stats".to_string(),
-                    command: "cat /proc/net/dev".to_string(),
+                    command: format!("cat {}", path.display()),
                     success,
                     output: output_str,
                     error: error_str,
@@ -374,7 +455,7 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "interface_stats".to_string(),
-                command: "cat /proc/net/dev".to_string(),
+                command: format!("cat {}", path.display()),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
@@ -388,7 +469,7 @@ impl DebugTools {
         let mut command = Command::new("iperf3");
         command.args(["--version"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -431,7 +512,7 @@ impl DebugTools {
         let mut command = Command::new("ip");
         command.args(["netns", "list"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -464,19 +545,33 @@ impl DebugTools {
         }
     }
 
-    pub async fn run_tcpdump_sample(&self, interface: Option<&str>) -> DebugToolResult {
+    /// Capture a packet sample: 10 packets by default, or everything seen for `duration_secs`
+    pub async fn run_tcpdump_sample(&self, interface: Option<&str>, duration_secs: Option<u64>) -> DebugToolResult {
         let start_time = std::time::Instant::now();
-        let mut command = Command::new("tcpdump");
-        
         let interface_arg = interface.unwrap_or("any");
-        command.args(["-i", interface_arg, "-c", "10", "-n"]);
 
-        let result = command.output();
+        let (mut command, command_str) = match duration_secs {
+            Some(secs) => {
+                // Cap the packet count so a busy interface doesn't flood the output
+                let mut command = Command::new("timeout");
+                command.args([&secs.to_string(), "tcpdump", "-i", interface_arg, "-c", "1000", "-n"]);
+                (command, format!("timeout {} tcpdump -i {} -c 1000 -n", secs, interface_arg))
+            }
+            None => {
+                let mut command = Command::new("tcpdump");
+                command.args(["-i", interface_arg, "-c", "10", "-n"]);
+                (command, format!("tcpdump -i {} -c 10 -n", interface_arg))
+            }
+        };
+
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
             Ok(output) => {
-                let success = output.status.success();
+                // timeout exits with 124 when the capture ran for the full duration
+                let success = output.status.success()
+                    || (duration_secs.is_some() && output.status.code() == Some(124));
                 let output_str = String::from_utf8_lossy(&output.stdout).to_string();
                 let error_str = if success {
                     None
@@ -486,7 +581,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "tcpdump_sample".to_string(),
-                    command: format!("tcpdump -i {} -c 10 -n", interface_arg),
+                    command: command_str,
                     success,
                     output: output_str,
                     error: error_str,
@@ -495,7 +590,7 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "tcpdump_sample".to_string(),
-                command: format!("tcpdump -i {} -c 10 -n", interface_arg),
+                command: command_str,
                 success: false,
                 output: String::new(),
                 error: Some(format!("tcpdump failed: {}. May need root privileges.", e)),
@@ -509,7 +604,7 @@ impl DebugTools {
         let mut command = Command::new("ip");
         command.args(["link", "show", "type", "bridge"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -546,7 +641,7 @@ impl DebugTools {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("iwconfig");
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -584,7 +679,7 @@ impl DebugTools {
         let mut command = Command::new("nft");
         command.args(["list", "ruleset"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -628,7 +723,7 @@ impl DebugTools {
             let mut command = Command::new("dig");
             command.args([format!("@{}", dns_server).as_str(), domain, "+time=2", "+short"]);
             
-            if let Ok(output) = command.output() {
+            if let Ok(output) = command.pooled_output() {
                 let response_time = start_time.elapsed().as_millis();
                 let success = output.status.success();
                 let result_text = if success {
@@ -666,7 +761,7 @@ impl DebugTools {
         let mut command = Command::new("netstat");
         command.args(["-tuln"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -705,7 +800,7 @@ impl DebugTools {
         let mut command = Command::new("ufw");
         command.args(["status", "verbose"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -744,7 +839,7 @@ impl DebugTools {
         let mut command = Command::new("systemctl");
         command.args(["status", "NetworkManager", "--no-pager"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -783,7 +878,7 @@ impl DebugTools {
         let mut command = Command::new("cat");
         command.args(["/etc/resolv.conf"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -816,6 +911,94 @@ impl DebugTools {
         }
     }
 
+    /// Resolve a name through the system resolver stack (nsswitch, /etc/hosts, systemd-resolved)
+    /// with `getent hosts`, which is what applications use, unlike `dig`
+    pub async fn run_getent_hosts(&self, name: &str) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let result = self.command_output(Command::new("getent").args(["hosts", name]));
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let command = format!("getent hosts {}", name);
+
+        // The nsswitch order explains why getent and dig can disagree
+        let nsswitch = std::fs::read_to_string("/etc/nsswitch.conf")
+            .ok()
+            .and_then(|content| nsswitch_hosts_line(&content));
+
+        match result {
+            Ok(output) => {
+                let success = output.status.success();
+                let mut output_str = String::from_utf8_lossy(&output.stdout).to_string();
+                // getent exits 2 with no output when the name does not resolve
+                let error_str = if success {
+                    None
+                } else if output.status.code() == Some(2) {
+                    Some(format!("'{}' did not resolve through the system resolver", name))
+                } else {
+                    Some(String::from_utf8_lossy(&output.stderr).to_string())
+                };
+                if let Some(line) = nsswitch {
+                    output_str.push_str(&format!("\nnsswitch.conf: {}\n", line));
+                }
+
+                DebugToolResult {
+                    tool_name: "getent_hosts".to_string(),
+                    command,
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "getent_hosts".to_string(),
+                command,
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
+    /// systemd-resolved's view: DNS servers and search domains per link
+    pub async fn run_resolvectl_status(&self) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let mut command = Command::new("resolvectl");
+        command.args(["status"]);
+
+        let result = self.command_output(&mut command);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(output) => {
+                let success = output.status.success();
+                let output_str = String::from_utf8_lossy(&output.stdout).to_string();
+                let error_str = if success {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(&output.stderr).to_string())
+                };
+
+                DebugToolResult {
+                    tool_name: "resolvectl_status".to_string(),
+                    command: "resolvectl status".to_string(),
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "resolvectl_status".to_string(),
+                command: "resolvectl status".to_string(),
+                success: false,
+                output: String::new(),
+                error: Some(format!("resolvectl not available (systemd-resolved may not be in use): {}", e)),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
     /// Check network connectivity with standard hosts
     pub async fn run_connectivity_test(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
@@ -833,7 +1016,7 @@ impl DebugTools {
             let mut command = Command::new("ping");
             command.args(["-c", "2", "-W", "3", host]);
             
-            if let Ok(output) = command.output() {
+            if let Ok(output) = command.pooled_output() {
                 let success = output.status.success();
                 let result_text = if success {
                     format!("✅ {} ({}): REACHABLE", description, host)
@@ -866,44 +1049,80 @@ impl DebugTools {
         }
     }
 
+    /// Try to resolve `name` through the system resolver, then public DNS servers, then by
+    /// pinging host names. The slower paths only run when the system resolver fails, so a
+    /// healthy host pays for one `getent` call.
+    pub async fn get_dns_resolution_status(&self, name: &str) -> DnsResolutionStatus {
+        let system_resolver = self
+            .command_output(Command::new("getent").args(["hosts", name]))
+            .ok()
+            .map(|output| output.status.success());
+        let mut status = DnsResolutionStatus {
+            name: name.to_string(),
+            system_resolver,
+            ..Default::default()
+        };
+        if system_resolver == Some(true) {
+            return status;
+        }
+        status.public_servers = dns_test_resolved(&self.run_dns_test(name).await.output);
+        status.ping_by_name = connectivity_resolved_by_name(&self.run_connectivity_test().await.output);
+        status
+    }
+
     /// Comprehensive network health check - runs multiple diagnostic tools automatically
-    pub async fn run_network_health_check(&self) -> Vec<DebugToolResult> {
+    pub async fn run_network_health_check(&self) -> CompositeToolResult {
+        self.run_network_health_check_with_progress(|_| {}).await
+    }
+
+    /// Network health check that calls `on_result` after each of its
+    /// [`NETWORK_HEALTH_CHECK_STEPS`] tools finishes (e.g. to drive a progress bar)
+    pub async fn run_network_health_check_with_progress(
+        &self,
+        mut on_result: impl FnMut(&DebugToolResult),
+    ) -> CompositeToolResult {
+        let start_time = std::time::Instant::now();
         let mut results = Vec::new();
+        let mut record = |result: DebugToolResult| {
+            on_result(&result);
+            results.push(result);
+        };
         
         // 1. Check network interfaces
-        results.push(self.run_ip_addr().await);
+        record(self.run_ip_addr().await);
         
         // 2. Check routing table
-        results.push(self.run_ip_route().await);
+        record(self.run_ip_route().await);
         
         // 3. Test connectivity
-        results.push(self.run_connectivity_test().await);
+        record(self.run_connectivity_test().await);
         
         // 4. Check DNS configuration
-        results.push(self.run_dns_config().await);
+        record(self.run_dns_config().await);
         
         // 5. Test DNS resolution
-        results.push(self.run_dns_test("google.com").await);
+        record(self.run_dns_test("google.com").await);
         
         // 6. Check active network connections
-        results.push(self.run_ss().await);
+        record(self.run_ss().await);
         
         // 7. Check firewall status (iptables)
-        results.push(self.run_iptables().await);
+        record(self.run_iptables().await);
         
         // 8. Check UFW status if available
-        results.push(self.run_ufw_status().await);
+        record(self.run_ufw_status().await);
         
         // 9. Check NetworkManager status if available
-        results.push(self.run_networkmanager_status().await);
+        record(self.run_networkmanager_status().await);
         
         // 10. Check nftables if available
-        results.push(self.run_nftables().await);
+        record(self.run_nftables().await);
         
         // 11. Check wireless information if available
-        results.push(self.run_wireless_info().await);
+        record(self.run_wireless_info().await);
         
-        results
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        CompositeToolResult::new("network_health_check", results, execution_time)
     }
 
     /// Quick network setup check for standard users
@@ -916,7 +1135,7 @@ impl DebugTools {
         let mut warnings = Vec::new();
         let mut errors = Vec::new();
         
-        for result in &health_results {
+        for result in &health_results.results {
             match result.tool_name.as_str() {
                 "ip_addr" => {
                     if result.success {
@@ -1034,15 +1253,9 @@ impl DebugTools {
         
         full_output.push_str(&format!("🏁 Overall Status: {}\n", overall_status));
         
-        // Extract the actual commands that were run
-        let commands_run = health_results.iter()
-            .map(|r| r.command.clone())
-            .collect::<Vec<_>>()
-            .join("; ");
-        
         DebugToolResult {
             tool_name: "network_setup_check".to_string(),
-            command: commands_run,
+            command: health_results.command(),
             success: errors.is_empty(),
             output: full_output,
             error: if errors.is_empty() { None } else { Some(format!("{} issues found", errors.len())) },
@@ -1051,10 +1264,163 @@ impl DebugTools {
     }
 }
 
+/// The `dev` of the first `default` route in `ip route` output; `ip` lists the lowest
+/// metric first
+pub fn default_route_interface(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        if words.next()? != "default" {
+            return None;
+        }
+        words.skip_while(|word| *word != "dev").nth(1).map(str::to_string)
+    })
+}
+
+/// The interface of the lowest-metric default route in /proc/net/route
+/// (`Iface Destination Gateway Flags RefCnt Use Metric Mask ...`, addresses in hex)
+pub fn proc_route_default_interface(content: &str) -> Option<String> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let metric: u32 = fields.get(6)?.parse().ok()?;
+            (fields.get(1) == Some(&"00000000") && fields.get(7) == Some(&"00000000")).then(|| (metric, fields[0]))
+        })
+        .min_by_key(|(metric, _)| *metric)
+        .map(|(_, interface)| interface.to_string())
+}
+
+/// Parts of an `ethtool -S` counter name that mark it as an error or drop counter. Names
+/// vary by driver (`rx_crc_errors`, `rx_missed_errors`, `tx_timeout_count`, `rx_no_buffer_count`).
+const ETHTOOL_ERROR_MARKERS: [&str; 9] = ["err", "drop", "crc", "miss", "fifo", "overrun", "timeout", "discard", "no_buf"];
+
+/// Non-zero error and drop counters from `ethtool -S` output (`     rx_crc_errors: 12`)
+pub fn notable_ethtool_counters(output: &str) -> Vec<(String, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            let name = name.trim();
+            let value: u64 = value.trim().parse().ok()?;
+            let lower = name.to_lowercase();
+            (value > 0 && ETHTOOL_ERROR_MARKERS.iter().any(|marker| lower.contains(marker)))
+                .then(|| (name.to_string(), value))
+        })
+        .collect()
+}
+
+/// Whether any server in `dns_test` output (`DNS Server 8.8.8.8: 142.250.74.46 (23ms)`)
+/// returned an answer; `None` when dig could not be run at all
+fn dns_test_resolved(output: &str) -> Option<bool> {
+    if output.trim().is_empty() {
+        return None;
+    }
+    Some(output.lines().any(|line| {
+        line.split_once(": ")
+            .map(|(_, answer)| answer.rsplit_once(" (").map_or(answer, |(answer, _)| answer).trim())
+            .is_some_and(|answer| !answer.is_empty() && answer != "FAILED" && !answer.starts_with(";;"))
+    }))
+}
+
+/// Whether `connectivity_test` reached any host given by name rather than address
+/// (`✅ GitHub (HTTPS connectivity) (github.com): REACHABLE`); `None` when ping could not be run
+fn connectivity_resolved_by_name(output: &str) -> Option<bool> {
+    let by_name: Vec<&str> = output
+        .lines()
+        .filter(|line| {
+            line.rsplit_once(" (")
+                .and_then(|(_, host)| host.split_once(')'))
+                .is_some_and(|(host, _)| host.parse::<std::net::IpAddr>().is_err())
+        })
+        .collect();
+    if by_name.is_empty() || by_name.iter().all(|line| line.ends_with("PING FAILED")) {
+        return None;
+    }
+    Some(by_name.iter().any(|line| line.ends_with(": REACHABLE")))
+}
+
+/// The `hosts:` line of nsswitch.conf, e.g. `files resolve [!UNAVAIL=return] dns`
+fn nsswitch_hosts_line(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let line = line.split('#').next()?.trim();
+        let sources = line.strip_prefix("hosts:")?;
+        Some(format!("hosts: {}", sources.split_whitespace().collect::<Vec<_>>().join(" ")))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_default_route_interface() {
+        let ip_route = "default via 192.168.1.1 dev enp3s0 proto dhcp src 192.168.1.20 metric 100\ndefault via 10.0.0.1 dev wlan0 proto dhcp metric 600\n192.168.1.0/24 dev enp3s0 proto kernel scope link\n";
+        assert_eq!(default_route_interface(ip_route).as_deref(), Some("enp3s0"));
+        assert_eq!(default_route_interface("10.0.0.0/8 dev ens5 scope link\n"), None);
+
+        let proc_route = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+wlan0\t00000000\t0100000A\t0003\t0\t0\t600\t00000000\t0\t0\t0\n\
+ens5\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+ens5\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n";
+        assert_eq!(proc_route_default_interface(proc_route).as_deref(), Some("ens5"));
+    }
+
+    #[test]
+    fn test_notable_ethtool_counters() {
+        let output = "NIC statistics:\n     rx_packets: 123456\n     tx_packets: 98765\n     rx_crc_errors: 12\n     rx_missed_errors: 0\n     tx_dropped: 3\n     rx_no_buffer_count: 7\n     tx_timeout_count: 0\n";
+        assert_eq!(
+            notable_ethtool_counters(output),
+            vec![
+                ("rx_crc_errors".to_string(), 12),
+                ("tx_dropped".to_string(), 3),
+                ("rx_no_buffer_count".to_string(), 7),
+            ]
+        );
+        assert!(notable_ethtool_counters("NIC statistics:\n     rx_errors: 0\n").is_empty());
+    }
+
+    #[test]
+    fn test_nsswitch_hosts_line() {
+        let content = "# comment\npasswd:         files systemd\nhosts:          files mymachines resolve [!UNAVAIL=return] dns  # order matters\n";
+        assert_eq!(
+            nsswitch_hosts_line(content).as_deref(),
+            Some("hosts: files mymachines resolve [!UNAVAIL=return] dns")
+        );
+        assert!(nsswitch_hosts_line("passwd: files\n").is_none());
+    }
+
+    #[test]
+    fn test_dns_resolution_parsing() {
+        let dns_test = "DNS Server 8.8.8.8: FAILED\nDNS Server 1.1.1.1:  (2004ms)\nDNS Server 9.9.9.9: 140.82.121.4 (31ms)";
+        assert_eq!(dns_test_resolved(dns_test), Some(true));
+        assert_eq!(dns_test_resolved("DNS Server 8.8.8.8: FAILED\nDNS Server 1.1.1.1:  (2004ms)"), Some(false));
+        assert_eq!(dns_test_resolved(""), None);
+
+        let unresolved = "✅ Google DNS (8.8.8.8): REACHABLE\n❌ Google (DNS resolution test) (google.com): UNREACHABLE\n❌ GitHub (HTTPS connectivity) (github.com): UNREACHABLE";
+        assert_eq!(connectivity_resolved_by_name(unresolved), Some(false));
+        let resolved = "❌ Google DNS (8.8.8.8): UNREACHABLE\n✅ GitHub (HTTPS connectivity) (github.com): REACHABLE";
+        assert_eq!(connectivity_resolved_by_name(resolved), Some(true));
+        assert_eq!(connectivity_resolved_by_name("❌ Google (DNS resolution test) (google.com): PING FAILED"), None);
+
+        let failing = DnsResolutionStatus {
+            name: "google.com".to_string(),
+            system_resolver: Some(false),
+            public_servers: None,
+            ping_by_name: Some(false),
+        };
+        assert!(failing.is_failing());
+        assert!(!DnsResolutionStatus { public_servers: Some(true), ..failing.clone() }.is_failing());
+        assert!(!DnsResolutionStatus::default().is_failing());
+    }
+
+    #[tokio::test]
+    async fn test_getent_hosts_command_format() {
+        let result = DebugTools::new().run_getent_hosts("localhost").await;
+        assert_eq!(result.tool_name, "getent_hosts");
+        assert_eq!(result.command, "getent hosts localhost");
+    }
+
     #[tokio::test]
     async fn test_network_debug_tools_command_format() {
         let debug_tools = DebugTools::new();
@@ -1115,14 +1481,19 @@ mod tests {
         assert_eq!(result.command, "ethtool lo");
 
         // Test tcpdump with interface
-        let result = debug_tools.run_tcpdump_sample(Some("lo")).await;
+        let result = debug_tools.run_tcpdump_sample(Some("lo"), None).await;
         assert_eq!(result.tool_name, "tcpdump_sample");
         assert_eq!(result.command, "tcpdump -i lo -c 10 -n");
 
         // Test tcpdump without interface (default to "any")
-        let result = debug_tools.run_tcpdump_sample(None).await;
+        let result = debug_tools.run_tcpdump_sample(None, None).await;
         assert_eq!(result.tool_name, "tcpdump_sample");
         assert_eq!(result.command, "tcpdump -i any -c 10 -n");
+
+        // Test tcpdump with a sample duration
+        let result = debug_tools.run_tcpdump_sample(Some("lo"), Some(1)).await;
+        assert_eq!(result.tool_name, "tcpdump_sample");
+        assert_eq!(result.command, "timeout 1 tcpdump -i lo -c 1000 -n");
     }
 
     #[tokio::test]
@@ -1330,11 +1701,25 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_network_health_check_reports_progress() {
+        let debug_tools = DebugTools::new();
+        let mut seen = Vec::new();
+
+        let composite = debug_tools
+            .run_network_health_check_with_progress(|result| seen.push(result.tool_name.clone()))
+            .await;
+
+        assert_eq!(seen.len() as u64, NETWORK_HEALTH_CHECK_STEPS);
+        assert_eq!(composite.results.len(), seen.len());
+        assert_eq!(seen[0], "ip_addr");
+    }
+
     #[tokio::test]
     async fn test_network_health_check() {
         let debug_tools = DebugTools::new();
 
-        let results = debug_tools.run_network_health_check().await;
+        let results = debug_tools.run_network_health_check().await.results;
         
         // Should run multiple network diagnostic tools
         assert!(results.len() >= 8); // At least 8 tools should be checked
@@ -1414,7 +1799,7 @@ mod tests {
         let debug_tools = DebugTools::new();
 
         // Health check should complete even if some tools fail
-        let results = debug_tools.run_network_health_check().await;
+        let results = debug_tools.run_network_health_check().await.results;
         
         // Should have attempted all tools
         assert!(results.len() >= 8);
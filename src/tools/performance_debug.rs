@@ -7,7 +7,7 @@ impl DebugTools {
         let mut command = Command::new("top");
         command.args(["-b", "-n", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -45,7 +45,7 @@ impl DebugTools {
         let mut command = Command::new("vmstat");
         command.args(["1", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -83,7 +83,7 @@ impl DebugTools {
         let mut command = Command::new("sar");
         command.args(["-u", "-r", "-d", "1", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -121,7 +121,7 @@ impl DebugTools {
         let mut command = Command::new("mpstat");
         command.args(["1", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -159,7 +159,7 @@ impl DebugTools {
         let mut command = Command::new("iotop");
         command.args(["-b", "-n", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -197,7 +197,7 @@ impl DebugTools {
         let mut command = Command::new("htop");
         command.args(["-t", "-d", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -235,7 +235,7 @@ impl DebugTools {
         let mut command = Command::new("nethogs");
         command.args(["-t", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -273,7 +273,7 @@ impl DebugTools {
         let mut command = Command::new("perf");
         command.args(["stat", "-a", "sleep", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -311,7 +311,7 @@ impl DebugTools {
         let mut command = Command::new("sysbench");
         command.args(["cpu", "--cpu-max-prime=10000", "run"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -0,0 +1,106 @@
+//! One process-wide limit on how many external commands RAID runs at once, shared by
+//! system-info collection and the AI agent's tools (`performance.max_parallelism`).
+
+use std::io;
+use std::process::{Command, Output};
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+struct PoolState {
+    running: usize,
+    /// `None` until configured: one command per CPU
+    limit: Option<usize>,
+}
+
+static STATE: Mutex<PoolState> = Mutex::new(PoolState { running: 0, limit: None });
+static RELEASED: Condvar = Condvar::new();
+
+fn state() -> MutexGuard<'static, PoolState> {
+    // The state is two counters, so a panic while holding the lock leaves nothing half-done
+    STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// One command per CPU, or one when the CPU count is unknown
+pub fn default_parallelism() -> usize {
+    std::thread::available_parallelism().map_or(1, usize::from)
+}
+
+/// Set how many commands may run at once; `None` goes back to one per CPU. Commands
+/// already running keep their permits.
+pub fn configure(max_parallelism: Option<usize>) {
+    state().limit = max_parallelism.map(|limit| limit.max(1));
+    RELEASED.notify_all();
+}
+
+/// The limit in effect
+pub fn max_parallelism() -> usize {
+    state().limit.unwrap_or_else(default_parallelism)
+}
+
+/// A slot in the pool, given back when dropped
+#[must_use = "the slot is released as soon as the permit is dropped"]
+pub struct Permit(());
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        state().running -= 1;
+        RELEASED.notify_one();
+    }
+}
+
+/// Wait for a free slot. This blocks the calling thread, like the command it guards.
+pub fn acquire() -> Permit {
+    let mut state = state();
+    while state.running >= state.limit.unwrap_or_else(default_parallelism) {
+        state = RELEASED.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+    state.running += 1;
+    Permit(())
+}
+
+/// `Command::output` that first takes a slot in the pool
+pub trait PooledOutput {
+    fn pooled_output(&mut self) -> io::Result<Output>;
+}
+
+impl PooledOutput for Command {
+    fn pooled_output(&mut self) -> io::Result<Output> {
+        let _permit = acquire();
+        self.output()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_pool_bounds_concurrent_commands() {
+        configure(Some(2));
+        assert_eq!(max_parallelism(), 2);
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..6)
+            .map(|_| {
+                let (running, peak) = (running.clone(), peak.clone());
+                std::thread::spawn(move || {
+                    let _permit = acquire();
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        // Other tests may hold slots too, so the workers can't count on getting both
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+
+        configure(None);
+        assert_eq!(max_parallelism(), default_parallelism());
+    }
+}
@@ -1,13 +1,23 @@
-use super::{DebugToolResult, DebugTools};
+use super::{DebugToolResult, DebugTools, ProcessFdUsage, ProcessUsage, ToolAvailability};
+use crate::cli::SortKey;
+use crate::sysinfo::HostPaths;
 use std::process::Command;
 
+/// Share of the `NOFILE` soft limit at which a process is flagged
+pub const FD_LIMIT_WARN_RATIO: f64 = 0.8;
+
+/// Default length of a strace_pid sample
+pub const STRACE_DEFAULT_SECS: u64 = 5;
+/// Longest strace_pid sample; tracing slows the traced process down
+pub const STRACE_MAX_SECS: u64 = 30;
+
 impl DebugTools {
     pub async fn run_lsof(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("lsof");
         command.args(["-i"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -45,7 +55,7 @@ impl DebugTools {
         let mut command = Command::new("lsof");
         command.args(["-p", pid]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -78,6 +88,208 @@ impl DebugTools {
         }
     }
 
+    /// Find the process holding a specific TCP/UDP port
+    pub async fn run_lsof_port(&self, port: u16) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let mut command = Command::new("lsof");
+        let port_arg = format!(":{}", port);
+        command.args(["-i", &port_arg]);
+
+        let result = self.command_output(&mut command);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(output) => {
+                let output_str = String::from_utf8_lossy(&output.stdout).to_string();
+                let stderr_str = String::from_utf8_lossy(&output.stderr).to_string();
+                // lsof exits with 1 and prints nothing when no process matches
+                let nothing_found =
+                    !output.status.success() && output_str.trim().is_empty() && stderr_str.trim().is_empty();
+                let success = output.status.success() || nothing_found;
+                let output_str = if nothing_found {
+                    format!("No process is using port {}", port)
+                } else {
+                    output_str
+                };
+                let error_str = if success { None } else { Some(stderr_str) };
+
+                DebugToolResult {
+                    tool_name: "lsof_port".to_string(),
+                    command: format!("lsof -i :{}", port),
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "lsof_port".to_string(),
+                command: format!("lsof -i :{}", port),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
+    /// Find the processes holding a specific file open (e.g. "file is locked")
+    pub async fn run_lsof_file(&self, path: &str) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let mut command = Command::new("lsof");
+        command.args(["--", path]);
+
+        let result = self.command_output(&mut command);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(output) => {
+                let output_str = String::from_utf8_lossy(&output.stdout).to_string();
+                let stderr_str = String::from_utf8_lossy(&output.stderr).to_string();
+                // lsof exits with 1 and prints nothing when no process matches
+                let nothing_found =
+                    !output.status.success() && output_str.trim().is_empty() && stderr_str.trim().is_empty();
+                let success = output.status.success() || nothing_found;
+                let output_str = if nothing_found {
+                    format!("No process has {} open", path)
+                } else {
+                    output_str
+                };
+                let error_str = if success { None } else { Some(stderr_str) };
+
+                DebugToolResult {
+                    tool_name: "lsof_file".to_string(),
+                    command: format!("lsof -- {}", path),
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "lsof_file".to_string(),
+                command: format!("lsof -- {}", path),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
+    /// Show the top N processes sorted by CPU or memory usage
+    pub async fn run_top_consumers(&self, by: SortKey, n: usize) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let sort_field = match by {
+            SortKey::Cpu => "pcpu",
+            SortKey::Mem => "pmem",
+        };
+        let sort_arg = format!("--sort=-{}", sort_field);
+        let mut command = Command::new("ps");
+        command.args(["-eo", "pid,user,pcpu,pmem,rss,comm", &sort_arg]);
+
+        let result = self.command_output(&mut command);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let command_str = format!(
+            "ps -eo pid,user,pcpu,pmem,rss,comm --sort=-{} | head -n {}",
+            sort_field,
+            n + 1
+        );
+
+        match result {
+            Ok(output) => {
+                let success = output.status.success();
+                // Keep the header plus the top N rows
+                let output_str = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .take(n + 1)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let error_str = if success {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(&output.stderr).to_string())
+                };
+
+                DebugToolResult {
+                    tool_name: "top_consumers".to_string(),
+                    command: command_str,
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "top_consumers".to_string(),
+                command: command_str,
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
+    /// Open file descriptors and threads against their limits, for one PID or the top CPU and memory consumers
+    pub async fn run_process_limits(&self, pid: Option<u32>) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let (pids, command_str) = match pid {
+            Some(pid) => (vec![pid], format!("cat /proc/{0}/limits /proc/{0}/status; ls /proc/{0}/fd", pid)),
+            None => (
+                self.top_consumer_pids(5).await,
+                "cat /proc/<pid>/limits /proc/<pid>/status; ls /proc/<pid>/fd (top 5 CPU and memory consumers)".to_string(),
+            ),
+        };
+
+        let usages: Vec<ProcessFdUsage> = pids
+            .into_iter()
+            .filter_map(|pid| read_process_fd_usage(&self.host_paths, pid))
+            .collect();
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        if usages.is_empty() {
+            let error = match pid {
+                Some(pid) => format!("Cannot read /proc/{}/limits (no such process?)", pid),
+                None => "Could not read /proc limits for any top process".to_string(),
+            };
+            return DebugToolResult {
+                tool_name: "process_limits".to_string(),
+                command: command_str,
+                success: false,
+                output: String::new(),
+                error: Some(error),
+                execution_time_ms: execution_time,
+            };
+        }
+
+        DebugToolResult {
+            tool_name: "process_limits".to_string(),
+            command: command_str,
+            success: true,
+            output: format_fd_usage_table(&usages),
+            error: None,
+            execution_time_ms: execution_time,
+        }
+    }
+
+    /// PIDs of the top `n` CPU consumers followed by the top `n` memory consumers, without duplicates
+    pub async fn top_consumer_pids(&self, n: usize) -> Vec<u32> {
+        let mut pids = Vec::new();
+        for by in [SortKey::Cpu, SortKey::Mem] {
+            let result = self.run_top_consumers(by, n).await;
+            if !result.success {
+                continue;
+            }
+            for process in parse_process_usage(&result.output) {
+                if !pids.contains(&process.pid) {
+                    pids.push(process.pid);
+                }
+            }
+        }
+        pids
+    }
+
     pub async fn run_strace(&self, pid: &str) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("strace");
@@ -85,7 +297,7 @@ impl DebugTools {
 
         // Removed command.timeout, as std::process::Command does not have this method
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -118,12 +330,84 @@ impl DebugTools {
         }
     }
 
+    /// Count the syscalls a (possibly stuck) process makes for a few seconds, following its
+    /// threads: `timeout <n> strace -f -p <pid> -c`. A process blocked in one syscall shows
+    /// little or nothing; the summary names the call it keeps retrying otherwise.
+    pub async fn run_strace_pid(&self, pid: u32, duration_secs: u64) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let secs = duration_secs.clamp(1, STRACE_MAX_SECS);
+        let command_str = format!("timeout {} strace -f -p {} -c", secs, pid);
+
+        if !self.check_tool_availability("strace") {
+            return DebugToolResult {
+                tool_name: "strace_pid".to_string(),
+                command: command_str,
+                success: false,
+                output: String::new(),
+                error: Some("strace is not installed. Install it (e.g. `apt install strace` or `pacman -S strace`) to trace a stuck process.".to_string()),
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+            };
+        }
+
+        let mut command = Command::new("timeout");
+        command.args([&secs.to_string(), "strace", "-f", "-p", &pid.to_string(), "-c"]);
+
+        let result = self.command_output(&mut command);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(output) => {
+                // strace writes the summary to stderr; timeout exits with 124 after the full sample
+                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+                let attached = !stderr.contains("attach:") && !stderr.contains("Operation not permitted");
+                let success = attached && (output.status.success() || output.status.code() == Some(124));
+                if success {
+                    let mut output_str = stderr;
+                    if !output_str.contains("syscall") {
+                        output_str.push_str(&format!(
+                            "No syscalls in {} seconds: PID {} is blocked inside one call or sleeping. Check its state and wait channel in /proc/{}/status and /proc/{}/wchan.\n",
+                            secs, pid, pid, pid
+                        ));
+                    }
+                    DebugToolResult {
+                        tool_name: "strace_pid".to_string(),
+                        command: command_str,
+                        success,
+                        output: output_str,
+                        error: None,
+                        execution_time_ms: execution_time,
+                    }
+                } else {
+                    let ptrace_scope = std::fs::read_to_string(self.host_paths.proc("sys/kernel/yama/ptrace_scope"))
+                        .ok()
+                        .and_then(|value| value.trim().parse().ok());
+                    DebugToolResult {
+                        tool_name: "strace_pid".to_string(),
+                        command: command_str,
+                        success: false,
+                        output: String::new(),
+                        error: Some(strace_failure_reason(&stderr, pid, ptrace_scope)),
+                        execution_time_ms: execution_time,
+                    }
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "strace_pid".to_string(),
+                command: command_str,
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
     pub async fn run_pmap(&self, pid: &str) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("pmap");
         command.args([pid]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -161,7 +445,7 @@ impl DebugTools {
         let mut command = Command::new("pidstat");
         command.args(["-u", "-r", "-d", "1", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -199,7 +483,7 @@ impl DebugTools {
         let mut command = Command::new("pgrep");
         command.args(["-f", pattern]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -237,7 +521,7 @@ impl DebugTools {
         let mut command = Command::new("pkill");
         command.args(["-f", pattern]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -275,7 +559,7 @@ impl DebugTools {
         let mut command = Command::new("ps");
         command.args(["ax", "-o", "pid,ni,comm"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -308,3 +592,248 @@ impl DebugTools {
         }
     }
 }
+
+/// Explain why strace could not attach, most commonly missing ptrace permission
+pub fn strace_failure_reason(stderr: &str, pid: u32, ptrace_scope: Option<u8>) -> String {
+    if stderr.contains("No such process") {
+        return format!("PID {} does not exist (it may have exited)", pid);
+    }
+    if stderr.contains("Operation not permitted") {
+        let hint = match ptrace_scope {
+            Some(3) => "kernel.yama.ptrace_scope is 3, which disables ptrace until reboot".to_string(),
+            Some(scope) if scope > 0 => format!(
+                "kernel.yama.ptrace_scope is {}; re-run RAID with sudo, or temporarily set it to 0 with `sysctl kernel.yama.ptrace_scope=0`",
+                scope
+            ),
+            _ => "re-run RAID with sudo, or as the user that owns the process".to_string(),
+        };
+        return format!("ptrace permission denied for PID {}: {}", pid, hint);
+    }
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        format!("strace could not trace PID {}", pid)
+    } else {
+        stderr.to_string()
+    }
+}
+
+/// Parse `ps -eo pid,user,pcpu,pmem,rss,comm` output into structured rows
+pub fn parse_process_usage(output: &str) -> Vec<ProcessUsage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 6 {
+                return None;
+            }
+
+            // The header row (and any malformed line) fails to parse here
+            Some(ProcessUsage {
+                pid: fields[0].parse().ok()?,
+                user: fields[1].to_string(),
+                cpu_percent: fields[2].parse().ok()?,
+                mem_percent: fields[3].parse().ok()?,
+                rss_kb: fields[4].parse().ok()?,
+                command: fields[5..].join(" "),
+            })
+        })
+        .collect()
+}
+
+/// Soft limit from a `/proc/<pid>/limits` row such as "Max open files"; `None` when unlimited or missing
+pub fn parse_soft_limit(limits: &str, name: &str) -> Option<u64> {
+    limits
+        .lines()
+        .find_map(|line| line.strip_prefix(name))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|soft| soft.parse().ok())
+}
+
+/// Read fd and thread counts and their limits for `pid`; `None` when the process is gone
+pub fn read_process_fd_usage(paths: &HostPaths, pid: u32) -> Option<ProcessFdUsage> {
+    let proc_dir = paths.proc(&pid.to_string());
+    let limits = std::fs::read_to_string(proc_dir.join("limits")).ok()?;
+    let status = std::fs::read_to_string(proc_dir.join("status")).unwrap_or_default();
+
+    Some(ProcessFdUsage {
+        pid,
+        command: std::fs::read_to_string(proc_dir.join("comm"))
+            .map(|comm| comm.trim().to_string())
+            .unwrap_or_default(),
+        open_fds: std::fs::read_dir(proc_dir.join("fd")).ok().map(|entries| entries.count()),
+        fd_soft_limit: parse_soft_limit(&limits, "Max open files"),
+        threads: status
+            .lines()
+            .find_map(|line| line.strip_prefix("Threads:"))
+            .and_then(|count| count.trim().parse().ok()),
+        process_soft_limit: parse_soft_limit(&limits, "Max processes"),
+    })
+}
+
+/// Whether the process uses at least `FD_LIMIT_WARN_RATIO` of its `NOFILE` soft limit
+pub fn is_near_fd_limit(usage: &ProcessFdUsage) -> bool {
+    match (usage.open_fds, usage.fd_soft_limit) {
+        (Some(open), Some(limit)) if limit > 0 => open as f64 >= limit as f64 * FD_LIMIT_WARN_RATIO,
+        _ => false,
+    }
+}
+
+/// One line per process, flagging those close to their open-file limit
+pub fn format_fd_usage_table(usages: &[ProcessFdUsage]) -> String {
+    let known = |value: Option<usize>| value.map(|v| v.to_string()).unwrap_or_else(|| "?".to_string());
+    let limit = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_else(|| "unlimited".to_string());
+
+    let mut table = format!(
+        "{:<8} {:<16} {:>6} {:>10} {:>8} {:>10}\n",
+        "PID", "COMMAND", "FDS", "NOFILE", "THREADS", "NPROC"
+    );
+    for usage in usages {
+        table.push_str(&format!(
+            "{:<8} {:<16} {:>6} {:>10} {:>8} {:>10}",
+            usage.pid,
+            usage.command,
+            known(usage.open_fds),
+            limit(usage.fd_soft_limit),
+            known(usage.threads),
+            limit(usage.process_soft_limit)
+        ));
+        if is_near_fd_limit(usage) {
+            table.push_str("  ⚠️ near open-file limit");
+        }
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lsof_port_command_format() {
+        let debug_tools = DebugTools::new();
+
+        let result = debug_tools.run_lsof_port(8080).await;
+        assert_eq!(result.tool_name, "lsof_port");
+        assert_eq!(result.command, "lsof -i :8080");
+    }
+
+    #[tokio::test]
+    async fn test_lsof_file_command_format() {
+        let debug_tools = DebugTools::new();
+
+        let result = debug_tools.run_lsof_file("/var/lib/dpkg/lock").await;
+        assert_eq!(result.tool_name, "lsof_file");
+        assert_eq!(result.command, "lsof -- /var/lib/dpkg/lock");
+        if !result.success {
+            assert!(result.error.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_top_consumers_command_format() {
+        let debug_tools = DebugTools::new();
+
+        let result = debug_tools.run_top_consumers(SortKey::Cpu, 5).await;
+        assert_eq!(result.tool_name, "top_consumers");
+        assert_eq!(result.command, "ps -eo pid,user,pcpu,pmem,rss,comm --sort=-pcpu | head -n 6");
+        if result.success {
+            assert!(result.output.lines().count() <= 6);
+        }
+
+        let result = debug_tools.run_top_consumers(SortKey::Mem, 3).await;
+        assert_eq!(result.command, "ps -eo pid,user,pcpu,pmem,rss,comm --sort=-pmem | head -n 4");
+    }
+
+    #[tokio::test]
+    async fn test_strace_pid_command_is_bounded() {
+        let debug_tools = DebugTools::new();
+        let result = debug_tools.run_strace_pid(999_999_999, 600).await;
+        assert_eq!(result.tool_name, "strace_pid");
+        assert_eq!(result.command, format!("timeout {} strace -f -p 999999999 -c", STRACE_MAX_SECS));
+        assert!(!result.success);
+        assert!(result.error.is_some());
+    }
+
+    #[test]
+    fn test_strace_failure_reason() {
+        let denied = "strace: attach: ptrace(PTRACE_SEIZE, 42): Operation not permitted\n";
+        assert_eq!(
+            strace_failure_reason(denied, 42, Some(1)),
+            "ptrace permission denied for PID 42: kernel.yama.ptrace_scope is 1; re-run RAID with sudo, or temporarily set it to 0 with `sysctl kernel.yama.ptrace_scope=0`"
+        );
+        assert!(strace_failure_reason(denied, 42, Some(3)).contains("disables ptrace until reboot"));
+        assert!(strace_failure_reason(denied, 42, None).ends_with("re-run RAID with sudo, or as the user that owns the process"));
+        assert_eq!(
+            strace_failure_reason("strace: attach: ptrace(PTRACE_SEIZE, 7): No such process\n", 7, Some(1)),
+            "PID 7 does not exist (it may have exited)"
+        );
+    }
+
+    #[test]
+    fn test_parse_soft_limit() {
+        let limits = "Limit                     Soft Limit           Hard Limit           Units
+Max processes             63432                63432                processes
+Max open files            1024                 524288               files
+Max locked memory         unlimited            unlimited            bytes
+";
+        assert_eq!(parse_soft_limit(limits, "Max open files"), Some(1024));
+        assert_eq!(parse_soft_limit(limits, "Max processes"), Some(63432));
+        assert_eq!(parse_soft_limit(limits, "Max locked memory"), None);
+        assert_eq!(parse_soft_limit(limits, "Max msgqueue size"), None);
+    }
+
+    #[test]
+    fn test_near_fd_limit_is_flagged() {
+        let mut usage = ProcessFdUsage {
+            pid: 42,
+            command: "nginx".to_string(),
+            open_fds: Some(1000),
+            fd_soft_limit: Some(1024),
+            threads: Some(4),
+            process_soft_limit: None,
+        };
+        assert!(is_near_fd_limit(&usage));
+        assert!(format_fd_usage_table(std::slice::from_ref(&usage)).contains("near open-file limit"));
+
+        usage.open_fds = Some(100);
+        assert!(!is_near_fd_limit(&usage));
+        usage.open_fds = None;
+        assert!(!is_near_fd_limit(&usage));
+    }
+
+    #[tokio::test]
+    async fn test_process_limits_for_own_pid() {
+        let pid = std::process::id();
+        let usage = read_process_fd_usage(&HostPaths::default(), pid).expect("own /proc entry is readable");
+        assert!(usage.open_fds.unwrap_or(0) > 0);
+        assert!(usage.threads.unwrap_or(0) >= 1);
+
+        let result = DebugTools::new().run_process_limits(Some(pid)).await;
+        assert_eq!(result.tool_name, "process_limits");
+        assert!(result.success);
+        assert!(result.output.contains(&pid.to_string()));
+    }
+
+    #[test]
+    fn test_parse_process_usage() {
+        let output = "    PID USER     %CPU %MEM   RSS COMMAND\n   1234 postgres 42.5 12.0 980000 postgres\n      1 root      0.1  0.2 12000 systemd\n   4321 alice     3.0  8.1 650000 Web Content";
+        let processes = parse_process_usage(output);
+
+        assert_eq!(processes.len(), 3);
+        assert_eq!(processes[0].pid, 1234);
+        assert_eq!(processes[0].user, "postgres");
+        assert_eq!(processes[0].cpu_percent, 42.5);
+        assert_eq!(processes[0].mem_percent, 12.0);
+        assert_eq!(processes[0].rss_kb, 980000);
+        assert_eq!(processes[0].command, "postgres");
+        assert_eq!(processes[2].command, "Web Content");
+    }
+
+    #[test]
+    fn test_parse_process_usage_ignores_malformed_lines() {
+        assert!(parse_process_usage("").is_empty());
+        assert!(parse_process_usage("PID USER %CPU %MEM RSS COMMAND").is_empty());
+        assert!(parse_process_usage("garbage line").is_empty());
+    }
+}
@@ -1,13 +1,84 @@
-use super::{DebugToolResult, DebugTools};
+use super::{DebugToolResult, DebugTools, FailedLoginSource, SecurityDenial};
+use super::pool::PooledOutput;
+use chrono::{Datelike, NaiveDateTime, TimeDelta};
 use std::process::Command;
 
 impl DebugTools {
+    /// Summarize recent SELinux AVC and AppArmor denials from the audit log and kernel messages
+    pub async fn run_security_denials(&self) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let (log, sources) = read_denial_logs();
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let command = if sources.is_empty() {
+            "ausearch -m avc -ts recent; journalctl -k --since -1h".to_string()
+        } else {
+            sources.join("; ")
+        };
+
+        if sources.is_empty() {
+            return DebugToolResult {
+                tool_name: "security_denials".to_string(),
+                command,
+                success: false,
+                output: String::new(),
+                error: Some("Could not read the audit log, the kernel journal or /var/log/kern.log (try as root)".to_string()),
+                execution_time_ms: execution_time,
+            };
+        }
+
+        DebugToolResult {
+            tool_name: "security_denials".to_string(),
+            command,
+            success: true,
+            output: summarize_denials(&parse_security_denials(&log)),
+            error: None,
+            execution_time_ms: execution_time,
+        }
+    }
+
+    /// Failed SSH logins in the last 24 hours, grouped by source address
+    pub async fn run_auth_summary(&self) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let (log, source) = read_auth_log();
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        let Some(command) = source else {
+            return DebugToolResult {
+                tool_name: "auth_summary".to_string(),
+                command: "journalctl _COMM=sshd --since -24h; /var/log/auth.log; /var/log/secure".to_string(),
+                success: false,
+                output: String::new(),
+                error: Some("Could not read sshd logs from the journal, /var/log/auth.log or /var/log/secure (try as root)".to_string()),
+                execution_time_ms: execution_time,
+            };
+        };
+
+        DebugToolResult {
+            tool_name: "auth_summary".to_string(),
+            command,
+            success: true,
+            output: summarize_failed_logins(&parse_failed_logins(&log)),
+            error: None,
+            execution_time_ms: execution_time,
+        }
+    }
+
+    /// Failed SSH logins by source as structured data (empty when no log source is readable)
+    pub async fn get_failed_logins(&self) -> Vec<FailedLoginSource> {
+        parse_failed_logins(&read_auth_log().0)
+    }
+
+    /// Recent denials as structured data (empty when no log source is readable)
+    pub async fn get_security_denials(&self) -> Vec<SecurityDenial> {
+        parse_security_denials(&read_denial_logs().0)
+    }
+
     pub async fn run_auditctl(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("auditctl");
         command.args(["-l"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -45,7 +116,7 @@ impl DebugTools {
         let mut command = Command::new("ausearch");
         command.args(["-m", "all", "--start", "today"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -82,7 +153,7 @@ impl DebugTools {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("sestatus");
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -119,7 +190,7 @@ impl DebugTools {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("getenforce");
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -157,7 +228,7 @@ impl DebugTools {
         let mut command = Command::new("semodule");
         command.args(["-l"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -195,7 +266,7 @@ impl DebugTools {
         let mut command = Command::new("ps");
         command.args(["ef"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -228,11 +299,12 @@ impl DebugTools {
         }
     }
 
+    /// Who is logged in right now and what they are running
     pub async fn run_w(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("w");
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -265,11 +337,14 @@ impl DebugTools {
         }
     }
 
-    pub async fn run_last(&self) -> DebugToolResult {
+    /// Most recent logins and reboots from wtmp (default 20 entries)
+    pub async fn run_last(&self, lines: Option<usize>) -> DebugToolResult {
         let start_time = std::time::Instant::now();
+        let lines = lines.unwrap_or(20);
         let mut command = Command::new("last");
+        command.args(["-n", &lines.to_string()]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -284,7 +359,7 @@ impl DebugTools {
 
                 DebugToolResult {
                     tool_name: "last".to_string(),
-                    command: "last".to_string(),
+                    command: format!("last -n {}", lines),
                     success,
                     output: output_str,
                     error: error_str,
@@ -293,7 +368,7 @@ impl DebugTools {
             }
             Err(e) => DebugToolResult {
                 tool_name: "last".to_string(),
-                command: "last".to_string(),
+                command: format!("last -n {}", lines),
                 success: false,
                 output: String::new(),
                 error: Some(e.to_string()),
@@ -307,7 +382,7 @@ impl DebugTools {
         let mut command = Command::new("fail2ban-client");
         command.args(["status"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -345,7 +420,7 @@ impl DebugTools {
         let mut command = Command::new("clamscan");
         command.args(["--version"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -378,3 +453,312 @@ impl DebugTools {
         }
     }
 }
+
+/// Collect denial lines from ausearch (SELinux) and kernel messages (AppArmor, and SELinux
+/// without auditd). Returns the combined text and the commands that could be read.
+fn read_denial_logs() -> (String, Vec<String>) {
+    let run = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .pooled_output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    let mut log = String::new();
+    let mut sources = Vec::new();
+    // ausearch exits non-zero when nothing matches, so only count it as a source when it prints
+    if let Some(output) = run("ausearch", &["-m", "avc", "-ts", "recent"]) {
+        log.push_str(&output);
+        sources.push("ausearch -m avc -ts recent".to_string());
+    }
+    if let Some(output) = run("journalctl", &["-k", "--since", "-1h", "--no-pager"]) {
+        log.push_str(&output);
+        sources.push("journalctl -k --since -1h --no-pager".to_string());
+    } else if let Ok(content) = std::fs::read_to_string("/var/log/kern.log") {
+        let lines: Vec<&str> = content.lines().collect();
+        log.push_str(&lines[lines.len().saturating_sub(2000)..].join("\n"));
+        sources.push("tail -n 2000 /var/log/kern.log".to_string());
+    }
+    (log, sources)
+}
+
+/// sshd messages from the last day: the journal when it has any, otherwise the tail of
+/// the syslog auth file (Debian: auth.log, RHEL: secure) cut to the last 24 hours
+fn read_auth_log() -> (String, Option<String>) {
+    let journal = Command::new("journalctl")
+        .args(["_COMM=sshd", "--since", "-24h", "--no-pager"])
+        .pooled_output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        .filter(|log| log.lines().any(|line| line.contains("sshd")));
+    if let Some(log) = journal {
+        return (log, Some("journalctl _COMM=sshd --since -24h --no-pager".to_string()));
+    }
+
+    for path in ["/var/log/auth.log", "/var/log/secure"] {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            let lines: Vec<&str> = content.lines().collect();
+            let tail = lines[lines.len().saturating_sub(5000)..].join("\n");
+            let now = chrono::Local::now().naive_local();
+            return (lines_from_last_day(&tail, now), Some(format!("tail -n 5000 {}", path)));
+        }
+    }
+    (String::new(), None)
+}
+
+/// Keep the syslog lines logged in the 24 hours before `now`. Lines without a timestamp
+/// RAID can read are kept, since there's no telling how old they are.
+fn lines_from_last_day(log: &str, now: NaiveDateTime) -> String {
+    log.lines()
+        .filter(|line| syslog_timestamp(line, now).is_none_or(|logged| now - logged <= TimeDelta::hours(24)))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// The local time a syslog line was written: RFC 3339 (`2026-10-16T09:00:01+00:00 host ...`)
+/// or the traditional `Oct 16 09:00:01 host ...`, which has no year, so it's the latest
+/// year that doesn't put the line after `now`
+fn syslog_timestamp(line: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let first = line.split_whitespace().next()?;
+    if let Ok(logged) = chrono::DateTime::parse_from_rfc3339(first) {
+        return Some(logged.with_timezone(&chrono::Local).naive_local());
+    }
+
+    let stamp: Vec<&str> = line.split_whitespace().take(3).collect();
+    let logged = NaiveDateTime::parse_from_str(
+        &format!("{} {}", now.year(), stamp.join(" ")),
+        "%Y %b %d %H:%M:%S",
+    )
+    .ok()?;
+    if logged > now {
+        logged.with_year(now.year() - 1)
+    } else {
+        Some(logged)
+    }
+}
+
+/// Group sshd `Failed <method> for [invalid user] <user> from <address>` lines by address,
+/// most attempts first
+pub fn parse_failed_logins(log: &str) -> Vec<FailedLoginSource> {
+    let mut sources: Vec<FailedLoginSource> = Vec::new();
+    for line in log.lines() {
+        let Some(start) = line.find("Failed ") else { continue };
+        let Some((_, rest)) = line[start..].split_once(" for ") else { continue };
+        let rest = rest.strip_prefix("invalid user ").unwrap_or(rest);
+        let Some((user, rest)) = rest.split_once(" from ") else { continue };
+        let Some(address) = rest.split_whitespace().next() else { continue };
+
+        let index = match sources.iter().position(|source| source.address == address) {
+            Some(index) => index,
+            None => {
+                sources.push(FailedLoginSource {
+                    address: address.to_string(),
+                    attempts: 0,
+                    users: Vec::new(),
+                });
+                sources.len() - 1
+            }
+        };
+        let source = &mut sources[index];
+        source.attempts += 1;
+        if !source.users.iter().any(|known| known == user) {
+            source.users.push(user.to_string());
+        }
+    }
+    sources.sort_by_key(|source| std::cmp::Reverse(source.attempts));
+    sources
+}
+
+/// Failed logins from one address at which the pattern looks like password guessing
+pub const BRUTE_FORCE_THRESHOLD: usize = 10;
+
+pub fn summarize_failed_logins(sources: &[FailedLoginSource]) -> String {
+    if sources.is_empty() {
+        return "No failed SSH logins in the last 24 hours".to_string();
+    }
+    let total: usize = sources.iter().map(|source| source.attempts).sum();
+    let mut summary = format!("{} failed SSH logins from {} addresses\n", total, sources.len());
+    for source in sources {
+        let flag = if source.attempts >= BRUTE_FORCE_THRESHOLD { " ⚠️ possible brute force" } else { "" };
+        summary.push_str(&format!(
+            "- {}: {} attempts (users: {}){}\n",
+            source.address,
+            source.attempts,
+            source.users.join(", "),
+            flag
+        ));
+    }
+    summary
+}
+
+/// Value of `key="value"` or `key=value` in an audit record
+fn audit_field(line: &str, key: &str) -> Option<String> {
+    let start = line.find(&format!(" {}=", key))? + key.len() + 2;
+    let rest = &line[start..];
+    let value = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => rest.split_whitespace().next()?,
+    };
+    Some(value.to_string())
+}
+
+/// Parse SELinux `avc:  denied  { read } ...` and AppArmor `apparmor="DENIED" ...` records,
+/// skipping duplicates of the same process, operation and target
+pub fn parse_security_denials(log: &str) -> Vec<SecurityDenial> {
+    let mut denials: Vec<SecurityDenial> = Vec::new();
+    for line in log.lines() {
+        let denial = if line.contains("apparmor=\"DENIED\"") {
+            SecurityDenial {
+                module: "AppArmor".to_string(),
+                operation: audit_field(line, "operation").unwrap_or_else(|| "unknown".to_string()),
+                process: audit_field(line, "comm"),
+                target: audit_field(line, "name"),
+                profile: audit_field(line, "profile"),
+            }
+        } else if let Some(start) = line.find("avc:").filter(|_| line.contains("denied")) {
+            let permissions = line[start..]
+                .split_once('{')
+                .and_then(|(_, rest)| rest.split_once('}'))
+                .map(|(perms, _)| perms.trim().to_string());
+            SecurityDenial {
+                module: "SELinux".to_string(),
+                operation: permissions.unwrap_or_else(|| "unknown".to_string()),
+                process: audit_field(line, "comm"),
+                target: audit_field(line, "name").or_else(|| audit_field(line, "path")),
+                profile: audit_field(line, "scontext"),
+            }
+        } else {
+            continue;
+        };
+
+        if !denials.contains(&denial) {
+            denials.push(denial);
+        }
+    }
+    denials
+}
+
+/// One line per distinct denial, e.g. `AppArmor: nginx denied open on /etc/ssl/key.pem (profile /usr/sbin/nginx)`
+pub fn describe_denial(denial: &SecurityDenial) -> String {
+    let mut line = format!(
+        "{}: {} denied {}",
+        denial.module,
+        denial.process.as_deref().unwrap_or("unknown process"),
+        denial.operation
+    );
+    if let Some(target) = &denial.target {
+        line.push_str(&format!(" on {}", target));
+    }
+    if let Some(profile) = &denial.profile {
+        let label = if denial.module == "AppArmor" { "profile" } else { "context" };
+        line.push_str(&format!(" ({} {})", label, profile));
+    }
+    line
+}
+
+pub fn summarize_denials(denials: &[SecurityDenial]) -> String {
+    if denials.is_empty() {
+        return "No recent SELinux or AppArmor denials".to_string();
+    }
+    let selinux = denials.iter().filter(|d| d.module == "SELinux").count();
+    let mut summary = format!(
+        "{} distinct denials (SELinux: {}, AppArmor: {})\n",
+        denials.len(),
+        selinux,
+        denials.len() - selinux
+    );
+    for denial in denials {
+        summary.push_str(&format!("- {}\n", describe_denial(denial)));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AVC: &str = r#"type=AVC msg=audit(1700000000.123:456): avc:  denied  { read write } for  pid=1234 comm="httpd" name="index.html" dev="sda1" ino=42 scontext=system_u:system_r:httpd_t:s0 tcontext=unconfined_u:object_r:user_home_t:s0 tclass=file permissive=0"#;
+    const APPARMOR: &str = r#"Nov 14 10:00:00 host kernel: audit: type=1400 audit(1700000000.123:457): apparmor="DENIED" operation="open" profile="/usr/sbin/nginx" name="/etc/ssl/private/key.pem" pid=999 comm="nginx" requested_mask="r" denied_mask="r" fsuid=0 ouid=0"#;
+
+    #[test]
+    fn test_parse_security_denials() {
+        let log = format!("{}\nunrelated kernel line\n{}\n{}\n", AVC, APPARMOR, APPARMOR);
+        let denials = parse_security_denials(&log);
+        assert_eq!(denials.len(), 2);
+
+        assert_eq!(denials[0].module, "SELinux");
+        assert_eq!(denials[0].operation, "read write");
+        assert_eq!(denials[0].process.as_deref(), Some("httpd"));
+        assert_eq!(denials[0].target.as_deref(), Some("index.html"));
+        assert_eq!(denials[0].profile.as_deref(), Some("system_u:system_r:httpd_t:s0"));
+
+        assert_eq!(denials[1].module, "AppArmor");
+        assert_eq!(denials[1].operation, "open");
+        assert_eq!(denials[1].process.as_deref(), Some("nginx"));
+        assert_eq!(
+            describe_denial(&denials[1]),
+            "AppArmor: nginx denied open on /etc/ssl/private/key.pem (profile /usr/sbin/nginx)"
+        );
+    }
+
+    #[test]
+    fn test_summarize_denials() {
+        assert_eq!(summarize_denials(&[]), "No recent SELinux or AppArmor denials");
+
+        let summary = summarize_denials(&parse_security_denials(APPARMOR));
+        assert!(summary.starts_with("1 distinct denials (SELinux: 0, AppArmor: 1)"));
+    }
+
+    #[test]
+    fn test_lines_from_last_day() {
+        let now = NaiveDateTime::parse_from_str("2026-01-02 10:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let log = "\
+Dec 31 09:00:00 web-1 sshd[1]: Failed password for root from 203.0.113.5 port 1 ssh2
+Jan  1 09:00:00 web-1 sshd[2]: Failed password for root from 203.0.113.6 port 2 ssh2
+Jan  1 11:00:00 web-1 sshd[3]: Failed password for root from 203.0.113.7 port 3 ssh2
+continued without a timestamp
+";
+        assert_eq!(
+            lines_from_last_day(log, now),
+            "Jan  1 11:00:00 web-1 sshd[3]: Failed password for root from 203.0.113.7 port 3 ssh2\n\
+             continued without a timestamp\n"
+        );
+        // A year-less date after `now` was written last year
+        assert_eq!(
+            syslog_timestamp("Dec 31 09:00:00 web-1 sshd[1]: x", now).unwrap().year(),
+            2025
+        );
+    }
+
+    #[test]
+    fn test_parse_failed_logins() {
+        let log = "\
+Oct 16 09:00:01 web-1 sshd[100]: Failed password for root from 203.0.113.5 port 40000 ssh2
+Oct 16 09:00:03 web-1 sshd[100]: Failed password for invalid user admin from 203.0.113.5 port 40002 ssh2
+Oct 16 09:00:04 web-1 sshd[100]: Failed password for root from 203.0.113.5 port 40004 ssh2
+Oct 16 09:05:00 web-1 sshd[200]: Failed publickey for deploy from 2001:db8::7 port 50000 ssh2
+Oct 16 09:05:01 web-1 sshd[200]: Accepted publickey for deploy from 2001:db8::7 port 50001 ssh2
+";
+        let sources = parse_failed_logins(log);
+        assert_eq!(
+            sources,
+            vec![
+                FailedLoginSource {
+                    address: "203.0.113.5".to_string(),
+                    attempts: 3,
+                    users: vec!["root".to_string(), "admin".to_string()],
+                },
+                FailedLoginSource {
+                    address: "2001:db8::7".to_string(),
+                    attempts: 1,
+                    users: vec!["deploy".to_string()],
+                },
+            ]
+        );
+        assert!(summarize_failed_logins(&sources).starts_with("4 failed SSH logins from 2 addresses"));
+        assert!(!summarize_failed_logins(&sources).contains("brute force"));
+    }
+}
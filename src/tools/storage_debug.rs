@@ -7,7 +7,7 @@ impl DebugTools {
         let mut command = Command::new("iostat");
         command.args(["-x", "1", "1"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -45,7 +45,7 @@ impl DebugTools {
         let mut command = Command::new("smartctl");
         command.args(["-a", device]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -83,7 +83,7 @@ impl DebugTools {
         let mut command = Command::new("fdisk");
         command.args(["-l"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -121,7 +121,7 @@ impl DebugTools {
         let mut command = Command::new("lsblk");
         command.args(["-f"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -158,7 +158,7 @@ impl DebugTools {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("mount");
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -196,7 +196,7 @@ impl DebugTools {
         let mut command = Command::new("du");
         command.args(["-sh", path]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -236,7 +236,7 @@ impl DebugTools {
             path, "-type", "f", "-size", "+100M", "-exec", "ls", "-lh", "{}", ";",
         ]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -274,7 +274,7 @@ impl DebugTools {
         let mut command = Command::new("hdparm");
         command.args(["-I", device]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -311,7 +311,7 @@ impl DebugTools {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("blkid");
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
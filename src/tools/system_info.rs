@@ -1,13 +1,18 @@
-use super::{DebugToolResult, DebugTools};
+use super::{DebugToolResult, DebugTools, TimeSyncStatus};
+use crate::sysinfo::{collect_system_info_with, format_bytes, CollectOptions, FilesystemUsage, ProcessTree, SystemInfo};
+use super::pool::PooledOutput;
 use std::process::Command;
 
+/// Clock offset beyond which TLS, etcd and Kerberos start failing in practice
+pub const CLOCK_OFFSET_WARN_MS: f64 = 500.0;
+
 impl DebugTools {
     pub async fn run_ps_aux(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("ps");
         command.args(["aux"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -40,12 +45,50 @@ impl DebugTools {
         }
     }
 
+    /// The process hierarchy, built from the parent links in `<procfs>/<pid>/stat` so it works
+    /// without `ps`. Parents of many children and deeply nested processes are listed first.
+    pub async fn run_ps_tree(&self) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let command = format!("read {}/*/stat", self.host_paths.procfs_root.display());
+        let tree = ProcessTree::read(&self.host_paths);
+        if tree.is_empty() {
+            return DebugToolResult {
+                tool_name: "ps_tree".to_string(),
+                command,
+                success: false,
+                output: String::new(),
+                error: Some(format!("no processes found in {}", self.host_paths.procfs_root.display())),
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+            };
+        }
+
+        let highlights = tree.highlights();
+        let mut output = format!("{} processes\n", tree.len());
+        if !highlights.is_empty() {
+            output.push_str("\nStands out:\n");
+            for highlight in &highlights {
+                output.push_str(&format!("  ⚠ {}\n", highlight.summary()));
+            }
+        }
+        output.push('\n');
+        output.push_str(&tree.render());
+
+        DebugToolResult {
+            tool_name: "ps_tree".to_string(),
+            command,
+            success: true,
+            output,
+            error: None,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+        }
+    }
+
     pub async fn run_netstat(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("netstat");
         command.args(["-tuln"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -83,7 +126,7 @@ impl DebugTools {
         let mut command = Command::new("df");
         command.args(["-h"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -116,12 +159,17 @@ impl DebugTools {
         }
     }
 
+    /// Structured per-filesystem usage (`df -kPT`), for reasoning about which mount is full
+    pub fn parse_disk_usage(&self) -> Vec<FilesystemUsage> {
+        crate::sysinfo::collect_disk_usage()
+    }
+
     pub async fn run_free(&self) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("free");
         command.args(["-h"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -153,4 +201,312 @@ impl DebugTools {
             },
         }
     }
+
+    /// Collect a fresh `SystemInfo` and summarize it as a tool result, for an agent that
+    /// suspects its session copy is stale
+    pub async fn run_refresh_system_info(&self, options: &CollectOptions) -> (SystemInfo, DebugToolResult) {
+        let start_time = std::time::Instant::now();
+        let info = collect_system_info_with(options);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        let result = DebugToolResult {
+            tool_name: "refresh_system_info".to_string(),
+            command: "collect system information".to_string(),
+            success: true,
+            output: describe_system_info(&info),
+            error: None,
+            execution_time_ms: execution_time,
+        };
+        (info, result)
+    }
+
+    /// Active swap areas: `swapon --show`
+    pub async fn run_swapon(&self) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let mut command = Command::new("swapon");
+        command.args(["--show"]);
+
+        let result = self.command_output(&mut command);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(output) => {
+                let success = output.status.success();
+                let mut output_str = String::from_utf8_lossy(&output.stdout).to_string();
+                // swapon prints nothing at all when no swap is active
+                if success && output_str.trim().is_empty() {
+                    output_str = "No swap devices or files are active".to_string();
+                }
+                let error_str = if success {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(&output.stderr).to_string())
+                };
+
+                DebugToolResult {
+                    tool_name: "swapon".to_string(),
+                    command: "swapon --show".to_string(),
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "swapon".to_string(),
+                command: "swapon --show".to_string(),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
+    /// Clock synchronization: `timedatectl status`, plus the measured offset from
+    /// `chronyc tracking` or `ntpq -p` when either is installed
+    pub async fn run_timedatectl(&self) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let (status, outputs) = read_time_sync();
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let command = if outputs.is_empty() {
+            "timedatectl status".to_string()
+        } else {
+            outputs.iter().map(|(source, _)| source.as_str()).collect::<Vec<_>>().join("; ")
+        };
+
+        if outputs.is_empty() {
+            return DebugToolResult {
+                tool_name: "timedatectl".to_string(),
+                command,
+                success: false,
+                output: String::new(),
+                error: Some("Could not run timedatectl, chronyc or ntpq".to_string()),
+                execution_time_ms: execution_time,
+            };
+        }
+
+        let mut output = format!("{}\n", describe_time_sync(&status));
+        for (source, text) in &outputs {
+            output.push_str(&format!("\n$ {}\n{}", source, text));
+        }
+
+        DebugToolResult {
+            tool_name: "timedatectl".to_string(),
+            command,
+            success: true,
+            output,
+            error: None,
+            execution_time_ms: execution_time,
+        }
+    }
+
+    /// Structured clock synchronization state (`None` when no time tool could be run)
+    pub async fn get_time_sync_status(&self) -> Option<TimeSyncStatus> {
+        let (status, outputs) = read_time_sync();
+        (!outputs.is_empty()).then_some(status)
+    }
+}
+
+/// Run timedatectl and the first available NTP client, returning the parsed status and
+/// each (command, output) pair that succeeded
+fn read_time_sync() -> (TimeSyncStatus, Vec<(String, String)>) {
+    let run = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .pooled_output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    let mut status = TimeSyncStatus::default();
+    let mut outputs = Vec::new();
+    if let Some(output) = run("timedatectl", &["status"]) {
+        status = parse_timedatectl(&output);
+        outputs.push(("timedatectl status".to_string(), output));
+    }
+    if let Some(output) = run("chronyc", &["tracking"]) {
+        status.offset_ms = parse_chrony_offset_ms(&output);
+        status.offset_source = status.offset_ms.map(|_| "chronyc".to_string());
+        outputs.push(("chronyc tracking".to_string(), output));
+    } else if let Some(output) = run("ntpq", &["-p"]) {
+        status.offset_ms = parse_ntpq_offset_ms(&output);
+        status.offset_source = status.offset_ms.map(|_| "ntpq".to_string());
+        outputs.push(("ntpq -p".to_string(), output));
+    }
+    (status, outputs)
+}
+
+fn yes_no(value: &str) -> Option<bool> {
+    match value.trim() {
+        "yes" | "active" => Some(true),
+        "no" | "inactive" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse `timedatectl status`; handles both current ("System clock synchronized",
+/// "NTP service") and older systemd ("NTP synchronized", "Network time on") labels
+pub fn parse_timedatectl(output: &str) -> TimeSyncStatus {
+    let mut status = TimeSyncStatus::default();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        match key.trim() {
+            "System clock synchronized" | "NTP synchronized" => status.synchronized = yes_no(value),
+            "NTP service" | "Network time on" => status.ntp_service_active = yes_no(value),
+            _ => {}
+        }
+    }
+    status
+}
+
+/// Offset in ms from `chronyc tracking`'s "System time : 0.000123 seconds fast of NTP time"
+pub fn parse_chrony_offset_ms(output: &str) -> Option<f64> {
+    let line = output.lines().find(|line| line.trim_start().starts_with("System time"))?;
+    let (_, value) = line.split_once(':')?;
+    let mut words = value.split_whitespace();
+    let seconds: f64 = words.next()?.parse().ok()?;
+    let direction = words.nth(1)?;
+    let sign = if direction == "slow" { -1.0 } else { 1.0 };
+    Some(sign * seconds * 1000.0)
+}
+
+/// Offset in ms of the selected peer (the `*` line) in `ntpq -p` output
+pub fn parse_ntpq_offset_ms(output: &str) -> Option<f64> {
+    let line = output.lines().find(|line| line.starts_with('*'))?;
+    // remote refid st t when poll reach delay offset jitter
+    line.split_whitespace().nth(8)?.parse().ok()
+}
+
+/// A refreshed snapshot in the same shape as the agent's system context, plus the state
+/// most likely to change during a session
+pub fn describe_system_info(info: &SystemInfo) -> String {
+    let mut output = info.context_summary();
+    if info.pressure.is_available() {
+        output.push_str("Pressure stall information (PSI, % of time stalled):\n");
+        output.push_str(&info.pressure.to_context());
+    }
+    for fs in &info.filesystems {
+        output.push_str(&format!(
+            "Filesystem {}: {}% used, {} free\n",
+            fs.mount_point,
+            fs.use_percent,
+            format_bytes(fs.available_bytes)
+        ));
+    }
+    output.push_str(&format!("Zombie processes: {}\n", info.zombie_count));
+    if info.systemd.failed_units.is_empty() {
+        output.push_str("Failed units: none\n");
+    } else {
+        output.push_str(&format!("Failed units: {}\n", info.systemd.failed_units.join(", ")));
+    }
+    output
+}
+
+/// One-line summary of the clock state
+pub fn describe_time_sync(status: &TimeSyncStatus) -> String {
+    let synchronized = match status.synchronized {
+        Some(true) => "synchronized",
+        Some(false) => "NOT synchronized",
+        None => "synchronization unknown",
+    };
+    let service = match status.ntp_service_active {
+        Some(true) => ", NTP service active",
+        Some(false) => ", NTP service inactive",
+        None => "",
+    };
+    let offset = match (status.offset_ms, &status.offset_source) {
+        (Some(offset), Some(source)) => format!(", offset {:+.1} ms ({})", offset, source),
+        _ => String::new(),
+    };
+    format!("System clock {}{}{}", synchronized, service, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timedatectl() {
+        let output = "               Local time: Fri 2026-10-16 10:00:00 UTC
+           Universal time: Fri 2026-10-16 10:00:00 UTC
+                Time zone: Etc/UTC (UTC, +0000)
+System clock synchronized: no
+              NTP service: inactive
+          RTC in local TZ: no
+";
+        let status = parse_timedatectl(output);
+        assert_eq!(status.synchronized, Some(false));
+        assert_eq!(status.ntp_service_active, Some(false));
+
+        let legacy = parse_timedatectl("  Network time on: yes\n NTP synchronized: yes\n");
+        assert_eq!(legacy.synchronized, Some(true));
+        assert_eq!(legacy.ntp_service_active, Some(true));
+    }
+
+    #[test]
+    fn test_parse_ntp_offsets() {
+        let chrony = "Reference ID    : A9FEA97B (169.254.169.123)
+Stratum         : 4
+System time     : 0.750000000 seconds slow of NTP time
+Last offset     : +0.000012345 seconds
+";
+        assert_eq!(parse_chrony_offset_ms(chrony), Some(-750.0));
+
+        let ntpq = "     remote           refid      st t when poll reach   delay   offset  jitter
+==============================================================================
+ 0.pool.ntp.org  .POOL.          16 p    -   64    0    0.000    0.000   0.000
+*time.example.co 192.0.2.1        2 u   33   64  377    1.204   12.345   0.210
+";
+        assert_eq!(parse_ntpq_offset_ms(ntpq), Some(12.345));
+        assert_eq!(parse_ntpq_offset_ms("no peers"), None);
+    }
+
+    #[test]
+    fn test_describe_time_sync() {
+        let status = TimeSyncStatus {
+            synchronized: Some(true),
+            ntp_service_active: Some(true),
+            offset_ms: Some(-0.4),
+            offset_source: Some("chronyc".to_string()),
+        };
+        assert_eq!(
+            describe_time_sync(&status),
+            "System clock synchronized, NTP service active, offset -0.4 ms (chronyc)"
+        );
+    }
+
+    #[test]
+    fn test_describe_system_info() {
+        let mut info = SystemInfo {
+            os: "Ubuntu 24.04".to_string(),
+            cpu: "8 cores".to_string(),
+            free_memory: "2 GB".to_string(),
+            total_memory: "16 GB".to_string(),
+            free_disk: "40 GB".to_string(),
+            total_disk: "100 GB".to_string(),
+            zombie_count: 2,
+            ..Default::default()
+        };
+        info.systemd.failed_units = vec!["nginx.service".to_string()];
+        info.filesystems = vec![FilesystemUsage {
+            filesystem: "/dev/sda1".to_string(),
+            fs_type: "ext4".to_string(),
+            mount_point: "/".to_string(),
+            total_bytes: 100 * 1024 * 1024 * 1024,
+            used_bytes: 93 * 1024 * 1024 * 1024,
+            available_bytes: 7 * 1024 * 1024 * 1024,
+            use_percent: 93,
+        }];
+
+        assert_eq!(
+            describe_system_info(&info),
+            "Operating System: Ubuntu 24.04\nCPU: 8 cores\nMemory: 2 GB/16 GB\nDisk: 40 GB/100 GB\n\
+             Swap: none configured\n\
+             Filesystem /: 93% used, 7.0 GiB free\n\
+             Zombie processes: 2\n\
+             Failed units: nginx.service\n"
+        );
+    }
 }
@@ -1,13 +1,23 @@
-use super::{DebugToolResult, DebugTools};
+use super::{DebugToolResult, DebugTools, ServiceStatusDetail};
+use super::pool::PooledOutput;
 use std::process::Command;
 
+/// Automatic restarts after which a unit counts as crash-looping even between attempts
+pub const SERVICE_RESTART_LOOP_THRESHOLD: u32 = 3;
+
+/// Most units `get_service_statuses` queries, so a box with many failed units stays quick
+pub const SERVICE_STATUS_MAX_UNITS: usize = 10;
+
+/// The `systemctl show` properties `get_service_status` reads
+const SERVICE_STATUS_PROPERTIES: &str = "ActiveState,SubState,Result,ExecMainStatus,MainPID,NRestarts";
+
 impl DebugTools {
     pub async fn run_systemctl_status(&self, service_name: &str) -> DebugToolResult {
         let start_time = std::time::Instant::now();
         let mut command = Command::new("systemctl");
         command.args(["status", service_name, "--no-pager"]);
 
-        let result = command.output();
+        let result = self.command_output(&mut command);
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match result {
@@ -39,4 +49,166 @@ impl DebugTools {
             },
         }
     }
+
+    /// Show the effective unit definition, including override drop-ins
+    pub async fn run_systemctl_cat(&self, unit: &str) -> DebugToolResult {
+        let start_time = std::time::Instant::now();
+        let mut command = Command::new("systemctl");
+        command.args(["cat", unit, "--no-pager"]);
+
+        let result = self.command_output(&mut command);
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(output) => {
+                let success = output.status.success();
+                let output_str = String::from_utf8_lossy(&output.stdout).to_string();
+                let error_str = if success {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(&output.stderr).to_string())
+                };
+
+                DebugToolResult {
+                    tool_name: "systemctl_cat".to_string(),
+                    command: format!("systemctl cat {} --no-pager", unit),
+                    success,
+                    output: output_str,
+                    error: error_str,
+                    execution_time_ms: execution_time,
+                }
+            }
+            Err(e) => DebugToolResult {
+                tool_name: "systemctl_cat".to_string(),
+                command: format!("systemctl cat {} --no-pager", unit),
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                execution_time_ms: execution_time,
+            },
+        }
+    }
+
+    /// Structured state of a unit from `systemctl show`; `run_systemctl_status` keeps the
+    /// human-readable view
+    pub async fn get_service_status(&self, unit: &str) -> ServiceStatusDetail {
+        let output = Command::new("systemctl")
+            .args(["show", unit, &format!("--property={}", SERVICE_STATUS_PROPERTIES)])
+            .pooled_output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default();
+        parse_systemctl_show(unit, &output)
+    }
+
+    /// `get_service_status` of the first `SERVICE_STATUS_MAX_UNITS` units
+    pub async fn get_service_statuses(&self, units: &[String]) -> Vec<ServiceStatusDetail> {
+        let mut statuses = Vec::new();
+        for unit in units.iter().take(SERVICE_STATUS_MAX_UNITS) {
+            statuses.push(self.get_service_status(unit).await);
+        }
+        statuses
+    }
+}
+
+/// Parse the `Key=value` lines of `systemctl show`. `MainPID=0` means no main process.
+pub fn parse_systemctl_show(unit: &str, output: &str) -> ServiceStatusDetail {
+    let mut detail = ServiceStatusDetail {
+        unit: unit.to_string(),
+        ..Default::default()
+    };
+    for (key, value) in output.lines().filter_map(|line| line.split_once('=')) {
+        let value = value.trim();
+        match key {
+            "ActiveState" => detail.active_state = value.to_string(),
+            "SubState" => detail.sub_state = value.to_string(),
+            "Result" => detail.result = value.to_string(),
+            "ExecMainStatus" => detail.exec_main_status = value.parse().ok(),
+            "MainPID" => detail.main_pid = value.parse().ok().filter(|pid| *pid != 0),
+            "NRestarts" => detail.n_restarts = value.parse().ok(),
+            _ => {}
+        }
+    }
+    detail
+}
+
+impl ServiceStatusDetail {
+    /// systemd is restarting it (`auto-restart`) or already has several times
+    pub fn is_crash_looping(&self) -> bool {
+        self.sub_state == "auto-restart"
+            || self.n_restarts.is_some_and(|restarts| restarts >= SERVICE_RESTART_LOOP_THRESHOLD)
+    }
+
+    /// The last run ended badly: a non-`success` result or a non-zero exit status
+    pub fn exited_abnormally(&self) -> bool {
+        (!self.result.is_empty() && self.result != "success") || self.exec_main_status.is_some_and(|status| status != 0)
+    }
+
+    /// How the last run ended, e.g. "exited with status 1 (exit-code)" or "was killed by the OOM killer"
+    pub fn exit_description(&self) -> String {
+        let status = self.exec_main_status.unwrap_or_default();
+        match self.result.as_str() {
+            "oom-kill" => "was killed by the OOM killer".to_string(),
+            "signal" | "core-dump" => format!("was killed by signal {} ({})", status, self.result),
+            "timeout" => "timed out starting or stopping (timeout)".to_string(),
+            "" | "success" => format!("exited with status {}", status),
+            result => format!("exited with status {} ({})", status, result),
+        }
+    }
+
+    /// One line for the AI context
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{}: {} ({})", self.unit, self.active_state, self.sub_state);
+        if !self.result.is_empty() {
+            summary.push_str(&format!(", result {}", self.result));
+        }
+        if let Some(status) = self.exec_main_status {
+            summary.push_str(&format!(", main process exit status {}", status));
+        }
+        if let Some(restarts) = self.n_restarts {
+            summary.push_str(&format!(", {} restarts", restarts));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_systemctl_show() {
+        let detail = parse_systemctl_show(
+            "api.service",
+            "ActiveState=activating\nSubState=auto-restart\nResult=exit-code\nExecMainStatus=1\nMainPID=0\nNRestarts=7\n",
+        );
+        assert_eq!(
+            detail,
+            ServiceStatusDetail {
+                unit: "api.service".to_string(),
+                active_state: "activating".to_string(),
+                sub_state: "auto-restart".to_string(),
+                result: "exit-code".to_string(),
+                exec_main_status: Some(1),
+                main_pid: None,
+                n_restarts: Some(7),
+            }
+        );
+        assert!(detail.is_crash_looping());
+        assert!(detail.exited_abnormally());
+        assert_eq!(detail.exit_description(), "exited with status 1 (exit-code)");
+
+        let healthy = parse_systemctl_show(
+            "nginx.service",
+            "ActiveState=active\nSubState=running\nResult=success\nExecMainStatus=0\nMainPID=812\n",
+        );
+        assert_eq!(healthy.main_pid, Some(812));
+        assert_eq!(healthy.n_restarts, None);
+        assert!(!healthy.is_crash_looping());
+        assert!(!healthy.exited_abnormally());
+
+        let killed = parse_systemctl_show("db.service", "ActiveState=failed\nResult=oom-kill\nExecMainStatus=9\n");
+        assert_eq!(killed.exit_description(), "was killed by the OOM killer");
+    }
 }
@@ -1,23 +1,71 @@
+use crate::output::{ToolCallRecord, Verbosity};
 use crate::sysinfo::SystemInfo;
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
 use std::time::Duration;
 
 pub struct UIFormatter {
     use_colors: bool,
+    show_progress_bars: bool,
 }
 
 impl UIFormatter {
     pub fn new(use_colors: bool) -> Self {
-        // Auto-detect if we should use colors based on terminal support
-        let use_colors = use_colors && is_terminal();
-        Self { use_colors }
+        Self::for_stdout(use_colors, is_terminal())
+    }
+
+    /// Colors and progress indicators would corrupt piped output, so both are
+    /// disabled when stdout is not a terminal. Colors also honor `NO_COLOR`.
+    fn for_stdout(use_colors: bool, stdout_is_terminal: bool) -> Self {
+        Self {
+            use_colors: use_colors && stdout_is_terminal && !no_color_requested(),
+            show_progress_bars: stdout_is_terminal,
+        }
+    }
+
+    /// Disable progress indicators (`ui.progress_indicators: false` or `--no-progress`)
+    pub fn with_progress(mut self, enabled: bool) -> Self {
+        self.show_progress_bars = self.show_progress_bars && enabled;
+        self
+    }
+
+    /// Show progress indicators even when stdout is not a terminal (`--force-progress`)
+    pub fn force_progress(mut self, force: bool) -> Self {
+        self.show_progress_bars = self.show_progress_bars || force;
+        self
+    }
+
+    /// Whether progress indicators are shown
+    pub fn shows_progress(&self) -> bool {
+        self.show_progress_bars
+    }
+
+    /// Progress bar for operations that run several tools, e.g. "3/11 tools (ip_addr)".
+    /// Prints nothing when progress indicators are disabled.
+    pub fn progress_bar(&self, total: u64) -> ToolProgress {
+        if !self.show_progress_bars {
+            return ToolProgress { bar: None };
+        }
+
+        let pb = ProgressBar::new(total);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{bar:30.cyan/blue} {pos}/{len} tools ({msg}) ETA {eta}")
+                .unwrap()
+                .progress_chars("█▓░"),
+        );
+        ToolProgress { bar: Some(pb) }
     }
 
     pub fn show_progress<F, R>(&self, message: &str, operation: F) -> R
     where
         F: FnOnce() -> R,
     {
+        if !self.show_progress_bars {
+            return operation();
+        }
+
         if !self.use_colors {
             // Simple text-based progress for non-color terminals
             println!("🔄 {}", message);
@@ -121,6 +169,39 @@ impl UIFormatter {
     }
 }
 
+/// Handle returned by [`UIFormatter::progress_bar`]
+pub struct ToolProgress {
+    bar: Option<ProgressBar>,
+}
+
+impl ToolProgress {
+    /// Mark one more tool as done
+    pub fn inc(&self, label: &str) {
+        if let Some(pb) = &self.bar {
+            pb.set_message(label.to_string());
+            pb.inc(1);
+        }
+    }
+
+    /// Start over from zero, e.g. when the tools run again on a retry
+    pub fn restart(&self) {
+        if let Some(pb) = &self.bar {
+            pb.reset();
+        }
+    }
+
+    /// Clear the bar once all tools have run
+    pub fn finish(&self) {
+        if let Some(pb) = &self.bar {
+            pb.finish_and_clear();
+        }
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.bar.is_none()
+    }
+}
+
 enum HeaderLevel {
     Main,
     Section,
@@ -133,15 +214,15 @@ impl Default for UIFormatter {
     }
 }
 
-pub fn print_results(info: &SystemInfo, analysis: &str, verbose: bool) {
+pub fn print_results(info: &SystemInfo, analysis: &str, verbosity: Verbosity) {
     let formatter = UIFormatter::default();
-    print_results_with_formatter(info, analysis, verbose, &formatter);
+    print_results_with_formatter(info, analysis, verbosity, &formatter);
 }
 
 pub fn print_results_with_formatter(
     info: &SystemInfo,
     analysis: &str,
-    verbose: bool,
+    verbosity: Verbosity,
     formatter: &UIFormatter,
 ) {
     // Main header
@@ -149,6 +230,7 @@ pub fn print_results_with_formatter(
 
     // System Overview
     println!("{}", formatter.format_header("📊 System Overview", HeaderLevel::Section));
+    println!("{}", formatter.format_info_line("Environment", &info.environment.summary(), "🌐"));
     println!("{}", formatter.format_info_line("OS", &info.os, "🖥️"));
     println!("{}", formatter.format_info_line("CPU", &info.cpu, "⚡"));
     println!("{}", formatter.format_metric(&info.free_memory, &info.total_memory, "Memory", "💾"));
@@ -185,7 +267,7 @@ pub fn print_results_with_formatter(
     let has_container_issues = info
         .containers
         .iter()
-        .any(|container| !container.status.contains("Up"));
+        .any(|container| !container.is_healthy());
 
     let system_healthy = !has_failed_services && !has_significant_errors && !has_container_issues;
 
@@ -202,8 +284,8 @@ pub fn print_results_with_formatter(
 
     // System Logs
     println!("{}", formatter.format_header("📋 System Logs", HeaderLevel::Section));
-    if verbose {
-        // Verbose mode - show all logs
+    if verbosity == Verbosity::Full {
+        // Full mode - show all logs
         let total_recent_errors = info.journal.recent_errors.len();
         let total_boot_errors = info.journal.boot_errors.len();
 
@@ -229,6 +311,20 @@ pub fn print_results_with_formatter(
         } else {
             println!("{}", formatter.format_success("No errors found"));
         }
+    } else if verbosity == Verbosity::Quiet {
+        // Quiet mode - counts only
+        let error_count = info
+            .journal
+            .recent_errors
+            .iter()
+            .chain(info.journal.boot_errors.iter())
+            .filter(|entry| !is_common_non_critical_error(&entry.message))
+            .count();
+        if error_count > 0 {
+            println!("{}", formatter.format_warning(&format!("{} significant errors", error_count)));
+        } else {
+            println!("{}", formatter.format_success("No significant errors found"));
+        }
     } else {
         // Normal mode - filter significant errors
         let mut error_count = 0;
@@ -275,9 +371,9 @@ pub fn print_results_with_formatter(
         let mut unhealthy_containers = 0;
 
         for container in &info.containers {
-            if container.status.contains("Up") {
+            if container.is_healthy() {
                 healthy_containers += 1;
-                if verbose {
+                if verbosity.is_detailed() {
                     println!("  {} {} ({})", 
                         formatter.format_success(""), 
                         container.name, 
@@ -286,6 +382,9 @@ pub fn print_results_with_formatter(
                 }
             } else {
                 unhealthy_containers += 1;
+                if verbosity == Verbosity::Quiet {
+                    continue;
+                }
                 println!("  {} {} ({})", 
                     formatter.format_warning(""), 
                     container.name, 
@@ -347,7 +446,7 @@ pub fn print_results_with_formatter(
             let unhealthy_count = info
                 .containers
                 .iter()
-                .filter(|c| !c.status.contains("Up"))
+                .filter(|c| !c.is_healthy())
                 .count();
             println!("   • {} unhealthy containers", unhealthy_count);
         }
@@ -393,6 +492,43 @@ fn is_common_non_critical_error(message: &str) -> bool {
         .any(|error| message_lower.contains(error))
 }
 
+/// Distinct commands in the order they first ran, skipping blanks
+pub fn unique_commands<'a>(commands: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut unique: Vec<&str> = Vec::new();
+    for command in commands.into_iter().map(str::trim) {
+        if !command.is_empty() && !unique.contains(&command) {
+            unique.push(command);
+        }
+    }
+    unique
+}
+
+/// Footer listing the commands behind an analysis, so users can reproduce it themselves
+pub fn print_commands_footer<'a>(formatter: &UIFormatter, commands: impl IntoIterator<Item = &'a str>) {
+    let commands = unique_commands(commands);
+    if commands.is_empty() {
+        return;
+    }
+
+    println!("{}", formatter.format_header("🧾 Commands executed", HeaderLevel::Section));
+    for command in commands {
+        println!("  $ {}", command);
+    }
+}
+
+/// The agent's stated reason for each tool it ran, in order
+pub fn print_reasoning_transcript(formatter: &UIFormatter, records: &[ToolCallRecord]) {
+    if records.is_empty() {
+        return;
+    }
+
+    println!("{}", formatter.format_header("🧠 Why each tool was run", HeaderLevel::Section));
+    for (index, record) in records.iter().enumerate() {
+        println!("  {}. {}", index + 1, record.command);
+        println!("     {}", record.reasoning.as_deref().unwrap_or("(no reasoning given)"));
+    }
+}
+
 pub fn print_history(checks: &[(i64, String, SystemInfo, String)]) {
     let formatter = UIFormatter::default();
     
@@ -422,7 +558,7 @@ pub fn print_history(checks: &[(i64, String, SystemInfo, String)]) {
         let has_container_issues = system_info
             .containers
             .iter()
-            .any(|container| !container.status.contains("Up"));
+            .any(|container| !container.is_healthy());
 
         let was_healthy = !has_failed_services && !has_significant_errors && !has_container_issues;
 
@@ -436,17 +572,19 @@ pub fn print_history(checks: &[(i64, String, SystemInfo, String)]) {
     }
 }
 
-// Simple TTY detection - fallback to always true if detection fails
 fn is_terminal() -> bool {
-    // Try to detect if we're in a terminal
-    // For now, we'll use a simple heuristic
-    std::env::var("TERM").is_ok() && !std::env::var("NO_COLOR").is_ok()
+    std::io::stdout().is_terminal()
+}
+
+/// https://no-color.org: any non-empty `NO_COLOR` disables colors
+fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sysinfo::{SystemInfo, KubernetesInfo, ContainerInfo, SystemdInfo, SystemdUnit, CgroupInfo, JournalInfo, JournalEntry};
+    use crate::sysinfo::{SystemInfo, KubernetesInfo, ContainerInfo, SystemdInfo, SystemdUnit, CgroupInfo, JournalInfo, JournalEntry, PressureInfo};
 
     fn create_test_system_info() -> SystemInfo {
         SystemInfo {
@@ -470,6 +608,7 @@ mod tests {
                     image: "nginx:latest".to_string(),
                     status: "Up 1 hour".to_string(),
                     ports: vec!["80:80".to_string()],
+                    ..Default::default()
                 },
             ],
             systemd: SystemdInfo {
@@ -480,6 +619,7 @@ mod tests {
                         name: "nginx.service".to_string(),
                         status: "active".to_string(),
                         description: "Nginx web server".to_string(),
+                        sub_state: "running".to_string(),
                     },
                 ],
             },
@@ -487,9 +627,27 @@ mod tests {
                 version: "v2".to_string(),
                 cgroup_path: "/system.slice".to_string(),
                 controllers: vec!["memory".to_string(), "cpu".to_string()],
+                enabled_controllers: Some(vec!["memory".to_string(), "cpu".to_string()]),
                 memory_limit: Some("8GB".to_string()),
                 cpu_limit: Some("4".to_string()),
+                memory_usage: None,
+                cpu_throttled_percent: None,
+                cpu_nr_periods: None,
+                cpu_nr_throttled: None,
+                cpu_throttled_usec: None,
             },
+            pressure: PressureInfo::default(),
+            zombie_count: 0,
+            zombie_parents: vec![],
+            swap: Default::default(),
+            filesystems: vec![],
+            cpu_steal_percent: None,
+            environment: Default::default(),
+            running_kernel: String::new(),
+            newest_installed_kernel: None,
+            needs_reboot: false,
+            network_interfaces: vec![],
+            process_highlights: vec![],
             journal: JournalInfo {
                 recent_errors: vec![
                     JournalEntry {
@@ -501,6 +659,7 @@ mod tests {
                 ],
                 boot_errors: vec![],
                 recent_warnings: vec![],
+                disk_usage: None,
             },
         }
     }
@@ -512,7 +671,7 @@ mod tests {
         
         // The actual color setting depends on terminal detection
         // Just verify the formatter can be created
-        assert_eq!(formatter_color.use_colors, is_terminal());
+        assert_eq!(formatter_color.use_colors, is_terminal() && !no_color_requested());
         assert!(!formatter_no_color.use_colors);
     }
 
@@ -536,6 +695,16 @@ mod tests {
         assert_eq!(result, 42);
     }
 
+    #[test]
+    fn test_progress_bar_disabled() {
+        let formatter = UIFormatter::new(true).with_progress(false);
+        let progress = formatter.progress_bar(3);
+
+        assert!(progress.is_hidden());
+        progress.inc("ip_addr");
+        progress.finish();
+    }
+
     #[test]
     fn test_format_header_no_color() {
         let formatter = UIFormatter::new(false);
@@ -619,8 +788,8 @@ mod tests {
         // approach to capture and verify output
         
         // For now, just verify it doesn't panic
-        print_results_with_formatter(&system_info, "Test analysis", false, &formatter);
-        print_results_with_formatter(&system_info, "Test analysis", true, &formatter);
+        print_results_with_formatter(&system_info, "Test analysis", Verbosity::Normal, &formatter);
+        print_results_with_formatter(&system_info, "Test analysis", Verbosity::Full, &formatter);
     }
 
     #[test]
@@ -633,7 +802,7 @@ mod tests {
         let formatter = UIFormatter::new(false);
         
         // Should not panic with a healthy system
-        print_results_with_formatter(&system_info, "System is healthy", false, &formatter);
+        print_results_with_formatter(&system_info, "System is healthy", Verbosity::Normal, &formatter);
     }
 
     #[test]
@@ -645,7 +814,7 @@ mod tests {
         let formatter = UIFormatter::new(false);
         
         // Should not panic with system issues
-        print_results_with_formatter(&system_info, "Issues detected", false, &formatter);
+        print_results_with_formatter(&system_info, "Issues detected", Verbosity::Normal, &formatter);
     }
 
     #[test]
@@ -658,7 +827,7 @@ mod tests {
         let formatter = UIFormatter::new(false);
         
         // Should handle Kubernetes environment properly
-        print_results_with_formatter(&system_info, "Running in Kubernetes", false, &formatter);
+        print_results_with_formatter(&system_info, "Running in Kubernetes", Verbosity::Normal, &formatter);
     }
 
     #[test]
@@ -667,7 +836,9 @@ mod tests {
         let formatter = UIFormatter::new(false);
         
         // Test verbose mode output
-        print_results_with_formatter(&system_info, "Verbose analysis", true, &formatter);
+        print_results_with_formatter(&system_info, "Verbose analysis", Verbosity::Full, &formatter);
+        print_results_with_formatter(&system_info, "Detailed analysis", Verbosity::Detailed, &formatter);
+        print_results_with_formatter(&system_info, "Quiet analysis", Verbosity::Quiet, &formatter);
     }
 
     #[test]
@@ -696,27 +867,26 @@ mod tests {
 
     #[test]
     fn test_ui_formatter_color_detection() {
-        // Test with environment variables
         unsafe {
-            std::env::set_var("TERM", "xterm-256color");
             std::env::remove_var("NO_COLOR");
         }
-        assert!(is_terminal());
-        
+        let tty = UIFormatter::for_stdout(true, true);
+        assert!(tty.use_colors);
+        assert!(tty.show_progress_bars);
+
+        // Piped output gets neither colors nor progress, whatever the config says
+        let piped = UIFormatter::for_stdout(true, false);
+        assert!(!piped.use_colors);
+        assert!(!piped.show_progress_bars);
+        assert!(piped.force_progress(true).show_progress_bars);
+
         unsafe {
             std::env::set_var("NO_COLOR", "1");
         }
-        assert!(!is_terminal());
-        
-        unsafe {
-            std::env::remove_var("TERM");
-            std::env::remove_var("NO_COLOR");
-        }
-        assert!(!is_terminal());
-        
+        assert!(!UIFormatter::for_stdout(true, true).use_colors);
+
         // Clean up
         unsafe {
-            std::env::remove_var("TERM");
             std::env::remove_var("NO_COLOR");
         }
     }
@@ -729,7 +899,7 @@ mod tests {
         let formatter = UIFormatter::new(false);
         
         // Should handle empty containers list properly
-        print_results_with_formatter(&system_info, "No containers", false, &formatter);
+        print_results_with_formatter(&system_info, "No containers", Verbosity::Normal, &formatter);
     }
 
     #[test]
@@ -753,6 +923,15 @@ mod tests {
         let formatter = UIFormatter::new(false);
         
         // Should properly filter common errors
-        print_results_with_formatter(&system_info, "Mixed errors", false, &formatter);
+        print_results_with_formatter(&system_info, "Mixed errors", Verbosity::Normal, &formatter);
+    }
+
+    #[test]
+    fn test_unique_commands() {
+        let commands = unique_commands(["df -h", "ps aux", " df -h ", "", "ps aux --sort=-%cpu"]);
+        assert_eq!(commands, vec!["df -h", "ps aux", "ps aux --sort=-%cpu"]);
+
+        // Nothing to print is not an error
+        print_commands_footer(&UIFormatter::new(false), std::iter::empty());
     }
 }
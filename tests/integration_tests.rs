@@ -113,4 +113,85 @@ fn test_config_loading() {
     // Test basic config loading
     let config = RaidConfig::default();
     assert!(config.validate().is_ok());
-} 
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_library_reexports() {
+    // The core API should be usable straight from the crate root
+    let debug_tools = raid::DebugTools::new();
+    let result: raid::DebugToolResult = debug_tools.run_free().await;
+    assert_eq!(result.tool_name, "free");
+
+    let system_info: raid::SystemInfo = raid::collect_system_info();
+    let report: raid::SystemHealthReport =
+        raid::create_system_health_report(&system_info, "No issues found", false);
+    assert_eq!(report.analysis, "No issues found");
+
+    let known_issues = raid::KnownIssuesDatabase::new().await;
+    assert!(!known_issues.get_all_issues().await.is_empty());
+
+    let provider: Box<dyn raid::AIProvider> = Box::new(DummyAI);
+    assert_eq!(provider.name(), "DummyAI");
+}
+
+/// Answer every HTTP request like an Ollama server whose model always gives a final answer
+fn spawn_stub_ollama() -> String {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            // The request line and headers, then as much body as Content-Length promises
+            while let Ok(read) = stream.read(&mut buffer) {
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_lowercase();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .and_then(|value| value.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+            }
+            let body = r#"{"models":[],"response":"COMPLETE: The root filesystem has plenty of free space left.","done":true}"#;
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        }
+    });
+    format!("http://{}", address)
+}
+
+#[test]
+fn test_machine_readable_ai_output_is_only_the_report() {
+    let base_url = spawn_stub_ollama();
+    let home = tempfile::tempdir().unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_raid"))
+        .args(["--ai-provider", "local", "--ai-base-url", &base_url, "--format", "json"])
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .current_dir(home.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|e| panic!("stdout is not one JSON report ({}):\n{}", e, String::from_utf8_lossy(&output.stdout)));
+    assert!(report["analysis"].as_str().unwrap().contains("plenty of free space"));
+}